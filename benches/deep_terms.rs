@@ -0,0 +1,273 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+//! Measures the conversions and the parsing of deeply nested interactions.
+//! Run with *cargo bench --bench deep_terms*.
+
+
+
+use std::time::{Duration, Instant};
+
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::character::complete::digit1;
+use nom::combinator::{map, value};
+use nom::error::ParseError;
+use nom::IResult;
+
+use common_sequence_diagram_io::conversion::lang_to_repr::FromInteractionTermToInternalRepresentation;
+use common_sequence_diagram_io::conversion::repr_to_lang::FromInternalRepresentationToInteractionTerm;
+use common_sequence_diagram_io::from_text::context_aware_parser::ContextAwareInteractionParser;
+use common_sequence_diagram_io::from_text::parse::parse_interaction_strict;
+use common_sequence_diagram_io::from_text::util::delimited_lang_parser::DelimitedInteractionLanguageParser;
+use common_sequence_diagram_io::internal_representation::{CommonIoInteractionInterface, InteractionOperatorRepresentation};
+
+
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+enum BenchOperators {
+    Seq,
+    Loop
+}
+
+impl InteractionOperatorRepresentation for BenchOperators {
+
+    fn arity(&self) -> usize {
+        match self {
+            BenchOperators::Seq => 2,
+            BenchOperators::Loop => 1
+        }
+    }
+
+    fn is_associative(&self) -> bool {
+        match self {
+            BenchOperators::Seq => true,
+            BenchOperators::Loop => false
+        }
+    }
+
+}
+
+struct BenchCioII {}
+
+impl CommonIoInteractionInterface for BenchCioII {
+    type InteractionLeafPatternType = usize;
+    type InteractionOperatorType = BenchOperators;
+}
+
+
+
+
+#[derive(Clone)]
+enum BenchTerm {
+    Action(usize),
+    Seq(Box<BenchTerm>,Box<BenchTerm>),
+    Loop(Box<BenchTerm>)
+}
+
+// the default destructor is recursive
+impl Drop for BenchTerm {
+    fn drop(&mut self) {
+        let mut to_drop = vec![];
+        match self {
+            BenchTerm::Action(_) => {},
+            BenchTerm::Seq(i1, i2) => {
+                to_drop.push(std::mem::replace(i1.as_mut(), BenchTerm::Action(0)));
+                to_drop.push(std::mem::replace(i2.as_mut(), BenchTerm::Action(0)));
+            },
+            BenchTerm::Loop(i1) => {
+                to_drop.push(std::mem::replace(i1.as_mut(), BenchTerm::Action(0)));
+            }
+        }
+        while let Some(mut term) = to_drop.pop() {
+            match &mut term {
+                BenchTerm::Action(_) => {},
+                BenchTerm::Seq(i1, i2) => {
+                    to_drop.push(std::mem::replace(i1.as_mut(), BenchTerm::Action(0)));
+                    to_drop.push(std::mem::replace(i2.as_mut(), BenchTerm::Action(0)));
+                },
+                BenchTerm::Loop(i1) => {
+                    to_drop.push(std::mem::replace(i1.as_mut(), BenchTerm::Action(0)));
+                }
+            }
+        }
+    }
+}
+
+impl FromInteractionTermToInternalRepresentation<BenchCioII> for BenchTerm {
+
+    fn get_operator_at_root(&self) -> Option<BenchOperators> {
+        match self {
+            BenchTerm::Action(_) => None,
+            BenchTerm::Seq(_,_) => Some(BenchOperators::Seq),
+            BenchTerm::Loop(_) => Some(BenchOperators::Loop)
+        }
+    }
+
+    fn get_subinteractions(&self) -> Vec<&Self> {
+        match self {
+            BenchTerm::Action(_) => vec![],
+            BenchTerm::Seq(i1,i2) => vec![i1,i2],
+            BenchTerm::Loop(i1) => vec![i1]
+        }
+    }
+
+    fn identify_pattern_at_interaction_leaf(&self) -> Option<usize> {
+        match self {
+            BenchTerm::Action(x) => Some(*x),
+            _ => None
+        }
+    }
+
+    fn merge_patterns_under_operator_if_possible(
+        _parent_op : &BenchOperators,
+        _p1 : &usize,
+        _p2 : &usize
+    ) -> Option<usize> {
+        None
+    }
+
+}
+
+impl FromInternalRepresentationToInteractionTerm<BenchCioII> for BenchTerm {
+
+    fn get_empty_interaction() -> Self {
+        BenchTerm::Action(0)
+    }
+
+    fn instantiate_interaction_under_operator(operator : &BenchOperators, sub_ints : &mut Vec<Self>) -> Option<Self> {
+        match (operator,sub_ints.len()) {
+            (BenchOperators::Seq,2) => {
+                let i2 = sub_ints.pop().unwrap();
+                let i1 = sub_ints.pop().unwrap();
+                Some(BenchTerm::Seq(Box::new(i1),Box::new(i2)))
+            },
+            (BenchOperators::Loop,1) => {
+                Some(BenchTerm::Loop(Box::new(sub_ints.pop().unwrap())))
+            },
+            _ => None
+        }
+    }
+
+    fn transform_pattern_to_term(pattern : &usize) -> Self {
+        BenchTerm::Action(*pattern)
+    }
+
+}
+
+
+
+struct BenchParser {}
+
+impl DelimitedInteractionLanguageParser for BenchParser {
+
+    fn left_parenthesis_char(&self) -> char {
+        '('
+    }
+
+    fn right_parenthesis_char(&self) -> char {
+        ')'
+    }
+
+    fn separator_char(&self) -> char {
+        ','
+    }
+
+}
+
+impl ContextAwareInteractionParser<BenchCioII> for BenchParser {
+
+    fn parse_operator<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<&'a str, BenchOperators, E> {
+        alt(
+            (
+                value(BenchOperators::Seq, tag("seq")),
+                value(BenchOperators::Loop, tag("loop"))
+            )
+        )(input)
+    }
+
+    fn parse_explicit_pattern<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<&'a str, usize, E> {
+        map(digit1, |x : &str| x.parse::<usize>().unwrap())(input)
+    }
+
+}
+
+
+
+/** 
+ * The former folding of associative operands, which is recursive and removes the first operand at each step.
+ * **/
+fn fold_by_removing_first_operand(operands : &mut Vec<BenchTerm>) -> BenchTerm {
+    if operands.len() == 2 {
+        let i2 = operands.pop().unwrap();
+        let i1 = operands.pop().unwrap();
+        BenchTerm::Seq(Box::new(i1),Box::new(i2))
+    } else if operands.len() == 1 {
+        operands.pop().unwrap()
+    } else {
+        let i1 = operands.remove(0);
+        let i2 = fold_by_removing_first_operand(operands);
+        BenchTerm::Seq(Box::new(i1),Box::new(i2))
+    }
+}
+
+fn measure<T>(mut f : impl FnMut() -> T) -> (Duration,T) {
+    let start = Instant::now();
+    let got = f();
+    (start.elapsed(),got)
+}
+
+
+
+fn main() {
+    println!("folding n operands under seq");
+    for num_operands in [1_000, 4_000, 16_000] {
+        let (former,former_term) = measure(|| fold_by_removing_first_operand(&mut (0..num_operands).map(BenchTerm::Action).collect()));
+        let (current,current_term) = measure(|| BenchTerm::fold_associative_operands_recursively(&BenchOperators::Seq, &mut (0..num_operands).map(BenchTerm::Action).collect()));
+        println!("    n = {:>7} : former {:>10.3?} / current {:>10.3?}", num_operands, former, current);
+        drop(former_term);
+        drop(current_term);
+    }
+    for num_operands in [100_000, 1_000_000] {
+        let (current,current_term) = measure(|| BenchTerm::fold_associative_operands_recursively(&BenchOperators::Seq, &mut (0..num_operands).map(BenchTerm::Action).collect()));
+        println!("    n = {:>7} : former (not run, quadratic and recursive) / current {:>10.3?}", num_operands, current);
+        drop(current_term);
+    }
+    // ***
+    println!("converting n nested loops to the internal representation and back");
+    for depth in [1_000, 100_000, 1_000_000] {
+        let mut term = BenchTerm::Action(0);
+        for _ in 0..depth {
+            term = BenchTerm::Loop(Box::new(term));
+        }
+        let (to_repr,int_repr) = measure(|| term.to_io_repr(true, true));
+        let (from_repr,converted_back) = measure(|| BenchTerm::from_io_repr(&int_repr));
+        println!("    n = {:>7} : to_io_repr {:>10.3?} / from_io_repr {:>10.3?}", depth, to_repr, from_repr);
+        drop(int_repr);
+        drop(converted_back);
+    }
+    // ***
+    println!("parsing n nested loops");
+    let parser = BenchParser{};
+    for depth in [1_000, 100_000, 1_000_000] {
+        let input = format!("{}0{}", "loop(".repeat(depth), ")".repeat(depth));
+        let (parsing,int_repr) = measure(|| parse_interaction_strict::<BenchCioII,BenchParser>(&input, &parser).unwrap());
+        println!("    n = {:>7} : {:>10.3?}", depth, parsing);
+        drop(int_repr);
+    }
+}
@@ -0,0 +1,75 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+use std::fmt;
+
+
+
+/** 
+ * Error that may occur when converting between an interaction language and the internal representation.
+ * **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    // the interaction has more nested operators than the maximal nesting depth given for the conversion
+    NestingTooDeep{
+        max_depth : usize
+    },
+    // an operator of the internal representation is applied to a number of operands that it does not accept
+    ArityMismatch{
+        operator : String,
+        min_operands : usize,
+        max_operands : Option<usize>,
+        got : usize,
+        // the path from the root to the operator, i.e., the indices of the operands that lead to it
+        path : Vec<usize>
+    },
+    // a leaf pattern of the internal representation has no counterpart in the interaction language
+    InvalidPattern{
+        pattern : String,
+        reason : String,
+        // the path from the root to the pattern, i.e., the indices of the operands that lead to it
+        path : Vec<usize>
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::NestingTooDeep { max_depth } => {
+                write!(f, "more than {} nested operators", max_depth)
+            },
+            ConversionError::ArityMismatch { operator, min_operands, max_operands, got, path } => {
+                match max_operands {
+                    None => {
+                        write!(f, "operator '{}' at {:?} expects at least {} operands but got {}", operator, path, min_operands, got)
+                    },
+                    Some(max) if max == min_operands => {
+                        write!(f, "operator '{}' at {:?} expects {} operands but got {}", operator, path, min_operands, got)
+                    },
+                    Some(max) => {
+                        write!(f, "operator '{}' at {:?} expects between {} and {} operands but got {}", operator, path, min_operands, max, got)
+                    }
+                }
+            },
+            ConversionError::InvalidPattern { pattern, reason, path } => {
+                write!(f, "pattern '{}' at {:?} cannot be converted : {}", pattern, path, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
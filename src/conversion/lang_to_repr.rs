@@ -15,12 +15,14 @@ limitations under the License.
 */
 
 
-use crate::internal_representation::{InteractionInternalRepresentation,InteractionOperatorRepresentation,CommonIoInteractionInterface};
+use crate::conversion::error::ConversionError;
+use crate::conversion::nesting::{AssociativeNestingItem, AssociativeNestings};
+use crate::internal_representation::{InteractionInternalRepresentation,InteractionOperatorRepresentation,AnnotatedCommonIoInteractionInterface};
 
 
 
 
-pub trait FromInteractionTermToInternalRepresentation<CioII : CommonIoInteractionInterface> : Sized + Clone {
+pub trait FromInteractionTermToInternalRepresentation<CioII : AnnotatedCommonIoInteractionInterface> : Sized + Clone {
 
     /** 
      * Returns the operator at the root of the interaction term if it is one.
@@ -39,6 +41,14 @@ pub trait FromInteractionTermToInternalRepresentation<CioII : CommonIoInteractio
      * **/
     fn identify_pattern_at_interaction_leaf(&self) -> Option<CioII::InteractionLeafPatternType>;
 
+    /** 
+     * If an annotation is attached to the root of the interaction, returns it together with the annotated interaction.
+     * By default, the interaction language has no annotations.
+     * **/
+    fn identify_annotation_at_root(&self) -> Option<(CioII::InteractionAnnotationType,&Self)> {
+        None
+    }
+
     /** 
      * If possible, merges two patterns that have been found and that are linked by a certain operator.
      * **/
@@ -51,33 +61,25 @@ pub trait FromInteractionTermToInternalRepresentation<CioII : CommonIoInteractio
     /** 
      * A tool function to recursively get all the sub-interactions under an associative operator.
      * For instance, if applied to "f(a,f(b,c))" where "f" is associative, it will return "[a,b,c]".
+     * The sub-interactions are explored using an explicit stack so that deeply nested terms do not overflow the call stack.
      * **/
      fn get_associative_operands_recursively<'a>(
         &'a self,
         considered_associative_operator : &CioII::InteractionOperatorType
     ) -> Vec<&'a Self> {
-        // ***
-        let consider_sub_interactions = match self.get_operator_at_root() {
-            None => {
-                false
-            },
-            Some(got_at_root) => {
-                &got_at_root == considered_associative_operator
-            }
-        };
-        // ***
         let mut operands : Vec<&Self> = Vec::new();
-        // ***
-        if consider_sub_interactions {
-            for sub_int in self.get_subinteractions() {
-                operands.extend( 
-                    sub_int.get_associative_operands_recursively(considered_associative_operator) 
-                );
+        let mut to_explore : Vec<&Self> = vec![self];
+        while let Some(int) = to_explore.pop() {
+            match int.get_operator_at_root() {
+                Some(got_at_root) if &got_at_root == considered_associative_operator => {
+                    // the sub-interactions are explored from left to right
+                    to_explore.extend(int.get_subinteractions().into_iter().rev());
+                },
+                _ => {
+                    operands.push(int);
+                }
             }
-        } else {
-            operands.push(self);
         }
-        // ***
         operands
     }
 
@@ -89,101 +91,277 @@ pub trait FromInteractionTermToInternalRepresentation<CioII : CommonIoInteractio
         merge_patterns : bool,
         flatten_operands_under_associative_operators : bool
     ) -> InteractionInternalRepresentation<CioII> {
-        match self.identify_pattern_at_interaction_leaf() {
-            Some(pattern) => {
-                InteractionInternalRepresentation::LeafPattern(pattern)
-            },
-            None => {
-                // patterns must cover all non-operator symbols (more precisely all operators of arity 0)
-                // so here we must be able to identify the root operator
-                let op_at_root = self.get_operator_at_root().unwrap();
-                let raw_operands = if flatten_operands_under_associative_operators && op_at_root.is_associative() {
-                    self.get_associative_operands_recursively(&op_at_root)
-                } else {
-                    self.get_subinteractions()
-                };
-                let mut operands = vec![];
-                let mut last_pattern : Option<<CioII as CommonIoInteractionInterface>::InteractionLeafPatternType> = None;
-                for raw_op in raw_operands {
-                    let operand_io_repr = raw_op.to_io_repr(
-                        merge_patterns,
-                        flatten_operands_under_associative_operators
-                    );
-                    match operand_io_repr {
-                        InteractionInternalRepresentation::LeafPattern(pt) => {
-                            match last_pattern {
-                                Some(prev_pt) => {
-                                    if merge_patterns {
-                                        match Self::merge_patterns_under_operator_if_possible(
-                                            &op_at_root, 
-                                            &prev_pt, 
-                                            &pt
-                                        ) {
-                                            None => {
-                                                operands.push(
-                                                    InteractionInternalRepresentation::LeafPattern(prev_pt)
-                                                );
-                                                last_pattern = Some(pt);
-                                            },
-                                            Some(merged_pt) => {
-                                                last_pattern = Some(merged_pt);
-                                            }
-                                        }
-                                    } else {
-                                        operands.push(
-                                            InteractionInternalRepresentation::LeafPattern(prev_pt)
-                                        );
-                                        last_pattern = Some(pt);
-                                    }
-                                },
-                                None => {
-                                    last_pattern = Some(pt);
-                                }
-                            }
-                        },
-                        _ => {
-                            if let Some(prev_pt) = last_pattern {
-                                operands.push(
-                                    InteractionInternalRepresentation::LeafPattern(prev_pt)
-                                );
-                                last_pattern = None;
-                            }
-                            operands.push(operand_io_repr);
-                        }
-                    }
-                }
-                // ***
-                if let Some(pt) = last_pattern {
-                    operands.push(
-                        InteractionInternalRepresentation::LeafPattern(pt)
-                    );
-                }
-                // ***
-                debug_assert!(!operands.is_empty());
-                if operands.len() == 1 && op_at_root.arity() >= 2 {
-                    // here we have an associative binary operator which subterms amounts to a single pattern
-                    // so we only keep the pattern
-                    operands.pop().unwrap()
-                } else {
-                    InteractionInternalRepresentation::Operator(op_at_root, operands)
-                }
-            }
-        }
+        // without a maximal nesting depth, the conversion cannot fail
+        self.try_to_io_repr(merge_patterns, flatten_operands_under_associative_operators, None).unwrap()
+     }
+
+    /** 
+     * Conversion from the concrete interaction language to this crate's internal representation,
+     * returning an error if the term has more than *max_nesting_depth* nested operators 
+     * (those under which operands are flattened being counted once, and those whose operands are merged into a single pattern being counted).
+     * The term is explored using an explicit stack so that deeply nested terms do not overflow the call stack.
+     * **/
+     fn try_to_io_repr(
+        &self,
+        merge_patterns : bool,
+        flatten_operands_under_associative_operators : bool,
+        max_nesting_depth : Option<usize>
+    ) -> Result<InteractionInternalRepresentation<CioII>,ConversionError> {
+        convert_to_io_repr(self, merge_patterns, flatten_operands_under_associative_operators, max_nesting_depth, None)
+     }
+
+    /** 
+     * Conversion from the concrete interaction language to this crate's internal representation,
+     * also returning the original nesting of the operands that have been flattened under associative operators,
+     * so that *from_io_repr_restoring_nestings* may rebuild the exact same term.
+     * The nesting of an operator is not recorded if some of its operands have been merged into a single pattern.
+     * **/
+     fn to_io_repr_recording_nestings(
+        &self,
+        merge_patterns : bool,
+        flatten_operands_under_associative_operators : bool
+    ) -> (InteractionInternalRepresentation<CioII>,AssociativeNestings) {
+        let mut nestings = AssociativeNestings::new();
+        // without a maximal nesting depth, the conversion cannot fail
+        let int_repr = convert_to_io_repr(
+            self, 
+            merge_patterns, 
+            flatten_operands_under_associative_operators, 
+            None, 
+            Some(&mut nestings)
+        ).unwrap();
+        (int_repr,nestings)
      }
 
 }
 
 
 
+/** 
+ * Gets all the sub-interactions under an associative operator as in *get_associative_operands_recursively*,
+ * along with the description of how they were nested.
+ * **/
+fn get_associative_operands_and_nesting<'a,CioII,Term>
+    (
+        term : &'a Term,
+        considered_associative_operator : &CioII::InteractionOperatorType
+    ) -> (Vec<&'a Term>,Vec<AssociativeNestingItem>)
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Term : FromInteractionTermToInternalRepresentation<CioII>
+{
+    let mut operands : Vec<&Term> = Vec::new();
+    let mut nesting : Vec<AssociativeNestingItem> = Vec::new();
+    let mut to_explore : Vec<&Term> = vec![term];
+    while let Some(int) = to_explore.pop() {
+        match int.get_operator_at_root() {
+            Some(got_at_root) if &got_at_root == considered_associative_operator => {
+                let sub_ints = int.get_subinteractions();
+                nesting.push(AssociativeNestingItem::Application(sub_ints.len()));
+                to_explore.extend(sub_ints.into_iter().rev());
+            },
+            _ => {
+                nesting.push(AssociativeNestingItem::Operand);
+                operands.push(int);
+            }
+        }
+    }
+    (operands,nesting)
+}
 
 
 
+/** 
+ * The conversion behind *try_to_io_repr* and *to_io_repr_recording_nestings*,
+ * which records the original nesting of flattened operands only if *nestings* is given.
+ * **/
+fn convert_to_io_repr<CioII,Term>
+    (
+        term : &Term,
+        merge_patterns : bool,
+        flatten_operands_under_associative_operators : bool,
+        max_nesting_depth : Option<usize>,
+        mut nestings : Option<&mut AssociativeNestings>
+    ) -> Result<InteractionInternalRepresentation<CioII>,ConversionError>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Term : FromInteractionTermToInternalRepresentation<CioII>
+{
+    let mut stack : Vec<ToIoReprFrame<CioII,Term>> = vec![];
+    let mut to_convert = term;
+    'descend: loop {
+        let mut completed = loop {
+            let mut annotations = vec![];
+            while let Some((annotation,annotated)) = to_convert.identify_annotation_at_root() {
+                annotations.push(annotation);
+                to_convert = annotated;
+            }
+            if let Some(pattern) = to_convert.identify_pattern_at_interaction_leaf() {
+                break InteractionInternalRepresentation::LeafPattern(pattern).with_annotations(annotations);
+            }
+            // patterns must cover all non-operator symbols
+            // so here we must be able to identify the root operator (which may accept no operands)
+            let op_at_root = to_convert.get_operator_at_root().unwrap();
+            if let Some(max_depth) = max_nesting_depth {
+                if stack.len() >= max_depth {
+                    return Err(ConversionError::NestingTooDeep { max_depth });
+                }
+            }
+            let mut nesting = None;
+            let raw_operands = if flatten_operands_under_associative_operators && op_at_root.is_associative() {
+                if nestings.is_some() {
+                    let (raw_operands,got_nesting) = get_associative_operands_and_nesting(to_convert, &op_at_root);
+                    let num_applications = got_nesting.iter()
+                        .filter(|item| matches!(item, AssociativeNestingItem::Application(_)))
+                        .count();
+                    if num_applications > 1 {
+                        nesting = Some(got_nesting);
+                    }
+                    raw_operands
+                } else {
+                    to_convert.get_associative_operands_recursively(&op_at_root)
+                }
+            } else {
+                to_convert.get_subinteractions()
+            };
+            // the index at which the operator will be found among the operands of its parent
+            let index_in_parent = match stack.last() {
+                Some(parent) => parent.operands.len(),
+                None => 0
+            };
+            let num_raw_operands = raw_operands.len();
+            let mut raw_operands = raw_operands.into_iter();
+            let first_operand = raw_operands.next();
+            stack.push(
+                ToIoReprFrame{
+                    operator:op_at_root,
+                    raw_operands,
+                    operands:vec![],
+                    index_in_parent,
+                    num_raw_operands,
+                    nesting,
+                    annotations
+                }
+            );
+            match first_operand {
+                Some(raw_op) => {
+                    to_convert = raw_op;
+                },
+                None => {
+                    break close_frame(&mut stack, &mut nestings);
+                }
+            }
+        };
+        loop {
+            let Some(frame) = stack.last_mut() else {
+                return Ok(completed);
+            };
+            frame.add_operand::<Term>(completed, merge_patterns);
+            match frame.raw_operands.next() {
+                Some(raw_op) => {
+                    to_convert = raw_op;
+                    continue 'descend;
+                },
+                None => {
+                    completed = close_frame(&mut stack, &mut nestings);
+                }
+            }
+        }
+    }
+}
 
 
 
+/** 
+ * Pops the frame at the top of the stack and returns the corresponding internal representation,
+ * recording the original nesting of its operands if required.
+ * **/
+fn close_frame<CioII : AnnotatedCommonIoInteractionInterface,Term>
+    (
+        stack : &mut Vec<ToIoReprFrame<CioII,Term>>,
+        nestings : &mut Option<&mut AssociativeNestings>
+    ) -> InteractionInternalRepresentation<CioII> 
+{
+    let mut frame = stack.pop().unwrap();
+    let nesting = frame.nesting.take();
+    let annotations = std::mem::take(&mut frame.annotations);
+    let num_raw_operands = frame.num_raw_operands;
+    let index_in_parent = frame.index_in_parent;
+    let int_repr = frame.finish();
+    if let (Some(nestings),Some(nesting)) = (nestings.as_mut(),nesting) {
+        match &int_repr {
+            // no operands have been merged
+            InteractionInternalRepresentation::Operator(_, operands) if operands.len() == num_raw_operands => {
+                let mut path : Vec<usize> = vec![];
+                for (depth,ancestor) in stack.iter().enumerate() {
+                    if depth > 0 {
+                        path.push(ancestor.index_in_parent);
+                    }
+                    // an annotated node has the annotated sub-interaction at index 0
+                    path.extend(ancestor.annotations.iter().map(|_| 0));
+                }
+                if !stack.is_empty() {
+                    path.push(index_in_parent);
+                }
+                path.extend(annotations.iter().map(|_| 0));
+                nestings.insert(path, nesting);
+            },
+            _ => {}
+        }
+    }
+    int_repr.with_annotations(annotations)
+}
 
 
 
+/** 
+ * An operator of the concrete interaction language whose operands are being converted.
+ * **/
+struct ToIoReprFrame<'a,CioII : AnnotatedCommonIoInteractionInterface,Term> {
+    operator : CioII::InteractionOperatorType,
+    raw_operands : std::vec::IntoIter<&'a Term>,
+    operands : Vec<InteractionInternalRepresentation<CioII>>,
+    index_in_parent : usize,
+    num_raw_operands : usize,
+    // the original nesting of the operands, if it must be recorded
+    nesting : Option<Vec<AssociativeNestingItem>>,
+    // the annotations attached to the operator, from the outermost to the innermost
+    annotations : Vec<CioII::InteractionAnnotationType>
+}
 
+impl<'a,CioII : AnnotatedCommonIoInteractionInterface,Term> ToIoReprFrame<'a,CioII,Term> {
 
+    fn add_operand<T>(&mut self, operand_io_repr : InteractionInternalRepresentation<CioII>, merge_patterns : bool)
+    where 
+        T : FromInteractionTermToInternalRepresentation<CioII>
+    {
+        if merge_patterns {
+            if let (
+                Some(InteractionInternalRepresentation::LeafPattern(prev_pt)),
+                InteractionInternalRepresentation::LeafPattern(pt)
+            ) = (self.operands.last_mut(), &operand_io_repr) {
+                if let Some(merged_pt) = T::merge_patterns_under_operator_if_possible(
+                    &self.operator, 
+                    prev_pt, 
+                    pt
+                ) {
+                    *prev_pt = merged_pt;
+                    return;
+                }
+            }
+        }
+        self.operands.push(operand_io_repr);
+    }
+
+    fn finish(mut self) -> InteractionInternalRepresentation<CioII> {
+        debug_assert!(!self.operands.is_empty() || self.operator.accepts_number_of_operands(0));
+        if self.operands.len() == 1 && !self.operator.accepts_number_of_operands(1) {
+            // here we have an operator (e.g. associative and binary) which subterms amount to a single pattern
+            // so we only keep the pattern
+            self.operands.pop().unwrap()
+        } else {
+            InteractionInternalRepresentation::Operator(self.operator, self.operands)
+        }
+    }
 
+}
@@ -16,4 +16,6 @@ limitations under the License.
 
 
 pub mod repr_to_lang;
-pub mod lang_to_repr;
\ No newline at end of file
+pub mod lang_to_repr;
+pub mod error;
+pub mod nesting;
\ No newline at end of file
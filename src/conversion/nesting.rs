@@ -0,0 +1,58 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+use std::collections::BTreeMap;
+
+
+
+/** 
+ * The shape of the terms built when folding the operands of an associative operator.
+ * For instance, with "f" a binary associative operator and operands "[a,b,c,d]":
+ * - *Right* builds "f(a,f(b,f(c,d)))"
+ * - *Left* builds "f(f(f(a,b),c),d)"
+ * - *Balanced* builds "f(f(a,b),f(c,d))"
+ * **/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AssociativeFoldStrategy {
+    #[default]
+    Right,
+    Left,
+    Balanced
+}
+
+
+
+/** 
+ * An element of the original nesting of the operands of an associative operator, in pre-order.
+ * For instance, "f(a,f(b,c))" is described by "[Application(2),Operand,Application(2),Operand,Operand]".
+ * **/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssociativeNestingItem {
+    // one of the flattened operands
+    Operand,
+    // an application of the associative operator to the given number of sub-terms
+    Application(usize)
+}
+
+
+
+/**
+ The original nesting of the flattened operands of the associative operators of an interaction internal representation.
+Each operator is identified by its path from the root, i.e., the indices of the operands that lead to it (the root having the empty path).
+Only the operators which operands were nested in more than one application are recorded.
+ **/
+pub type AssociativeNestings = BTreeMap<Vec<usize>,Vec<AssociativeNestingItem>>;
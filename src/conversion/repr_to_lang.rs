@@ -15,11 +15,13 @@ limitations under the License.
 */
 
 
-use crate::internal_representation::{InteractionInternalRepresentation,InteractionOperatorRepresentation,CommonIoInteractionInterface};
+use crate::conversion::error::ConversionError;
+use crate::conversion::nesting::{AssociativeFoldStrategy, AssociativeNestingItem, AssociativeNestings};
+use crate::internal_representation::{InteractionInternalRepresentation,InteractionOperatorRepresentation,AnnotatedCommonIoInteractionInterface};
 
 
 
-pub trait FromInternalRepresentationToInteractionTerm<CioII : CommonIoInteractionInterface> : Sized + Clone {
+pub trait FromInternalRepresentationToInteractionTerm<CioII : AnnotatedCommonIoInteractionInterface> : Sized + Clone {
 
     /**
      * Returns the empty interaction of the given interaction language.
@@ -30,7 +32,7 @@ pub trait FromInternalRepresentationToInteractionTerm<CioII : CommonIoInteractio
      /**
       * A tool function to help construct interaction terms.
       * For instance, given operator "f" of arity 2, this function applied to "f,vec![i1,i2]" should return "Some(f(i1,i2))"
-      * Should return None when the arity of the operator does not match the size of the input Vec.
+      * Should return None when the size of the input Vec is not within the bounds given by the *min_arity* and *max_arity* of the operator.
       * **/
      fn instantiate_interaction_under_operator(operator : &CioII::InteractionOperatorType, sub_ints : &mut Vec<Self>) -> Option<Self>;
 
@@ -39,57 +41,503 @@ pub trait FromInternalRepresentationToInteractionTerm<CioII : CommonIoInteractio
      * **/
     fn transform_pattern_to_term(pattern : &CioII::InteractionLeafPatternType) -> Self;
 
+    /**
+     * Obtain an interaction term from a description of a pattern of communication,
+     * or the reason why the pattern has no counterpart in the interaction language.
+     * By default, all patterns can be transformed.
+     * **/
+    fn try_transform_pattern_to_term(pattern : &CioII::InteractionLeafPatternType) -> Result<Self,String> {
+        Ok(Self::transform_pattern_to_term(pattern))
+    }
+
+    /**
+     * Attaches an annotation of the internal representation to an interaction term.
+     * By default, annotations have no counterpart in the interaction language and are dropped.
+     * **/
+    fn annotate_term(_annotation : &CioII::InteractionAnnotationType, term : Self) -> Self {
+        term
+    }
+
     /** 
-     * A tool function to recursively fold a number of interactions under a binary associative operator.
+     * A tool function to fold a number of interactions under an associative operator.
      * For instance, if applied to "(f,vec![a,b,c,d])" where "f" is a binary associative operator, it will return "f(a,f(b,f(c,d)))".
+     * If "f" accepts at most 3 operands, it will return "f(a,b,f(c,d))", and if it accepts 4 or more, "f(a,b,c,d)".
+     * The nested applications are built from the innermost one, in linear time and without recursion.
      * **/
     fn fold_associative_operands_recursively(operator : &CioII::InteractionOperatorType, operands : &mut Vec<Self>) -> Self {
-        //assert!(operator.is_associative());
-        let ops_num = operands.len();
-        if ops_num == 2 {
-            let i2 = operands.pop().unwrap();
-            let i1 = operands.pop().unwrap();
-            Self::instantiate_interaction_under_operator(operator,&mut vec![i1,i2]).unwrap()
-        } else if ops_num == 1 {
-            operands.pop().unwrap().clone()
-        } else if ops_num == 0 {
-            Self::get_empty_interaction()
-        } else {
-            let i1 = operands.remove(0);
-            let i2 = Self::fold_associative_operands_recursively(operator,operands);
-            Self::instantiate_interaction_under_operator(operator,&mut vec![i1,i2]).unwrap()
+        fold_to_the_right::<CioII,Self>(operator, operands).unwrap()
+    }
+
+    /** 
+     * A tool function to fold a number of interactions under an associative operator, nesting the applications on the left.
+     * For instance, if applied to "(f,vec![a,b,c,d])" where "f" is a binary associative operator, it will return "f(f(f(a,b),c),d)".
+     * If "f" accepts at most 3 operands, it will return "f(f(a,b,c),d)", and if it accepts 4 or more, "f(a,b,c,d)".
+     * **/
+    fn fold_associative_operands_to_the_left(operator : &CioII::InteractionOperatorType, operands : &mut Vec<Self>) -> Self {
+        fold_to_the_left::<CioII,Self>(operator, operands).unwrap()
+    }
+
+    /** 
+     * A tool function to fold a number of interactions under an associative operator so that the resulting term has a minimal depth.
+     * For instance, if applied to "(f,vec![a,b,c,d,e])" where "f" is a binary associative operator, it will return "f(f(f(a,b),f(c,d)),e)".
+     * The operands are grouped level by level, each group having as many operands as the operator accepts.
+     * **/
+    fn fold_associative_operands_in_balanced_tree(operator : &CioII::InteractionOperatorType, operands : &mut Vec<Self>) -> Self {
+        fold_in_balanced_tree::<CioII,Self>(operator, operands).unwrap()
+    }
+
+    /** 
+     * Folds a number of interactions under an associative operator, with the shape given by the *strategy*.
+     * **/
+    fn fold_associative_operands(
+        operator : &CioII::InteractionOperatorType, 
+        operands : &mut Vec<Self>,
+        strategy : &AssociativeFoldStrategy
+    ) -> Self {
+        match strategy {
+            AssociativeFoldStrategy::Right => {
+                Self::fold_associative_operands_recursively(operator, operands)
+            },
+            AssociativeFoldStrategy::Left => {
+                Self::fold_associative_operands_to_the_left(operator, operands)
+            },
+            AssociativeFoldStrategy::Balanced => {
+                Self::fold_associative_operands_in_balanced_tree(operator, operands)
+            }
         }
     }
 
      /** 
       * Conversion from this crate's internal representation to the concrete interaction language.
+      * The internal representation is explored using an explicit stack so that deeply nested interactions do not overflow the call stack.
       * **/
      fn from_io_repr(io_int_repr : &InteractionInternalRepresentation<CioII>) -> Self {
-        match io_int_repr {
-            InteractionInternalRepresentation::LeafPattern(leaf_pattern) => {
-                Self::transform_pattern_to_term(leaf_pattern)
-            },
-            InteractionInternalRepresentation::Operator(operator, sub_ints_reprs) => {
-                let mut sub_ints : Vec<Self> = sub_ints_reprs.iter().map(Self::from_io_repr).collect();
-                if operator.is_associative() {
-                    Self::fold_associative_operands_recursively(operator, &mut sub_ints)
-                } else {
-                    Self::instantiate_interaction_under_operator(operator,&mut sub_ints).unwrap()
+        convert_from_io_repr(io_int_repr, &AssociativeFoldStrategy::Right, None, false).unwrap_or_else(|err| panic!("{}", err))
+     }
+
+     /** 
+      * Conversion from this crate's internal representation to the concrete interaction language,
+      * returning an error instead of panicking if an operator is applied to a number of operands it does not accept
+      * (e.g., several operands under an associative operator which does not accept at least two of them to fold them)
+      * or if a pattern cannot be transformed to a term (see *try_transform_pattern_to_term*).
+      * This allows validating internal representations which have been built programmatically.
+      * **/
+     fn try_from_io_repr(io_int_repr : &InteractionInternalRepresentation<CioII>) -> Result<Self,ConversionError> {
+        convert_from_io_repr(io_int_repr, &AssociativeFoldStrategy::Right, None, true)
+     }
+
+     /** 
+      * Conversion from this crate's internal representation to the concrete interaction language,
+      * the operands of associative operators being folded with the shape given by the *strategy*.
+      * **/
+     fn from_io_repr_with_fold_strategy(
+        io_int_repr : &InteractionInternalRepresentation<CioII>,
+        strategy : &AssociativeFoldStrategy
+    ) -> Self {
+        convert_from_io_repr(io_int_repr, strategy, None, false).unwrap_or_else(|err| panic!("{}", err))
+     }
+
+     /** 
+      * Conversion from this crate's internal representation to the concrete interaction language,
+      * the operands of associative operators being nested as they were in the term from which the *nestings* have been recorded
+      * (see *to_io_repr_recording_nestings*).
+      * The operands of the operators which nesting is not recorded, or does not fit their operands, are folded with the shape given by the *strategy*.
+      * **/
+     fn from_io_repr_restoring_nestings(
+        io_int_repr : &InteractionInternalRepresentation<CioII>,
+        nestings : &AssociativeNestings,
+        strategy : &AssociativeFoldStrategy
+    ) -> Self {
+        convert_from_io_repr(io_int_repr, strategy, Some(nestings), false).unwrap_or_else(|err| panic!("{}", err))
+     }
+
+}
+
+
+
+/** 
+ * The conversion behind *from_io_repr* and its variants.
+ * If *check_operands* is set, operators must be applied to a number of operands they accept in the internal representation
+ * (otherwise, for instance, an associative operator applied to a single operand is replaced by that operand).
+ * The internal representation is explored using an explicit stack so that deeply nested interactions do not overflow the call stack.
+ * **/
+fn convert_from_io_repr<CioII,Term>
+    (
+        io_int_repr : &InteractionInternalRepresentation<CioII>,
+        strategy : &AssociativeFoldStrategy,
+        nestings : Option<&AssociativeNestings>,
+        check_operands : bool
+    ) -> Result<Term,ConversionError>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Term : FromInternalRepresentationToInteractionTerm<CioII>
+{
+    let mut stack : Vec<FromIoReprFrame<CioII,Term>> = vec![];
+    // the path from the root to the sub-interaction being converted
+    let mut path : Vec<usize> = vec![];
+    let mut to_convert = io_int_repr;
+    'descend: loop {
+        let mut completed = loop {
+            match to_convert {
+                InteractionInternalRepresentation::LeafPattern(leaf_pattern) => {
+                    match Term::try_transform_pattern_to_term(leaf_pattern) {
+                        Ok(term) => {
+                            break term;
+                        },
+                        Err(reason) => {
+                            return Err(
+                                ConversionError::InvalidPattern { 
+                                    pattern : format!("{:?}", leaf_pattern), 
+                                    reason, 
+                                    path 
+                                }
+                            );
+                        }
+                    }
+                },
+                InteractionInternalRepresentation::Operator(operator, sub_ints_reprs) => {
+                    let mut sub_ints_reprs = sub_ints_reprs.iter();
+                    match sub_ints_reprs.next() {
+                        Some(first) => {
+                            stack.push(FromIoReprFrame{root:FromIoReprRoot::Operator(operator),sub_ints_reprs,sub_ints:vec![]});
+                            path.push(0);
+                            to_convert = first;
+                        },
+                        None => {
+                            break apply_operator_to_terms(operator, vec![], strategy, None, check_operands, &path)?;
+                        }
+                    }
+                },
+                InteractionInternalRepresentation::Annotated(annotation, annotated) => {
+                    let sub_ints_reprs = [].iter();
+                    stack.push(FromIoReprFrame{root:FromIoReprRoot::Annotation(annotation),sub_ints_reprs,sub_ints:vec![]});
+                    path.push(0);
+                    to_convert = annotated;
+                }
+            }
+        };
+        loop {
+            let Some(frame) = stack.last_mut() else {
+                return Ok(completed);
+            };
+            frame.sub_ints.push(completed);
+            match frame.sub_ints_reprs.next() {
+                Some(next) => {
+                    *path.last_mut().unwrap() = frame.sub_ints.len();
+                    to_convert = next;
+                    continue 'descend;
+                },
+                None => {
+                    let mut frame = stack.pop().unwrap();
+                    path.pop();
+                    completed = match frame.root {
+                        FromIoReprRoot::Operator(operator) => {
+                            let nesting = nestings.and_then(|nestings| nestings.get(&path));
+                            apply_operator_to_terms(operator, frame.sub_ints, strategy, nesting, check_operands, &path)?
+                        },
+                        FromIoReprRoot::Annotation(annotation) => {
+                            Term::annotate_term(annotation, frame.sub_ints.pop().unwrap())
+                        }
+                    };
                 }
             }
+        }
+    }
+}
+
+
 
+/** 
+ * Folds operands to the right (see *fold_associative_operands_recursively*),
+ * returning None if the interaction language does not accept one of the applications
+ * or if several operands are folded under an operator which does not accept at least two operands.
+ * **/
+fn fold_to_the_right<CioII,Term>
+    (
+        operator : &CioII::InteractionOperatorType,
+        operands : &mut Vec<Term>
+    ) -> Option<Term>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Term : FromInternalRepresentationToInteractionTerm<CioII>
+{
+    let ops_num = operands.len();
+    if ops_num == 1 {
+        return operands.pop();
+    } 
+    if ops_num == 0 {
+        return Some(Term::get_empty_interaction());
+    }
+    let max_arity = match operator.max_arity() {
+        Some(max) if max < 2 => {
+            return None;
+        },
+        Some(max) if ops_num > max => {
+            max
+        },
+        _ => {
+            let mut sub_ints : Vec<Term> = std::mem::take(operands);
+            return Term::instantiate_interaction_under_operator(operator,&mut sub_ints);
         }
-     }
+    };
+    // each application but the innermost one has *max_arity - 1* direct operands, the last operand being the next application
+    let num_direct_operands = max_arity - 1;
+    let num_outer_applications = (ops_num - max_arity).div_ceil(num_direct_operands);
+    let mut innermost_operands = operands.split_off(num_outer_applications*num_direct_operands);
+    let mut folded = Term::instantiate_interaction_under_operator(operator,&mut innermost_operands)?;
+    while !operands.is_empty() {
+        let mut sub_ints = operands.split_off(operands.len() - num_direct_operands);
+        sub_ints.push(folded);
+        folded = Term::instantiate_interaction_under_operator(operator,&mut sub_ints)?;
+    }
+    Some(folded)
+}
+
+
+
+/** 
+ * Folds operands to the left (see *fold_associative_operands_to_the_left*),
+ * returning None if the interaction language does not accept one of the applications
+ * or if several operands are folded under an operator which does not accept at least two operands.
+ * **/
+fn fold_to_the_left<CioII,Term>
+    (
+        operator : &CioII::InteractionOperatorType,
+        operands : &mut Vec<Term>
+    ) -> Option<Term>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Term : FromInternalRepresentationToInteractionTerm<CioII>
+{
+    let ops_num = operands.len();
+    if ops_num == 1 {
+        return operands.pop();
+    } 
+    if ops_num == 0 {
+        return Some(Term::get_empty_interaction());
+    }
+    let max_arity = match operator.max_arity() {
+        Some(max) if max < 2 => {
+            return None;
+        },
+        Some(max) if ops_num > max => {
+            max
+        },
+        _ => {
+            let mut sub_ints : Vec<Term> = std::mem::take(operands);
+            return Term::instantiate_interaction_under_operator(operator,&mut sub_ints);
+        }
+    };
+    let mut remaining = std::mem::take(operands).into_iter();
+    let mut sub_ints : Vec<Term> = remaining.by_ref().take(max_arity).collect();
+    let mut folded = Term::instantiate_interaction_under_operator(operator,&mut sub_ints)?;
+    loop {
+        // each application but the innermost one has the previous application as its first operand
+        let mut sub_ints : Vec<Term> = vec![folded];
+        sub_ints.extend(remaining.by_ref().take(max_arity - 1));
+        if sub_ints.len() == 1 {
+            return sub_ints.pop();
+        }
+        folded = Term::instantiate_interaction_under_operator(operator,&mut sub_ints)?;
+    }
+}
+
+
+
+/** 
+ * Folds operands in a balanced tree (see *fold_associative_operands_in_balanced_tree*),
+ * returning None if the interaction language does not accept one of the applications
+ * or if several operands are folded under an operator which does not accept at least two operands.
+ * **/
+fn fold_in_balanced_tree<CioII,Term>
+    (
+        operator : &CioII::InteractionOperatorType,
+        operands : &mut Vec<Term>
+    ) -> Option<Term>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Term : FromInternalRepresentationToInteractionTerm<CioII>
+{
+    let ops_num = operands.len();
+    if ops_num == 1 {
+        return operands.pop();
+    } 
+    if ops_num == 0 {
+        return Some(Term::get_empty_interaction());
+    }
+    let max_arity = match operator.max_arity() {
+        Some(max) if max < 2 => {
+            return None;
+        },
+        Some(max) => {
+            max
+        },
+        None => {
+            ops_num
+        }
+    };
+    let mut level : Vec<Term> = std::mem::take(operands);
+    while level.len() > 1 {
+        let mut next_level : Vec<Term> = Vec::with_capacity(level.len().div_ceil(max_arity));
+        let mut remaining = level.into_iter();
+        loop {
+            let mut sub_ints : Vec<Term> = remaining.by_ref().take(max_arity).collect();
+            match sub_ints.len() {
+                0 => {
+                    break;
+                },
+                // a last isolated operand is grouped at the next level
+                1 => {
+                    next_level.push(sub_ints.pop().unwrap());
+                },
+                _ => {
+                    next_level.push(Term::instantiate_interaction_under_operator(operator,&mut sub_ints)?);
+                }
+            }
+        }
+        level = next_level;
+    }
+    level.pop()
+}
 
 
 
+/** 
+ * An operator (or an annotation) of the internal representation whose operands (or annotated sub-interaction) are being converted.
+ * **/
+struct FromIoReprFrame<'a,CioII : AnnotatedCommonIoInteractionInterface,Term> {
+    root : FromIoReprRoot<'a,CioII>,
+    sub_ints_reprs : std::slice::Iter<'a,InteractionInternalRepresentation<CioII>>,
+    sub_ints : Vec<Term>
+}
 
+enum FromIoReprRoot<'a,CioII : AnnotatedCommonIoInteractionInterface> {
+    Operator(&'a CioII::InteractionOperatorType),
+    Annotation(&'a CioII::InteractionAnnotationType)
 }
 
 
 
+fn apply_operator_to_terms<CioII,Term>
+    (
+        operator : &CioII::InteractionOperatorType,
+        sub_ints : Vec<Term>,
+        strategy : &AssociativeFoldStrategy,
+        nesting : Option<&Vec<AssociativeNestingItem>>,
+        check_operands : bool,
+        path : &[usize]
+    ) -> Result<Term,ConversionError>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Term : FromInternalRepresentationToInteractionTerm<CioII>
+{
+    let mut sub_ints = sub_ints;
+    let num_operands = sub_ints.len();
+    // several operands cannot be folded under an associative operator which does not accept at least two operands
+    let unfoldable = operator.is_associative() && num_operands >= 2 && matches!(operator.max_arity(), Some(max) if max < 2);
+    let applied = if unfoldable || (check_operands && !operator.accepts_number_of_operands(num_operands)) {
+        None
+    } else if operator.is_associative() && (num_operands >= 2 || !operator.accepts_number_of_operands(num_operands)) {
+        // an associative operator which accepts a single operand is kept as is
+        match nesting {
+            Some(nesting) if nesting_fits_operands(operator, num_operands, nesting) => {
+                restore_nesting(operator, sub_ints, nesting)
+            },
+            _ if !check_operands => {
+                // the folding functions of the interaction language may have been redefined
+                Some(Term::fold_associative_operands(operator, &mut sub_ints, strategy))
+            },
+            _ => {
+                match strategy {
+                    AssociativeFoldStrategy::Right => fold_to_the_right(operator, &mut sub_ints),
+                    AssociativeFoldStrategy::Left => fold_to_the_left(operator, &mut sub_ints),
+                    AssociativeFoldStrategy::Balanced => fold_in_balanced_tree(operator, &mut sub_ints)
+                }
+            }
+        }
+    } else {
+        Term::instantiate_interaction_under_operator(operator,&mut sub_ints)
+    };
+    applied.ok_or_else(|| {
+        let (min_operands,max_operands) = if unfoldable {
+            (operator.min_arity(),operator.max_arity())
+        } else {
+            operator.get_operands_bounds_in_internal_representation()
+        };
+        ConversionError::ArityMismatch { 
+            operator : format!("{:?}", operator), 
+            min_operands, 
+            max_operands, 
+            got : num_operands, 
+            path : path.to_vec() 
+        }
+    })
+}
 
 
 
+/** 
+ * Checks that a recorded nesting describes a well-formed term with exactly *num_operands* operands,
+ * in which each application of the *operator* has a number of sub-terms that the operator accepts.
+ * **/
+fn nesting_fits_operands<Operator : InteractionOperatorRepresentation>
+    (
+        operator : &Operator,
+        num_operands : usize,
+        nesting : &[AssociativeNestingItem]
+    ) -> bool
+{
+    let mut num_operands_in_nesting = 0;
+    // the number of sub-terms built when reading the nesting from its end
+    let mut num_built : usize = 0;
+    for item in nesting.iter().rev() {
+        match item {
+            AssociativeNestingItem::Operand => {
+                num_operands_in_nesting += 1;
+                num_built += 1;
+            },
+            AssociativeNestingItem::Application(num_sub_terms) => {
+                if *num_sub_terms > num_built || !operator.accepts_number_of_operands(*num_sub_terms) {
+                    return false;
+                }
+                num_built = num_built - num_sub_terms + 1;
+            }
+        }
+    }
+    num_built == 1 && num_operands_in_nesting == num_operands
+}
+
 
 
+/** 
+ * Builds the term described by a recorded nesting (which fits the operands, see *nesting_fits_operands*),
+ * reading it from its end so that the innermost applications are built first.
+ * Returns None if the interaction language does not accept one of the applications.
+ * **/
+fn restore_nesting<CioII,Term>
+    (
+        operator : &CioII::InteractionOperatorType,
+        sub_ints : Vec<Term>,
+        nesting : &[AssociativeNestingItem]
+    ) -> Option<Term>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Term : FromInternalRepresentationToInteractionTerm<CioII>
+{
+    let mut sub_ints = sub_ints;
+    // the sub-terms already built, the leftmost one being on top
+    let mut built : Vec<Term> = vec![];
+    for item in nesting.iter().rev() {
+        match item {
+            AssociativeNestingItem::Operand => {
+                built.push(sub_ints.pop().unwrap());
+            },
+            AssociativeNestingItem::Application(num_sub_terms) => {
+                let mut application_sub_ints : Vec<Term> = built.split_off(built.len() - num_sub_terms);
+                application_sub_ints.reverse();
+                built.push(Term::instantiate_interaction_under_operator(operator,&mut application_sub_ints)?);
+            }
+        }
+    }
+    built.pop()
+}
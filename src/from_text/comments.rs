@@ -0,0 +1,49 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use crate::from_text::context_aware_parser::ContextAwareInteractionParser;
+use crate::from_text::error::ParseError;
+use crate::from_text::parse::parse_interaction_and_record_layout;
+use crate::internal_representation::{AnnotatedCommonIoInteractionInterface, InteractionComments, InteractionInternalRepresentation};
+
+
+
+/** 
+ * Parses an interaction and collects the comments it contains so that they can be printed back.
+ * The comments are collected while parsing, as by the default *parse_interaction_inner*.
+ * Comments are attached to the sub-interaction which follows them.
+ * Comments which are not followed by any sub-interaction are trailing comments of the sub-interaction which precedes them 
+ * i.e., the last operand of the enclosing operator (before a right parenthesis) or the root (at the end of the input).
+ * Comments located within leaf patterns or between an operator and its left parenthesis are not collected.
+ * **/
+pub fn parse_interaction_with_comments<CioII,Parser>
+    (
+        input_str : &str,
+        parser : &Parser
+    ) -> 
+        Result<
+            (InteractionInternalRepresentation<CioII>,InteractionComments), 
+            ParseError
+        > 
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : ContextAwareInteractionParser<CioII>
+{
+    let (int_repr,layout) = parse_interaction_and_record_layout(input_str, parser)?;
+    Ok((int_repr,layout.comments))
+}
@@ -15,98 +15,156 @@ limitations under the License.
 */
 
 
-use nom::branch::alt;
-use nom::character::complete::multispace0;
-use nom::combinator::map;
-use nom::multi::separated_list0;
-use nom::sequence::{delimited, tuple};
 use nom::IResult;
-use nom::error::{make_error, ParseError};
 
-use crate::internal_representation::{CommonIoInteractionInterface, InteractionInternalRepresentation, InteractionOperatorRepresentation};
+use crate::internal_representation::{AnnotatedCommonIoInteractionInterface, InteractionInternalRepresentation, OperatorParameters};
+use crate::from_text::error::{InteractionParseError, ParseErrorKind};
 use crate::from_text::util::delimited_lang_parser::DelimitedInteractionLanguageParser;
+use crate::from_text::util::iterative_parser::parse_interaction_without_recursion;
 
 
 
 
 
 
-pub trait ContextAwareInteractionParser<CioII : CommonIoInteractionInterface> : DelimitedInteractionLanguageParser {
+pub trait ContextAwareInteractionParser<CioII : AnnotatedCommonIoInteractionInterface> : DelimitedInteractionLanguageParser {
 
-    fn parse_operator<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> 
+    fn parse_operator<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> 
             IResult<
                 &'a str,
                 CioII::InteractionOperatorType,
                 E
             >;
 
-    fn parse_explicit_pattern<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> 
+    /** 
+     * Applies the argument list which follows an operator (None if there is none) to the operator returned by *parse_operator*.
+     * Returns None if the operator does not accept these parameters.
+     * By default, operators accept no parameters.
+     * **/
+    fn apply_operator_parameters(
+        &self, 
+        operator : CioII::InteractionOperatorType,
+        parameters : Option<OperatorParameters>
+    ) -> Option<CioII::InteractionOperatorType> {
+        match parameters {
+            None => {
+                Some(operator)
+            },
+            Some(_) => {
+                None
+            }
+        }
+    }
+
+    /** 
+     * Parses an operator followed by its optional argument list (see *operator_parameters_delimiters*).
+     * If the operator does not accept the parameters, the error is located at the beginning of the operator 
+     * and has the kind *ParseErrorKind::InvalidOperatorParameters*.
+     * **/
+    fn parse_operator_with_parameters<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> 
+            IResult<
+                &'a str,
+                CioII::InteractionOperatorType,
+                E
+            > {
+        let (after_operator,operator) = self.parse_operator(input)?;
+        let (rem,parameters) = self.parse_operator_parameters(after_operator)?;
+        let has_parameters = parameters.is_some();
+        match self.apply_operator_parameters(operator, parameters) {
+            Some(operator) => {
+                Ok((rem,operator))
+            },
+            None => {
+                let kind = ParseErrorKind::InvalidOperatorParameters { 
+                    operator: input[..(input.len() - after_operator.len())].trim().to_owned(), 
+                    parameters: has_parameters.then(|| after_operator[..(after_operator.len() - rem.len())].trim().to_owned())
+                };
+                Err(nom::Err::Error(E::from_parse_error_kind(input, kind)))
+            }
+        }
+    }
+
+    /** 
+     * Parses a leaf pattern.
+     * Names which are well-formed but unknown should be reported with the kind *ParseErrorKind::UnknownName*
+     * (see *parse_element_of_preexisting_vec_and_return_index*).
+     * **/
+    fn parse_explicit_pattern<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> 
             IResult<
                 &'a str,
                 CioII::InteractionLeafPatternType,
                 E
             >;
 
-    fn parse_interaction_with_operator_at_root<'a, E: ParseError<&'a str>>(&self,input : &'a str) -> IResult<&'a str,InteractionInternalRepresentation<CioII>,E> 
-    {
-        let mut parser = tuple(
-            (
-                |x| self.parse_operator(x),
-                multispace0,
-                nom::character::complete::char(self.left_parenthesis_char()),
-                multispace0,
-                separated_list0(
-                    delimited(
-                        multispace0,
-                        nom::character::complete::char(self.separator_char()), 
-                        multispace0
-                    ),
-                    |x| self.parse_interaction_inner(x)
-                ),
-                multispace0,
-                nom::character::complete::char(self.right_parenthesis_char())
-            )
-        );
-        match parser(input) {
-            IResult::Ok((rem, (operator, _, _, _, operands,_,_))) => {
-                let arity = operator.arity();
-                let num_operands = operands.len();
-                if (operator.is_associative() && num_operands >= 2) || (num_operands==arity) {
-                    IResult::Ok((rem, InteractionInternalRepresentation::Operator(operator,operands)))
-                } else {
-                    IResult::Err(nom::Err::Error(make_error::<&'a str, E>(input, nom::error::ErrorKind::Count)))
+    /** 
+     * Called when no leaf pattern could be parsed at the beginning of the input, before trying to parse an operator there,
+     * so that parsers which record names as they resolve them can discard those resolved while trying the leaf pattern
+     * (see *SharedSignature::backtrack*).
+     * By default, it does nothing.
+     * **/
+    fn backtrack(&self, _input : &str) {}
+
+    /** 
+     * Interprets the content of an annotation (see the *annotation_delimiters* of the *syntax*), returning None if it is not a valid annotation.
+     * By default, annotations are not supported.
+     * **/
+    fn interpret_annotation(&self, _content : &str) -> Option<CioII::InteractionAnnotationType> {
+        None
+    }
+
+    /** 
+     * Parses the annotations which precede a sub-interaction (from the outermost to the innermost), 
+     * as well as the whitespace and comments which follow each of them.
+     * If an annotation is not valid (see *interpret_annotation*), the error is a failure located at the beginning of the annotation 
+     * and has the kind *ParseErrorKind::InvalidAnnotation*.
+     * **/
+    fn parse_annotations<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> 
+            IResult<
+                &'a str,
+                Vec<CioII::InteractionAnnotationType>,
+                E
+            > {
+        let mut annotations = vec![];
+        let mut rem = input;
+        while matches!(self.syntax().annotation_delimiters(), Some((opening,_)) if rem.starts_with(opening)) {
+            let (after_annotation,content) = self.parse_annotation_content(rem)?;
+            match self.interpret_annotation(content) {
+                Some(annotation) => {
+                    annotations.push(annotation);
+                },
+                None => {
+                    let annotation = rem[..(rem.len() - after_annotation.len())].to_owned();
+                    return Err(nom::Err::Failure(E::from_parse_error_kind(rem, ParseErrorKind::InvalidAnnotation { annotation })));
                 }
-            },
-            IResult::Err(e) => {
-                IResult::Err(e)
             }
+            let (after_whitespace,_) = self.parse_whitespace_and_comments(after_annotation)?;
+            rem = after_whitespace;
         }
+        Ok((rem,annotations))
+    }
+
+    /** 
+     * Parses an interaction which has an operator at its root, starting exactly at the beginning of the input.
+     * **/
+    fn parse_interaction_with_operator_at_root<'a, E: InteractionParseError<'a>>(&self,input : &'a str) -> IResult<&'a str,InteractionInternalRepresentation<CioII>,E> 
+    {
+        parse_interaction_without_recursion(self, input, true, None)
     }
 
-    fn parse_interaction_inner<'a, E: ParseError<&'a str>>(&self,
+    /** 
+     * Parses an interaction (either a leaf pattern or an operator applied to sub-interactions) 
+     * as well as the whitespace and comments around it.
+     * Nested sub-interactions are parsed using an explicit stack so that deeply nested interactions do not overflow the call stack
+     * (see also *max_nesting_depth*).
+     * **/
+    fn parse_interaction_inner<'a, E: InteractionParseError<'a>>(&self,
         input : &'a str) -> 
             IResult<
                 &'a str,
                 InteractionInternalRepresentation<CioII>,
                 E
             > {
-
-        delimited(
-            multispace0,
-            alt(
-                (
-                    // tries to match the (beginning of the) input string to
-                    // a leaf pattern of the Interaction Language
-                    map(|x| self.parse_explicit_pattern(x),|y| InteractionInternalRepresentation::LeafPattern(y)),
-                    // tries to match the (beginning of the) input string to
-                    // an interaction with an operator at its root
-                    |x| self.parse_interaction_with_operator_at_root(x)
-                )
-            ),
-            multispace0
-        )(input)
-
+        parse_interaction_without_recursion(self, input, false, None)
     }
 
 }
-
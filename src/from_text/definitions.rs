@@ -0,0 +1,830 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use crate::from_text::context_aware_parser::ContextAwareInteractionParser;
+use crate::from_text::error::{get_line_and_column, ParseError, ParseErrorKind};
+use crate::from_text::includes::InteractionSourceResolver;
+use crate::from_text::parse::parse_interaction_strict;
+use crate::from_text::util::delimited_lang_parser::{get_char_indices_outside_comments_and_annotations, skip_whitespace_and_comments, DelimitedInteractionLanguageParser};
+use crate::internal_representation::{AnnotatedCommonIoInteractionInterface, InteractionInternalRepresentation};
+
+
+
+/** 
+ * Parses an interaction preceded by definitions of named sub-interactions, as in:
+ * 
+ * *let handshake(x,y) = seq(x -- req -> y, y -- ack -> x);*
+ * *let ping = a -- m -> b;*
+ * *par(handshake(a,b), ping, handshake(b,c))*
+ * 
+ * Each reference to a definition is expanded into the interaction internal representation.
+ * The arguments of a reference are substituted for the parameters of the definition wherever they appear as names,
+ * so that parameters may stand for lifelines, messages or whole sub-interactions.
+ * Definitions may refer to one another (whatever their order) as long as there are no cycles.
+ * A name only refers to a definition where a sub-interaction may occur (at the start of an operand or of an argument,
+ * and followed by a separator, a right parenthesis or the end of the text), 
+ * so that the lifelines and messages of patterns may have the name of a definition.
+ * Neither definitions nor their parameters may have the name of a keyword (see *ParseErrorKind::ReservedName*).
+ * The length of the expanded text is bounded by *max_expansion_length*.
+ * The keyword, the assignment and the terminator of definitions are given by the *DelimitedInteractionLanguageParser*.
+ * Contrary to *parse_interaction*, the whole input must be consumed.
+ * Errors are located in the original input (e.g., within the definition in which they occur).
+ * Include directives are not supported (see *parse_interaction_with_includes*).
+ * **/
+pub fn parse_interaction_with_definitions<CioII,Parser>
+    (
+        input_str : &str,
+        parser : &Parser
+    ) -> 
+        Result<
+            InteractionInternalRepresentation<CioII>, 
+            ParseError
+        > 
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : ContextAwareInteractionParser<CioII>
+{
+    let root = Source{name:None,text:input_str.to_owned()};
+    parse_interaction_from_sources(root, parser, &NoIncludeResolver{})
+}
+
+
+
+struct NoIncludeResolver {}
+
+impl InteractionSourceResolver for NoIncludeResolver {
+    fn resolve(&self, _path : &str, _including_source : Option<&str>) -> Result<(String,String),String> {
+        Err("include directives require a resolver".to_owned())
+    }
+}
+
+
+
+/** 
+ * A text from which definitions or an interaction are parsed, 
+ * *name* identifying it in error messages (None when parsing a single text).
+ * **/
+pub(crate) struct Source {
+    pub name : Option<String>,
+    pub text : String
+}
+
+
+
+/** 
+ * Parses the interaction at the end of the *root* source,
+ * together with the definitions of the root source and of the sources it (transitively) includes.
+ * **/
+pub(crate) fn parse_interaction_from_sources<CioII,Parser,Resolver>
+    (
+        root : Source,
+        parser : &Parser,
+        resolver : &Resolver
+    ) -> 
+        Result<
+            InteractionInternalRepresentation<CioII>, 
+            ParseError
+        > 
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : ContextAwareInteractionParser<CioII>,
+    Resolver : InteractionSourceResolver + ?Sized
+{
+    let mut sources = vec![root];
+    let mut definitions = vec![];
+    let main_offset = load_source(parser, resolver, &mut sources, 0, &mut vec![], &mut definitions)?;
+    let mut definitions_by_name = HashMap::new();
+    for definition in &definitions {
+        if definitions_by_name.insert(definition.name.as_str(), definition).is_some() {
+            return Err(
+                make_error(
+                    &sources,
+                    definition.source, 
+                    definition.offset, 
+                    ParseErrorKind::DuplicateDefinition { name: definition.name.clone() }, 
+                    vec![]
+                )
+            );
+        }
+    }
+    for definition in &definitions {
+        let reserved = std::iter::once(&definition.name)
+            .chain(definition.parameters.iter())
+            .find(|name| is_reserved_name(parser, name));
+        if let Some(name) = reserved {
+            return Err(
+                make_error(
+                    &sources,
+                    definition.source, 
+                    definition.offset, 
+                    ParseErrorKind::ReservedName { name: name.clone() }, 
+                    vec![]
+                )
+            );
+        }
+    }
+    check_definitions_are_acyclic(&sources, parser, &definitions)?;
+    let expansion = expand_references(
+        &sources, 
+        parser, 
+        &definitions_by_name, 
+        &sources[0].text[main_offset..], 
+        0,
+        main_offset
+    )?;
+    parse_interaction_strict(&expansion.text, parser).map_err(
+        |err| {
+            let mut err = err;
+            let (source,offset) = expansion.get_original_location(err.offset, &sources);
+            err.source = sources[source].name.clone();
+            err.offset = offset;
+            (err.line,err.column) = get_line_and_column(&sources[source].text, offset);
+            for frame in err.context.iter_mut() {
+                let (source,offset) = expansion.get_original_location(frame.offset, &sources);
+                frame.source = sources[source].name.clone();
+                frame.offset = offset;
+                (frame.line,frame.column) = get_line_and_column(&sources[source].text, offset);
+            }
+            err
+        }
+    )
+}
+
+
+
+struct Definition {
+    name : String,
+    // the index of the source in which it is defined and the offset of its name in that source
+    source : usize,
+    offset : usize,
+    parameters : Vec<String>,
+    body : String,
+    body_offset : usize
+}
+
+
+
+/** 
+ * What precedes the interaction in a source : definitions and include directives
+ * (with the offsets of the included paths).
+ * **/
+struct Header {
+    definitions : Vec<Definition>,
+    includes : Vec<(String,usize)>,
+    main_offset : usize
+}
+
+
+
+/** 
+ * Text obtained by expanding references, 
+ * together with the sources and offsets from which the pieces of text come.
+ * **/
+#[derive(Default)]
+struct Expansion {
+    text : String,
+    // for each piece : its offset in the expanded text, the index of its source and its offset in that source
+    origins : Vec<(usize,usize,usize)>
+}
+
+impl Expansion {
+
+    fn push_original(&mut self, piece : &str, source : usize, original_offset : usize) {
+        let is_contiguous = match self.origins.last() {
+            Some((expanded_start,last_source,original_start)) => {
+                *last_source == source && original_start + (self.text.len() - expanded_start) == original_offset
+            },
+            None => {
+                false
+            }
+        };
+        if !is_contiguous {
+            self.origins.push((self.text.len(),source,original_offset));
+        }
+        self.text.push_str(piece);
+    }
+
+    fn push_expansion(&mut self, other : &Expansion) {
+        for (expanded_start,source,original_start) in &other.origins {
+            self.origins.push((self.text.len() + expanded_start, *source, *original_start));
+        }
+        self.text.push_str(&other.text);
+    }
+
+    fn get_original_location(&self, expanded_offset : usize, sources : &[Source]) -> (usize,usize) {
+        match self.origins.iter().rev().find(|(expanded_start,_,_)| *expanded_start <= expanded_offset) {
+            Some((expanded_start,source,original_start)) => {
+                (*source, (original_start + (expanded_offset - expanded_start)).min(sources[*source].text.len()))
+            },
+            None => {
+                (0,0)
+            }
+        }
+    }
+
+}
+
+
+
+fn make_error(sources : &[Source], source : usize, offset : usize, kind : ParseErrorKind, expected : Vec<String>) -> ParseError {
+    ParseError::new(&sources[source].text, offset, kind, expected, vec![]).in_source(sources[source].name.clone())
+}
+
+
+
+/** 
+ * Parses the header of a source, loads the sources it includes (recursively) and collects all the definitions.
+ * Returns the offset at which the interaction starts in the source.
+ * *stack* contains the sources which are being loaded, so as to detect cyclic includes.
+ * **/
+fn load_source<Parser,Resolver>
+    (
+        parser : &Parser,
+        resolver : &Resolver,
+        sources : &mut Vec<Source>,
+        current : usize,
+        stack : &mut Vec<usize>,
+        definitions : &mut Vec<Definition>
+    ) -> Result<usize,ParseError>
+where 
+    Parser : DelimitedInteractionLanguageParser,
+    Resolver : InteractionSourceResolver + ?Sized
+{
+    let header = parse_header(sources, current, parser)?;
+    definitions.extend(header.definitions);
+    stack.push(current);
+    for (path,offset) in header.includes {
+        let (name,text) = resolver.resolve(&path, sources[current].name.as_deref()).map_err(
+            |reason| make_error(sources, current, offset, ParseErrorKind::UnresolvedInclude { path: path.clone(), reason }, vec![])
+        )?;
+        if let Some(position) = stack.iter().position(|x| sources[*x].name.as_deref() == Some(name.as_str())) {
+            let mut cycle : Vec<String> = stack[position..].iter().map(|x| sources[*x].name.clone().unwrap_or_default()).collect();
+            cycle.push(name);
+            return Err(make_error(sources, current, offset, ParseErrorKind::CyclicInclude { cycle }, vec![]));
+        }
+        if sources.iter().any(|source| source.name.as_deref() == Some(name.as_str())) {
+            // already included elsewhere
+            continue;
+        }
+        sources.push(Source{name:Some(name),text});
+        let included = sources.len() - 1;
+        let main_offset = load_source(parser, resolver, sources, included, stack, definitions)?;
+        // included sources only contain definitions
+        let text = &sources[included].text;
+        if main_offset < text.len() {
+            return Err(
+                make_error(
+                    sources, 
+                    included, 
+                    main_offset, 
+                    ParseErrorKind::Lexical, 
+                    vec![format!("'{}'", parser.syntax().definition_keyword()), format!("'{}'", parser.include_keyword())]
+                )
+            );
+        }
+    }
+    stack.pop();
+    Ok(header.main_offset)
+}
+
+
+
+/** 
+ * Returns the name at the beginning of the input, if any.
+ * **/
+fn get_name(input : &str) -> Option<&str> {
+    if !input.starts_with(|c : char| c.is_alphabetic()) {
+        return None;
+    }
+    let length = input.find(|c : char| !(c.is_alphanumeric() || c == '_')).unwrap_or(input.len());
+    Some(&input[..length])
+}
+
+
+
+/** 
+ * If the input starts with the keyword followed by whitespace or a comment, returns what follows the keyword.
+ * **/
+fn strip_keyword<'a,Parser>(parser : &Parser, input : &'a str, keyword : &str) -> Option<&'a str> 
+where 
+    Parser : DelimitedInteractionLanguageParser
+{
+    match input.strip_prefix(keyword) {
+        Some(after_keyword) if after_keyword.starts_with(|c : char| c.is_whitespace()) || parser.get_comment_length(after_keyword).is_some() => {
+            Some(skip_whitespace_and_comments(parser, after_keyword))
+        },
+        _ => {
+            None
+        }
+    }
+}
+
+
+
+/** 
+ * Parses the definitions and include directives at the beginning of a source.
+ * **/
+fn parse_header<Parser>(sources : &[Source], source : usize, parser : &Parser) -> Result<Header,ParseError> 
+where 
+    Parser : DelimitedInteractionLanguageParser
+{
+    let input_str = sources[source].text.as_str();
+    let syntax_error = |rem : &str, expected : String| {
+        make_error(sources, source, input_str.len() - rem.len(), ParseErrorKind::Lexical, vec![expected])
+    };
+    let terminator = parser.syntax().definition_terminator_char();
+    let mut header = Header{definitions:vec![],includes:vec![],main_offset:0};
+    let mut rem = skip_whitespace_and_comments(parser, input_str);
+    loop {
+        if let Some(after_keyword) = strip_keyword(parser, rem, parser.include_keyword()) {
+            let path = after_keyword.strip_prefix('"')
+                .and_then(|x| x.find('"').map(|length| &x[..length]))
+                .ok_or_else(|| syntax_error(after_keyword, "a quoted path".to_owned()))?;
+            header.includes.push((path.to_owned(), input_str.len() - after_keyword.len()));
+            rem = skip_whitespace_and_comments(parser, &after_keyword[(path.len() + 2)..]);
+            rem = rem.strip_prefix(terminator).ok_or_else(|| syntax_error(rem, format!("'{}'", terminator)))?;
+            rem = skip_whitespace_and_comments(parser, rem);
+            continue;
+        }
+        let after_keyword = match strip_keyword(parser, rem, parser.syntax().definition_keyword()) {
+            Some(after_keyword) => {
+                after_keyword
+            },
+            None => {
+                header.main_offset = input_str.len() - rem.len();
+                return Ok(header);
+            }
+        };
+        let offset = input_str.len() - after_keyword.len();
+        let name = get_name(after_keyword).ok_or_else(|| syntax_error(after_keyword, "a name".to_owned()))?;
+        rem = skip_whitespace_and_comments(parser, &after_keyword[name.len()..]);
+        // the parameters
+        let mut parameters = vec![];
+        if let Some(after_parenthesis) = rem.strip_prefix(parser.left_parenthesis_char()) {
+            rem = skip_whitespace_and_comments(parser, after_parenthesis);
+            loop {
+                let parameter = get_name(rem).ok_or_else(|| syntax_error(rem, "a name".to_owned()))?;
+                parameters.push(parameter.to_owned());
+                rem = skip_whitespace_and_comments(parser, &rem[parameter.len()..]);
+                if let Some(after_separator) = rem.strip_prefix(parser.separator_char()) {
+                    rem = skip_whitespace_and_comments(parser, after_separator);
+                } else if let Some(after_parenthesis) = rem.strip_prefix(parser.right_parenthesis_char()) {
+                    rem = skip_whitespace_and_comments(parser, after_parenthesis);
+                    break;
+                } else {
+                    return Err(syntax_error(rem, format!("'{}' or '{}'", parser.separator_char(), parser.right_parenthesis_char())));
+                }
+            }
+        }
+        let body = rem.strip_prefix(parser.syntax().definition_assignment_char()).ok_or_else(
+            || syntax_error(rem, format!("'{}'", parser.syntax().definition_assignment_char()))
+        )?;
+        // the body extends until the terminator, which may not be nested in parentheses
+        let mut depth : usize = 0;
+        let mut body_length = None;
+        for (index,c) in get_char_indices_outside_comments_and_annotations(parser, body) {
+            if c == parser.left_parenthesis_char() {
+                depth += 1;
+            } else if c == parser.right_parenthesis_char() {
+                depth = depth.saturating_sub(1);
+            } else if c == terminator && depth == 0 {
+                body_length = Some(index);
+                break;
+            }
+        }
+        let body_length = body_length.ok_or_else(|| syntax_error(&input_str[input_str.len()..], format!("'{}'", terminator)))?;
+        header.definitions.push(
+            Definition{
+                name:name.to_owned(),
+                source,
+                offset,
+                parameters,
+                body:body[..body_length].to_owned(),
+                body_offset:input_str.len() - body.len()
+            }
+        );
+        rem = skip_whitespace_and_comments(parser, &body[(body_length + terminator.len_utf8())..]);
+    }
+}
+
+
+
+/** 
+ * Whether the name is a keyword of the language, i.e., an operator or the keyword of definitions or include directives.
+ * **/
+fn is_reserved_name<CioII,Parser>(parser : &Parser, name : &str) -> bool 
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : ContextAwareInteractionParser<CioII>
+{
+    if name == parser.syntax().definition_keyword() || name == parser.include_keyword() {
+        return true;
+    }
+    matches!(parser.parse_operator::<nom::error::Error<&str>>(name), Ok((rem,_)) if rem.is_empty())
+}
+
+
+
+/** 
+ * Returns the names which occur in the text (outside of comments and annotations) together with their offsets in the text
+ * and whether they are at the start of an operand i.e., only preceded by the start of the text, 
+ * a left parenthesis or a separator (ignoring whitespace, comments and annotations).
+ * **/
+fn get_names_in_text<'a,Parser>(parser : &Parser, text : &'a str) -> Vec<(usize,&'a str,bool)> 
+where 
+    Parser : DelimitedInteractionLanguageParser
+{
+    let mut names = vec![];
+    let mut index = 0;
+    let mut at_operand_start = true;
+    while index < text.len() {
+        let rest = &text[index..];
+        if let Some(length) = parser.get_comment_length(rest).or_else(|| parser.get_annotation_length(rest)) {
+            index += length;
+            continue;
+        }
+        let c = rest.chars().next().unwrap();
+        if c.is_alphanumeric() || c == '_' {
+            let length = rest.find(|x : char| !(x.is_alphanumeric() || x == '_')).unwrap_or(rest.len());
+            if c.is_alphabetic() {
+                names.push((index,&rest[..length],at_operand_start));
+            }
+            at_operand_start = false;
+            index += length;
+        } else {
+            if !c.is_whitespace() {
+                at_operand_start = c == parser.left_parenthesis_char() || c == parser.separator_char();
+            }
+            index += c.len_utf8();
+        }
+    }
+    names
+}
+
+
+
+/** 
+ * The arguments of a reference to a definition (as ranges in the text) 
+ * and the index at which the reference ends in the text (None if its arguments are not closed).
+ * **/
+type ReferenceExtent = (Vec<(usize,usize)>,Option<usize>);
+
+
+
+/** 
+ * Returns the extent of the reference made by a name at the start of an operand (see *get_names_in_text*), if it is one.
+ * A name only refers to a definition where a sub-interaction may occur, 
+ * i.e., if it is followed (after its arguments, if any) by a separator, a right parenthesis or the end of the text,
+ * so that the lifelines and messages of patterns may have the name of a definition.
+ * **/
+fn get_reference_extent<Parser>(parser : &Parser, text : &str, name_index : usize, name : &str) -> Option<ReferenceExtent>
+where 
+    Parser : DelimitedInteractionLanguageParser
+{
+    let mut arguments = vec![];
+    let mut end = name_index + name.len();
+    let after_name = skip_whitespace_and_comments(parser, &text[end..]);
+    if let Some(after_parenthesis) = after_name.strip_prefix(parser.left_parenthesis_char()) {
+        let arguments_start = text.len() - after_parenthesis.len();
+        let mut depth : usize = 0;
+        let mut argument_start = arguments_start;
+        let mut arguments_end = None;
+        for (char_index,c) in get_char_indices_outside_comments_and_annotations(parser, after_parenthesis) {
+            let char_index = arguments_start + char_index;
+            if c == parser.left_parenthesis_char() {
+                depth += 1;
+            } else if c == parser.right_parenthesis_char() {
+                if depth == 0 {
+                    arguments.push((argument_start,char_index));
+                    arguments_end = Some(char_index + c.len_utf8());
+                    break;
+                }
+                depth -= 1;
+            } else if c == parser.separator_char() && depth == 0 {
+                arguments.push((argument_start,char_index));
+                argument_start = char_index + c.len_utf8();
+            }
+        }
+        match arguments_end {
+            Some(arguments_end) => {
+                end = arguments_end;
+            },
+            None => {
+                return Some((arguments,None));
+            }
+        }
+        if arguments.len() == 1 && skip_whitespace_and_comments(parser, &text[arguments[0].0..arguments[0].1]).is_empty() {
+            arguments.clear();
+        }
+    }
+    let after_reference = skip_whitespace_and_comments(parser, &text[end..]);
+    if after_reference.is_empty() 
+        || after_reference.starts_with(parser.separator_char()) 
+        || after_reference.starts_with(parser.right_parenthesis_char()) {
+        Some((arguments,Some(end)))
+    } else {
+        None
+    }
+}
+
+
+
+fn check_definitions_are_acyclic<Parser>(sources : &[Source], parser : &Parser, definitions : &[Definition]) -> Result<(),ParseError> 
+where 
+    Parser : DelimitedInteractionLanguageParser
+{
+    let indices : HashMap<&str,usize> = definitions.iter().enumerate().map(|(index,def)| (def.name.as_str(),index)).collect();
+    let references : Vec<Vec<usize>> = definitions.iter().map(
+        |def| {
+            get_names_in_text(parser, &def.body).into_iter()
+                .filter(|(_,name,at_operand_start)| *at_operand_start && !def.parameters.iter().any(|param| param == name))
+                .filter_map(|(name_index,name,_)| indices.get(name).map(|index| (name_index,name,*index)))
+                .filter(|(name_index,name,_)| get_reference_extent(parser, &def.body, *name_index, name).is_some())
+                .map(|(_,_,index)| index)
+                .collect()
+        }
+    ).collect();
+    // depth-first search using an explicit stack : 0 for not visited, 1 for being visited and 2 for visited
+    fn find_cycle(start : usize, references : &[Vec<usize>], states : &mut [u8]) -> Option<Vec<usize>> {
+        // the definitions being visited, with the index of the next reference to follow from each of them
+        let mut path : Vec<(usize,usize)> = vec![(start,0)];
+        states[start] = 1;
+        while let Some((current,next_reference)) = path.last_mut() {
+            let Some(next) = references[*current].get(*next_reference).cloned() else {
+                states[*current] = 2;
+                path.pop();
+                continue;
+            };
+            *next_reference += 1;
+            match states[next] {
+                0 => {
+                    states[next] = 1;
+                    path.push((next,0));
+                },
+                1 => {
+                    let start = path.iter().position(|(x,_)| *x == next).unwrap();
+                    let mut cycle : Vec<usize> = path[start..].iter().map(|(x,_)| *x).collect();
+                    cycle.push(next);
+                    return Some(cycle);
+                },
+                _ => {}
+            }
+        }
+        None
+    }
+    let mut states = vec![0;definitions.len()];
+    for index in 0..definitions.len() {
+        if states[index] == 0 {
+            if let Some(cycle) = find_cycle(index, &references, &mut states) {
+                let first = &definitions[cycle[0]];
+                return Err(
+                    make_error(
+                        sources,
+                        first.source,
+                        first.offset, 
+                        ParseErrorKind::CyclicDefinition { cycle: cycle.into_iter().map(|x| definitions[x].name.clone()).collect() }, 
+                        vec![]
+                    )
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+
+
+/** 
+ * What a text whose references are being expanded is.
+ * **/
+enum ExpansionFrameKind {
+    // the interaction
+    Interaction,
+    // an argument of a reference, which is expanded separately before being bound to a parameter
+    Argument,
+    // the body of a definition, referred to at the given offset in the given source
+    Body(usize,usize)
+}
+
+
+/** 
+ * A reference to a definition whose arguments are being expanded.
+ * **/
+struct PendingReference<'a> {
+    definition : &'a Definition,
+    // the offset of the reference in the source of the text
+    name_offset : usize,
+    arguments : Vec<(usize,usize)>,
+    next_argument : usize,
+    // the expanded arguments, bound to the parameters of the definition
+    bindings : HashMap<String,Expansion>
+}
+
+
+/** 
+ * A text (located at *text_offset* in the *source*) whose references are being expanded, 
+ * given the *bindings* of the parameters of the definition in which it is.
+ * **/
+struct ExpansionFrame<'a> {
+    kind : ExpansionFrameKind,
+    text : &'a str,
+    source : usize,
+    text_offset : usize,
+    bindings : Rc<HashMap<String,Expansion>>,
+    names : std::vec::IntoIter<(usize,&'a str,bool)>,
+    // the index up to which the text has been expanded
+    index : usize,
+    pending : Option<PendingReference<'a>>
+}
+
+impl<'a> ExpansionFrame<'a> {
+
+    fn new<Parser>(
+        parser : &Parser, 
+        kind : ExpansionFrameKind, 
+        text : &'a str, 
+        source : usize, 
+        text_offset : usize, 
+        bindings : Rc<HashMap<String,Expansion>>
+    ) -> Self 
+    where 
+        Parser : DelimitedInteractionLanguageParser
+    {
+        let names = get_names_in_text(parser, text).into_iter();
+        Self{kind,text,source,text_offset,bindings,names,index:0,pending:None}
+    }
+
+}
+
+
+
+/** 
+ * Expands the references to definitions in the interaction *text* (located at *text_offset* in the *source*).
+ * The arguments of references and the bodies of definitions are expanded using an explicit stack
+ * so that long chains of references do not overflow the call stack.
+ * **/
+fn expand_references<'a,Parser>
+    (
+        sources : &[Source],
+        parser : &Parser,
+        definitions : &HashMap<&str,&'a Definition>,
+        text : &'a str,
+        source : usize,
+        text_offset : usize
+    ) -> Result<Expansion,ParseError>
+where 
+    Parser : DelimitedInteractionLanguageParser
+{
+    // the expansion of the interaction and those of the arguments being expanded
+    let mut expansions = vec![Expansion::default()];
+    // the names of the definitions whose bodies are being expanded
+    let mut stack : Vec<&str> = vec![];
+    let mut stacked : HashSet<&str> = HashSet::new();
+    let mut frames = vec![ExpansionFrame::new(parser, ExpansionFrameKind::Interaction, text, source, text_offset, Rc::new(HashMap::new()))];
+    loop {
+        let frame = frames.last_mut().unwrap();
+        if let Some(pending) = &mut frame.pending {
+            if pending.next_argument < pending.arguments.len() {
+                // the arguments are expanded in the current context
+                let (start,end) = pending.arguments[pending.next_argument];
+                let argument = ExpansionFrame::new(
+                    parser, 
+                    ExpansionFrameKind::Argument, 
+                    &frame.text[start..end], 
+                    frame.source, 
+                    frame.text_offset + start, 
+                    frame.bindings.clone()
+                );
+                expansions.push(Expansion::default());
+                frames.push(argument);
+            } else {
+                let pending = frame.pending.take().unwrap();
+                stack.push(&pending.definition.name);
+                stacked.insert(&pending.definition.name);
+                let body = ExpansionFrame::new(
+                    parser, 
+                    ExpansionFrameKind::Body(frame.source,pending.name_offset), 
+                    &pending.definition.body, 
+                    pending.definition.source, 
+                    pending.definition.body_offset, 
+                    Rc::new(pending.bindings)
+                );
+                frames.push(body);
+            }
+            continue;
+        }
+        let expansion = expansions.last_mut().unwrap();
+        let Some((name_index,name,at_operand_start)) = frame.names.next() else {
+            // the text is completely expanded
+            let frame = frames.pop().unwrap();
+            expansion.push_original(&frame.text[frame.index..], frame.source, frame.text_offset + frame.index);
+            match frame.kind {
+                ExpansionFrameKind::Interaction => {
+                    return Ok(expansions.pop().unwrap());
+                },
+                ExpansionFrameKind::Argument => {
+                    let argument = expansions.pop().unwrap();
+                    let pending = frames.last_mut().unwrap().pending.as_mut().unwrap();
+                    pending.bindings.insert(pending.definition.parameters[pending.next_argument].clone(), argument);
+                    pending.next_argument += 1;
+                },
+                ExpansionFrameKind::Body(source,offset) => {
+                    stacked.remove(stack.pop().unwrap());
+                    check_expansion_length(sources, parser, source, offset, expansion)?;
+                }
+            }
+            continue;
+        };
+        if name_index < frame.index {
+            // within the arguments of a reference that has already been expanded
+            continue;
+        }
+        let name_offset = frame.text_offset + name_index;
+        if let Some(bound) = frame.bindings.get(name) {
+            expansion.push_original(&frame.text[frame.index..name_index], frame.source, frame.text_offset + frame.index);
+            frame.index = name_index + name.len();
+            expansion.push_expansion(bound);
+            check_expansion_length(sources, parser, frame.source, name_offset, expansion)?;
+            continue;
+        }
+        let Some(definition) = definitions.get(name).filter(|_| at_operand_start) else {
+            continue;
+        };
+        let Some((arguments,end)) = get_reference_extent(parser, frame.text, name_index, name) else {
+            continue;
+        };
+        let end = end.ok_or_else(
+            || make_error(
+                sources, 
+                frame.source, 
+                frame.text_offset + frame.text.len(), 
+                ParseErrorKind::Lexical, 
+                vec![format!("'{}'", parser.right_parenthesis_char())]
+            )
+        )?;
+        if arguments.len() != definition.parameters.len() {
+            return Err(
+                make_error(
+                    sources,
+                    frame.source,
+                    name_offset, 
+                    ParseErrorKind::DefinitionArityMismatch { name: name.to_owned(), expected: definition.parameters.len(), got: arguments.len() }, 
+                    vec![]
+                )
+            );
+        }
+        if stacked.contains(name) {
+            let mut cycle : Vec<String> = stack.iter().map(|x| x.to_string()).collect();
+            cycle.push(name.to_owned());
+            return Err(make_error(sources, frame.source, name_offset, ParseErrorKind::CyclicDefinition { cycle }, vec![]));
+        }
+        expansion.push_original(&frame.text[frame.index..name_index], frame.source, frame.text_offset + frame.index);
+        frame.index = end;
+        frame.pending = Some(PendingReference{definition,name_offset,arguments,next_argument:0,bindings:HashMap::new()});
+    }
+}
+
+
+
+/** 
+ * Fails if the expansion is longer than the *max_expansion_length*, 
+ * the error being located at the reference (at *offset* in the *source*) whose expansion made it too long.
+ * **/
+fn check_expansion_length<Parser>
+    (
+        sources : &[Source],
+        parser : &Parser,
+        source : usize,
+        offset : usize,
+        expansion : &Expansion
+    ) -> Result<(),ParseError>
+where 
+    Parser : DelimitedInteractionLanguageParser
+{
+    match parser.max_expansion_length() {
+        Some(max_length) if expansion.text.len() > max_length => {
+            Err(make_error(sources, source, offset, ParseErrorKind::ExpansionTooLarge { max_length }, vec![]))
+        },
+        _ => {
+            Ok(())
+        }
+    }
+}
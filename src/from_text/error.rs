@@ -0,0 +1,503 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+use std::fmt;
+
+use nom::error::ErrorKind;
+
+
+
+/** 
+ * The different kinds of errors that may occur when parsing an interaction.
+ * **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    // the input does not match the syntax of the language (unexpected character, keyword etc.)
+    Lexical,
+    // a well-formed name has been found where a lifeline or message name was expected 
+    // but it does not correspond to any lifeline or message that is known in the context
+    UnknownName{
+        name : String
+    },
+    // an operator has been applied to a number of operands that is not compatible with its arity
+    // *max_operands* is None if there is no upper bound (associative operators)
+    ArityMismatch{
+        operator : String,
+        min_operands : usize,
+        max_operands : Option<usize>,
+        got : usize
+    },
+    // an operator has been given parameters it does not accept, or no parameters while it requires some
+    // *parameters* is None in the latter case
+    InvalidOperatorParameters{
+        operator : String,
+        parameters : Option<String>
+    },
+    // an annotation (kept with its delimiters) which is not accepted by the parser
+    InvalidAnnotation{
+        annotation : String
+    },
+    // the interaction has more nested operators than the maximal nesting depth of the parser
+    NestingTooDeep{
+        max_depth : usize
+    },
+    // an interaction has been parsed but it is followed by some input that is neither whitespace nor comments
+    TrailingInput{
+        trailing : String
+    },
+    // a named sub-interaction is defined more than once
+    DuplicateDefinition{
+        name : String
+    },
+    // a name is declared more than once in the same section of a signature header
+    DuplicateDeclaration{
+        name : String
+    },
+    // named sub-interactions whose definitions refer to one another in a cycle
+    // the first and last names of the cycle are the same
+    CyclicDefinition{
+        cycle : Vec<String>
+    },
+    // a named sub-interaction is referred to with a number of arguments different from its number of parameters
+    DefinitionArityMismatch{
+        name : String,
+        expected : usize,
+        got : usize
+    },
+    // a named sub-interaction or one of its parameters has the name of a keyword of the language (e.g. an operator)
+    ReservedName{
+        name : String
+    },
+    // expanding the references to named sub-interactions produces a text longer than *max_length* bytes
+    ExpansionTooLarge{
+        max_length : usize
+    },
+    // the source designated by an include directive could not be resolved
+    UnresolvedInclude{
+        path : String,
+        reason : String
+    },
+    // sources which include one another in a cycle
+    // the first and last names of the cycle are the same
+    CyclicInclude{
+        cycle : Vec<String>
+    }
+}
+
+
+
+/** 
+ * An operator within which the error occurred, 
+ * *operand_index* (starting at 0) being the index of the operand in which the error is located.
+ * *offset*, *line* and *column* locate the beginning of the operator in the text 
+ * of the source named *source* (None when parsing a single text).
+ * **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseContextFrame {
+    pub operator : String,
+    pub operand_index : usize,
+    pub source : Option<String>,
+    pub offset : usize,
+    pub line : usize,
+    pub column : usize
+}
+
+
+
+/** 
+ * Error that may occur when parsing an interaction.
+ * *offset* is the byte offset at which the error is located and *line* and *column* (starting at 1) its position in the text.
+ * *expected* describes the constructs that were expected at that position (if known).
+ * *context* is the stack of operators within which the error is located, from the outermost to the innermost.
+ * *source* is the name of the source (e.g., the file) in which the error is located (None when parsing a single text).
+ * **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub source : Option<String>,
+    pub offset : usize,
+    pub line : usize,
+    pub column : usize,
+    pub kind : ParseErrorKind,
+    pub expected : Vec<String>,
+    pub context : Vec<ParseContextFrame>
+}
+
+impl ParseError {
+    
+    /** 
+     * Builds an error located at the byte *offset* of the *input*, computing its line and column.
+     * **/
+    pub fn new(input : &str,
+               offset : usize,
+               kind : ParseErrorKind,
+               expected : Vec<String>,
+               context : Vec<ParseContextFrame>) -> Self {
+        let (line,column) = get_line_and_column(input, offset);
+        Self{source:None,offset,line,column,kind,expected,context}
+    }
+
+    /** 
+     * Same as *new* but with the line and column taken from the *positions* computed for the *input*.
+     * **/
+    pub(crate) fn with_positions(positions : &TextPositions,
+                                 input : &str,
+                                 offset : usize,
+                                 kind : ParseErrorKind,
+                                 expected : Vec<String>,
+                                 context : Vec<ParseContextFrame>) -> Self {
+        let (line,column) = positions.get_line_and_column(input, offset);
+        Self{source:None,offset,line,column,kind,expected,context}
+    }
+
+    /** 
+     * Sets the name of the source in which the error is located.
+     * **/
+    pub fn in_source(mut self, source : Option<String>) -> Self {
+        self.source = source;
+        self
+    }
+
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(source) = &self.source {
+            write!(f, "in '{}' ", source)?;
+        }
+        write!(f, "at line {} column {} : ", self.line, self.column)?;
+        match &self.kind {
+            ParseErrorKind::Lexical => {
+                write!(f, "syntax error")?;
+            },
+            ParseErrorKind::UnknownName { name } => {
+                write!(f, "unknown name '{}'", name)?;
+            },
+            ParseErrorKind::ArityMismatch { operator, min_operands, max_operands, got } => {
+                match max_operands {
+                    None => {
+                        write!(f, "operator '{}' expects at least {} operands but got {}", operator, min_operands, got)?;
+                    },
+                    Some(max) if max == min_operands => {
+                        write!(f, "operator '{}' expects {} operands but got {}", operator, min_operands, got)?;
+                    },
+                    Some(max) => {
+                        write!(f, "operator '{}' expects between {} and {} operands but got {}", operator, min_operands, max, got)?;
+                    }
+                }
+            },
+            ParseErrorKind::InvalidOperatorParameters { operator, parameters } => {
+                match parameters {
+                    None => {
+                        write!(f, "operator '{}' requires parameters", operator)?;
+                    },
+                    Some(parameters) => {
+                        write!(f, "operator '{}' does not accept the parameters '{}'", operator, parameters)?;
+                    }
+                }
+            },
+            ParseErrorKind::InvalidAnnotation { annotation } => {
+                write!(f, "invalid annotation '{}'", annotation)?;
+            },
+            ParseErrorKind::NestingTooDeep { max_depth } => {
+                write!(f, "more than {} nested operators", max_depth)?;
+            },
+            ParseErrorKind::TrailingInput { trailing } => {
+                let first_line = trailing.lines().next().unwrap_or_default();
+                write!(f, "unexpected input after the end of the interaction : '{}'", first_line)?;
+            },
+            ParseErrorKind::DuplicateDefinition { name } => {
+                write!(f, "'{}' is defined more than once", name)?;
+            },
+            ParseErrorKind::DuplicateDeclaration { name } => {
+                write!(f, "'{}' is declared more than once", name)?;
+            },
+            ParseErrorKind::CyclicDefinition { cycle } => {
+                write!(f, "cyclic definitions : {}", cycle.join(" -> "))?;
+            },
+            ParseErrorKind::DefinitionArityMismatch { name, expected, got } => {
+                write!(f, "'{}' expects {} arguments but got {}", name, expected, got)?;
+            },
+            ParseErrorKind::ReservedName { name } => {
+                write!(f, "'{}' is a keyword and cannot be defined", name)?;
+            },
+            ParseErrorKind::ExpansionTooLarge { max_length } => {
+                write!(f, "the expansion of the definitions is longer than {} bytes", max_length)?;
+            },
+            ParseErrorKind::UnresolvedInclude { path, reason } => {
+                write!(f, "cannot include '{}' : {}", path, reason)?;
+            },
+            ParseErrorKind::CyclicInclude { cycle } => {
+                write!(f, "cyclic includes : {}", cycle.join(" -> "))?;
+            }
+        }
+        if !self.expected.is_empty() {
+            write!(f, ", expected {}", self.expected.join(" or "))?;
+        }
+        for frame in self.context.iter().rev() {
+            write!(f, "\n    in operand {} of '{}' ", frame.operand_index, frame.operator)?;
+            if let Some(source) = &frame.source {
+                write!(f, "in '{}' ", source)?;
+            }
+            write!(f, "at line {} column {}", frame.line, frame.column)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+
+
+/** 
+ * Returns the line and column (both starting at 1) at which the byte *offset* is located in the *input*.
+ * The column is counted in characters.
+ * **/
+pub fn get_line_and_column(input : &str, offset : usize) -> (usize,usize) {
+    let before = &input[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|x| x + 1).unwrap_or(0);
+    let column = before[line_start..].chars().count() + 1;
+    (line,column)
+}
+
+
+
+/** 
+ * The positions of the lines and characters of an input, 
+ * with which the lines and columns of many offsets are computed without counting from the start of the input each time.
+ * **/
+pub(crate) struct TextPositions {
+    // the byte offsets at which the lines start
+    line_starts : Vec<usize>,
+    // the number of characters before byte offsets taken at regular intervals
+    char_counts : Vec<(usize,usize)>
+}
+
+impl TextPositions {
+
+    // the interval (in bytes) between two recorded numbers of characters
+    const CHAR_COUNT_INTERVAL : usize = 256;
+
+    pub(crate) fn new(input : &str) -> Self {
+        let mut line_starts = vec![0];
+        let mut char_counts = vec![(0,0)];
+        for (char_count,(offset,c)) in input.char_indices().enumerate() {
+            if offset >= char_counts.last().unwrap().0 + Self::CHAR_COUNT_INTERVAL {
+                char_counts.push((offset,char_count));
+            }
+            if c == '\n' {
+                line_starts.push(offset + 1);
+            }
+        }
+        Self{line_starts,char_counts}
+    }
+
+    fn count_chars_before(&self, input : &str, offset : usize) -> usize {
+        let index = self.char_counts.partition_point(|(recorded,_)| *recorded <= offset) - 1;
+        let (recorded_offset,char_count) = self.char_counts[index];
+        char_count + input[recorded_offset..offset].chars().count()
+    }
+
+    /** 
+     * Returns the same line and column as *get_line_and_column* for the *input* from which the positions are computed.
+     * **/
+    pub(crate) fn get_line_and_column(&self, input : &str, offset : usize) -> (usize,usize) {
+        let line = self.line_starts.partition_point(|start| *start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = self.count_chars_before(input, offset) - self.count_chars_before(input, line_start) + 1;
+        (line,column)
+    }
+
+}
+
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseFailureCause {
+    Kind(ErrorKind),
+    Char(char)
+}
+
+
+
+/** 
+ * The nom error types with which interactions can be parsed.
+ * In addition to the errors of nom, they can be built from a *ParseErrorKind* where the error is detected 
+ * and they can record the operators within which the error is located, as it is propagated through them.
+ * Error types which cannot hold this information keep the closest *ErrorKind* and ignore the operators.
+ * **/
+pub trait InteractionParseError<'a> : nom::error::ParseError<&'a str> {
+
+    /** 
+     * Builds an error of the given kind located at the beginning of the input.
+     * **/
+    fn from_parse_error_kind(input : &'a str, kind : ParseErrorKind) -> Self;
+
+    /** 
+     * Records that the error is located in the operand number *operand_index* (starting at 0) of an operator,
+     * *operator* being the slice of the text in which this operator (and its parameters) is written.
+     * Operators are recorded from the innermost to the outermost.
+     * **/
+    fn within_operator(self, operator : &'a str, operand_index : usize) -> Self;
+
+}
+
+/** 
+ * Returns the *ErrorKind* which corresponds the most to the given *ParseErrorKind*,
+ * for the error types which cannot hold the latter.
+ * **/
+pub fn get_closest_nom_error_kind(kind : &ParseErrorKind) -> ErrorKind {
+    match kind {
+        ParseErrorKind::UnknownName { .. } => {
+            ErrorKind::Verify
+        },
+        ParseErrorKind::ArityMismatch { .. } | ParseErrorKind::DefinitionArityMismatch { .. } => {
+            ErrorKind::Count
+        },
+        ParseErrorKind::NestingTooDeep { .. } | ParseErrorKind::ExpansionTooLarge { .. } => {
+            ErrorKind::TooLarge
+        },
+        ParseErrorKind::TrailingInput { .. } => {
+            ErrorKind::Eof
+        },
+        _ => {
+            ErrorKind::Fail
+        }
+    }
+}
+
+impl<'a> InteractionParseError<'a> for nom::error::Error<&'a str> {
+
+    fn from_parse_error_kind(input : &'a str, kind : ParseErrorKind) -> Self {
+        Self::new(input, get_closest_nom_error_kind(&kind))
+    }
+
+    fn within_operator(self, _operator : &'a str, _operand_index : usize) -> Self {
+        self
+    }
+
+}
+
+impl<'a> InteractionParseError<'a> for nom::error::VerboseError<&'a str> {
+
+    fn from_parse_error_kind(input : &'a str, kind : ParseErrorKind) -> Self {
+        <Self as nom::error::ParseError<&'a str>>::from_error_kind(input, get_closest_nom_error_kind(&kind))
+    }
+
+    fn within_operator(self, _operator : &'a str, _operand_index : usize) -> Self {
+        self
+    }
+
+}
+
+impl<'a> InteractionParseError<'a> for () {
+
+    fn from_parse_error_kind(_input : &'a str, _kind : ParseErrorKind) -> Self {}
+
+    fn within_operator(self, _operator : &'a str, _operand_index : usize) -> Self {}
+
+}
+
+
+
+/** 
+ * A nom error type which keeps track of the furthest position reached in the input
+ * and of all the failures that happened at that position.
+ * It is the error type used by *parse_interaction* and is then converted into a *ParseError*.
+ * **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFailureTrace<'a> {
+    // the remaining input at the furthest position at which parsing failed
+    pub remaining : &'a str,
+    pub causes : Vec<ParseFailureCause>,
+    // the kind of the error, if it is known where the error is detected (otherwise it is a syntax error)
+    // it is boxed so that the trace, which is returned by all the parsers, stays small
+    pub kind : Option<Box<ParseErrorKind>>,
+    // the operators within which the error is located (their texts and the indices of the operands), from the innermost to the outermost
+    pub context : Vec<(&'a str,usize)>
+}
+
+impl<'a> ParseFailureTrace<'a> {
+
+    pub fn new(remaining : &'a str, causes : Vec<ParseFailureCause>) -> Self {
+        Self{remaining,causes,kind:None,context:vec![]}
+    }
+
+}
+
+impl<'a> nom::error::ParseError<&'a str> for ParseFailureTrace<'a> {
+
+    fn from_error_kind(input: &'a str, kind: ErrorKind) -> Self {
+        Self::new(input, vec![ParseFailureCause::Kind(kind)])
+    }
+
+    fn append(_input: &'a str, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    fn from_char(input: &'a str, c: char) -> Self {
+        Self::new(input, vec![ParseFailureCause::Char(c)])
+    }
+
+    fn or(mut self, other: Self) -> Self {
+        match self.remaining.len().cmp(&other.remaining.len()) {
+            std::cmp::Ordering::Less => {
+                self
+            },
+            std::cmp::Ordering::Greater => {
+                other
+            },
+            std::cmp::Ordering::Equal => {
+                for cause in other.causes {
+                    if !self.causes.contains(&cause) {
+                        self.causes.push(cause);
+                    }
+                }
+                // an error whose kind is known takes precedence over syntax errors at the same position
+                // and any other kind takes precedence over an unknown name
+                // (e.g., an operator with an incorrect number of operands cannot be parsed as a lifeline name either)
+                let self_is_weaker = match self.kind.as_deref() {
+                    None | Some(ParseErrorKind::UnknownName { .. }) => true,
+                    Some(_) => false
+                };
+                if self_is_weaker && other.kind.is_some() {
+                    self.kind = other.kind;
+                }
+                if self.context.is_empty() {
+                    self.context = other.context;
+                }
+                self
+            }
+        }
+    }
+
+}
+
+impl<'a> InteractionParseError<'a> for ParseFailureTrace<'a> {
+
+    fn from_parse_error_kind(input : &'a str, kind : ParseErrorKind) -> Self {
+        let mut trace = Self::new(input, vec![ParseFailureCause::Kind(get_closest_nom_error_kind(&kind))]);
+        trace.kind = Some(Box::new(kind));
+        trace
+    }
+
+    fn within_operator(mut self, operator : &'a str, operand_index : usize) -> Self {
+        self.context.push((operator,operand_index));
+        self
+    }
+
+}
@@ -0,0 +1,99 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use std::path::{Path, PathBuf};
+
+use crate::from_text::context_aware_parser::ContextAwareInteractionParser;
+use crate::from_text::definitions::{parse_interaction_from_sources, Source};
+use crate::from_text::error::{ParseError, ParseErrorKind};
+use crate::internal_representation::{AnnotatedCommonIoInteractionInterface, InteractionInternalRepresentation};
+
+
+
+/** 
+ * Decides how the paths which appear in include directives map to texts.
+ * **/
+pub trait InteractionSourceResolver {
+
+    /** 
+     * Returns the name and the text of the source designated by *path*, 
+     * *including_source* being the name of the source in which the include directive occurs 
+     * (None for the root source of *parse_interaction_with_includes*).
+     * The name identifies the source in error messages and when detecting cyclic includes,
+     * so that two paths designating the same source should be resolved to the same name.
+     * In case of failure, returns the reason why the path cannot be resolved.
+     * **/
+    fn resolve(&self, path : &str, including_source : Option<&str>) -> Result<(String,String),String>;
+
+}
+
+
+
+/** 
+ * Resolves paths as files, relative paths being relative to the directory of the including file.
+ * Sources are named after their canonical paths.
+ * **/
+pub struct FileSystemSourceResolver {}
+
+impl InteractionSourceResolver for FileSystemSourceResolver {
+
+    fn resolve(&self, path : &str, including_source : Option<&str>) -> Result<(String,String),String> {
+        let mut file_path = PathBuf::from(path);
+        if file_path.is_relative() {
+            if let Some(directory) = including_source.and_then(|x| Path::new(x).parent()) {
+                file_path = directory.join(file_path);
+            }
+        }
+        let canonical = file_path.canonicalize().map_err(|e| e.to_string())?;
+        let text = std::fs::read_to_string(&canonical).map_err(|e| e.to_string())?;
+        Ok((canonical.to_string_lossy().into_owned(),text))
+    }
+
+}
+
+
+
+/** 
+ * Parses the interaction at the end of the source designated by *root_path*, which may be preceded by 
+ * include directives (e.g. *include "handshakes.txt";*) and definitions of named sub-interactions 
+ * (see *parse_interaction_with_definitions*).
+ * The definitions of the included sources (which may themselves include other sources) can then be referred to.
+ * Included sources may only contain include directives and definitions.
+ * Each source is included at most once and cyclic includes are reported as errors.
+ * Errors carry the name of the source in which they are located.
+ * **/
+pub fn parse_interaction_with_includes<CioII,Parser,Resolver>
+    (
+        root_path : &str,
+        parser : &Parser,
+        resolver : &Resolver
+    ) -> 
+        Result<
+            InteractionInternalRepresentation<CioII>, 
+            ParseError
+        > 
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : ContextAwareInteractionParser<CioII>,
+    Resolver : InteractionSourceResolver + ?Sized
+{
+    let (name,text) = resolver.resolve(root_path, None).map_err(
+        |reason| ParseError::new("", 0, ParseErrorKind::UnresolvedInclude { path: root_path.to_owned(), reason }, vec![], vec![])
+    )?;
+    parse_interaction_from_sources(Source{name:Some(name),text}, parser, resolver)
+}
@@ -0,0 +1,571 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+
+use nom::branch::alt;
+use nom::combinator::consumed;
+use nom::error::{make_error, ErrorKind};
+use nom::sequence::{preceded, tuple};
+use nom::IResult;
+
+use crate::from_text::context_aware_parser::ContextAwareInteractionParser;
+use crate::from_text::error::{InteractionParseError, ParseError, ParseErrorKind, ParseFailureTrace};
+use crate::from_text::parse::make_parse_error_from_trace;
+use crate::internal_representation::{AnnotatedCommonIoInteractionInterface, InfixAssociativity, InfixNotationTable, InteractionInternalRepresentation, InteractionOperatorRepresentation, OperatorNotation};
+
+
+
+/** 
+ * Parses an interaction written in the infix syntax described by the *InfixNotationTable* of the parser, 
+ * requiring the whole input to be consumed.
+ * Binary operators are written *i1 symbol i2*, unary operators *symbol(i)* and
+ * operators which are not in the table are written as usual, i.e., *op(i1,...,in)*, their operands being themselves in the infix syntax.
+ * Parentheses group sub-interactions.
+ * 
+ * Consecutive applications of the same associative operator which are not grouped by parentheses are flattened
+ * so that, e.g., *i1 ; i2 ; i3* and *seq(i1,i2,i3)* have the same internal representation.
+ * **/
+pub fn parse_infix_interaction<CioII,Parser>
+    (
+        input_str : &str,
+        parser : &Parser
+    ) -> 
+        Result<
+            InteractionInternalRepresentation<CioII>, 
+            ParseError
+        > 
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : ContextAwareInteractionParser<CioII> + InfixNotationTable<CioII>
+{
+    let notations = get_infix_notations::<CioII,Parser>(parser);
+    match parse_infix_expression::<CioII,Parser,ParseFailureTrace>(parser, &notations, input_str) {
+        Err(nom::Err::Error(trace)) | Err(nom::Err::Failure(trace)) => {
+            Err(make_parse_error_from_trace(input_str, trace))
+        },
+        Err(nom::Err::Incomplete(_)) => {
+            Err(
+                ParseError::new(
+                    input_str, 
+                    input_str.len(), 
+                    ParseErrorKind::Lexical, 
+                    vec![], 
+                    vec![]
+                )
+            )
+        },
+        Ok((rem,(int_repr,_))) => {
+            if rem.is_empty() {
+                Ok(int_repr)
+            } else {
+                Err(
+                    ParseError::new(
+                        input_str, 
+                        input_str.len() - rem.len(), 
+                        ParseErrorKind::TrailingInput { trailing: rem.to_owned() }, 
+                        vec![], 
+                        vec![]
+                    )
+                )
+            }
+        }
+    }
+}
+
+
+
+struct InfixNotations<Operator> {
+    // binary operators, sorted by decreasing length of their symbols so that the longest symbol is always matched
+    binary : Vec<OperatorNotation<Operator>>,
+    prefix : Vec<OperatorNotation<Operator>>
+}
+
+fn get_infix_notations<CioII,Parser>(parser : &Parser) -> InfixNotations<CioII::InteractionOperatorType>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : InfixNotationTable<CioII>
+{
+    let mut binary = vec![];
+    let mut prefix = vec![];
+    for notation in parser.get_operator_notations() {
+        match notation.operator.arity() {
+            1 => {
+                prefix.push(notation);
+            },
+            2 => {
+                binary.push(notation);
+            },
+            _ => {}
+        }
+    }
+    binary.sort_by_key(|x| std::cmp::Reverse(x.symbol.len()));
+    InfixNotations{binary,prefix}
+}
+
+
+
+/** 
+ * The order in which the alternatives of a primary sub-interaction are tried.
+ * **/
+const INFIX_PREFIX_OPERATION : usize = 0;
+const INFIX_PARENTHESIZED_GROUP : usize = 1;
+const INFIX_LEAF_PATTERN : usize = 2;
+const INFIX_OPERATOR_APPLICATION : usize = 3;
+
+
+
+/** 
+ * An expression or a primary sub-interaction which is being parsed.
+ * **/
+enum InfixFrame<'a,'n,CioII : AnnotatedCommonIoInteractionInterface,E> {
+    // an expression, i.e., primary sub-interactions separated by binary operators, 
+    // whose next primary sub-interaction is being parsed
+    Expression{
+        operands : Vec<(InteractionInternalRepresentation<CioII>,bool)>,
+        operators : Vec<&'n OperatorNotation<CioII::InteractionOperatorType>>
+    },
+    // a primary sub-interaction, one of the expressions within it being parsed
+    Primary{
+        // the input at which the primary sub-interaction starts (after its annotations)
+        at : &'a str,
+        annotations : Vec<CioII::InteractionAnnotationType>,
+        // the combined errors of the alternatives that have already been tried at *at*
+        error : Option<E>,
+        alternative : InfixAlternative<'a,'n,CioII>
+    }
+}
+
+enum InfixAlternative<'a,'n,CioII : AnnotatedCommonIoInteractionInterface> {
+    // *symbol(i)* for a unary operator in the notation table
+    PrefixOperation(&'n OperatorNotation<CioII::InteractionOperatorType>),
+    // *(i)*
+    ParenthesizedGroup,
+    // *op(i1,...,in)*, as in the usual syntax but with operands in the infix syntax
+    OperatorApplication{
+        operator_text : &'a str,
+        operator : CioII::InteractionOperatorType,
+        operands : Vec<InteractionInternalRepresentation<CioII>>
+    }
+}
+
+impl<CioII : AnnotatedCommonIoInteractionInterface> InfixAlternative<'_,'_,CioII> {
+
+    fn index(&self) -> usize {
+        match self {
+            InfixAlternative::PrefixOperation(_) => INFIX_PREFIX_OPERATION,
+            InfixAlternative::ParenthesizedGroup => INFIX_PARENTHESIZED_GROUP,
+            InfixAlternative::OperatorApplication{..} => INFIX_OPERATOR_APPLICATION
+        }
+    }
+
+}
+
+enum InfixStep<'a,CioII : AnnotatedCommonIoInteractionInterface,E> {
+    // parses an expression starting at the given input
+    Expression(&'a str),
+    // parses the next primary sub-interaction of the expression at the top of the stack, starting at the given input
+    Primary(&'a str),
+    // tries the alternatives of a primary sub-interaction from the *next* one
+    Alternatives{
+        at : &'a str,
+        annotations : Vec<CioII::InteractionAnnotationType>,
+        next : usize,
+        error : Option<E>
+    },
+    // a primary sub-interaction of the expression at the top of the stack has been parsed, 
+    // excluding the whitespace which follows it
+    PrimaryParsed{
+        rem : &'a str,
+        int_repr : InteractionInternalRepresentation<CioII>,
+        is_grouped : bool,
+        annotations : Vec<CioII::InteractionAnnotationType>
+    },
+    // the alternative of the primary sub-interaction at the top of the stack has failed
+    AlternativeFailed(nom::Err<E>),
+    // an error occurred within the expression at the top of the stack
+    Unwind(nom::Err<E>)
+}
+
+
+
+/** 
+ * Parses a sequence of primary sub-interactions separated by binary operators, 
+ * combining them according to the precedences and associativities of the operators (i.e., shunting-yard).
+ * Primary sub-interactions are prefix operations, parenthesized groups, leaf patterns and operator applications, 
+ * which are tried in that order, as *alt* would, the errors of the alternatives being combined in the same manner.
+ * Nested expressions are parsed using an explicit stack, so that deeply nested interactions do not overflow the call stack,
+ * and a group or an application nested within *max_nesting_depth* other ones is reported as in the usual syntax.
+ * Also returns whether or not the parsed interaction is grouped, i.e., whether or not it must be kept as is
+ * when it is the operand of an application of the same associative operator.
+ * **/
+fn parse_infix_expression<'a,CioII,Parser,E>
+    (
+        parser : &Parser,
+        notations : &InfixNotations<CioII::InteractionOperatorType>,
+        input : &'a str
+    ) -> IResult<&'a str,(InteractionInternalRepresentation<CioII>,bool),E>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : ContextAwareInteractionParser<CioII>,
+    E : InteractionParseError<'a>
+{
+    let combine = |error : Option<E>, other : E| {
+        match error {
+            None => {
+                other
+            },
+            Some(error) => {
+                error.or(other)
+            }
+        }
+    };
+    let mut stack : Vec<InfixFrame<'a,'_,CioII,E>> = vec![];
+    // the number of groups and operator applications on the stack
+    let mut depth : usize = 0;
+    let mut step = InfixStep::Expression(input);
+    loop {
+        step = match step {
+            InfixStep::Expression(rem) => {
+                stack.push(InfixFrame::Expression{operands:vec![],operators:vec![]});
+                InfixStep::Primary(rem)
+            },
+            InfixStep::Primary(rem) => {
+                let annotated = preceded(
+                    |x| parser.parse_whitespace_and_comments(x),
+                    |x| parser.parse_annotations(x)
+                )(rem);
+                match annotated {
+                    Ok((at,annotations)) => {
+                        InfixStep::Alternatives{at,annotations,next:INFIX_PREFIX_OPERATION,error:None}
+                    },
+                    Err(e) => {
+                        InfixStep::Unwind(e)
+                    }
+                }
+            },
+            InfixStep::Alternatives{at,annotations,next,error} => {
+                // the input after the opening of a group or of an application, and the corresponding alternative
+                let opened : IResult<&'a str,InfixAlternative<'a,'_,CioII>,E> = match next {
+                    INFIX_PREFIX_OPERATION => {
+                        match notations.prefix.iter().find(|x| at.starts_with(x.symbol)) {
+                            None => {
+                                Err(nom::Err::Error(make_error(at, ErrorKind::Tag)))
+                            },
+                            Some(notation) => {
+                                tuple(
+                                    (
+                                        |x| parser.parse_whitespace_and_comments(x),
+                                        nom::character::complete::char(parser.left_parenthesis_char())
+                                    )
+                                )(&at[notation.symbol.len()..]).map(|(rem,_)| (rem,InfixAlternative::PrefixOperation(notation)))
+                            }
+                        }
+                    },
+                    INFIX_PARENTHESIZED_GROUP => {
+                        tuple(
+                            (
+                                |x| parser.parse_whitespace_and_comments(x),
+                                nom::character::complete::char(parser.left_parenthesis_char())
+                            )
+                        )(at).map(|(rem,_)| (rem,InfixAlternative::ParenthesizedGroup))
+                    },
+                    INFIX_LEAF_PATTERN => {
+                        match parser.parse_explicit_pattern(at) {
+                            Ok((rem,leaf)) => {
+                                step = InfixStep::PrimaryParsed{
+                                    rem,
+                                    int_repr:InteractionInternalRepresentation::LeafPattern(leaf),
+                                    is_grouped:true,
+                                    annotations
+                                };
+                                continue;
+                            },
+                            Err(e) => {
+                                Err(e)
+                            }
+                        }
+                    },
+                    INFIX_OPERATOR_APPLICATION => {
+                        tuple(
+                            (
+                                consumed(|x| parser.parse_operator_with_parameters(x)),
+                                |x| parser.parse_whitespace_and_comments(x),
+                                nom::character::complete::char(parser.left_parenthesis_char())
+                            )
+                        )(at).map(
+                            |(rem,((operator_text,operator),_,_))| (rem,InfixAlternative::OperatorApplication{operator_text,operator,operands:vec![]})
+                        )
+                    },
+                    _ => {
+                        // none of the alternatives applies
+                        step = InfixStep::Unwind(nom::Err::Error(E::append(at, ErrorKind::Alt, error.unwrap())));
+                        continue;
+                    }
+                };
+                match opened {
+                    Ok((rem,alternative)) => {
+                        if let Some(max_depth) = parser.max_nesting_depth() {
+                            if depth >= max_depth {
+                                step = InfixStep::Unwind(
+                                    nom::Err::Failure(E::from_parse_error_kind(at, ParseErrorKind::NestingTooDeep { max_depth }))
+                                );
+                                continue;
+                            }
+                        }
+                        stack.push(InfixFrame::Primary{at,annotations,error,alternative});
+                        depth += 1;
+                        if matches!(stack.last(), Some(InfixFrame::Primary{alternative:InfixAlternative::OperatorApplication{..},..})) {
+                            let (after_whitespace,_) = match parser.parse_whitespace_and_comments::<E>(rem) {
+                                Ok(got) => {
+                                    got
+                                },
+                                Err(e) => {
+                                    step = InfixStep::AlternativeFailed(e);
+                                    continue;
+                                }
+                            };
+                            let closing : IResult<&'a str,char,E> = nom::character::complete::char(parser.right_parenthesis_char())(after_whitespace);
+                            if let Ok((after_closing,_)) = closing {
+                                // operators which accept no operands
+                                step = close_infix_operator_application(&mut stack, &mut depth, after_closing);
+                                continue;
+                            }
+                        }
+                        InfixStep::Expression(rem)
+                    },
+                    Err(nom::Err::Error(e)) => {
+                        InfixStep::Alternatives{at,annotations,next:next + 1,error:Some(combine(error, e))}
+                    },
+                    Err(e) => {
+                        InfixStep::Unwind(e)
+                    }
+                }
+            },
+            InfixStep::PrimaryParsed{rem,int_repr,is_grouped,annotations} => {
+                let rem = match parser.parse_whitespace_and_comments::<E>(rem) {
+                    Ok((after_whitespace,_)) => {
+                        after_whitespace
+                    },
+                    Err(e) => {
+                        step = InfixStep::Unwind(e);
+                        continue;
+                    }
+                };
+                let primary = if annotations.is_empty() {
+                    (int_repr,is_grouped)
+                } else {
+                    // an annotated expression is never flattened with its parent
+                    (int_repr.with_annotations(annotations),true)
+                };
+                let Some(InfixFrame::Expression{operands,operators}) = stack.last_mut() else {
+                    unreachable!("a primary sub-interaction is always parsed within an expression");
+                };
+                operands.push(primary);
+                if let Some(notation) = notations.binary.iter().find(|x| rem.starts_with(x.symbol)) {
+                    // the operators on the stack which bind at least as tightly are applied first
+                    while let Some(previous) = operators.last() {
+                        let applies_first = previous.precedence > notation.precedence 
+                            || (previous.precedence == notation.precedence && previous.associativity == InfixAssociativity::Left);
+                        if !applies_first {
+                            break;
+                        }
+                        apply_infix_binary_operators(operands, operators);
+                    }
+                    operators.push(notation);
+                    step = InfixStep::Primary(&rem[notation.symbol.len()..]);
+                    continue;
+                }
+                while !operators.is_empty() {
+                    apply_infix_binary_operators(operands, operators);
+                }
+                let (expression,is_grouped) = operands.pop().unwrap();
+                stack.pop();
+                // the expression is closed by the primary sub-interaction within which it is
+                match stack.last_mut() {
+                    None => {
+                        return Ok((rem,(expression,is_grouped)));
+                    },
+                    Some(InfixFrame::Primary{alternative:InfixAlternative::OperatorApplication{operator_text,operands,..},..}) => {
+                        let operand_index = operands.len();
+                        operands.push(expression);
+                        let delimiter : IResult<&'a str,char,E> = alt(
+                            (
+                                nom::character::complete::char(parser.separator_char()),
+                                nom::character::complete::char(parser.right_parenthesis_char())
+                            )
+                        )(rem);
+                        match delimiter {
+                            Ok((after_delimiter,delimiter)) if delimiter == parser.separator_char() => {
+                                InfixStep::Expression(after_delimiter)
+                            },
+                            Ok((after_delimiter,_)) => {
+                                close_infix_operator_application(&mut stack, &mut depth, after_delimiter)
+                            },
+                            Err(e) => {
+                                let operator_text : &'a str = operator_text;
+                                InfixStep::AlternativeFailed(e.map(|e| e.within_operator(operator_text, operand_index)))
+                            }
+                        }
+                    },
+                    Some(InfixFrame::Primary{..}) => {
+                        let closing : IResult<&'a str,char,E> = nom::character::complete::char(parser.right_parenthesis_char())(rem);
+                        match closing {
+                            Ok((after_closing,_)) => {
+                                let Some(InfixFrame::Primary{annotations,alternative,..}) = stack.pop() else {
+                                    unreachable!();
+                                };
+                                depth -= 1;
+                                let int_repr = match alternative {
+                                    InfixAlternative::PrefixOperation(notation) => {
+                                        InteractionInternalRepresentation::Operator(notation.operator.clone(), vec![expression])
+                                    },
+                                    _ => {
+                                        expression
+                                    }
+                                };
+                                InfixStep::PrimaryParsed{rem:after_closing,int_repr,is_grouped:true,annotations}
+                            },
+                            Err(e) => {
+                                InfixStep::AlternativeFailed(e)
+                            }
+                        }
+                    },
+                    Some(InfixFrame::Expression{..}) => {
+                        unreachable!("an expression is always parsed within a primary sub-interaction or at the root");
+                    }
+                }
+            },
+            InfixStep::AlternativeFailed(e) => {
+                let Some(InfixFrame::Primary{at,annotations,error,alternative}) = stack.pop() else {
+                    unreachable!();
+                };
+                depth -= 1;
+                match e {
+                    nom::Err::Error(e) => {
+                        // the next alternatives are tried at the same input
+                        InfixStep::Alternatives{at,annotations,next:alternative.index() + 1,error:Some(combine(error, e))}
+                    },
+                    other => {
+                        InfixStep::Unwind(other)
+                    }
+                }
+            },
+            InfixStep::Unwind(e) => {
+                // the expression within which the error occurred is abandoned
+                while matches!(stack.last(), Some(InfixFrame::Expression{..})) {
+                    stack.pop();
+                }
+                match stack.last() {
+                    None => {
+                        return Err(e);
+                    },
+                    Some(InfixFrame::Primary{alternative:InfixAlternative::OperatorApplication{operator_text,operands,..},..}) => {
+                        let (operator_text,operand_index) : (&'a str,usize) = (operator_text,operands.len());
+                        InfixStep::AlternativeFailed(e.map(|e| e.within_operator(operator_text, operand_index)))
+                    },
+                    Some(_) => {
+                        InfixStep::AlternativeFailed(e)
+                    }
+                }
+            }
+        };
+    }
+}
+
+
+
+/** 
+ * Closes the operator application at the top of the stack, its closing parenthesis having been parsed,
+ * checking the number of its operands.
+ * **/
+fn close_infix_operator_application<'a,CioII,E>
+    (
+        stack : &mut Vec<InfixFrame<'a,'_,CioII,E>>,
+        depth : &mut usize,
+        rem : &'a str
+    ) -> InfixStep<'a,CioII,E>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    E : InteractionParseError<'a>
+{
+    let Some(InfixFrame::Primary{at,alternative:InfixAlternative::OperatorApplication{operator_text,operator,operands},..}) = stack.last() else {
+        unreachable!();
+    };
+    if !operator.accepts_number_of_operands(operands.len()) {
+        let (min_operands,max_operands) = operator.get_operands_bounds_in_internal_representation();
+        let kind = ParseErrorKind::ArityMismatch { 
+            operator: operator_text.trim().to_owned(), 
+            min_operands, 
+            max_operands, 
+            got: operands.len() 
+        };
+        return InfixStep::AlternativeFailed(nom::Err::Error(E::from_parse_error_kind(at, kind)));
+    }
+    let Some(InfixFrame::Primary{annotations,alternative:InfixAlternative::OperatorApplication{operator,operands,..},..}) = stack.pop() else {
+        unreachable!();
+    };
+    *depth -= 1;
+    InfixStep::PrimaryParsed{
+        rem,
+        int_repr:InteractionInternalRepresentation::Operator(operator,operands),
+        is_grouped:true,
+        annotations
+    }
+}
+
+
+
+/** 
+ * Pops the operator at the top of the stack and replaces its operands (the last ones) by its application to them,
+ * flattening the operands which are themselves ungrouped applications of the same associative operator.
+ * Consecutive applications of the same associative operator at the top of the stack are applied at once
+ * so that long chains of right-associative operators are flattened in linear time.
+ * **/
+fn apply_infix_binary_operators<CioII>
+    (
+        operands : &mut Vec<(InteractionInternalRepresentation<CioII>,bool)>,
+        operators : &mut Vec<&OperatorNotation<CioII::InteractionOperatorType>>
+    )
+where 
+    CioII : AnnotatedCommonIoInteractionInterface
+{
+    let notation = operators.pop().unwrap();
+    let is_associative = notation.operator.is_associative();
+    let mut applications = 1;
+    while is_associative && matches!(operators.last(), Some(previous) if std::ptr::eq(*previous, notation)) {
+        operators.pop();
+        applications += 1;
+    }
+    let mut flattened = vec![];
+    for (mut operand,operand_is_grouped) in operands.split_off(operands.len() - applications - 1) {
+        match &mut operand {
+            InteractionInternalRepresentation::Operator(sub_op, sub_operands) 
+                    if !operand_is_grouped && is_associative && *sub_op == notation.operator => {
+                if flattened.is_empty() {
+                    flattened = std::mem::take(sub_operands);
+                } else {
+                    flattened.append(sub_operands);
+                }
+            },
+            _ => {
+                flattened.push(operand);
+            }
+        }
+    }
+    operands.push((InteractionInternalRepresentation::Operator(notation.operator.clone(), flattened),false));
+}
@@ -17,4 +17,12 @@ limitations under the License.
 
 pub mod util;
 pub mod parse;
+pub mod recovery;
+pub mod comments;
+pub mod spans;
+pub mod definitions;
+pub mod includes;
+pub mod signature;
+pub mod infix;
+pub mod error;
 pub mod context_aware_parser;
\ No newline at end of file
@@ -17,13 +17,20 @@ limitations under the License.
 
 
 
-use nom::error::VerboseError;
+use nom::error::ErrorKind;
+use nom::Offset;
 
 use crate::from_text::context_aware_parser::ContextAwareInteractionParser;
-use crate::internal_representation::{CommonIoInteractionInterface, InteractionInternalRepresentation};
+use crate::from_text::error::{ParseContextFrame, ParseError, ParseErrorKind, ParseFailureCause, ParseFailureTrace, TextPositions};
+use crate::from_text::util::iterative_parser::parse_interaction_without_recursion;
+use crate::from_text::util::layout_recorder::InteractionLayoutRecorder;
+use crate::internal_representation::{AnnotatedCommonIoInteractionInterface, InteractionInternalRepresentation};
 
 
 
+/** 
+ * Parses an interaction from the beginning of the input, ignoring whatever follows it.
+ * **/
 pub fn parse_interaction<CioII,Parser>
     (
         input_str : &str,
@@ -31,22 +38,220 @@ pub fn parse_interaction<CioII,Parser>
     ) -> 
         Result<
             InteractionInternalRepresentation<CioII>, 
-            String
+            ParseError
         > 
 where 
-    CioII : CommonIoInteractionInterface,
+    CioII : AnnotatedCommonIoInteractionInterface,
     Parser : ContextAwareInteractionParser<CioII>
 {
-    match parser.parse_interaction_inner::<VerboseError<&str>>(input_str) {
-        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
-            Err(nom::error::convert_error(input_str, e))
+    parse_interaction_and_get_remaining_input(input_str, parser).map(|(_,int_repr)| int_repr)
+}
+
+
+
+/** 
+ * Parses an interaction, requiring the whole input to be consumed.
+ * Contrary to *parse_interaction*, if the interaction is followed by anything else than whitespace and comments,
+ * an error of kind *ParseErrorKind::TrailingInput* is returned instead of silently ignoring the remaining input.
+ * **/
+pub fn parse_interaction_strict<CioII,Parser>
+    (
+        input_str : &str,
+        parser : &Parser
+    ) -> 
+        Result<
+            InteractionInternalRepresentation<CioII>, 
+            ParseError
+        > 
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : ContextAwareInteractionParser<CioII>
+{
+    let (rem,int_repr) = parse_interaction_and_get_remaining_input(input_str, parser)?;
+    if rem.is_empty() {
+        Ok(int_repr)
+    } else {
+        let offset = input_str.len() - rem.len();
+        Err(
+            ParseError::new(
+                input_str, 
+                offset, 
+                ParseErrorKind::TrailingInput { trailing: rem.to_owned() }, 
+                vec![], 
+                vec![]
+            )
+        )
+    }
+}
+
+
+
+fn parse_interaction_and_get_remaining_input<'a,CioII,Parser>
+    (
+        input_str : &'a str,
+        parser : &Parser
+    ) -> 
+        Result<
+            (&'a str,InteractionInternalRepresentation<CioII>), 
+            ParseError
+        > 
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : ContextAwareInteractionParser<CioII>
+{
+    into_parse_result(input_str, parser.parse_interaction_inner::<ParseFailureTrace<'a>>(input_str))
+}
+
+
+
+/** 
+ * Parses an interaction from the beginning of the input, ignoring whatever follows it, 
+ * and records the spans and comments of its sub-interactions (see *InteractionLayoutRecorder*).
+ * The interaction is parsed as by the default *parse_interaction_inner*.
+ * **/
+pub(crate) fn parse_interaction_and_record_layout<'a,CioII,Parser>
+    (
+        input_str : &'a str,
+        parser : &Parser
+    ) -> 
+        Result<
+            (InteractionInternalRepresentation<CioII>,InteractionLayoutRecorder<'a>), 
+            ParseError
+        > 
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : ContextAwareInteractionParser<CioII>
+{
+    let mut layout = InteractionLayoutRecorder::new(input_str);
+    let parsed = parse_interaction_without_recursion::<CioII,Parser,ParseFailureTrace<'a>>(parser, input_str, false, Some(&mut layout));
+    let (_,int_repr) = into_parse_result(input_str, parsed)?;
+    Ok((int_repr,layout))
+}
+
+
+
+fn into_parse_result<'a,CioII>
+    (
+        input_str : &'a str,
+        parsed : nom::IResult<&'a str,InteractionInternalRepresentation<CioII>,ParseFailureTrace<'a>>
+    ) -> 
+        Result<
+            (&'a str,InteractionInternalRepresentation<CioII>), 
+            ParseError
+        > 
+where 
+    CioII : AnnotatedCommonIoInteractionInterface
+{
+    match parsed {
+        Err(nom::Err::Error(trace)) | Err(nom::Err::Failure(trace)) => {
+            Err(make_parse_error_from_trace(input_str, trace))
+        },
+        Err(nom::Err::Incomplete(_)) => {
+            Err(
+                ParseError::new(
+                    input_str, 
+                    input_str.len(), 
+                    ParseErrorKind::Lexical, 
+                    vec![], 
+                    vec![]
+                )
+            )
+        },
+        Ok(got) => {
+            Ok(got)
+        }
+    }
+}
+
+
+
+pub(crate) fn make_parse_error_from_trace
+    (
+        input_str : &str,
+        trace : ParseFailureTrace
+    ) -> ParseError
+{
+    make_parse_error_from_trace_with_positions(input_str, &TextPositions::new(input_str), trace)
+}
+
+
+
+/** 
+ * Same as *make_parse_error_from_trace* with the *positions* of the input already computed, 
+ * so that building many errors does not count lines from the start of the input for each of them.
+ * **/
+pub(crate) fn make_parse_error_from_trace_with_positions
+    (
+        input_str : &str,
+        positions : &TextPositions,
+        trace : ParseFailureTrace
+    ) -> ParseError
+{
+    let offset = input_str.len() - trace.remaining.len();
+    let context = get_context_frames(input_str, positions, &trace.context);
+    if let Some(kind) = trace.kind {
+        return ParseError::with_positions(positions, input_str, offset, *kind, vec![], context);
+    }
+    let mut expected = vec![];
+    for cause in &trace.causes {
+        if let Some(description) = describe_expected_construct(cause) {
+            if !expected.contains(&description) {
+                expected.push(description);
+            }
+        }
+    }
+    ParseError::with_positions(positions, input_str, offset, ParseErrorKind::Lexical, expected, context)
+}
+
+
+
+fn describe_expected_construct(cause : &ParseFailureCause) -> Option<String> {
+    match cause {
+        ParseFailureCause::Char(c) => {
+            Some(format!("'{}'", c))
+        },
+        ParseFailureCause::Kind(ErrorKind::Tag) => {
+            Some("a keyword or symbol".to_owned())
+        },
+        ParseFailureCause::Kind(ErrorKind::Alpha) | ParseFailureCause::Kind(ErrorKind::AlphaNumeric) => {
+            Some("a name".to_owned())
         },
-        Ok( (_,int_repr)) => {
-            Ok(int_repr)
+        ParseFailureCause::Kind(ErrorKind::Digit) => {
+            Some("a number".to_owned())
         },
-        _ => {
-            panic!()
+        ParseFailureCause::Kind(ErrorKind::TakeUntil) => {
+            Some("the end of the comment".to_owned())
+        },
+        ParseFailureCause::Kind(ErrorKind::Eof) => {
+            Some("the end of the input".to_owned())
+        },
+        ParseFailureCause::Kind(_) => {
+            None
         }
     }
 }
 
+
+
+/** 
+ * Returns the frames of the operators recorded in a *ParseFailureTrace* (from the innermost to the outermost),
+ * from the outermost to the innermost.
+ * **/
+fn get_context_frames(input_str : &str, positions : &TextPositions, recorded : &[(&str,usize)]) -> Vec<ParseContextFrame> {
+    let mut context = vec![];
+    for (operator_text,operand_index) in recorded.iter().rev() {
+        let operator_offset = input_str.offset(operator_text);
+        let (line,column) = positions.get_line_and_column(input_str, operator_offset);
+        context.push(
+            ParseContextFrame{
+                operator:operator_text.trim().to_owned(),
+                operand_index:*operand_index,
+                source:None,
+                offset:operator_offset,
+                line,
+                column
+            }
+        );
+    }
+    context
+}
\ No newline at end of file
@@ -0,0 +1,402 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use nom::combinator::consumed;
+use nom::IResult;
+use nom::error::ParseError as NomParseError;
+
+use crate::from_text::context_aware_parser::ContextAwareInteractionParser;
+use crate::from_text::util::delimited_lang_parser::get_char_indices_outside_comments_and_annotations;
+use crate::from_text::error::{InteractionParseError, ParseError, ParseErrorKind, ParseFailureCause, ParseFailureTrace, TextPositions};
+use crate::from_text::parse::make_parse_error_from_trace_with_positions;
+use crate::internal_representation::{AnnotatedCommonIoInteractionInterface, InteractionInternalRepresentation, InteractionOperatorRepresentation};
+
+
+
+/** 
+ * A parser which, in addition to the normal parsing, is able to recover from errors.
+ * Whenever a sub-interaction cannot be parsed, it is replaced in the internal representation 
+ * by a placeholder leaf pattern provided by *make_error_placeholder*.
+ * **/
+pub trait RecoveringInteractionParser<CioII : AnnotatedCommonIoInteractionInterface> : ContextAwareInteractionParser<CioII> {
+
+    fn make_error_placeholder(&self, error : &ParseError) -> CioII::InteractionLeafPatternType;
+
+}
+
+
+
+/** 
+ * Parses an interaction while recovering from errors, so that all the errors are reported in a single pass.
+ * When a sub-interaction cannot be parsed, the parser resynchronises at the next *separator_char* or
+ * *right_parenthesis_char* of the enclosing operator and an error placeholder is inserted in place of the sub-interaction.
+ * The parser then backtracks to the beginning of the sub-interaction (see *ContextAwareInteractionParser::backtrack*),
+ * so that the names resolved within the skipped text are discarded.
+ * Operators with an incorrect number of operands are also replaced by placeholders, 
+ * as are operators which exceed the maximal nesting depth of the parser (see *max_nesting_depth*).
+ * Input which follows the interaction (other than whitespace) is reported as a *ParseErrorKind::TrailingInput* error.
+ * The returned errors are sorted by order of appearance in the input and, if there are none, 
+ * the returned interaction is the same as that returned by *parse_interaction_strict*.
+ * **/
+pub fn parse_interaction_with_recovery<CioII,Parser>
+    (
+        input_str : &str,
+        parser : &Parser
+    ) -> 
+        (
+            InteractionInternalRepresentation<CioII>, 
+            Vec<ParseError>
+        )
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : RecoveringInteractionParser<CioII>
+{
+    let positions = TextPositions::new(input_str);
+    let mut errors = vec![];
+    let (rem,int_repr) = recover_interaction(input_str, &positions, parser, &mut errors);
+    if !rem.is_empty() {
+        errors.push(
+            ParseError::with_positions(
+                &positions,
+                input_str, 
+                input_str.len() - rem.len(), 
+                ParseErrorKind::TrailingInput { trailing: rem.to_owned() }, 
+                vec![], 
+                vec![]
+            )
+        );
+    }
+    errors.sort_by_key(|err| err.offset);
+    (int_repr,errors)
+}
+
+
+
+/** 
+ * An operator whose operands are being recovered.
+ * **/
+struct RecoveryFrame<'a,CioII : AnnotatedCommonIoInteractionInterface> {
+    // the input at which the operator starts
+    input : &'a str,
+    // the text of the operator and of its parameters
+    operator_text : &'a str,
+    operator : CioII::InteractionOperatorType,
+    // the annotations which precede the operator
+    annotations : Vec<CioII::InteractionAnnotationType>,
+    operands : Vec<InteractionInternalRepresentation<CioII>>
+}
+
+
+
+/** 
+ * Parses an interaction while recovering from errors, using an explicit stack of the operators being parsed 
+ * so that deeply nested interactions do not overflow the call stack.
+ * An operator which exceeds *max_nesting_depth* is replaced by a placeholder.
+ * **/
+fn recover_interaction<'a,CioII,Parser>
+    (
+        input_str : &'a str,
+        positions : &TextPositions,
+        parser : &Parser,
+        errors : &mut Vec<ParseError>
+    ) -> (&'a str, InteractionInternalRepresentation<CioII>)
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : RecoveringInteractionParser<CioII>
+{
+    let mut stack : Vec<RecoveryFrame<'a,CioII>> = vec![];
+    // the operators within which the sub-interaction being parsed is located and the indices of the operands
+    let mut context : Vec<(&'a str,usize)> = vec![];
+    let mut rem = input_str;
+    // whether the end of the input has been reached within an operator which is not closed
+    // (which is only reported for the innermost such operator)
+    let mut unterminated_reported = false;
+    loop {
+        // recovers a sub-interaction starting at *rem*
+        // (None if it is an operator whose operands remain to be parsed)
+        let input = skip_whitespace(input_str, positions, parser, rem, &context, errors);
+        let recovered = match parser.parse_annotations::<ParseFailureTrace<'a>>(input) {
+            Ok((at_node,annotations)) => {
+                match recover_node(input_str, positions, parser, at_node, stack.len(), &context, errors) {
+                    RecoveredNode::Leaf(after_leaf,int_repr) => {
+                        rem = skip_whitespace(input_str, positions, parser, after_leaf, &context, errors);
+                        Some(int_repr.with_annotations(annotations))
+                    },
+                    RecoveredNode::Operator(after_header,operator_text,operator) => {
+                        context.push((operator_text,0));
+                        stack.push(RecoveryFrame{input:at_node,operator_text,operator,annotations,operands:vec![]});
+                        rem = after_header;
+                        if !rem.starts_with(parser.right_parenthesis_char()) {
+                            continue;
+                        }
+                        // an operator without operands
+                        None
+                    }
+                }
+            },
+            Err(nom::Err::Error(trace)) | Err(nom::Err::Failure(trace)) => {
+                parser.backtrack(input);
+                rem = skip_to_synchronisation_point(parser, input);
+                Some(make_placeholder(input_str, positions, parser, trace, &context, errors))
+            },
+            Err(nom::Err::Incomplete(_)) => {
+                parser.backtrack(input);
+                rem = skip_to_synchronisation_point(parser, input);
+                Some(make_placeholder(input_str, positions, parser, ParseFailureTrace::new(input, vec![]), &context, errors))
+            }
+        };
+        // adds the recovered sub-interaction to the operands of the operator at the top of the stack
+        // and closes the operators which are complete
+        let mut recovered = recovered;
+        'closing: loop {
+            let Some(frame) = stack.last_mut() else {
+                // the stack is only empty once the whole interaction has been recovered
+                return (rem, recovered.unwrap());
+            };
+            if let Some(operand) = recovered.take() {
+                frame.operands.push(operand);
+            }
+            loop {
+                if let Some(after_separator) = rem.strip_prefix(parser.separator_char()) {
+                    rem = after_separator;
+                    if let Some(last) = context.last_mut() {
+                        last.1 += 1;
+                    }
+                    break 'closing;
+                }
+                if let Some(after_closing) = rem.strip_prefix(parser.right_parenthesis_char()) {
+                    rem = after_closing;
+                    break;
+                }
+                // neither a separator nor a right parenthesis after the operand
+                if rem.is_empty() && unterminated_reported {
+                    break;
+                }
+                let trace = ParseFailureTrace::new(
+                    rem,
+                    vec![
+                        ParseFailureCause::Char(parser.separator_char()),
+                        ParseFailureCause::Char(parser.right_parenthesis_char())
+                    ]
+                );
+                errors.push(make_parse_error_in_context(input_str, positions, trace, &context));
+                if rem.is_empty() {
+                    unterminated_reported = true;
+                    break;
+                }
+                rem = skip_to_synchronisation_point(parser, rem);
+            }
+            // the operator at the top of the stack is closed
+            let frame = stack.pop().unwrap();
+            context.pop();
+            rem = skip_whitespace(input_str, positions, parser, rem, &context, errors);
+            let operator_repr = if frame.operator.accepts_number_of_operands(frame.operands.len()) {
+                InteractionInternalRepresentation::Operator(frame.operator,frame.operands)
+            } else {
+                // the names resolved within the operands are discarded together with the operator
+                parser.backtrack(frame.input);
+                let (min_operands,max_operands) = frame.operator.get_operands_bounds_in_internal_representation();
+                let kind = ParseErrorKind::ArityMismatch { 
+                    operator: frame.operator_text.trim().to_owned(), 
+                    min_operands, 
+                    max_operands, 
+                    got: frame.operands.len() 
+                };
+                make_placeholder(input_str, positions, parser, ParseFailureTrace::from_parse_error_kind(frame.input, kind), &context, errors)
+            };
+            recovered = Some(operator_repr.with_annotations(frame.annotations));
+        }
+    }
+}
+
+
+
+enum RecoveredNode<'a,CioII : AnnotatedCommonIoInteractionInterface> {
+    // a leaf pattern or a placeholder, followed by the remaining input
+    Leaf(&'a str,InteractionInternalRepresentation<CioII>),
+    // the header of an operator (up to the whitespace after its left parenthesis), its text and the operator
+    Operator(&'a str,&'a str,CioII::InteractionOperatorType)
+}
+
+
+
+/** 
+ * Recovers a sub-interaction (which annotations have already been parsed) 
+ * located at depth *depth* i.e., within *depth* operators,
+ * trying a leaf pattern and then the header of an operator, as in *parse_interaction_inner*.
+ * **/
+fn recover_node<'a,CioII,Parser>
+    (
+        input_str : &'a str,
+        positions : &TextPositions,
+        parser : &Parser,
+        input : &'a str,
+        depth : usize,
+        context : &[(&'a str,usize)],
+        errors : &mut Vec<ParseError>
+    ) -> RecoveredNode<'a,CioII>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : RecoveringInteractionParser<CioII>
+{
+    let leaf_trace = match parser.parse_explicit_pattern::<ParseFailureTrace<'a>>(input) {
+        Ok((rem,leaf)) => {
+            return RecoveredNode::Leaf(rem, InteractionInternalRepresentation::LeafPattern(leaf));
+        },
+        Err(nom::Err::Error(trace)) | Err(nom::Err::Failure(trace)) => {
+            trace
+        },
+        Err(nom::Err::Incomplete(_)) => {
+            ParseFailureTrace::new(input, vec![])
+        }
+    };
+    parser.backtrack(input);
+    let operator_header : IResult<&'a str,_,ParseFailureTrace<'a>> = nom::sequence::tuple(
+        (
+            consumed(|x| parser.parse_operator_with_parameters(x)),
+            |x| parser.parse_whitespace_and_comments(x),
+            nom::character::complete::char(parser.left_parenthesis_char()),
+            |x| parser.parse_whitespace_and_comments(x)
+        )
+    )(input);
+    let trace = match operator_header {
+        Ok((rem,((operator_text,operator),_,_,_))) => {
+            match parser.max_nesting_depth() {
+                Some(max_depth) if depth >= max_depth => {
+                    ParseFailureTrace::from_parse_error_kind(input, ParseErrorKind::NestingTooDeep { max_depth })
+                },
+                _ => {
+                    return RecoveredNode::Operator(rem,operator_text,operator);
+                }
+            }
+        },
+        Err(nom::Err::Error(operator_trace)) | Err(nom::Err::Failure(operator_trace)) => {
+            leaf_trace.or(operator_trace)
+        },
+        Err(nom::Err::Incomplete(_)) => {
+            leaf_trace
+        }
+    };
+    // the names resolved within the text which is skipped are discarded
+    parser.backtrack(input);
+    let placeholder = make_placeholder(input_str, positions, parser, trace, context, errors);
+    RecoveredNode::Leaf(skip_to_synchronisation_point(parser, input), placeholder)
+}
+
+
+
+/** 
+ * Reports the error corresponding to the trace and returns the placeholder which replaces the erroneous sub-interaction.
+ * **/
+fn make_placeholder<'a,CioII,Parser>
+    (
+        input_str : &'a str,
+        positions : &TextPositions,
+        parser : &Parser,
+        trace : ParseFailureTrace<'a>,
+        context : &[(&'a str,usize)],
+        errors : &mut Vec<ParseError>
+    ) -> InteractionInternalRepresentation<CioII>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : RecoveringInteractionParser<CioII>
+{
+    let error = make_parse_error_in_context(input_str, positions, trace, context);
+    let placeholder = parser.make_error_placeholder(&error);
+    errors.push(error);
+    InteractionInternalRepresentation::LeafPattern(placeholder)
+}
+
+
+
+/** 
+ * Skips whitespace and comments, reporting unterminated block comments.
+ * **/
+fn skip_whitespace<'a,CioII,Parser>
+    (
+        input_str : &'a str,
+        positions : &TextPositions,
+        parser : &Parser,
+        input : &'a str,
+        context : &[(&'a str,usize)],
+        errors : &mut Vec<ParseError>
+    ) -> &'a str
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : RecoveringInteractionParser<CioII>
+{
+    match parser.parse_whitespace_and_comments::<ParseFailureTrace<'a>>(input) {
+        Ok((rem,_)) => {
+            rem
+        },
+        Err(nom::Err::Error(trace)) | Err(nom::Err::Failure(trace)) => {
+            errors.push(make_parse_error_in_context(input_str, positions, trace, context));
+            &input[input.len()..]
+        },
+        Err(nom::Err::Incomplete(_)) => {
+            &input[input.len()..]
+        }
+    }
+}
+
+
+
+/** 
+ * Builds the error corresponding to the trace, 
+ * which is located within the operators of the *context* (from the outermost to the innermost).
+ * **/
+fn make_parse_error_in_context<'a>
+    (
+        input_str : &'a str,
+        positions : &TextPositions,
+        trace : ParseFailureTrace<'a>,
+        context : &[(&'a str,usize)]
+    ) -> ParseError 
+{
+    let mut trace = trace;
+    for (operator_text,operand_index) in context.iter().rev() {
+        trace = trace.within_operator(operator_text, *operand_index);
+    }
+    make_parse_error_from_trace_with_positions(input_str, positions, trace)
+}
+
+
+
+/** 
+ * Skips the input until the next separator or right parenthesis which is not nested within other parentheses.
+ * **/
+fn skip_to_synchronisation_point<'a,Parser,CioII>(parser : &Parser, input : &'a str) -> &'a str 
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : ContextAwareInteractionParser<CioII>
+{
+    let mut depth : usize = 0;
+    for (index,c) in get_char_indices_outside_comments_and_annotations(parser, input) {
+        if c == parser.left_parenthesis_char() {
+            depth += 1;
+        } else if c == parser.right_parenthesis_char() {
+            if depth == 0 {
+                return &input[index..];
+            }
+            depth -= 1;
+        } else if c == parser.separator_char() && depth == 0 {
+            return &input[index..];
+        }
+    }
+    &input[input.len()..]
+}
@@ -0,0 +1,321 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use nom::IResult;
+
+use crate::from_text::context_aware_parser::ContextAwareInteractionParser;
+use crate::from_text::error::{get_line_and_column, InteractionParseError, ParseError, ParseErrorKind};
+use crate::from_text::parse::parse_interaction_strict;
+use crate::from_text::util::delimited_lang_parser::{skip_whitespace_and_comments, DelimitedInteractionLanguageParser};
+use crate::from_text::util::parse_utils::parse_label_with_underscores;
+use crate::internal_representation::{AnnotatedCommonIoInteractionInterface, InteractionInternalRepresentation};
+
+
+
+/** 
+ * The names of the lifelines, messages and gates an interaction refers to,
+ * in the order in which they are declared.
+ * **/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InteractionSignature {
+    pub lifelines : Vec<String>,
+    pub messages : Vec<String>,
+    pub gates : Vec<String>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureCategory {
+    Lifeline,
+    Message,
+    Gate
+}
+
+impl InteractionSignature {
+
+    pub fn get_names(&self, category : SignatureCategory) -> &Vec<String> {
+        match category {
+            SignatureCategory::Lifeline => &self.lifelines,
+            SignatureCategory::Message => &self.messages,
+            SignatureCategory::Gate => &self.gates
+        }
+    }
+
+    fn get_names_mut(&mut self, category : SignatureCategory) -> &mut Vec<String> {
+        match category {
+            SignatureCategory::Lifeline => &mut self.lifelines,
+            SignatureCategory::Message => &mut self.messages,
+            SignatureCategory::Gate => &mut self.gates
+        }
+    }
+
+}
+
+
+
+/** 
+ * A signature through which a parser resolves the names of lifelines, messages and gates (see *parse_name*).
+ * If *interning*, the names which are not declared are added to the signature as they are resolved.
+ * Names resolved within alternatives which fail are discarded when another name is resolved at the same position
+ * or when the parser backtracks (see *backtrack*).
+ * **/
+#[derive(Debug)]
+pub struct SharedSignature {
+    signature : RefCell<InteractionSignature>,
+    interning : bool,
+    // the category of each interned name and the length of the input at which it has been resolved
+    interned : RefCell<Vec<(SignatureCategory,usize)>>
+}
+
+impl SharedSignature {
+
+    pub fn new(signature : InteractionSignature, interning : bool) -> Self {
+        SharedSignature{signature:RefCell::new(signature),interning,interned:RefCell::new(vec![])}
+    }
+
+    pub fn get_signature(&self) -> InteractionSignature {
+        self.signature.borrow().clone()
+    }
+
+    /** 
+     * Parses a name of the given category and returns its index in the signature, interning it if need be.
+     * Otherwise, if the name is well-formed but unknown, the error has the kind *ParseErrorKind::UnknownName*
+     * (see *parse_element_of_preexisting_vec_and_return_index*).
+     * **/
+    pub fn parse_name<'a, E: InteractionParseError<'a>>(&self, category : SignatureCategory, input : &'a str) -> IResult<&'a str, usize, E> {
+        let (rem,name) = parse_label_with_underscores(input)?;
+        // the names interned from here on have been resolved within alternatives which have failed
+        self.backtrack(input);
+        let mut signature = self.signature.borrow_mut();
+        let names = signature.get_names_mut(category);
+        if let Some(index) = names.iter().position(|x| *x == name) {
+            return Ok((rem,index));
+        }
+        if !self.interning {
+            return Err(nom::Err::Error(E::from_parse_error_kind(input.trim_start(), ParseErrorKind::UnknownName { name })));
+        }
+        names.push(name);
+        self.interned.borrow_mut().push((category,input.len()));
+        Ok((rem,names.len() - 1))
+    }
+
+    /** 
+     * Discards the names which have been interned at the beginning of the input or after it,
+     * when the parser backtracks to it (see *ContextAwareInteractionParser::backtrack*).
+     * **/
+    pub fn backtrack(&self, input : &str) {
+        let mut interned = self.interned.borrow_mut();
+        while let Some((category,length)) = interned.last() {
+            if *length > input.len() {
+                break;
+            }
+            self.signature.borrow_mut().get_names_mut(*category).pop();
+            interned.pop();
+        }
+    }
+
+}
+
+
+
+/** 
+ * A parser which resolves the names of lifelines, messages and gates through a *SharedSignature*.
+ * **/
+pub trait SignatureBasedInteractionParser<CioII : AnnotatedCommonIoInteractionInterface> : ContextAwareInteractionParser<CioII> + Sized {
+
+    /** 
+     * Builds a parser which resolves names with *SharedSignature::parse_name* 
+     * and whose *backtrack* calls *SharedSignature::backtrack*.
+     * **/
+    fn from_signature(signature : Rc<SharedSignature>) -> Self;
+
+}
+
+
+
+/** 
+ * Parses the signature header at the beginning of the input, as in *@lifelines{a,b,c} @messages{m,n} @gates{g1}*
+ * (see *signature_section_keywords* and *signature_section_delimiters*).
+ * Each section is optional and may occur several times, 
+ * its keyword being followed by a character which cannot continue a name (e.g., whitespace or the opening delimiter),
+ * the names being separated by the *separator_char* of the language.
+ * A name which is declared twice in the same section is reported with the kind *ParseErrorKind::DuplicateDeclaration*.
+ * Returns the declared names and the offset at which the header ends.
+ * **/
+pub fn parse_signature_header<Parser>(input_str : &str, parser : &Parser) -> Result<(InteractionSignature,usize),ParseError> 
+where 
+    Parser : DelimitedInteractionLanguageParser
+{
+    let syntax_error = |rem : &str, expected : String| {
+        ParseError::new(input_str, input_str.len() - rem.len(), ParseErrorKind::Lexical, vec![expected], vec![])
+    };
+    let (lifelines_keyword,messages_keyword,gates_keyword) = parser.signature_section_keywords();
+    let sections = [
+        (lifelines_keyword,SignatureCategory::Lifeline),
+        (messages_keyword,SignatureCategory::Message),
+        (gates_keyword,SignatureCategory::Gate)
+    ];
+    let (opening,closing) = parser.signature_section_delimiters();
+    let mut signature = InteractionSignature::default();
+    let mut rem = skip_whitespace_and_comments(parser, input_str);
+    loop {
+        // the keyword must not be the beginning of a longer name, as in *@gatesX*
+        let section = sections.iter().find_map(
+            |(keyword,category)| rem.strip_prefix(keyword)
+                .filter(|after| !after.starts_with(|c : char| c.is_alphanumeric() || c == '_'))
+                .map(|after| (*category,after))
+        );
+        let (category,after_section) = match section {
+            Some(got) => {
+                got
+            },
+            None => {
+                return Ok((signature, input_str.len() - rem.len()));
+            }
+        };
+        rem = skip_whitespace_and_comments(parser, after_section);
+        rem = rem.strip_prefix(opening).ok_or_else(|| syntax_error(rem, format!("'{}'", opening)))?;
+        rem = skip_whitespace_and_comments(parser, rem);
+        if let Some(after_closing) = rem.strip_prefix(closing) {
+            rem = skip_whitespace_and_comments(parser, after_closing);
+            continue;
+        }
+        loop {
+            if !rem.starts_with(|c : char| c.is_alphabetic()) {
+                return Err(syntax_error(rem, "a name".to_owned()));
+            }
+            let length = rem.find(|c : char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rem.len());
+            let name = rem[..length].to_owned();
+            let names = signature.get_names_mut(category);
+            if names.contains(&name) {
+                return Err(
+                    ParseError::new(
+                        input_str, 
+                        input_str.len() - rem.len(), 
+                        ParseErrorKind::DuplicateDeclaration { name }, 
+                        vec![], 
+                        vec![]
+                    )
+                );
+            }
+            names.push(name);
+            rem = skip_whitespace_and_comments(parser, &rem[length..]);
+            if let Some(after_separator) = rem.strip_prefix(parser.separator_char()) {
+                rem = skip_whitespace_and_comments(parser, after_separator);
+            } else if let Some(after_closing) = rem.strip_prefix(closing) {
+                rem = skip_whitespace_and_comments(parser, after_closing);
+                break;
+            } else {
+                return Err(syntax_error(rem, format!("'{}' or '{}'", parser.separator_char(), closing)));
+            }
+        }
+    }
+}
+
+
+
+/** 
+ * Parses an interaction preceded by a signature header (see *parse_signature_header*), 
+ * the parser being built from the declared signature.
+ * The whole input must be consumed.
+ * Returns the interaction together with the signature, from which the indices of the names in the interaction are taken.
+ * **/
+pub fn parse_interaction_with_signature<CioII,Parser>
+    (
+        input_str : &str
+    ) -> 
+        Result<
+            (InteractionInternalRepresentation<CioII>,InteractionSignature), 
+            ParseError
+        > 
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : SignatureBasedInteractionParser<CioII>
+{
+    parse_interaction_with_shared_signature::<CioII,Parser>(input_str, false)
+}
+
+
+
+/** 
+ * Parses an interaction, optionally preceded by a signature header, 
+ * interning the names which are not declared in the header as the parser resolves them (see *SharedSignature*), 
+ * so that whether a name is a lifeline, a message or a gate is decided by where it occurs.
+ * The whole input must be consumed.
+ * Returns the interaction together with the populated signature.
+ * **/
+pub fn parse_interaction_with_interning<CioII,Parser>
+    (
+        input_str : &str
+    ) -> 
+        Result<
+            (InteractionInternalRepresentation<CioII>,InteractionSignature), 
+            ParseError
+        > 
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : SignatureBasedInteractionParser<CioII>
+{
+    parse_interaction_with_shared_signature::<CioII,Parser>(input_str, true)
+}
+
+
+
+fn parse_interaction_with_shared_signature<CioII,Parser>
+    (
+        input_str : &str,
+        interning : bool
+    ) -> 
+        Result<
+            (InteractionInternalRepresentation<CioII>,InteractionSignature), 
+            ParseError
+        > 
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : SignatureBasedInteractionParser<CioII>
+{
+    let header_parser = Parser::from_signature(Rc::new(SharedSignature::new(InteractionSignature::default(), false)));
+    let (signature,header_length) = parse_signature_header(input_str, &header_parser)?;
+    let shared = Rc::new(SharedSignature::new(signature, interning));
+    let parser = Parser::from_signature(shared.clone());
+    match parse_interaction_strict(&input_str[header_length..], &parser) {
+        Ok(int_repr) => {
+            Ok((int_repr,shared.get_signature()))
+        },
+        Err(err) => {
+            Err(relocate_error(err, input_str, header_length))
+        }
+    }
+}
+
+
+
+/** 
+ * Relocates an error which occurred when parsing the input after its first *shift* bytes.
+ * **/
+fn relocate_error(mut err : ParseError, input_str : &str, shift : usize) -> ParseError {
+    err.offset += shift;
+    (err.line,err.column) = get_line_and_column(input_str, err.offset);
+    for frame in err.context.iter_mut() {
+        frame.offset += shift;
+        (frame.line,frame.column) = get_line_and_column(input_str, frame.offset);
+    }
+    err
+}
@@ -0,0 +1,91 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use std::collections::BTreeMap;
+
+use crate::from_text::context_aware_parser::ContextAwareInteractionParser;
+use crate::from_text::error::{get_line_and_column, ParseError};
+use crate::from_text::parse::parse_interaction_and_record_layout;
+use crate::internal_representation::{AnnotatedCommonIoInteractionInterface, InteractionInternalRepresentation};
+
+
+
+/** 
+ * The location of a sub-interaction in the text it has been parsed from,
+ * *start* and *end* being byte offsets (*end* being excluded).
+ * **/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SourceSpan {
+    pub start : usize,
+    pub end : usize
+}
+
+impl SourceSpan {
+
+    /** 
+     * Returns the line and column (both starting at 1) at which the span starts in the *input*.
+     * **/
+    pub fn get_start_line_and_column(&self, input : &str) -> (usize,usize) {
+        get_line_and_column(input, self.start)
+    }
+
+    /** 
+     * Returns the line and column (both starting at 1) at which the span ends in the *input*.
+     * **/
+    pub fn get_end_line_and_column(&self, input : &str) -> (usize,usize) {
+        get_line_and_column(input, self.end)
+    }
+
+    pub fn get_text<'a>(&self, input : &'a str) -> &'a str {
+        &input[self.start..self.end]
+    }
+
+}
+
+
+
+/**
+ The spans of all the leaf patterns and operators of an interaction internal representation.
+Each node is identified by its path from the root, i.e., the indices of the operands that lead to it (the root having the empty path).
+ **/
+pub type InteractionSpans = BTreeMap<Vec<usize>,SourceSpan>;
+
+
+
+/** 
+ * Parses an interaction and records the span of each of its sub-interactions,
+ * so that downstream analyses may report issues at a source location.
+ * The spans are recorded while parsing, as by the default *parse_interaction_inner*.
+ * The span of an operator goes from the operator itself to its right parenthesis included.
+ * **/
+pub fn parse_interaction_with_spans<CioII,Parser>
+    (
+        input_str : &str,
+        parser : &Parser
+    ) -> 
+        Result<
+            (InteractionInternalRepresentation<CioII>,InteractionSpans), 
+            ParseError
+        > 
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : ContextAwareInteractionParser<CioII>
+{
+    let (int_repr,layout) = parse_interaction_and_record_layout(input_str, parser)?;
+    Ok((int_repr,layout.spans))
+}
@@ -16,21 +16,270 @@ limitations under the License.
 
 
 
+use nom::character::complete::multispace0;
+use nom::error::{ErrorKind, ParseError};
+use nom::IResult;
 
+use crate::internal_representation::OperatorParameters;
+use crate::syntax::{DefaultInteractionLanguageSyntax, InteractionLanguageSyntax};
+
+
+
+/** 
+ * The delimiters, comments and keywords with which the interactions of a language are parsed 
+ * (the annotations and definitions being given by its *syntax*).
+ * **/
 pub trait DelimitedInteractionLanguageParser {
 
     fn left_parenthesis_char(&self) -> char;
     fn right_parenthesis_char(&self) -> char;
     fn separator_char(&self) -> char;
 
-}
+    /** 
+     * The syntax of annotations and definitions, which the printer of the language should share.
+     * By default, the language has no annotations and definitions are written as in *let name = interaction;*.
+     * **/
+    fn syntax(&self) -> &dyn InteractionLanguageSyntax {
+        &DefaultInteractionLanguageSyntax
+    }
+
+    /** 
+     * The start of a line comment, which extends until the end of the line.
+     * By default, the language has no line comments.
+     * **/
+    fn line_comment_start(&self) -> Option<&'static str> {
+        None
+    }
 
+    /** 
+     * The opening and closing delimiters of block comments.
+     * By default, the language has no block comments.
+     * **/
+    fn block_comment_delimiters(&self) -> Option<(&'static str,&'static str)> {
+        None
+    }
 
+    /** 
+     * The keyword of the directive which includes the definitions of another source, 
+     * as in *include "path";* (see *parse_interaction_with_includes*).
+     * The directive ends with the *definition_terminator_char* of the *syntax*.
+     * **/
+    fn include_keyword(&self) -> &'static str {
+        "include"
+    }
 
+    /** 
+     * The keywords which introduce the sections of lifelines, messages and gates of a signature header 
+     * (see *parse_signature_header*).
+     * **/
+    fn signature_section_keywords(&self) -> (&'static str,&'static str,&'static str) {
+        ("@lifelines","@messages","@gates")
+    }
 
+    /** 
+     * The opening and closing delimiters of the list of names of a section of a signature header.
+     * **/
+    fn signature_section_delimiters(&self) -> (char,char) {
+        ('{','}')
+    }
 
+    /** 
+     * The maximal length (in bytes) of the text obtained by expanding the references to definitions, beyond which 
+     * an error of kind *ParseErrorKind::ExpansionTooLarge* is returned 
+     * (definitions which refer several times to one another expand exponentially).
+     * By default, it is 16 MiB.
+     * **/
+    fn max_expansion_length(&self) -> Option<usize> {
+        Some(1 << 24)
+    }
 
+    /** 
+     * The maximal number of nested operators in a parsed interaction, beyond which 
+     * an error of kind *ParseErrorKind::NestingTooDeep* is returned.
+     * By default, there is no limit, as parsing, printing and dropping interactions do not recurse once per nesting level.
+     * A language may set one to keep its own recursive consumers of parsed interactions 
+     * (e.g., the conversion to recursive interaction terms) within the call stack.
+     * **/
+    fn max_nesting_depth(&self) -> Option<usize> {
+        None
+    }
 
+    /** 
+     * The opening and closing delimiters of the argument lists that may follow operators, as in *loop[2,5]* or *par{name}*.
+     * By default, operators have no parameters.
+     * **/
+    fn operator_parameters_delimiters(&self) -> Vec<(char,char)> {
+        vec![]
+    }
 
+    /** 
+     * If the input starts with an annotation, returns its length in bytes (delimiters included).
+     * An unterminated annotation extends until the end of the input.
+     * **/
+    fn get_annotation_length(&self, input : &str) -> Option<usize> {
+        let (opening,closing) = self.syntax().annotation_delimiters()?;
+        let content = input.strip_prefix(opening)?;
+        let nested_opening = opening.chars().last();
+        let mut depth : usize = 0;
+        for (index,c) in content.char_indices() {
+            if c == closing {
+                if depth == 0 {
+                    return Some(opening.len() + index + c.len_utf8());
+                }
+                depth -= 1;
+            } else if Some(c) == nested_opening {
+                depth += 1;
+            }
+        }
+        Some(input.len())
+    }
 
+    /** 
+     * Parses an annotation starting exactly at the beginning of the input and returns its content (without delimiters).
+     * **/
+    fn parse_annotation_content<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<&'a str, &'a str, E> {
+        let Some((opening,closing)) = self.syntax().annotation_delimiters().filter(|(opening,_)| input.starts_with(opening)) else {
+            return Err(nom::Err::Error(E::from_error_kind(input, ErrorKind::Tag)));
+        };
+        let length = self.get_annotation_length(input).unwrap();
+        let annotation = &input[..length];
+        if length < opening.len() + closing.len_utf8() || !annotation.ends_with(closing) {
+            return Err(nom::Err::Error(E::from_char(&input[length..], closing)));
+        }
+        Ok((&input[length..], &annotation[opening.len()..(length - closing.len_utf8())]))
+    }
 
+    /** 
+     * If the input starts with a comment, returns its length in bytes.
+     * An unterminated block comment extends until the end of the input.
+     * **/
+    fn get_comment_length(&self, input : &str) -> Option<usize> {
+        if let Some(start) = self.line_comment_start() {
+            if input.starts_with(start) {
+                return Some(input.find('\n').unwrap_or(input.len()));
+            }
+        }
+        if let Some((start,end)) = self.block_comment_delimiters() {
+            if let Some(content) = input.strip_prefix(start) {
+                let length = content.find(end)
+                    .map(|x| start.len() + x + end.len())
+                    .unwrap_or(input.len());
+                return Some(length);
+            }
+        }
+        None
+    }
+
+    /** 
+     * Parses the argument list which may follow an operator, i.e., arguments separated by the *separator_char*
+     * within one of the *operator_parameters_delimiters*.
+     * Separators that are nested within brackets or parentheses do not delimit arguments.
+     * If the input does not start (after whitespace and comments) with an opening delimiter, nothing is consumed and None is returned.
+     * **/
+    fn parse_operator_parameters<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<&'a str, Option<OperatorParameters>, E> {
+        let (at_opening,_) = self.parse_whitespace_and_comments::<E>(input)?;
+        let Some((opening,closing)) = self.operator_parameters_delimiters().into_iter()
+                .find(|(opening,_)| at_opening.starts_with(*opening)) else {
+            return Ok((input,None));
+        };
+        let content = &at_opening[opening.len_utf8()..];
+        let mut arguments = vec![];
+        let mut argument_start = 0;
+        let mut depth : usize = 0;
+        for (index,c) in content.char_indices() {
+            if c == closing && depth == 0 {
+                arguments.push(content[argument_start..index].trim().to_owned());
+                if arguments.len() == 1 && arguments[0].is_empty() {
+                    arguments.clear();
+                }
+                let rem = &content[(index + c.len_utf8())..];
+                return Ok((rem,Some(OperatorParameters::new(opening, closing, arguments))));
+            }
+            if c == opening || c == '(' || c == '[' || c == '{' {
+                depth += 1;
+            } else if depth > 0 && (c == closing || c == ')' || c == ']' || c == '}') {
+                depth -= 1;
+            } else if c == self.separator_char() && depth == 0 {
+                arguments.push(content[argument_start..index].trim().to_owned());
+                argument_start = index + c.len_utf8();
+            }
+        }
+        Err(nom::Err::Error(E::from_char(&content[content.len()..], closing)))
+    }
+
+    /** 
+     * Consumes whitespace and comments, which are handled in the same manner.
+     * Fails if a block comment is not terminated.
+     * **/
+    fn parse_whitespace_and_comments<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<&'a str, &'a str, E> {
+        let mut rem = input;
+        loop {
+            let (after_whitespace,_) = multispace0(rem)?;
+            rem = after_whitespace;
+            match self.get_comment_length(rem) {
+                None => {
+                    return Ok((rem, &input[..(input.len() - rem.len())]));
+                },
+                Some(length) => {
+                    let comment = &rem[..length];
+                    rem = &rem[length..];
+                    if let Some((start,end)) = self.block_comment_delimiters() {
+                        if comment.starts_with(start) && (comment.len() < start.len() + end.len() || !comment.ends_with(end)) {
+                            return Err(nom::Err::Error(E::from_error_kind(rem, ErrorKind::TakeUntil)));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+}
+
+
+
+/** 
+ * Skips whitespace and comments at the beginning of the input 
+ * (an unterminated block comment extending until the end of the input).
+ * **/
+pub(crate) fn skip_whitespace_and_comments<'a,Parser>(parser : &Parser, input : &'a str) -> &'a str 
+where 
+    Parser : DelimitedInteractionLanguageParser + ?Sized
+{
+    let mut rem = input.trim_start();
+    while let Some(length) = parser.get_comment_length(rem) {
+        rem = rem[length..].trim_start();
+    }
+    rem
+}
+
+
+
+/** 
+ * Iterates over the characters of the input which are not within comments or annotations, together with their byte offsets.
+ * Comments and annotations are skipped as they are reached, so that stopping early only costs the input that has been read.
+ * **/
+pub(crate) fn get_char_indices_outside_comments_and_annotations<'a,Parser>(
+    parser : &'a Parser, 
+    input : &'a str
+) -> impl Iterator<Item = (usize,char)> + 'a
+where 
+    Parser : DelimitedInteractionLanguageParser + ?Sized
+{
+    let mut index = 0;
+    std::iter::from_fn(move || {
+        while index < input.len() {
+            match parser.get_comment_length(&input[index..]).or_else(|| parser.get_annotation_length(&input[index..])) {
+                Some(length) => {
+                    index += length;
+                },
+                None => {
+                    let c = input[index..].chars().next().unwrap();
+                    let got = (index,c);
+                    index += c.len_utf8();
+                    return Some(got);
+                }
+            }
+        }
+        None
+    })
+}
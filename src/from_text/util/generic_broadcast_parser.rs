@@ -17,13 +17,11 @@ limitations under the License.
 
 use nom::branch::alt;
 use nom::bytes::complete::tag;
-use nom::character::complete::multispace0;
 use nom::combinator::{value,map};
-use nom::error::ParseError;
 use nom::IResult;
-use nom::multi::separated_list1;
-use nom::sequence::{delimited, tuple};
+use nom::sequence::tuple;
 
+use crate::from_text::error::InteractionParseError;
 use crate::from_text::util::delimited_lang_parser::DelimitedInteractionLanguageParser;
 
 
@@ -37,23 +35,36 @@ pub trait GenericBroadcastParser<GenericBroadcastOrigin,GenericMessage,GenericBr
 
     fn get_tag_for_message_transmission_from_origin(&self) -> &'static str;
 
-    fn parse_message<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<&'a str, GenericMessage,E>;
-
-    fn parse_broadcast_origin<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<&'a str, GenericBroadcastOrigin,E>;
-
-    fn parse_single_broadcast_targets<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<&'a str, GenericBroadcastTarget,E>;
-
-    fn parse_multiple_broadcast_targets<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<&'a str, Vec<GenericBroadcastTarget>,E> {
-        delimited(
-            nom::character::complete::char(self.left_parenthesis_char()),
-            separated_list1(
-                nom::character::complete::char(self.separator_char()), 
-                |x| self.parse_single_broadcast_targets(x)),
-                nom::character::complete::char(self.right_parenthesis_char())
-            )(input)
+    fn parse_message<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<&'a str, GenericMessage,E>;
+
+    fn parse_broadcast_origin<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<&'a str, GenericBroadcastOrigin,E>;
+
+    fn parse_single_broadcast_targets<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<&'a str, GenericBroadcastTarget,E>;
+
+    fn parse_multiple_broadcast_targets<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<&'a str, Vec<GenericBroadcastTarget>,E> {
+        let (mut rem,_) = nom::character::complete::char(self.left_parenthesis_char())(input)?;
+        // the targets are parsed one by one so that an unknown target is reported as such
+        // instead of as a missing right parenthesis
+        let mut targets = vec![];
+        loop {
+            let (before_target,_) = self.parse_whitespace_and_comments(rem)?;
+            let (after_target,target) = self.parse_single_broadcast_targets(before_target)?;
+            targets.push(target);
+            let (after_target,_) = self.parse_whitespace_and_comments(after_target)?;
+            let (after_delimiter,delimiter) = alt(
+                (
+                    nom::character::complete::char(self.separator_char()),
+                    nom::character::complete::char(self.right_parenthesis_char())
+                )
+            )(after_target)?;
+            rem = after_delimiter;
+            if delimiter == self.right_parenthesis_char() {
+                return Ok((rem,targets));
+            }
+        }
     }
 
-    fn parse_broadcast_targets<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<&'a str, Vec<GenericBroadcastTarget>,E> {
+    fn parse_broadcast_targets<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<&'a str, Vec<GenericBroadcastTarget>,E> {
         alt(
             (
                 map(nom::character::complete::char(self.get_empty_target_char()), |_| vec![]),
@@ -63,7 +74,7 @@ pub trait GenericBroadcastParser<GenericBroadcastOrigin,GenericMessage,GenericBr
         )(input)
     }
 
-    fn parse_broadcast_pattern<'a, E: ParseError<&'a str>>(
+    fn parse_broadcast_pattern<'a, E: InteractionParseError<'a>>(
         &self, 
         input : &'a str
     ) -> IResult<&'a str, GenericBroadcastPattern,E> {
@@ -75,16 +86,16 @@ pub trait GenericBroadcastParser<GenericBroadcastOrigin,GenericMessage,GenericBr
         )(input)
     }
 
-    fn parse_broadcast_without_origin<'a, E: ParseError<&'a str>>(
+    fn parse_broadcast_without_origin<'a, E: InteractionParseError<'a>>(
         &self, 
         input : &'a str
     ) -> IResult<&'a str, GenericBroadcastPattern,E> {
         let mut parser = tuple(
             (
                 |x| self.parse_message(x), 
-                multispace0,
+                |x| self.parse_whitespace_and_comments(x),
                 value((),tag(self.get_tag_for_message_reception_by_target())), 
-                multispace0,
+                |x| self.parse_whitespace_and_comments(x),
                 |x| self.parse_broadcast_targets(x)
             )
         );
@@ -97,17 +108,17 @@ pub trait GenericBroadcastParser<GenericBroadcastOrigin,GenericMessage,GenericBr
     }
 
 
-    fn parse_broadcast_with_origin<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<&'a str, GenericBroadcastPattern,E> {
+    fn parse_broadcast_with_origin<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<&'a str, GenericBroadcastPattern,E> {
         let mut parser = tuple(
             (
                 |x| self.parse_broadcast_origin(x), 
-                multispace0,
+                |x| self.parse_whitespace_and_comments(x),
                 value((),tag(self.get_tag_for_message_transmission_from_origin())), 
-                multispace0,
+                |x| self.parse_whitespace_and_comments(x),
                 |x| self.parse_message(x), 
-                multispace0,
+                |x| self.parse_whitespace_and_comments(x),
                 value((),tag(self.get_tag_for_message_reception_by_target())),
-                multispace0,
+                |x| self.parse_whitespace_and_comments(x),
                 |x| self.parse_broadcast_targets(x)
             )
         );
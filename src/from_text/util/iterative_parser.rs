@@ -0,0 +1,399 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use nom::branch::alt;
+use nom::combinator::consumed;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use crate::from_text::context_aware_parser::ContextAwareInteractionParser;
+use crate::from_text::error::{InteractionParseError, ParseErrorKind};
+use crate::from_text::util::layout_recorder::InteractionLayoutRecorder;
+use crate::internal_representation::{AnnotatedCommonIoInteractionInterface, InteractionInternalRepresentation, InteractionOperatorRepresentation};
+
+
+
+/** 
+ * An operator whose operands are being parsed.
+ * **/
+struct OperatorFrame<'a,CioII : AnnotatedCommonIoInteractionInterface,E> {
+    // the input at which the operator starts
+    input : &'a str,
+    // the text of the operator and of its parameters
+    operator_text : &'a str,
+    operator : CioII::InteractionOperatorType,
+    // the annotations which precede the operator
+    annotations : Vec<CioII::InteractionAnnotationType>,
+    operands : Vec<InteractionInternalRepresentation<CioII>>,
+    // the error obtained when trying to parse a leaf pattern where the operator starts
+    // (None for the root of *parse_interaction_with_operator_at_root*)
+    leaf_error : Option<E>,
+    // the error obtained when trying to close the parenthesis right after the operator
+    // (kept until the first operand is parsed)
+    closing_error : Option<E>
+}
+
+
+
+/** 
+ * Parses an interaction as *parse_interaction_inner* (or *parse_interaction_with_operator_at_root* if *operator_at_root*) 
+ * would if it were defined recursively, but using an explicit stack of the operators being parsed.
+ * Errors are combined in the same manner.
+ * If a *layout* is given, the spans and comments of the sub-interactions are recorded in it.
+ * **/
+pub(crate) fn parse_interaction_without_recursion<'a,CioII,Parser,E>
+    (
+        parser : &Parser,
+        input : &'a str,
+        operator_at_root : bool,
+        mut layout : Option<&mut InteractionLayoutRecorder<'a>>
+    ) -> IResult<&'a str,InteractionInternalRepresentation<CioII>,E>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : ContextAwareInteractionParser<CioII> + ?Sized,
+    E : InteractionParseError<'a>
+{
+    let mut stack : Vec<OperatorFrame<'a,CioII,E>> = vec![];
+    let mut rem = input;
+    'node: loop {
+        // parses a sub-interaction starting at *rem*
+        // (the stack is only empty when parsing the whole interaction)
+        let is_root = stack.is_empty();
+        if let (Some(layout),Some(frame)) = (layout.as_deref_mut(),stack.last()) {
+            layout.enter_operand(frame.operands.len());
+        }
+        let (at_node,leaf_error,annotations) = if is_root && operator_at_root {
+            (rem,None,vec![])
+        } else {
+            let annotated = nom::sequence::pair(
+                |x| parser.parse_whitespace_and_comments(x),
+                consumed(|x| parser.parse_annotations(x))
+            )(rem);
+            let (at_node,annotations) = match annotated {
+                Ok((at_node,(whitespace,(annotations_text,annotations)))) => {
+                    if let Some(layout) = layout.as_deref_mut() {
+                        layout.begin_node(parser, whitespace, annotations_text);
+                    }
+                    (at_node,annotations)
+                },
+                Err(e) => {
+                    return Err(unwind(e, true, &mut stack));
+                }
+            };
+            match parser.parse_explicit_pattern::<E>(at_node) {
+                Ok((after_leaf,leaf)) => {
+                    rem = after_leaf;
+                    if let Some(layout) = layout.as_deref_mut() {
+                        // leaf patterns may consume the whitespace which follows them
+                        let leaf_text = at_node[..(at_node.len() - after_leaf.len())].trim_end();
+                        layout.end_node(at_node, &at_node[leaf_text.len()..]);
+                    }
+                    let completed = InteractionInternalRepresentation::LeafPattern(leaf).with_annotations(annotations);
+                    match close_nodes(parser, &mut stack, rem, completed, operator_at_root, layout.as_deref_mut()) {
+                        NodeClosing::Done(got) => {
+                            return got;
+                        },
+                        NodeClosing::NextOperand(next) => {
+                            rem = next;
+                            continue 'node;
+                        }
+                    }
+                },
+                Err(nom::Err::Error(leaf_error)) => {
+                    parser.backtrack(at_node);
+                    (at_node,Some(leaf_error),annotations)
+                },
+                Err(e) => {
+                    return Err(unwind(e, true, &mut stack));
+                }
+            }
+        };
+        let header : IResult<&'a str,_,E> = tuple(
+            (
+                consumed(|x| parser.parse_operator_with_parameters(x)),
+                |x| parser.parse_whitespace_and_comments(x),
+                nom::character::complete::char(parser.left_parenthesis_char()),
+                |x| parser.parse_whitespace_and_comments(x)
+            )
+        )(at_node);
+        let (after_header,operator_text,operator) = match header {
+            Ok((after_header,((operator_text,operator),_,_,whitespace))) => {
+                if let Some(layout) = layout.as_deref_mut() {
+                    layout.take_comments(parser, whitespace);
+                }
+                (after_header,operator_text,operator)
+            },
+            Err(nom::Err::Error(header_error)) => {
+                let node_error = match leaf_error {
+                    Some(leaf_error) => {
+                        leaf_error.or(header_error)
+                    },
+                    None => {
+                        header_error
+                    }
+                };
+                return Err(unwind(nom::Err::Error(node_error), true, &mut stack));
+            },
+            Err(e) => {
+                return Err(unwind(e, true, &mut stack));
+            }
+        };
+        if let Some(max_depth) = parser.max_nesting_depth() {
+            if stack.len() >= max_depth {
+                let error = E::from_parse_error_kind(at_node, ParseErrorKind::NestingTooDeep { max_depth });
+                return Err(unwind(nom::Err::Failure(error), true, &mut stack));
+            }
+        }
+        let closing : IResult<&'a str,char,E> = nom::character::complete::char(parser.right_parenthesis_char())(after_header);
+        match closing {
+            Ok((after_closing,_)) => {
+                // an operator without operands
+                stack.push(OperatorFrame{input:at_node,operator_text,operator,annotations,operands:vec![],leaf_error,closing_error:None});
+                if let Some(layout) = layout.as_deref_mut() {
+                    layout.attach_pending_as_leading();
+                }
+                match close_operator(parser, &mut stack, after_closing, operator_at_root, layout.as_deref_mut()) {
+                    NodeClosing::Done(got) => {
+                        return got;
+                    },
+                    NodeClosing::NextOperand(next) => {
+                        rem = next;
+                    }
+                }
+            },
+            Err(nom::Err::Error(closing_error)) => {
+                stack.push(OperatorFrame{input:at_node,operator_text,operator,annotations,operands:vec![],leaf_error,closing_error:Some(closing_error)});
+                rem = after_header;
+            },
+            Err(e) => {
+                return Err(unwind(e, true, &mut stack));
+            }
+        }
+    }
+}
+
+
+
+enum NodeClosing<'a,CioII : AnnotatedCommonIoInteractionInterface,E> {
+    // the whole interaction has been parsed (or an error occurred)
+    Done(IResult<&'a str,InteractionInternalRepresentation<CioII>,E>),
+    // the next operand of the operator at the top of the stack starts at the given input
+    NextOperand(&'a str)
+}
+
+
+
+/** 
+ * Handles a sub-interaction that has just been parsed (excluding the whitespace which follows it) :
+ * it is added to the operands of the operator at the top of the stack, which is closed if it is followed by a right parenthesis, and so on.
+ * **/
+fn close_nodes<'a,CioII,Parser,E>
+    (
+        parser : &Parser,
+        stack : &mut Vec<OperatorFrame<'a,CioII,E>>,
+        input : &'a str,
+        completed : InteractionInternalRepresentation<CioII>,
+        operator_at_root : bool,
+        mut layout : Option<&mut InteractionLayoutRecorder<'a>>
+    ) -> NodeClosing<'a,CioII,E>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : ContextAwareInteractionParser<CioII> + ?Sized,
+    E : InteractionParseError<'a>
+{
+    let mut completed = completed;
+    let mut rem = input;
+    loop {
+        if !(operator_at_root && stack.is_empty()) {
+            match parser.parse_whitespace_and_comments::<E>(rem) {
+                Ok((after_whitespace,whitespace)) => {
+                    if let Some(layout) = layout.as_deref_mut() {
+                        layout.take_comments(parser, whitespace);
+                    }
+                    rem = after_whitespace;
+                },
+                Err(e) => {
+                    return NodeClosing::Done(Err(unwind(e, true, stack)));
+                }
+            }
+        }
+        let Some(frame) = stack.last_mut() else {
+            if let Some(layout) = layout.as_deref_mut() {
+                layout.attach_pending_as_trailing();
+            }
+            return NodeClosing::Done(Ok((rem,completed)));
+        };
+        frame.operands.push(completed);
+        let delimiter : IResult<&'a str,char,E> = alt(
+            (
+                nom::character::complete::char(parser.separator_char()),
+                nom::character::complete::char(parser.right_parenthesis_char())
+            )
+        )(rem);
+        match delimiter {
+            Ok((after_delimiter,delimiter)) if delimiter == parser.separator_char() => {
+                // the comments before the separator precede the next operand
+                if let Some(layout) = layout.as_deref_mut() {
+                    layout.leave_operand();
+                }
+                return NodeClosing::NextOperand(after_delimiter);
+            },
+            Ok((after_delimiter,_)) => {
+                if let Some(layout) = layout.as_deref_mut() {
+                    layout.attach_pending_as_trailing();
+                    layout.leave_operand();
+                }
+                match pop_operator(stack, after_delimiter, layout.as_deref_mut()) {
+                    Ok(operator_repr) => {
+                        completed = operator_repr;
+                        rem = after_delimiter;
+                    },
+                    Err(e) => {
+                        return NodeClosing::Done(Err(e));
+                    }
+                }
+            },
+            Err(e) => {
+                // the error is located after the last operand that has been parsed
+                let (operator_text,operand_index) = (frame.operator_text,frame.operands.len() - 1);
+                let e = e.map(|e| e.within_operator(operator_text, operand_index));
+                return NodeClosing::Done(Err(unwind(e, false, stack)));
+            }
+        }
+    }
+}
+
+
+
+/** 
+ * Closes the operator at the top of the stack, its closing parenthesis having been parsed.
+ * **/
+fn close_operator<'a,CioII,Parser,E>
+    (
+        parser : &Parser,
+        stack : &mut Vec<OperatorFrame<'a,CioII,E>>,
+        input : &'a str,
+        operator_at_root : bool,
+        mut layout : Option<&mut InteractionLayoutRecorder<'a>>
+    ) -> NodeClosing<'a,CioII,E>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Parser : ContextAwareInteractionParser<CioII> + ?Sized,
+    E : InteractionParseError<'a>
+{
+    match pop_operator(stack, input, layout.as_deref_mut()) {
+        Ok(operator_repr) => {
+            close_nodes(parser, stack, input, operator_repr, operator_at_root, layout)
+        },
+        Err(e) => {
+            NodeClosing::Done(Err(e))
+        }
+    }
+}
+
+
+
+/** 
+ * Pops the operator at the top of the stack, checking the number of its operands.
+ * *end* is the input which follows its right parenthesis.
+ * **/
+fn pop_operator<'a,CioII,E>
+    (
+        stack : &mut Vec<OperatorFrame<'a,CioII,E>>,
+        end : &'a str,
+        layout : Option<&mut InteractionLayoutRecorder<'a>>
+    ) -> Result<InteractionInternalRepresentation<CioII>,nom::Err<E>>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    E : InteractionParseError<'a>
+{
+    let frame = stack.last().unwrap();
+    if !frame.operator.accepts_number_of_operands(frame.operands.len()) {
+        let (min_operands,max_operands) = frame.operator.get_operands_bounds_in_internal_representation();
+        let kind = ParseErrorKind::ArityMismatch { 
+            operator: frame.operator_text.trim().to_owned(), 
+            min_operands, 
+            max_operands, 
+            got: frame.operands.len() 
+        };
+        let error = nom::Err::Error(E::from_parse_error_kind(frame.input, kind));
+        return Err(unwind(error, false, stack));
+    }
+    let frame = stack.pop().unwrap();
+    if let Some(layout) = layout {
+        layout.end_node(frame.input, end);
+    }
+    Ok(InteractionInternalRepresentation::Operator(frame.operator, frame.operands).with_annotations(frame.annotations))
+}
+
+
+
+/** 
+ * Propagates an error through the operators being parsed, combining it with the errors of the alternatives that have been tried,
+ * as the error would be propagated through the calls of a recursive parser.
+ * If *is_operand_error*, the error occurred when parsing an operand of the operator at the top of the stack 
+ * (or the whole interaction if the stack is empty). Otherwise, it occurred when parsing that operator itself.
+ * Each operator is recorded in the error (see *InteractionParseError::within_operator*) if the error occurred within one of its operands.
+ * **/
+fn unwind<'a,CioII,E>
+    (
+        error : nom::Err<E>,
+        is_operand_error : bool,
+        stack : &mut Vec<OperatorFrame<'a,CioII,E>>
+    ) -> nom::Err<E>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    E : InteractionParseError<'a>
+{
+    let mut error = error;
+    let mut is_operand_error = is_operand_error;
+    loop {
+        if is_operand_error {
+            let Some(frame) = stack.last_mut() else {
+                return error;
+            };
+            // an error in the first operand is combined with the error of the missing right parenthesis
+            if frame.operands.is_empty() {
+                if let Some(closing_error) = frame.closing_error.take() {
+                    error = match error {
+                        nom::Err::Error(e) => {
+                            nom::Err::Error(e.or(closing_error))
+                        },
+                        other => {
+                            other
+                        }
+                    };
+                }
+            }
+            let (operator_text,operand_index) = (frame.operator_text,frame.operands.len());
+            error = error.map(|e| e.within_operator(operator_text, operand_index));
+        }
+        let Some(frame) = stack.pop() else {
+            return error;
+        };
+        error = match (error,frame.leaf_error) {
+            (nom::Err::Error(e),Some(leaf_error)) => {
+                nom::Err::Error(leaf_error.or(e))
+            },
+            (other,_) => {
+                other
+            }
+        };
+        is_operand_error = true;
+    }
+}
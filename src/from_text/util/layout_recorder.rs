@@ -0,0 +1,157 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use nom::Offset;
+
+use crate::from_text::spans::{InteractionSpans, SourceSpan};
+use crate::from_text::util::delimited_lang_parser::DelimitedInteractionLanguageParser;
+use crate::internal_representation::InteractionComments;
+
+
+
+/** 
+ * Records the span and the comments of each sub-interaction while the interaction is being parsed
+ * (see *parse_interaction_without_recursion*), both keyed by the path of the sub-interaction.
+ * Comments are attached to the sub-interaction which follows them.
+ * Comments which are not followed by any sub-interaction follow the sub-interaction which precedes them :
+ * those before a right parenthesis are trailing comments of the last operand 
+ * (or leading comments of the operator if it has no operands) and those at the end of the input are trailing comments of the root.
+ * Comments located within leaf patterns or between an operator and its left parenthesis are not collected.
+ * Comments between an annotation and the sub-interaction it annotates are attached to the latter.
+ * **/
+pub(crate) struct InteractionLayoutRecorder<'a> {
+    input_str : &'a str,
+    // the path of the sub-interaction being parsed
+    path : Vec<usize>,
+    // for each element of the path, the offset of the annotation if it leads from an annotated node to its child 
+    annotation_starts : Vec<Option<usize>>,
+    // the comments which have been parsed but not yet attached
+    pending : Vec<String>,
+    pub spans : InteractionSpans,
+    pub comments : InteractionComments
+}
+
+
+
+impl<'a> InteractionLayoutRecorder<'a> {
+
+    pub fn new(input_str : &'a str) -> Self {
+        InteractionLayoutRecorder{
+            input_str,
+            path:vec![],
+            annotation_starts:vec![],
+            pending:vec![],
+            spans:InteractionSpans::new(),
+            comments:InteractionComments::new()
+        }
+    }
+
+    /** 
+     * Called before parsing an operand of the operator whose path is the current path.
+     * **/
+    pub fn enter_operand(&mut self, operand_index : usize) {
+        self.path.push(operand_index);
+        self.annotation_starts.push(None);
+    }
+
+    /** 
+     * Called once an operand has been parsed and followed by a delimiter.
+     * **/
+    pub fn leave_operand(&mut self) {
+        self.path.pop();
+        self.annotation_starts.pop();
+    }
+
+    /** 
+     * Called at the start of a sub-interaction with the whitespace and comments that precede it 
+     * and the text of the annotations (and of the whitespace and comments which follow each of them).
+     * **/
+    pub fn begin_node<Parser>(&mut self, parser : &Parser, whitespace : &str, annotations : &'a str)
+    where 
+        Parser : DelimitedInteractionLanguageParser + ?Sized
+    {
+        self.take_comments(parser, whitespace);
+        self.attach_pending_as_leading();
+        let mut rem = annotations;
+        loop {
+            rem = trim_whitespace(rem);
+            if let Some(length) = parser.get_comment_length(rem) {
+                self.pending.push(rem[..length].trim_end().to_owned());
+                rem = &rem[length..];
+            } else if let Some(length) = parser.get_annotation_length(rem) {
+                self.attach_pending_as_leading();
+                // the annotated sub-interaction is the single child of the annotated node
+                self.path.push(0);
+                self.annotation_starts.push(Some(self.input_str.offset(rem)));
+                rem = &rem[length..];
+            } else {
+                break;
+            }
+        }
+        self.attach_pending_as_leading();
+    }
+
+    /** 
+     * Called once the sub-interaction at the current path has been parsed, from *start* to *end*.
+     * The current path then becomes that of the outermost annotated node which encloses it.
+     * **/
+    pub fn end_node(&mut self, start : &'a str, end : &'a str) {
+        let end = self.input_str.offset(end);
+        self.spans.insert(self.path.clone(), SourceSpan{start:self.input_str.offset(start),end});
+        while let Some(Some(annotation_start)) = self.annotation_starts.last() {
+            let annotation_start = *annotation_start;
+            self.path.pop();
+            self.annotation_starts.pop();
+            self.spans.insert(self.path.clone(), SourceSpan{start:annotation_start,end});
+        }
+    }
+
+    /** 
+     * Collects the comments of some consumed whitespace, to be attached later on.
+     * **/
+    pub fn take_comments<Parser>(&mut self, parser : &Parser, whitespace : &str)
+    where 
+        Parser : DelimitedInteractionLanguageParser + ?Sized
+    {
+        let mut rem = trim_whitespace(whitespace);
+        while let Some(length) = parser.get_comment_length(rem) {
+            self.pending.push(rem[..length].trim_end().to_owned());
+            rem = trim_whitespace(&rem[length..]);
+        }
+    }
+
+    pub fn attach_pending_as_leading(&mut self) {
+        if !self.pending.is_empty() {
+            self.comments.leading.entry(self.path.clone()).or_default().append(&mut self.pending);
+        }
+    }
+
+    pub fn attach_pending_as_trailing(&mut self) {
+        if !self.pending.is_empty() {
+            self.comments.trailing.entry(self.path.clone()).or_default().append(&mut self.pending);
+        }
+    }
+
+}
+
+
+
+fn trim_whitespace(input : &str) -> &str {
+    // the whitespace recognized by *multispace0*
+    input.trim_start_matches([' ','\t','\r','\n'])
+}
@@ -17,3 +17,5 @@ limitations under the License.
 pub mod delimited_lang_parser;
 pub mod parse_utils;
 pub mod generic_broadcast_parser;
+pub(crate) mod layout_recorder;
+pub(crate) mod iterative_parser;
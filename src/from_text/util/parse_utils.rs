@@ -22,7 +22,9 @@ use nom::Err::Error;
 use nom::IResult;
 use nom::sequence::{delimited, tuple};
 use nom::character::complete::multispace0;
-use nom::error::{ErrorKind, ParseError};
+use nom::error::ParseError;
+
+use crate::from_text::error::{InteractionParseError, ParseErrorKind};
 
 
 
@@ -49,7 +51,13 @@ pub fn parse_label_with_underscores<'a,E: ParseError<&'a str>>(input : &'a str)
 
 
 
-pub fn parse_element_of_preexisting_vec_and_return_index<'a, E: ParseError<&'a str>>(reference_vec : &[String], input : &'a str) -> IResult<&'a str, usize,E> {
+/** 
+ * Parses a label and returns its index in *reference_vec*.
+ * If the label is well-formed but does not belong to *reference_vec*, 
+ * the error is located at the beginning of the label and has the kind *ParseErrorKind::UnknownName*,
+ * which is how unknown names are distinguished from syntax errors.
+ * **/
+pub fn parse_element_of_preexisting_vec_and_return_index<'a, E: InteractionParseError<'a>>(reference_vec : &[String], input : &'a str) -> IResult<&'a str, usize,E> {
     match parse_label_with_underscores(input) {
         Err(e) => {
             Err(e)
@@ -57,7 +65,7 @@ pub fn parse_element_of_preexisting_vec_and_return_index<'a, E: ParseError<&'a s
         Ok((rem,lab)) => {
             match reference_vec.iter().position(|s| s.eq(&lab)) {
                 None => {
-                    Err(Error(nom::error::make_error(input, ErrorKind::Fail)))
+                    Err(Error(E::from_parse_error_kind(input.trim_start(), ParseErrorKind::UnknownName { name: lab })))
                 }
                 Some(index) => {
                     Ok((rem,index))
@@ -16,6 +16,7 @@ limitations under the License.
 
 
 
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 
 
@@ -30,6 +31,51 @@ pub trait InteractionOperatorRepresentation {
      **/
     fn is_associative(&self) -> bool;
 
+    /**
+    The minimal number of operands of the operator in interaction terms.
+    By default, the operator has a fixed *arity*.
+     **/
+    fn min_arity(&self) -> usize {
+        self.arity()
+    }
+
+    /**
+    The maximal number of operands of the operator in interaction terms, None if there is no upper bound
+    (e.g. for an n-ary alternative).
+    By default, the operator has a fixed *arity*.
+     **/
+    fn max_arity(&self) -> Option<usize> {
+        Some(self.arity())
+    }
+
+    /**
+    The minimal and maximal numbers of operands of the operator in the internal representation.
+    They are those of interaction terms except that there is no upper bound for associative operators,
+    whose operands are flattened.
+     **/
+    fn get_operands_bounds_in_internal_representation(&self) -> (usize,Option<usize>) {
+        if self.is_associative() {
+            (self.min_arity(),None)
+        } else {
+            (self.min_arity(),self.max_arity())
+        }
+    }
+
+    /**
+    Whether or not the operator may be applied to *num_operands* operands in the internal representation.
+     **/
+    fn accepts_number_of_operands(&self, num_operands : usize) -> bool {
+        let (min_operands,max_operands) = self.get_operands_bounds_in_internal_representation();
+        match max_operands {
+            Some(max_operands) => {
+                num_operands >= min_operands && num_operands <= max_operands
+            },
+            None => {
+                num_operands >= min_operands
+            }
+        }
+    }
+
 }
 
 
@@ -49,18 +95,314 @@ pub trait CommonIoInteractionInterface : Sized {
 }
 
 
+/**
+ The interface through which this crate handles interaction languages, 
+which is that of *CommonIoInteractionInterface* together with the type of the annotations.
+Languages without annotations implement *CommonIoInteractionInterface*, which gives them *()* annotations.
+Languages with annotations implement this interface instead.
+ **/
+pub trait AnnotatedCommonIoInteractionInterface : Sized {
+
+    type InteractionLeafPatternType : Debug + Clone;
+
+    type InteractionOperatorType : Debug + InteractionOperatorRepresentation + Clone + PartialEq + Eq;
+
+    /**
+     The type of the annotations (e.g., identifiers, colors, notes or links to requirements) that may be attached to
+    the nodes of the interaction internal representation.
+     **/
+    type InteractionAnnotationType : Debug + Clone;
+
+}
+
+impl<CioII : CommonIoInteractionInterface> AnnotatedCommonIoInteractionInterface for CioII {
+    type InteractionLeafPatternType = CioII::InteractionLeafPatternType;
+    type InteractionOperatorType = CioII::InteractionOperatorType;
+    type InteractionAnnotationType = ();
+}
+
+
+
+
+/**
+ The arguments given to an operator between brackets, as in *loop[2,5]*, *alt[x>0]* or *par{name}*.
+The arguments are kept verbatim (trimmed), it is up to the language to interpret them
+(see *ContextAwareInteractionParser::apply_operator_parameters*).
+ **/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OperatorParameters {
+    pub opening : char,
+    pub closing : char,
+    pub arguments : Vec<String>
+}
+
+impl OperatorParameters {
+
+    pub fn new(opening : char, closing : char, arguments : Vec<String>) -> Self {
+        Self{opening,closing,arguments}
+    }
+
+}
+
+impl std::fmt::Display for OperatorParameters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}{}", self.opening, self.arguments.join(","), self.closing)
+    }
+}
+
 
 
 /**
  This is how interaction terms are encoded in this crate.
 This does not necessarily correspond to the concrete interaction language implementation.
 Rather, it is an internal representation that is proper to this present IO crate and facilitates IO operations.
+Kinds of nodes may be added in later versions, so matches on the representation outside of this crate need a wildcard arm.
  **/
- #[derive(Debug)]
-pub enum InteractionInternalRepresentation<CioII : CommonIoInteractionInterface> {
+#[non_exhaustive]
+pub enum InteractionInternalRepresentation<CioII : AnnotatedCommonIoInteractionInterface> {
     LeafPattern(CioII::InteractionLeafPatternType),
-    Operator(CioII::InteractionOperatorType, Vec<InteractionInternalRepresentation<CioII>>)
+    Operator(CioII::InteractionOperatorType, Vec<InteractionInternalRepresentation<CioII>>),
+    // a sub-interaction with an annotation attached to it, which is its single child (at index 0 in node paths)
+    Annotated(CioII::InteractionAnnotationType, Box<InteractionInternalRepresentation<CioII>>)
+}
+
+impl<CioII : AnnotatedCommonIoInteractionInterface> InteractionInternalRepresentation<CioII> {
+
+    /**
+     Attaches the annotations (from the outermost to the innermost) to the interaction.
+     **/
+    pub fn with_annotations(self, annotations : Vec<CioII::InteractionAnnotationType>) -> Self {
+        annotations.into_iter().rev().fold(
+            self, 
+            |annotated,annotation| InteractionInternalRepresentation::Annotated(annotation, Box::new(annotated))
+        )
+    }
+
+    /**
+     Moves the sub-interactions of the node (or of its annotated sub-interaction) to the given stack.
+    Moving the sub-interaction out of an annotated node requires putting another node in its place,
+    which is a copy of a leaf or of an operator without operands.
+     **/
+    fn take_sub_interactions(&mut self, to_drop : &mut Vec<Self>, placeholder : &mut Option<Self>) {
+        let sub_int = match self {
+            InteractionInternalRepresentation::LeafPattern(_) => {
+                return;
+            },
+            InteractionInternalRepresentation::Operator(_, sub_ints) => {
+                to_drop.extend(std::mem::take(sub_ints));
+                return;
+            },
+            InteractionInternalRepresentation::Annotated(_, annotated) => {
+                annotated.as_mut()
+            }
+        };
+        match sub_int {
+            InteractionInternalRepresentation::LeafPattern(_) => {},
+            InteractionInternalRepresentation::Operator(_, sub_ints) => {
+                to_drop.extend(std::mem::take(sub_ints));
+            },
+            InteractionInternalRepresentation::Annotated(..) => {
+                let placeholder = placeholder.get_or_insert_with(|| sub_int.copy_innermost_node()).copy_innermost_node();
+                to_drop.push(std::mem::replace(sub_int, placeholder));
+            }
+        }
+    }
+
+    /**
+     Copies the innermost node of a chain of annotations, without its operands.
+     **/
+    fn copy_innermost_node(&self) -> Self {
+        let mut int_repr = self;
+        loop {
+            match int_repr {
+                InteractionInternalRepresentation::LeafPattern(pattern) => {
+                    return InteractionInternalRepresentation::LeafPattern(pattern.clone());
+                },
+                InteractionInternalRepresentation::Operator(operator, _) => {
+                    return InteractionInternalRepresentation::Operator(operator.clone(), vec![]);
+                },
+                InteractionInternalRepresentation::Annotated(_, annotated) => {
+                    int_repr = annotated;
+                }
+            }
+        }
+    }
+
+}
+
+
+/**
+ Drops the interaction using an explicit stack so that deeply nested interactions do not overflow the call stack.
+The sub-interactions of each node are moved out of it before it is dropped.
+ **/
+impl<CioII : AnnotatedCommonIoInteractionInterface> Drop for InteractionInternalRepresentation<CioII> {
+
+    fn drop(&mut self) {
+        let mut to_drop = Vec::new();
+        let mut placeholder = None;
+        self.take_sub_interactions(&mut to_drop, &mut placeholder);
+        while let Some(mut int_repr) = to_drop.pop() {
+            int_repr.take_sub_interactions(&mut to_drop, &mut placeholder);
+        }
+    }
+
+}
+
+
+
+enum DebugStep<'a, CioII : AnnotatedCommonIoInteractionInterface> {
+    Node(&'a InteractionInternalRepresentation<CioII>, usize),
+    Text(String)
 }
 
+/**
+ Writes the same text as a derived implementation would, 
+but using an explicit stack so that deeply nested interactions do not overflow the call stack.
+ **/
+impl<CioII : AnnotatedCommonIoInteractionInterface> Debug for InteractionInternalRepresentation<CioII> {
+
+    fn fmt(&self, f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pretty = f.alternate();
+        // in the pretty form, the text of a field is indented at the level of its node
+        let field = |value : &dyn Debug, indent : usize| {
+            if pretty {
+                let margin = format!("\n{}", " ".repeat(indent));
+                format!("{:#?}", value).replace('\n', &margin)
+            } else {
+                format!("{:?}", value)
+            }
+        };
+        let mut steps = vec![DebugStep::Node(self, 0)];
+        while let Some(step) = steps.pop() {
+            let (node, indent) = match step {
+                DebugStep::Text(text) => {
+                    f.write_str(&text)?;
+                    continue;
+                },
+                DebugStep::Node(node, indent) => {
+                    (node, indent)
+                }
+            };
+            let inner = " ".repeat(indent + 4);
+            let outer = " ".repeat(indent);
+            // the steps are pushed in reverse order
+            match node {
+                InteractionInternalRepresentation::LeafPattern(pattern) => {
+                    if pretty {
+                        write!(f, "LeafPattern(\n{}{},\n{})", inner, field(pattern, indent + 4), outer)?;
+                    } else {
+                        write!(f, "LeafPattern({})", field(pattern, indent))?;
+                    }
+                },
+                InteractionInternalRepresentation::Operator(operator, sub_ints) => {
+                    if pretty {
+                        write!(f, "Operator(\n{}{},\n{}", inner, field(operator, indent + 4), inner)?;
+                        steps.push(DebugStep::Text(format!(",\n{})", outer)));
+                        if sub_ints.is_empty() {
+                            steps.push(DebugStep::Text("[]".to_string()));
+                        } else {
+                            let sub_margin = " ".repeat(indent + 8);
+                            steps.push(DebugStep::Text(format!("{}]", inner)));
+                            for sub_int in sub_ints.iter().rev() {
+                                steps.push(DebugStep::Text(",\n".to_string()));
+                                steps.push(DebugStep::Node(sub_int, indent + 8));
+                                steps.push(DebugStep::Text(sub_margin.clone()));
+                            }
+                            steps.push(DebugStep::Text("[\n".to_string()));
+                        }
+                    } else {
+                        write!(f, "Operator({}, [", field(operator, indent))?;
+                        steps.push(DebugStep::Text("])".to_string()));
+                        for (index, sub_int) in sub_ints.iter().enumerate().rev() {
+                            steps.push(DebugStep::Node(sub_int, indent));
+                            if index > 0 {
+                                steps.push(DebugStep::Text(", ".to_string()));
+                            }
+                        }
+                    }
+                },
+                InteractionInternalRepresentation::Annotated(annotation, annotated) => {
+                    if pretty {
+                        write!(f, "Annotated(\n{}{},\n{}", inner, field(annotation, indent + 4), inner)?;
+                        steps.push(DebugStep::Text(format!(",\n{})", outer)));
+                        steps.push(DebugStep::Node(annotated, indent + 4));
+                    } else {
+                        write!(f, "Annotated({}, ", field(annotation, indent))?;
+                        steps.push(DebugStep::Text(")".to_string()));
+                        steps.push(DebugStep::Node(annotated, indent));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+}
+
+
+
+
+/**
+ Comments attached to the nodes of an interaction internal representation.
+Each node is identified by its path from the root, i.e., the indices of the operands that lead to it (the root having the empty path).
+*leading* comments are written before their node and *trailing* comments after it.
+Comments are kept verbatim, including their delimiters.
+ **/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InteractionComments {
+    pub leading : BTreeMap<Vec<usize>,Vec<String>>,
+    pub trailing : BTreeMap<Vec<usize>,Vec<String>>
+}
 
+impl InteractionComments {
 
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leading.is_empty() && self.trailing.is_empty()
+    }
+
+}
+
+
+
+
+/**
+ How a binary operator written in infix form groups with the operators of the same precedence
+(e.g. with *Left*, *i1 op i2 op i3* is read as *(i1 op i2) op i3*).
+ **/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InfixAssociativity {
+    Left,
+    Right
+}
+
+
+
+/**
+ How an operator is written in the infix syntax.
+Operators of arity 2 are written in infix form *i1 symbol i2*, 
+operators with a higher *precedence* binding more tightly than those with a lower one.
+Operators of arity 1 are written in prefix form *symbol(i)*, *precedence* and *associativity* being then ignored.
+ **/
+#[derive(Debug, Clone)]
+pub struct OperatorNotation<Operator> {
+    pub operator : Operator,
+    pub symbol : &'static str,
+    pub precedence : usize,
+    pub associativity : InfixAssociativity
+}
+
+
+
+/**
+ The table of the operators which may be written in the infix syntax.
+Operators which are not in the table are written in the usual form *op(i1,...,in)*.
+ **/
+pub trait InfixNotationTable<CioII : AnnotatedCommonIoInteractionInterface> {
+
+    fn get_operator_notations(&self) -> Vec<OperatorNotation<CioII::InteractionOperatorType>>;
+
+}
@@ -16,7 +16,9 @@ limitations under the License.
 
 
 
+
 pub mod internal_representation;
+pub mod syntax;
 pub mod conversion;
 
 #[cfg(feature = "from_text")]
@@ -28,6 +30,9 @@ pub mod to_text;
 #[cfg(feature = "to_image")]
 pub mod to_image;
 
+#[cfg(feature = "to_svg")]
+pub mod to_svg;
+
 #[cfg(test)]
 mod tests;
 
@@ -0,0 +1,61 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+/** 
+ * The syntax which the parsers (see *DelimitedInteractionLanguageParser*) and the printers (see *ContextAwareInteractionPrinter*) 
+ * of a language have in common, so that what is printed can be parsed back.
+ * Both give it through their *syntax* method, so that a language which does not keep the defaults 
+ * implements it once and returns it from the parser and the printer.
+ * **/
+pub trait InteractionLanguageSyntax {
+
+    /** 
+     * The opening and closing delimiters of the annotations that may precede sub-interactions, as in *@{note} seq(a,b)*.
+     * Within an annotation, the last character of the opening delimiter and the closing delimiter must be balanced.
+     * By default, there are none, i.e., the language has no annotations.
+     * **/
+    fn annotation_delimiters(&self) -> Option<(&'static str,char)> {
+        None
+    }
+
+    /** 
+     * The keyword which introduces the definition of a named sub-interaction, 
+     * as in *let name = interaction;*.
+     * **/
+    fn definition_keyword(&self) -> &'static str {
+        "let"
+    }
+
+    fn definition_assignment_char(&self) -> char {
+        '='
+    }
+
+    fn definition_terminator_char(&self) -> char {
+        ';'
+    }
+
+}
+
+
+
+/** 
+ * The syntax of the languages which keep the defaults of *InteractionLanguageSyntax*.
+ * **/
+pub(crate) struct DefaultInteractionLanguageSyntax;
+
+impl InteractionLanguageSyntax for DefaultInteractionLanguageSyntax {}
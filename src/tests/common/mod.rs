@@ -20,7 +20,9 @@ limitations under the License.
 
 
 
-pub const DRAWING_GRAPHIC_FONT: &'static [u8] = include_bytes!("DejaVuSansMono.ttf");
+pub const DRAWING_GRAPHIC_FONT: &[u8] = include_bytes!("DejaVuSansMono.ttf");
+#[cfg(feature = "to_svg")]
+pub const DRAWING_GRAPHIC_FONT_FAMILY : &str = "DejaVu Sans Mono";
 pub const SCALE : f32 = 20.0;
 
 
@@ -55,11 +55,7 @@ impl InteractionOperatorRepresentation for ColorfulOperators {
     }
 
     fn is_associative(&self) -> bool {
-        match &self {
-            ColorfulOperators::Rougail(_) => true,
-            ColorfulOperators::Coreg(_) => true,
-            _ => false,
-        }
+        matches!(self, ColorfulOperators::Rougail(_) | ColorfulOperators::Coreg(_))
     }
 }
 
@@ -19,7 +19,6 @@ use image::Rgb;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::combinator::{map, value, opt};
-use nom::error::ParseError;
 use nom::IResult;
 use nom::multi::separated_list1;
 use nom::sequence::{delimited, tuple};
@@ -28,6 +27,7 @@ use image_colored_text::text::paragraph::*;
 use image_colored_text::text::line::*;
 
 use crate::from_text::context_aware_parser::ContextAwareInteractionParser;
+use crate::from_text::error::InteractionParseError;
 use crate::tests::lang_colorful::colorful_lang::*;
 use crate::tests::lang_colorful::core::internal_representation::*;
 use crate::internal_representation::*;
@@ -73,11 +73,11 @@ impl GenericBroadcastParser<ColorfulAction,usize,ColorfulAction,ColorfulLeafPatt
         "--"
     }
 
-    fn parse_message<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<&'a str, usize,E> {
+    fn parse_message<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<&'a str, usize,E> {
         parse_element_of_preexisting_vec_and_return_index(&self.ms_names,input)
     }
 
-    fn parse_broadcast_origin<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<&'a str, ColorfulAction,E> {
+    fn parse_broadcast_origin<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<&'a str, ColorfulAction,E> {
         map(
             tuple(
                 (
@@ -91,23 +91,23 @@ impl GenericBroadcastParser<ColorfulAction,usize,ColorfulAction,ColorfulLeafPatt
         (input)
     }
 
-    fn parse_single_broadcast_targets<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<&'a str, ColorfulAction,E> {
+    fn parse_single_broadcast_targets<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<&'a str, ColorfulAction,E> {
         self.parse_broadcast_origin(input)
     }
 }
 
 impl ColorfulContext {
 
-    fn parse_gate_or_lifeline<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<&'a str,GateOrLifeline,E> {
+    fn parse_gate_or_lifeline<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<&'a str,GateOrLifeline,E> {
         alt(
             (
                 map(
                     |x| parse_element_of_preexisting_vec_and_return_index(&self.lf_names,x),
-                    |l| GateOrLifeline::Lifeline(l)
+                    GateOrLifeline::Lifeline
                 ),
                 map(
                     |x| parse_element_of_preexisting_vec_and_return_index(&self.gt_names,x),
-                    |g| GateOrLifeline::Gate(g)
+                    GateOrLifeline::Gate
                 )
             )
         )(input)
@@ -115,7 +115,7 @@ impl ColorfulContext {
 
 }
 
-fn parse_note<'a, E: ParseError<&'a str>>(input : &'a str) -> IResult<&'a str, ColoredTextParagraph,E> {
+fn parse_note<'a, E: InteractionParseError<'a>>(input : &'a str) -> IResult<&'a str, ColoredTextParagraph,E> {
     let mut parser = delimited(
         nom::character::complete::char('{'),
         separated_list1(
@@ -143,9 +143,9 @@ fn parse_note<'a, E: ParseError<&'a str>>(input : &'a str) -> IResult<&'a str, C
 
 impl ContextAwareInteractionParser<ColorfulLangCioII> for ColorfulContext {
 
-    fn parse_operator<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<
+    fn parse_operator<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<
         &'a str,
-        <ColorfulLangCioII as CommonIoInteractionInterface>::InteractionOperatorType, 
+        <ColorfulLangCioII as AnnotatedCommonIoInteractionInterface>::InteractionOperatorType, 
         E> {
         alt(
             (
@@ -177,9 +177,9 @@ impl ContextAwareInteractionParser<ColorfulLangCioII> for ColorfulContext {
         (input)
     }
 
-    fn parse_explicit_pattern<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<
+    fn parse_explicit_pattern<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<
         &'a str,
-        <ColorfulLangCioII as CommonIoInteractionInterface>::InteractionLeafPatternType,
+        <ColorfulLangCioII as AnnotatedCommonIoInteractionInterface>::InteractionLeafPatternType,
         E> {
         self.parse_broadcast_pattern(input)
     }
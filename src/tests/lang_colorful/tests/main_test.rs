@@ -17,7 +17,6 @@ limitations under the License.
 
 
 
-use std::path::Path;
 
 use image::Rgb;
 use image_colored_text::text::line::ColoredTextLine;
@@ -29,7 +28,7 @@ use crate::tests::lang_colorful::colorful_lang::*;
 use crate::from_text::parse::parse_interaction;
 use crate::tests::lang_colorful::to_image::drawing_context::ColorfulDrawingContext;
 
-use crate::to_image::interface::draw_interaction_as_sequence_diagram;
+use crate::to_image::interface::try_draw_interaction_as_sequence_diagram;
 
 
 
@@ -103,10 +102,14 @@ r#"seq(
     ).unwrap_or_else(|x| {eprintln!("{}",x);panic!();});
 
     let drawing_context = ColorfulDrawingContext::new(ctx);
-    draw_interaction_as_sequence_diagram::<ColorfulLangCioII,usize,ColorfulDrawingContext,ColorfulDrawingContext>(
+    // the image is written in the temporary directory rather than in the repository
+    let file_path = std::env::temp_dir().join(format!("{}_{}.png",name,std::process::id()));
+    try_draw_interaction_as_sequence_diagram::<ColorfulLangCioII,usize,ColorfulDrawingContext,ColorfulDrawingContext>(
         &internal_repr,
         &drawing_context,
         &drawing_context,
-        &Path::new(&format!("{}.png",name))
-    );
+        &file_path
+    ).unwrap();
+    assert!(std::fs::metadata(&file_path).unwrap().len() > 0);
+    std::fs::remove_file(&file_path).unwrap();
 }
\ No newline at end of file
@@ -16,4 +16,6 @@ limitations under the License.
 
 
 
-pub mod main_test;
\ No newline at end of file
+pub mod main_test;
+#[cfg(feature = "to_svg")]
+pub mod svg_test;
\ No newline at end of file
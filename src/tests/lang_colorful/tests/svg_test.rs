@@ -0,0 +1,77 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use image::Rgb;
+use image_colored_text::text::line::ColoredTextLine;
+use image_colored_text::text::paragraph::{ColoredTextParagraph, MultiLineTextAlignment};
+
+use crate::tests::common::DRAWING_GRAPHIC_FONT_FAMILY;
+use crate::tests::lang_colorful::to_image::colorful_colors::*;
+use crate::tests::lang_colorful::core::internal_representation::*;
+use crate::tests::lang_colorful::colorful_lang::*;
+use crate::from_text::parse::parse_interaction;
+use crate::tests::lang_colorful::to_image::drawing_context::ColorfulDrawingContext;
+
+use crate::to_svg::interface::draw_interaction_as_svg_sequence_diagram;
+
+
+
+#[test]
+fn draw_svg_test() {
+    let ctx = ColorfulContext{
+        lf_names:vec!["bob".to_string(),"alice".to_string()],
+        lf_descs:vec![
+            ColoredTextParagraph::new(
+                vec![ColoredTextLine::new(vec![("bob".to_owned(), Rgb(COLORFUL_LIFELINE))])], 
+                MultiLineTextAlignment::Center, 
+                None, 
+                Some(Rgb(COLORFUL_BLACK))
+            ),
+            ColoredTextParagraph::new(
+                vec![ColoredTextLine::new(vec![("alice".to_owned(), Rgb(COLORFUL_LIFELINE))])], 
+                MultiLineTextAlignment::Center, 
+                None, 
+                Some(Rgb(COLORFUL_BLACK))
+            ),
+        ],
+        ms_names:vec!["discombobulate".to_string(),"befuddle".to_string()],
+        gt_names:vec![],
+    };
+    let input_text = 
+r#"seq(
+    bob--discombobulate->alice,
+    alice--befuddle->bob
+)"#;
+    let internal_repr = parse_interaction::<ColorfulLangCioII,ColorfulContext>(
+        input_text,&ctx
+    ).unwrap_or_else(|x| {eprintln!("{}",x);panic!();});
+
+    let drawing_context = ColorfulDrawingContext::new(ctx);
+    let file_path = std::env::temp_dir().join(format!("colorful_draw_svg_test_{}.svg",std::process::id()));
+    draw_interaction_as_svg_sequence_diagram::<ColorfulLangCioII,usize,ColorfulDrawingContext,ColorfulDrawingContext>(
+        &internal_repr,
+        &drawing_context,
+        &drawing_context,
+        DRAWING_GRAPHIC_FONT_FAMILY,
+        &file_path
+    ).unwrap();
+    let svg = std::fs::read_to_string(&file_path).unwrap();
+    std::fs::remove_file(&file_path).unwrap();
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.contains(">discombobulate</tspan>"));
+}
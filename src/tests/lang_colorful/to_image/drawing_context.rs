@@ -28,6 +28,7 @@ use crate::internal_representation::InteractionInternalRepresentation;
 use crate::tests::common::*;
 use crate::to_image::common_interaction_drawer::CommonInteractionDrawerTrait;
 use crate::to_image::draw::context_aware_drawer::ContextAwareInteractionDrawer;
+use crate::to_image::draw::canvas::DiagramCanvas;
 use crate::to_image::drawable::leaf::util::MessageExchangeLineStyle;
 use crate::to_image::extract::context_aware_extractor::ContextAwareInteractionDrawingInstructionsExtractor;
 
@@ -251,8 +252,8 @@ impl ContextAwareInteractionDrawingInstructionsExtractor<ColorfulLangCioII,usize
 
 
 impl ContextAwareInteractionDrawer<usize> for ColorfulDrawingContext {
-    fn draw_background(&self, image : &mut image::RgbImage, img_width : f32, img_height : f32) {
-        draw_uniform_colored_background(image,&img_width,&img_height,Rgb(COLORFUL_WHITE));
+    fn draw_background<Canvas : DiagramCanvas>(&self, canvas : &mut Canvas, img_width : f32, img_height : f32) {
+        draw_uniform_colored_background(canvas,&img_width,&img_height,Rgb(COLORFUL_WHITE));
     }
     
     fn get_lifelines_colors(&self, involved_lifelines : &[usize]) -> HashMap<usize,Rgb<u8>> {
@@ -18,6 +18,7 @@ limitations under the License.
 
 
 use crate::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::GeneralContext;
 
 #[derive(Debug,Clone)]
 pub struct MinimalLangCioII {}
@@ -46,7 +47,11 @@ impl MinimalBroadcastLeafPattern {
 
 #[derive(Debug,Clone,PartialEq,Eq)]
 pub enum MinimalOperators {
-    Strict,Seq,Alt,Par,Loop
+    Strict,Seq,Alt,Par,Loop,
+    // a loop with bounds, as in *loop[2,5]*
+    BoundedLoop(OperatorParameters),
+    // a (non-associative) choice between one or more alternatives
+    Choice
 }
 
 impl MinimalOperators {
@@ -65,8 +70,11 @@ impl MinimalOperators {
             MinimalOperators::Par => {
                 "par"
             },
-            MinimalOperators::Loop=> {
+            MinimalOperators::Loop | MinimalOperators::BoundedLoop(_) => {
                 "loop"
+            },
+            MinimalOperators::Choice => {
+                "choice"
             }
         }.to_owned()
     }
@@ -81,7 +89,9 @@ impl InteractionOperatorRepresentation for MinimalOperators {
             MinimalOperators::Seq => 2,
             MinimalOperators::Alt => 2,
             MinimalOperators::Par => 2,
-            MinimalOperators::Loop => 1
+            MinimalOperators::Loop => 1,
+            MinimalOperators::BoundedLoop(_) => 1,
+            MinimalOperators::Choice => 1
         }
     }
 
@@ -91,13 +101,39 @@ impl InteractionOperatorRepresentation for MinimalOperators {
             MinimalOperators::Seq => true,
             MinimalOperators::Alt => true,
             MinimalOperators::Par => true,
-            MinimalOperators::Loop => false
+            MinimalOperators::Loop => false,
+            MinimalOperators::BoundedLoop(_) => false,
+            MinimalOperators::Choice => false
+        }
+    }
+
+    fn max_arity(&self) -> Option<usize> {
+        match &self {
+            MinimalOperators::Choice => None,
+            _ => Some(self.arity())
         }
     }
 }
 
 
-impl CommonIoInteractionInterface for MinimalLangCioII {
+impl AnnotatedCommonIoInteractionInterface for MinimalLangCioII {
     type InteractionLeafPatternType = MinimalLeafPattern;
     type InteractionOperatorType = MinimalOperators;
-}
\ No newline at end of file
+    // annotations are kept verbatim (trimmed)
+    type InteractionAnnotationType = String;
+}
+
+
+impl InfixNotationTable<MinimalLangCioII> for GeneralContext {
+
+    fn get_operator_notations(&self) -> Vec<OperatorNotation<MinimalOperators>> {
+        vec![
+            OperatorNotation{operator:MinimalOperators::Alt,symbol:"+",precedence:1,associativity:InfixAssociativity::Left},
+            OperatorNotation{operator:MinimalOperators::Par,symbol:"||",precedence:2,associativity:InfixAssociativity::Left},
+            OperatorNotation{operator:MinimalOperators::Seq,symbol:";",precedence:3,associativity:InfixAssociativity::Left},
+            OperatorNotation{operator:MinimalOperators::Strict,symbol:"<<",precedence:4,associativity:InfixAssociativity::Left},
+            OperatorNotation{operator:MinimalOperators::Loop,symbol:"loop*",precedence:5,associativity:InfixAssociativity::Left}
+        ]
+    }
+
+}
@@ -51,19 +51,19 @@ impl FromInteractionTermToInternalRepresentation<MinimalLangCioII> for MinimalIn
     fn get_subinteractions(&self) -> Vec<&Self> {
         match self {
             MinimalInteraction::Strict(i1, i2) => {
-                vec![&*i1,&*i2]
+                vec![i1.as_ref(),i2.as_ref()]
             },
             MinimalInteraction::Seq(i1, i2) => {
-                vec![&*i1,&*i2]
+                vec![i1.as_ref(),i2.as_ref()]
             },
             MinimalInteraction::Alt(i1, i2) => {
-                vec![&*i1,&*i2]
+                vec![i1.as_ref(),i2.as_ref()]
             },
             MinimalInteraction::Par(i1, i2) => {
-                vec![&*i1,&*i2]
+                vec![i1.as_ref(),i2.as_ref()]
             },
             MinimalInteraction::Loop(i1)=> {
-                vec![&*i1]
+                vec![i1.as_ref()]
             }
             MinimalInteraction::Empty => {
                 vec![]
@@ -120,58 +120,50 @@ impl FromInteractionTermToInternalRepresentation<MinimalLangCioII> for MinimalIn
         match (p1,p2) {
             (MinimalLeafPattern::BROADCAST(b1),MinimalLeafPattern::BROADCAST(b2)) => {
                 match parent_op {
-                    MinimalOperators::Strict => {
-                        // b1 must be an emission and b2 a reception of the same message
-                        if b1.msg_id == b2.msg_id && 
+                    // b1 must be an emission and b2 a reception of the same message
+                    MinimalOperators::Strict if b1.msg_id == b2.msg_id && 
                         b1.origin_lf_id.is_some() && 
                         b2.origin_lf_id.is_none() && 
-                        b2.targets.iter().all(|b2_tar| !b1.targets.contains(b2_tar)){
-                            let mut new_targs =b1.targets.clone();
-                            new_targs.extend(b2.targets.iter().cloned());
-                            let new_b = MinimalBroadcastLeafPattern::new(
-                                b1.origin_lf_id, 
-                                b1.msg_id, 
-                                new_targs
-                            );
-                            Some(MinimalLeafPattern::BROADCAST(new_b))
-                        } else {
-                            None 
-                        }
+                        b2.targets.iter().all(|b2_tar| !b1.targets.contains(b2_tar)) => {
+                        let mut new_targs =b1.targets.clone();
+                        new_targs.extend(b2.targets.iter().cloned());
+                        let new_b = MinimalBroadcastLeafPattern::new(
+                            b1.origin_lf_id, 
+                            b1.msg_id, 
+                            new_targs
+                        );
+                        Some(MinimalLeafPattern::BROADCAST(new_b))
                     },
-                    MinimalOperators::Seq => {
-                        // b1 and b2 must involve the same message
-                        // either both are receptions
-                        // or b1 is an emission occurring on the same lifeline than b2
-                        if b1.msg_id == b2.msg_id {
-                            match (b1.origin_lf_id, b2.origin_lf_id) {
-                                (None,None) => {
-                                    let mut new_targs =b1.targets.clone();
-                                    new_targs.extend(b2.targets.iter().cloned());
+                    // b1 and b2 must involve the same message
+                    // either both are receptions
+                    // or b1 is an emission occurring on the same lifeline than b2
+                    MinimalOperators::Seq if b1.msg_id == b2.msg_id => {
+                        match (b1.origin_lf_id, b2.origin_lf_id) {
+                            (None,None) => {
+                                let mut new_targs =b1.targets.clone();
+                                new_targs.extend(b2.targets.iter().cloned());
+                                let new_b = MinimalBroadcastLeafPattern::new(
+                                    b1.origin_lf_id, 
+                                    b1.msg_id, 
+                                    new_targs
+                                );
+                                Some(MinimalLeafPattern::BROADCAST(new_b))
+                            },
+                            (Some(orig_lf),None) => {
+                                if b1.targets.is_empty() && b2.targets == vec![orig_lf] {
                                     let new_b = MinimalBroadcastLeafPattern::new(
                                         b1.origin_lf_id, 
                                         b1.msg_id, 
-                                        new_targs
+                                        b2.targets.clone()
                                     );
                                     Some(MinimalLeafPattern::BROADCAST(new_b))
-                                },
-                                (Some(orig_lf),None) => {
-                                    if b1.targets.is_empty() && b2.targets == vec![orig_lf] {
-                                        let new_b = MinimalBroadcastLeafPattern::new(
-                                            b1.origin_lf_id, 
-                                            b1.msg_id, 
-                                            b2.targets.clone()
-                                        );
-                                        Some(MinimalLeafPattern::BROADCAST(new_b))
-                                    } else {
-                                        None 
-                                    }
-                                },
-                                (_,_) => {
+                                } else {
                                     None 
                                 }
+                            },
+                            (_,_) => {
+                                None 
                             }
-                        } else {    
-                            None 
                         }
                     },
                     _ => {
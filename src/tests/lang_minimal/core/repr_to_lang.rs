@@ -25,7 +25,14 @@ use crate::tests::lang_minimal::minimal_lang::*;
 impl FromInternalRepresentationToInteractionTerm<MinimalLangCioII> for MinimalInteraction {
 
     fn instantiate_interaction_under_operator(operator : &MinimalOperators, sub_ints : &mut Vec<Self>) -> Option<Self> {
-        if operator == &MinimalOperators::Loop {
+        if operator == &MinimalOperators::Choice {
+            // the minimal language only has binary alternatives
+            if sub_ints.is_empty() {
+                None
+            } else {
+                Some(Self::fold_associative_operands_recursively(&MinimalOperators::Alt, sub_ints))
+            }
+        } else if matches!(operator, MinimalOperators::Loop | MinimalOperators::BoundedLoop(_)) {
             let i1 = sub_ints.pop().unwrap();
             Some(MinimalInteraction::Loop(Box::new(i1)))
         } else {
@@ -44,7 +51,7 @@ impl FromInternalRepresentationToInteractionTerm<MinimalLangCioII> for MinimalIn
                 MinimalOperators::Alt => {
                     Some(MinimalInteraction::Alt(Box::new(i1), Box::new(i2)))
                 },
-                MinimalOperators::Loop => {
+                MinimalOperators::Loop | MinimalOperators::BoundedLoop(_) | MinimalOperators::Choice => {
                     None
                 }
             }
@@ -57,15 +64,23 @@ impl FromInternalRepresentationToInteractionTerm<MinimalLangCioII> for MinimalIn
     }
     
     fn transform_pattern_to_term(pattern : &MinimalLeafPattern) -> MinimalInteraction {
+        Self::try_transform_pattern_to_term(pattern).unwrap_or_else(
+            |reason| panic!("cannot transform pattern {:?} to a term : {}", pattern, reason)
+        )
+    }
+    
+    fn try_transform_pattern_to_term(pattern : &MinimalLeafPattern) -> Result<MinimalInteraction,String> {
         match pattern {
             MinimalLeafPattern::EMPTY => {
-                MinimalInteraction::Empty
+                Ok(MinimalInteraction::Empty)
             },
             MinimalLeafPattern::BROADCAST(broadcast) => {
                 if broadcast.targets.is_empty() {
-                    let origin_lf_id = broadcast.origin_lf_id.unwrap();
+                    let Some(origin_lf_id) = broadcast.origin_lf_id else {
+                        return Err("a broadcast must have an origin or targets".to_string());
+                    };
                     let emission = MinimalAction::new(origin_lf_id, broadcast.msg_id,MinimalActionKind::Emission);
-                    MinimalInteraction::Action(emission)
+                    Ok(MinimalInteraction::Action(emission))
                 } else {
                     let mut receptions = broadcast.targets.iter().map(
                         |lf_id| MinimalInteraction::Action(MinimalAction::new(*lf_id, broadcast.msg_id,MinimalActionKind::Reception))
@@ -73,13 +88,13 @@ impl FromInternalRepresentationToInteractionTerm<MinimalLangCioII> for MinimalIn
                     let recs_int = Self::fold_associative_operands_recursively(&MinimalOperators::Seq, &mut receptions);
                     match broadcast.origin_lf_id {
                         None => {
-                            recs_int
+                            Ok(recs_int)
                         },
                         Some(origin_lf_id) => {
                             let em_int = MinimalInteraction::Action(
                                 MinimalAction::new(origin_lf_id, broadcast.msg_id,MinimalActionKind::Emission)
                             );
-                            MinimalInteraction::Strict(Box::new(em_int), Box::new(recs_int))
+                            Ok(MinimalInteraction::Strict(Box::new(em_int), Box::new(recs_int)))
                         }
                     }
                 }
@@ -19,11 +19,15 @@ limitations under the License.
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::combinator::{map, value};
-use nom::error::ParseError;
 use nom::IResult;
 
 
 use crate::from_text::context_aware_parser::ContextAwareInteractionParser;
+use crate::from_text::error::{InteractionParseError, ParseError as TextParseError};
+use crate::from_text::recovery::RecoveringInteractionParser;
+use std::rc::Rc;
+
+use crate::from_text::signature::{SharedSignature, SignatureBasedInteractionParser, SignatureCategory};
 use crate::tests::lang_minimal::minimal_lang::*;
 use crate::tests::lang_minimal::core::internal_representation::*;
 use crate::internal_representation::*;
@@ -33,6 +37,7 @@ use crate::internal_representation::*;
 use crate::from_text::util::delimited_lang_parser::DelimitedInteractionLanguageParser;
 use crate::from_text::util::generic_broadcast_parser::GenericBroadcastParser;
 use crate::from_text::util::parse_utils::parse_element_of_preexisting_vec_and_return_index;
+use crate::syntax::InteractionLanguageSyntax;
 
 
 impl DelimitedInteractionLanguageParser for GeneralContext {
@@ -48,6 +53,30 @@ impl DelimitedInteractionLanguageParser for GeneralContext {
     fn separator_char(&self) -> char {
         ','
     }
+
+    fn syntax(&self) -> &dyn InteractionLanguageSyntax {
+        self
+    }
+
+    fn line_comment_start(&self) -> Option<&'static str> {
+        Some("//")
+    }
+
+    fn block_comment_delimiters(&self) -> Option<(&'static str,&'static str)> {
+        Some(("/*","*/"))
+    }
+
+    fn max_nesting_depth(&self) -> Option<usize> {
+        Some(10_000)
+    }
+
+    fn operator_parameters_delimiters(&self) -> Vec<(char,char)> {
+        vec![('[',']'),('{','}')]
+    }
+
+    fn max_expansion_length(&self) -> Option<usize> {
+        Some(1 << 16)
+    }
 }
 
 
@@ -68,15 +97,15 @@ impl GenericBroadcastParser<usize,usize,usize,MinimalBroadcastLeafPattern> for G
         "--"
     }
 
-    fn parse_message<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<&'a str, usize,E> {
+    fn parse_message<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<&'a str, usize,E> {
         parse_element_of_preexisting_vec_and_return_index(&self.ms_names,input)
     }
 
-    fn parse_broadcast_origin<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<&'a str, usize,E> {
+    fn parse_broadcast_origin<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<&'a str, usize,E> {
         parse_element_of_preexisting_vec_and_return_index(&self.lf_names,input)
     }
 
-    fn parse_single_broadcast_targets<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<&'a str, usize,E> {
+    fn parse_single_broadcast_targets<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<&'a str, usize,E> {
         parse_element_of_preexisting_vec_and_return_index(&self.lf_names,input)
     }
 }
@@ -85,9 +114,9 @@ impl GenericBroadcastParser<usize,usize,usize,MinimalBroadcastLeafPattern> for G
 
 impl ContextAwareInteractionParser<MinimalLangCioII> for GeneralContext {
 
-    fn parse_operator<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<
+    fn parse_operator<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<
         &'a str,
-        <MinimalLangCioII as CommonIoInteractionInterface>::InteractionOperatorType, 
+        <MinimalLangCioII as AnnotatedCommonIoInteractionInterface>::InteractionOperatorType, 
         E> {
         alt(
             (
@@ -95,15 +124,16 @@ impl ContextAwareInteractionParser<MinimalLangCioII> for GeneralContext {
             value(MinimalOperators::Seq, tag("seq")),
             value(MinimalOperators::Alt, tag("alt")),
             value(MinimalOperators::Par, tag("par")),
-            value(MinimalOperators::Loop, tag("loop"))
+            value(MinimalOperators::Loop, tag("loop")),
+            value(MinimalOperators::Choice, tag("choice"))
             )
         )
         (input)
     }
 
-    fn parse_explicit_pattern<'a, E: ParseError<&'a str>>(&self, input : &'a str) -> IResult<
+    fn parse_explicit_pattern<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<
         &'a str,
-        <MinimalLangCioII as CommonIoInteractionInterface>::InteractionLeafPatternType,
+        <MinimalLangCioII as AnnotatedCommonIoInteractionInterface>::InteractionLeafPatternType,
         E> {
         // we have two kinds of patterns :
         // *m -> l* for the reception of *m* by *l*
@@ -113,21 +143,162 @@ impl ContextAwareInteractionParser<MinimalLangCioII> for GeneralContext {
         //     *(l2,l3)* for two or more lifelines
         alt(
             (
-                map(|x| self.parse_broadcast_pattern(x), |y| MinimalLeafPattern::BROADCAST(y)),
+                map(|x| self.parse_broadcast_pattern(x), MinimalLeafPattern::BROADCAST),
                 value(MinimalLeafPattern::EMPTY,alt((tag("0"),tag("o"))))
             )
         )
         (input)
     }
 
+    fn apply_operator_parameters(
+        &self, 
+        operator : MinimalOperators,
+        parameters : Option<OperatorParameters>
+    ) -> Option<MinimalOperators> {
+        match (operator,parameters) {
+            (operator,None) => {
+                Some(operator)
+            },
+            // the bounds of a loop are either *[max]* or *[min,max]*
+            (MinimalOperators::Loop,Some(parameters)) => {
+                let bounds : Vec<usize> = parameters.arguments.iter().filter_map(|x| x.parse().ok()).collect();
+                let are_valid_bounds = parameters.opening == '['
+                    && bounds.len() == parameters.arguments.len()
+                    && (bounds.len() == 1 || (bounds.len() == 2 && bounds[0] <= bounds[1]));
+                if are_valid_bounds {
+                    Some(MinimalOperators::BoundedLoop(parameters))
+                } else {
+                    None
+                }
+            },
+            _ => {
+                None
+            }
+        }
+    }
+
+    fn interpret_annotation(&self, content : &str) -> Option<String> {
+        // empty annotations carry no information and are rejected
+        let content = content.trim();
+        if content.is_empty() {
+            None
+        } else {
+            Some(content.to_owned())
+        }
+    }
+
+}
+
+
+
+impl RecoveringInteractionParser<MinimalLangCioII> for GeneralContext {
+
+    fn make_error_placeholder(&self, _error : &TextParseError) -> MinimalLeafPattern {
+        MinimalLeafPattern::EMPTY
+    }
+
+}
+
+
+
+impl RecoveringInteractionParser<MinimalLangCioII> for SignatureContext {
+
+    fn make_error_placeholder(&self, _error : &TextParseError) -> MinimalLeafPattern {
+        MinimalLeafPattern::EMPTY
+    }
+
+}
+
+
+
+impl DelimitedInteractionLanguageParser for SignatureContext {
+
+    fn left_parenthesis_char(&self) -> char {
+        self.syntax.left_parenthesis_char()
+    }
+
+    fn right_parenthesis_char(&self) -> char {
+        self.syntax.right_parenthesis_char()
+    }
+
+    fn separator_char(&self) -> char {
+        self.syntax.separator_char()
+    }
+
+    fn line_comment_start(&self) -> Option<&'static str> {
+        self.syntax.line_comment_start()
+    }
+
+    fn block_comment_delimiters(&self) -> Option<(&'static str,&'static str)> {
+        self.syntax.block_comment_delimiters()
+    }
+}
+
+
+impl GenericBroadcastParser<usize,usize,usize,MinimalBroadcastLeafPattern> for SignatureContext {
+    fn make_pattern(&self,origin : Option<usize>, message : usize, targets : Vec<usize>) -> MinimalBroadcastLeafPattern {
+        self.syntax.make_pattern(origin, message, targets)
+    }
+
+    fn get_empty_target_char(&self) -> char {
+        self.syntax.get_empty_target_char()
+    }
+
+    fn get_tag_for_message_reception_by_target(&self) -> &'static str {
+        self.syntax.get_tag_for_message_reception_by_target()
+    }
+
+    fn get_tag_for_message_transmission_from_origin(&self) -> &'static str {
+        self.syntax.get_tag_for_message_transmission_from_origin()
+    }
+
+    fn parse_message<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<&'a str, usize,E> {
+        self.signature.parse_name(SignatureCategory::Message, input)
+    }
 
+    fn parse_broadcast_origin<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<&'a str, usize,E> {
+        self.signature.parse_name(SignatureCategory::Lifeline, input)
+    }
 
+    fn parse_single_broadcast_targets<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<&'a str, usize,E> {
+        self.signature.parse_name(SignatureCategory::Lifeline, input)
+    }
 }
 
 
+impl ContextAwareInteractionParser<MinimalLangCioII> for SignatureContext {
 
+    fn parse_operator<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<
+        &'a str,
+        <MinimalLangCioII as AnnotatedCommonIoInteractionInterface>::InteractionOperatorType, 
+        E> {
+        self.syntax.parse_operator(input)
+    }
 
+    fn parse_explicit_pattern<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<
+        &'a str,
+        <MinimalLangCioII as AnnotatedCommonIoInteractionInterface>::InteractionLeafPatternType,
+        E> {
+        alt(
+            (
+                map(|x| self.parse_broadcast_pattern(x), MinimalLeafPattern::BROADCAST),
+                value(MinimalLeafPattern::EMPTY,alt((tag("0"),tag("o"))))
+            )
+        )
+        (input)
+    }
+
+    fn backtrack(&self, input : &str) {
+        self.signature.backtrack(input);
+    }
 
+}
 
 
+impl SignatureBasedInteractionParser<MinimalLangCioII> for SignatureContext {
 
+    fn from_signature(signature : Rc<SharedSignature>) -> Self {
+        SignatureContext{syntax:GeneralContext{lf_names:vec![],ms_names:vec![]},signature}
+    }
+
+}
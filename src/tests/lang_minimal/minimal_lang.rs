@@ -18,12 +18,25 @@ limitations under the License.
 
 
 
+use std::rc::Rc;
+
+use crate::from_text::signature::SharedSignature;
+use crate::syntax::InteractionLanguageSyntax;
+
+
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct GeneralContext {
     pub lf_names : Vec<String>,
     pub ms_names : Vec<String>
 }
 
+// the names are resolved through a signature (see *parse_interaction_with_interning*)
+pub struct SignatureContext {
+    pub syntax : GeneralContext,
+    pub signature : Rc<SharedSignature>
+}
+
 #[derive(Clone, PartialEq, Debug, Eq, PartialOrd, Ord, Hash)]
 pub enum MinimalActionKind {
     Emission,
@@ -58,6 +71,36 @@ pub enum MinimalInteraction {
     Loop(Box<MinimalInteraction>)
 }
 
+// the default destructor is recursive
+impl Drop for MinimalInteraction {
+    fn drop(&mut self) {
+        fn detach_sub_interactions(term : &mut MinimalInteraction, to_drop : &mut Vec<MinimalInteraction>) {
+            match term {
+                MinimalInteraction::Empty | MinimalInteraction::Action(_) => {},
+                MinimalInteraction::Strict(i1, i2) | MinimalInteraction::Seq(i1, i2) | 
+                MinimalInteraction::Alt(i1, i2) | MinimalInteraction::Par(i1, i2) => {
+                    to_drop.push(std::mem::replace(i1.as_mut(), MinimalInteraction::Empty));
+                    to_drop.push(std::mem::replace(i2.as_mut(), MinimalInteraction::Empty));
+                },
+                MinimalInteraction::Loop(i1) => {
+                    to_drop.push(std::mem::replace(i1.as_mut(), MinimalInteraction::Empty));
+                }
+            }
+        }
+        let mut to_drop = vec![];
+        detach_sub_interactions(self, &mut to_drop);
+        while let Some(mut term) = to_drop.pop() {
+            detach_sub_interactions(&mut term, &mut to_drop);
+        }
+    }
+}
+
 
 
+impl InteractionLanguageSyntax for GeneralContext {
 
+    fn annotation_delimiters(&self) -> Option<(&'static str,char)> {
+        Some(("@{",'}'))
+    }
+
+}
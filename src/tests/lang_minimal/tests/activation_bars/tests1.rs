@@ -0,0 +1,133 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use image::ImageFormat;
+
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::to_image::drawing_context::{MinimalDrawingContext, MY_COLOR_WHITE};
+use crate::tests::lang_minimal::tests::tool_test_draw::{tool_get_drawing_context, tool_get_layout, tool_parse_for_drawing};
+#[cfg(feature = "to_svg")]
+use crate::tests::lang_minimal::tests::tool_test_draw::tool_get_svg;
+use crate::to_image::interface::get_interaction_as_encoded_sequence_diagram_image;
+use crate::to_image::layout::model::DiagramLayout;
+
+
+
+fn get_drawing_context(activation_bar_width : f32) -> MinimalDrawingContext {
+    tool_get_drawing_context(
+        &["a","b","c"], 
+        &["call","return","m"], 
+        |drawing_context| {
+            drawing_context.activation_messages.insert(0);
+            drawing_context.deactivation_messages.insert(1);
+            drawing_context.activation_bar_width = activation_bar_width;
+        }
+    )
+}
+
+
+fn get_layout(input_text : &str, drawing_context : &MinimalDrawingContext) -> DiagramLayout<usize> {
+    tool_get_layout(input_text, drawing_context).unwrap()
+}
+
+
+
+#[test]
+fn test_activation_bars_of_calls_and_returns() {
+    let drawing_context = get_drawing_context(6.0);
+    let layout = get_layout(
+        "seq(a -- call -> b, b -- call -> c, c -- return -> b, b -- return -> a, a -- m -> b)",
+        &drawing_context
+    );
+    assert_eq!(layout.activation_bars.len(), 2);
+    let (b, c) = (&layout.lifelines[1], &layout.lifelines[2]);
+    let bar_of_b = layout.activation_bars.iter().find(|bar| bar.lifeline == 1).unwrap();
+    let bar_of_c = layout.activation_bars.iter().find(|bar| bar.lifeline == 2).unwrap();
+    // bars are centered on their lifelines
+    assert!((bar_of_b.x_left - (b.x_pos - 3.0)).abs() < 0.01 && (bar_of_b.x_right - (b.x_pos + 3.0)).abs() < 0.01);
+    assert!((bar_of_c.x_left - (c.x_pos - 3.0)).abs() < 0.01 && (bar_of_c.x_right - (c.x_pos + 3.0)).abs() < 0.01);
+    // bars start at the reception of the call and end at the emission of the return
+    let midlines : Vec<f32> = layout.leaf_patterns.iter().map(|p| p.midline_y).collect();
+    assert!((bar_of_b.y_top - midlines[0]).abs() < 0.01 && (bar_of_b.y_bottom - midlines[3]).abs() < 0.01);
+    assert!((bar_of_c.y_top - midlines[1]).abs() < 0.01 && (bar_of_c.y_bottom - midlines[2]).abs() < 0.01);
+    assert_eq!(bar_of_b.color, b.color);
+    assert_eq!(bar_of_b.fill_color, MY_COLOR_WHITE);
+}
+
+
+#[test]
+fn test_nested_and_unclosed_activation_bars() {
+    let drawing_context = get_drawing_context(6.0);
+    let layout = get_layout(
+        "seq(a -- call -> b, b -- call -> b, b -- return -> b, a -- call -> c)",
+        &drawing_context
+    );
+    assert_eq!(layout.activation_bars.len(), 3);
+    // the outermost bars are given first (from left to right)
+    let (outer, inner) = (&layout.activation_bars[0], &layout.activation_bars[2]);
+    assert_eq!((outer.lifeline, inner.lifeline), (1, 1));
+    // the nested bar is shifted towards the right
+    assert!((inner.x_left - (outer.x_left + 3.0)).abs() < 0.01 && (inner.x_right - (outer.x_right + 3.0)).abs() < 0.01);
+    assert!(outer.y_top < inner.y_top && inner.y_bottom < outer.y_bottom);
+    // bars which are not closed end at the bottom of their lifelines
+    let b = &layout.lifelines[1];
+    assert!((outer.y_bottom - b.y_bottom).abs() < 0.01);
+    let bar_of_c = &layout.activation_bars[1];
+    assert_eq!(bar_of_c.lifeline, 2);
+    assert!((bar_of_c.y_bottom - layout.lifelines[2].y_bottom).abs() < 0.01);
+}
+
+
+#[test]
+fn test_lifelines_are_spaced_to_fit_bars() {
+    let input_text = "seq(a -- call -> b, b -- call -> b, b -- call -> b, a -- m -> c)";
+    let narrow = get_layout(input_text, &get_drawing_context(6.0));
+    let wide = get_layout(input_text, &get_drawing_context(40.0));
+    let spacing = |layout : &DiagramLayout<usize>| layout.lifelines[2].x_pos - layout.lifelines[1].x_pos;
+    assert!(spacing(&wide) > spacing(&narrow));
+    // the innermost bar of b does not overlap with c
+    let innermost_bar_of_b = wide.activation_bars.iter().rfind(|bar| bar.lifeline == 1).unwrap();
+    assert!(innermost_bar_of_b.x_right < wide.lifelines[2].x_pos);
+}
+
+
+#[cfg(feature = "to_svg")]
+#[test]
+fn test_draw_activation_bars_as_svg() {
+    let svg = tool_get_svg("seq(a -- call -> b, b -- return -> a)", &get_drawing_context(6.0));
+    assert!(svg.contains(r##"fill="#ffffff""##));
+    assert!(svg.contains(">call</tspan>"));
+}
+
+
+#[test]
+fn test_draw_thin_activation_bars_as_png() {
+    let input_text = "seq(a -- call -> b, b -- return -> b)";
+    // bars narrower than a pixel are not filled
+    for activation_bar_width in [6.0, 0.5, 0.0] {
+        let drawing_context = get_drawing_context(activation_bar_width);
+        let internal_repr = tool_parse_for_drawing(input_text, &drawing_context);
+        let png = get_interaction_as_encoded_sequence_diagram_image::<MinimalLangCioII,usize,MinimalDrawingContext,MinimalDrawingContext>(
+            &internal_repr,
+            &drawing_context,
+            &drawing_context,
+            ImageFormat::Png
+        ).unwrap();
+        assert!(png.starts_with(&[0x89, b'P', b'N', b'G']));
+    }
+}
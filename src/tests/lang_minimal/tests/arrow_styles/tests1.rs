@@ -0,0 +1,130 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use image::{Rgb, RgbImage};
+
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::to_image::drawing_context::{MinimalDrawingContext, MY_COLOR_WHITE};
+use crate::tests::lang_minimal::tests::tool_test_draw::{tool_get_drawing_context, tool_get_layout, tool_parse_for_drawing};
+#[cfg(feature = "to_svg")]
+use crate::tests::lang_minimal::tests::tool_test_draw::tool_get_svg;
+use crate::to_image::drawable::leaf::util::{MessageArrowTailDecoration, MessageArrowheadKind, MessageLineDashPattern};
+use crate::to_image::interface::get_interaction_as_sequence_diagram_image;
+use crate::to_image::layout::model::DiagramLayout;
+
+
+
+fn get_drawing_context() -> MinimalDrawingContext {
+    tool_get_drawing_context(
+        &["a","b"], 
+        &["call","signal","reply","found","m"], 
+        |drawing_context| {
+            drawing_context.messages_line_styles.insert(
+                0, (MessageLineDashPattern::Solid, MessageArrowheadKind::Filled, MessageArrowTailDecoration::None)
+            );
+            drawing_context.messages_line_styles.insert(
+                1, (MessageLineDashPattern::Solid, MessageArrowheadKind::Half, MessageArrowTailDecoration::None)
+            );
+            drawing_context.messages_line_styles.insert(
+                2, (MessageLineDashPattern::Dashed, MessageArrowheadKind::Open, MessageArrowTailDecoration::None)
+            );
+            drawing_context.messages_line_styles.insert(
+                3, (MessageLineDashPattern::Dotted, MessageArrowheadKind::None, MessageArrowTailDecoration::Circle)
+            );
+        }
+    )
+}
+
+
+fn get_layout_and_image(input_text : &str) -> (DiagramLayout<usize>,RgbImage) {
+    let drawing_context = get_drawing_context();
+    let layout = tool_get_layout(input_text, &drawing_context).unwrap();
+    let internal_repr = tool_parse_for_drawing(input_text, &drawing_context);
+    let image = get_interaction_as_sequence_diagram_image::<MinimalLangCioII,usize,MinimalDrawingContext,MinimalDrawingContext>(
+        &internal_repr,
+        &drawing_context,
+        &drawing_context
+    ).unwrap();
+    (layout,image)
+}
+
+
+fn is_background(image : &RgbImage, x : f32, y : f32) -> bool {
+    *image.get_pixel(x.round() as u32, y.round() as u32) == Rgb(MY_COLOR_WHITE)
+}
+
+
+
+#[test]
+fn test_line_styles_in_layout() {
+    let (layout,_) = get_layout_and_image("seq(a -- call -> b, b -- reply -> a, a -- m -> b)");
+    let lines : Vec<_> = layout.leaf_patterns.iter().map(|p| &p.line).collect();
+    assert_eq!(lines[0].arrowhead_kind, MessageArrowheadKind::Filled);
+    assert_eq!(lines[1].dash_pattern, MessageLineDashPattern::Dashed);
+    // messages without specific styles are drawn with solid lines and open arrowheads
+    assert_eq!(lines[2].dash_pattern, MessageLineDashPattern::Solid);
+    assert_eq!(lines[2].arrowhead_kind, MessageArrowheadKind::Open);
+    assert_eq!(lines[2].tail_decoration, MessageArrowTailDecoration::None);
+}
+
+
+#[test]
+fn test_dashed_and_solid_lines_on_image() {
+    let (layout,image) = get_layout_and_image("seq(a -- m -> b, b -- reply -> a)");
+    let (x_a, x_b) = (layout.lifelines[0].x_pos, layout.lifelines[1].x_pos);
+    let count_gaps = |y : f32| ((x_a + 12.0) as u32..(x_b - 12.0) as u32).filter(|x| is_background(&image, *x as f32, y)).count();
+    assert_eq!(count_gaps(layout.leaf_patterns[0].midline_y), 0);
+    assert!(count_gaps(layout.leaf_patterns[1].midline_y) > 0);
+}
+
+
+#[test]
+fn test_arrowheads_on_image() {
+    let (layout,image) = get_layout_and_image("seq(a -- call -> b, a -- m -> b, a -- signal -> b)");
+    let x_b = layout.lifelines[1].x_pos;
+    let (filled, open, half) = (layout.leaf_patterns[0].midline_y, layout.leaf_patterns[1].midline_y, layout.leaf_patterns[2].midline_y);
+    // a filled arrowhead is filled between its barbs
+    assert!(!is_background(&image, x_b - 6.0, filled - 3.0));
+    assert!(!is_background(&image, x_b - 6.0, filled + 3.0));
+    // an open arrowhead is not
+    assert!(is_background(&image, x_b - 6.0, open - 3.0));
+    assert!(!is_background(&image, x_b - 6.0, open - 6.0));
+    assert!(!is_background(&image, x_b - 6.0, open + 6.0));
+    // a half arrowhead only has its upper barb
+    assert!(!is_background(&image, x_b - 6.0, half - 6.0));
+    assert!(is_background(&image, x_b - 6.0, half + 6.0));
+}
+
+
+#[cfg(feature = "to_svg")]
+#[test]
+fn test_arrow_styles_as_svg() {
+    let drawing_context = get_drawing_context();
+    let svg_of = |input_text : &str| tool_get_svg(input_text, &drawing_context);
+    let call = svg_of("a -- call -> b");
+    assert!(call.contains("<polygon"));
+    assert!(!call.contains("<polyline"));
+    let reply = svg_of("b -- reply -> a");
+    assert!(reply.contains(r#"stroke-dasharray="6 4""#));
+    assert!(reply.contains("<polyline"));
+    // a found message is drawn with a circle at its tail and without an arrowhead
+    let found = svg_of("found -> b");
+    assert!(found.contains(r#"stroke-dasharray="2 3""#));
+    assert!(found.contains("<circle"));
+    assert!(!found.contains("<polyline") && !found.contains("<polygon"));
+}
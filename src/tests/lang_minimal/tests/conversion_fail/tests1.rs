@@ -0,0 +1,196 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use crate::conversion::error::ConversionError;
+use crate::conversion::lang_to_repr::FromInteractionTermToInternalRepresentation;
+use crate::conversion::repr_to_lang::FromInternalRepresentationToInteractionTerm;
+use crate::internal_representation::{CommonIoInteractionInterface, InteractionInternalRepresentation, InteractionOperatorRepresentation};
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::{GeneralContext, MinimalInteraction};
+use crate::from_text::parse::parse_interaction_strict;
+
+
+
+fn emission(lf_id : usize) -> InteractionInternalRepresentation<MinimalLangCioII> {
+    InteractionInternalRepresentation::LeafPattern(
+        MinimalLeafPattern::BROADCAST(MinimalBroadcastLeafPattern::new(Some(lf_id), 0, vec![]))
+    )
+}
+
+
+
+#[test]
+fn test_try_convert_valid_interaction() {
+    let ctx = GeneralContext{lf_names:vec!["a".to_string(),"b".to_string()],ms_names:vec!["m".to_string()]};
+    let int_repr = parse_interaction_strict::<MinimalLangCioII,GeneralContext>("seq(a -- m -> b,loop(b -- m -> a),0)",&ctx).unwrap();
+    let term = MinimalInteraction::try_from_io_repr(&int_repr).unwrap();
+    assert_eq!(term, MinimalInteraction::from_io_repr(&int_repr));
+    assert_eq!(format!("{:?}",term.to_io_repr(true, true)), format!("{:?}",int_repr));
+}
+
+
+#[test]
+fn test_try_convert_arity_mismatch() {
+    // a loop with two operands, as the second operand of a seq
+    let int_repr = InteractionInternalRepresentation::<MinimalLangCioII>::Operator(
+        MinimalOperators::Seq,
+        vec![
+            emission(0),
+            InteractionInternalRepresentation::Operator(MinimalOperators::Loop, vec![emission(1),emission(0)])
+        ]
+    );
+    let err = MinimalInteraction::try_from_io_repr(&int_repr).unwrap_err();
+    assert_eq!(
+        err,
+        ConversionError::ArityMismatch { 
+            operator : "Loop".to_string(), 
+            min_operands : 1, 
+            max_operands : Some(1), 
+            got : 2, 
+            path : vec![1] 
+        }
+    );
+    assert_eq!(err.to_string(), "operator 'Loop' at [1] expects 1 operands but got 2");
+}
+
+
+#[test]
+fn test_try_convert_associative_operator_with_too_few_operands() {
+    let int_repr = InteractionInternalRepresentation::<MinimalLangCioII>::Operator(
+        MinimalOperators::Par,
+        vec![
+            emission(0),
+            InteractionInternalRepresentation::Operator(
+                MinimalOperators::Loop, 
+                vec![InteractionInternalRepresentation::Operator(MinimalOperators::Seq, vec![emission(1)])]
+            )
+        ]
+    );
+    let err = MinimalInteraction::try_from_io_repr(&int_repr).unwrap_err();
+    assert_eq!(
+        err,
+        ConversionError::ArityMismatch { 
+            operator : "Seq".to_string(), 
+            min_operands : 2, 
+            max_operands : None, 
+            got : 1, 
+            path : vec![1,0] 
+        }
+    );
+    assert_eq!(err.to_string(), "operator 'Seq' at [1, 0] expects at least 2 operands but got 1");
+}
+
+
+#[test]
+fn test_try_convert_invalid_pattern() {
+    // a broadcast with neither an origin nor targets
+    let invalid = InteractionInternalRepresentation::LeafPattern(
+        MinimalLeafPattern::BROADCAST(MinimalBroadcastLeafPattern::new(None, 0, vec![]))
+    );
+    let int_repr = InteractionInternalRepresentation::<MinimalLangCioII>::Operator(
+        MinimalOperators::Alt,
+        vec![emission(0),emission(1),invalid]
+    );
+    match MinimalInteraction::try_from_io_repr(&int_repr).unwrap_err() {
+        ConversionError::InvalidPattern { reason, path, .. } => {
+            assert_eq!(reason, "a broadcast must have an origin or targets");
+            assert_eq!(path, vec![2]);
+        },
+        err => {
+            panic!("unexpected error {:?}", err);
+        }
+    }
+}
+
+
+
+// a language with an associative operator which accepts a single operand, so that operands cannot be folded under it
+#[derive(Debug,Clone)]
+struct UnaryAssociativeCioII {}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+struct UnaryAssociativeOperator {}
+
+impl InteractionOperatorRepresentation for UnaryAssociativeOperator {
+
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn is_associative(&self) -> bool {
+        true
+    }
+
+}
+
+impl CommonIoInteractionInterface for UnaryAssociativeCioII {
+    type InteractionLeafPatternType = usize;
+    type InteractionOperatorType = UnaryAssociativeOperator;
+}
+
+#[derive(Debug,Clone,PartialEq)]
+enum UnaryAssociativeTerm {
+    Leaf(usize),
+    Application(Box<UnaryAssociativeTerm>)
+}
+
+impl FromInternalRepresentationToInteractionTerm<UnaryAssociativeCioII> for UnaryAssociativeTerm {
+
+    fn get_empty_interaction() -> Self {
+        UnaryAssociativeTerm::Leaf(0)
+    }
+
+    fn instantiate_interaction_under_operator(_operator : &UnaryAssociativeOperator, sub_ints : &mut Vec<Self>) -> Option<Self> {
+        if sub_ints.len() == 1 {
+            Some(UnaryAssociativeTerm::Application(Box::new(sub_ints.pop().unwrap())))
+        } else {
+            None
+        }
+    }
+
+    fn transform_pattern_to_term(pattern : &usize) -> Self {
+        UnaryAssociativeTerm::Leaf(*pattern)
+    }
+
+}
+
+
+#[test]
+fn test_try_convert_associative_operator_accepting_a_single_operand() {
+    let leaf = |id : usize| InteractionInternalRepresentation::<UnaryAssociativeCioII>::LeafPattern(id);
+    let single = InteractionInternalRepresentation::<UnaryAssociativeCioII>::Operator(UnaryAssociativeOperator{}, vec![leaf(1)]);
+    assert_eq!(
+        UnaryAssociativeTerm::try_from_io_repr(&single).unwrap(), 
+        UnaryAssociativeTerm::Application(Box::new(UnaryAssociativeTerm::Leaf(1)))
+    );
+    let several = InteractionInternalRepresentation::<UnaryAssociativeCioII>::Operator(
+        UnaryAssociativeOperator{}, 
+        vec![leaf(1),leaf(2),leaf(3)]
+    );
+    let err = UnaryAssociativeTerm::try_from_io_repr(&several).unwrap_err();
+    assert_eq!(
+        err,
+        ConversionError::ArityMismatch { 
+            operator : "UnaryAssociativeOperator".to_string(), 
+            min_operands : 1, 
+            max_operands : Some(1), 
+            got : 3, 
+            path : vec![] 
+        }
+    );
+}
@@ -0,0 +1,167 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use crate::conversion::error::ConversionError;
+use crate::conversion::lang_to_repr::FromInteractionTermToInternalRepresentation;
+use crate::conversion::repr_to_lang::FromInternalRepresentationToInteractionTerm;
+use crate::internal_representation::InteractionInternalRepresentation;
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::{GeneralContext, MinimalAction, MinimalActionKind, MinimalInteraction};
+use crate::from_text::error::ParseErrorKind;
+use crate::from_text::infix::parse_infix_interaction;
+use crate::from_text::parse::parse_interaction_strict;
+use crate::to_text::print::{print_interaction, print_interaction_infix};
+
+
+
+fn get_depth(int_repr : &InteractionInternalRepresentation<MinimalLangCioII>) -> usize {
+    let mut depth = 0;
+    let mut to_explore = vec![(int_repr,0)];
+    while let Some((int_repr,int_depth)) = to_explore.pop() {
+        depth = depth.max(int_depth);
+        if let InteractionInternalRepresentation::Operator(_, sub_ints) = int_repr {
+            to_explore.extend(sub_ints.iter().map(|x| (x,int_depth + 1)));
+        }
+    }
+    depth
+}
+
+fn make_nested_loops(depth : usize) -> String {
+    format!("{}a -- m -> b{}", "loop(\n".repeat(depth), ")".repeat(depth))
+}
+
+
+
+#[test]
+fn test_parse_deeply_nested_interaction() {
+    let ctx = GeneralContext{lf_names:vec!["a".to_string(),"b".to_string()],ms_names:vec!["m".to_string()]};
+    // the maximal nesting depth of the minimal language is 10000
+    let int_repr = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(&make_nested_loops(10_000),&ctx).unwrap();
+    assert_eq!(get_depth(&int_repr), 10_000);
+    // ***
+    // in the term, the pattern is a strict sequence of an emission and a reception
+    let term = MinimalInteraction::from_io_repr(&int_repr);
+    let err = term.try_to_io_repr(true, true, Some(10_000)).unwrap_err();
+    assert_eq!(err, ConversionError::NestingTooDeep { max_depth: 10_000 });
+    let converted_back = term.try_to_io_repr(true, true, Some(10_001)).unwrap();
+    assert_eq!(get_depth(&converted_back), 10_000);
+    drop(int_repr);
+    drop(converted_back);
+    // ***
+    let err = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(&make_nested_loops(10_001),&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::NestingTooDeep { max_depth: 10_000 });
+    assert_eq!((err.line,err.column), (10_001,1));
+}
+
+
+#[test]
+fn test_print_deeply_nested_interaction() {
+    let ctx = GeneralContext{lf_names:vec!["a".to_string(),"b".to_string()],ms_names:vec!["m".to_string()]};
+    let int_repr = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(&make_nested_loops(10_000),&ctx).unwrap();
+    // ***
+    let printed = print_interaction(&int_repr, &ctx);
+    let reparsed = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(&printed,&ctx).unwrap();
+    assert_eq!(get_depth(&reparsed), 10_000);
+    // ***
+    let printed = print_interaction_infix(&int_repr, &ctx);
+    assert!(printed.starts_with("loop*(loop*("));
+    let reparsed = parse_infix_interaction::<MinimalLangCioII,GeneralContext>(&printed,&ctx).unwrap();
+    assert_eq!(get_depth(&reparsed), 10_000);
+}
+
+
+#[test]
+fn test_convert_many_associative_operands() {
+    let num_operands = 100_000;
+    let action = MinimalInteraction::Action(MinimalAction::new(0, 0, MinimalActionKind::Emission));
+    let operands = (0..num_operands).map(|_| InteractionInternalRepresentation::LeafPattern(MinimalLeafPattern::EMPTY)).collect();
+    let int_repr = InteractionInternalRepresentation::<MinimalLangCioII>::Operator(MinimalOperators::Seq, operands);
+    // the folded term is nested *num_operands - 1* times
+    let term = MinimalInteraction::from_io_repr(&int_repr);
+    let term = MinimalInteraction::Par(Box::new(term), Box::new(action));
+    match &term.to_io_repr(false, true) {
+        InteractionInternalRepresentation::Operator(MinimalOperators::Par, sub_ints) => {
+            match &sub_ints[0] {
+                InteractionInternalRepresentation::Operator(MinimalOperators::Seq, seq_operands) => {
+                    assert_eq!(seq_operands.len(), num_operands);
+                },
+                _ => {
+                    panic!("expected the operands of seq to be flattened");
+                }
+            }
+        },
+        _ => {
+            panic!("expected par at the root");
+        }
+    }
+}
+
+
+#[test]
+fn test_drop_and_debug_deeply_nested_interaction() {
+    let depth = 1_000_000;
+    let mut int_repr = InteractionInternalRepresentation::<MinimalLangCioII>::LeafPattern(MinimalLeafPattern::EMPTY);
+    for i in 0..depth {
+        int_repr = if i % 3 == 0 {
+            InteractionInternalRepresentation::Operator(MinimalOperators::Loop, vec![int_repr])
+        } else {
+            InteractionInternalRepresentation::Annotated(i.to_string(), Box::new(int_repr))
+        };
+    }
+    let printed = format!("{:?}", int_repr);
+    assert!(printed.starts_with("Operator(Loop, [Annotated(\"999998\", Annotated(\"999997\", Operator(Loop, ["));
+    assert!(printed.contains("Annotated(\"1\", Operator(Loop, [LeafPattern(EMPTY)]))"));
+    drop(int_repr);
+    // a long chain of annotations
+    let int_repr = InteractionInternalRepresentation::<MinimalLangCioII>::LeafPattern(MinimalLeafPattern::EMPTY)
+        .with_annotations((0..depth).map(|i| i.to_string()).collect());
+    drop(int_repr);
+}
+
+
+// the representation with a derived implementation of Debug (which only reads the fields)
+#[allow(dead_code)]
+#[derive(Debug)]
+enum DerivedRepr {
+    LeafPattern(MinimalLeafPattern),
+    Operator(MinimalOperators, Vec<DerivedRepr>),
+    Annotated(String, Box<DerivedRepr>)
+}
+
+#[test]
+fn test_debug_same_as_derived() {
+    let broadcast = MinimalLeafPattern::BROADCAST(MinimalBroadcastLeafPattern::new(None, 0, vec![1]));
+    let int_repr = InteractionInternalRepresentation::<MinimalLangCioII>::Operator(
+        MinimalOperators::Seq, 
+        vec![
+            InteractionInternalRepresentation::LeafPattern(MinimalLeafPattern::EMPTY),
+            InteractionInternalRepresentation::Annotated("x".to_string(), Box::new(InteractionInternalRepresentation::LeafPattern(broadcast.clone()))),
+            InteractionInternalRepresentation::Operator(MinimalOperators::Par, vec![])
+        ]
+    );
+    let derived = DerivedRepr::Operator(
+        MinimalOperators::Seq, 
+        vec![
+            DerivedRepr::LeafPattern(MinimalLeafPattern::EMPTY),
+            DerivedRepr::Annotated("x".to_string(), Box::new(DerivedRepr::LeafPattern(broadcast))),
+            DerivedRepr::Operator(MinimalOperators::Par, vec![])
+        ]
+    );
+    assert_eq!(format!("{:?}", int_repr), format!("{:?}", derived));
+    assert_eq!(format!("{:#?}", int_repr), format!("{:#?}", derived));
+}
@@ -0,0 +1,114 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use ab_glyph::{Font, PxScale};
+use image::Rgb;
+use image_colored_text::draw::coord::DrawCoord;
+use image_colored_text::text::paragraph::ColoredTextParagraph;
+
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::GeneralContext;
+use crate::tests::lang_minimal::to_image::drawing_context::MinimalDrawingContext;
+use crate::from_text::parse::parse_interaction;
+use crate::to_image::draw::canvas::DiagramCanvas;
+use crate::to_image::interface::draw_interaction_on_canvas;
+
+
+
+/** 
+ * A canvas that only records the primitives that are called on it.
+ * **/
+struct RecordingCanvas {
+    width : f32,
+    height : f32,
+    lines : usize,
+    rects : usize,
+    texts : Vec<String>,
+    arrowheads : usize
+}
+
+impl DiagramCanvas for RecordingCanvas {
+
+    fn draw_line(&mut self, _start : (f32,f32), _end : (f32,f32), _color : Rgb<u8>) {
+        self.lines += 1;
+    }
+
+    fn draw_filled_rect(&mut self, _x_left : f32, _y_top : f32, _width : f32, _height : f32, _color : Rgb<u8>) {
+        self.rects += 1;
+    }
+
+    fn draw_text_paragraph(
+        &mut self,
+        _x_pos : &DrawCoord,
+        _y_pos : &DrawCoord,
+        paragraph : &ColoredTextParagraph,
+        _font : &impl Font,
+        _scale : impl Into<PxScale> + Copy
+    ) {
+        for line in &paragraph.lines {
+            let text : String = line.colored_segments.iter().map(|(t,_)| t.as_str()).collect();
+            self.texts.push(text);
+        }
+    }
+
+    fn draw_arrowhead(&mut self, _tip : (f32,f32), _pointing_rightward : bool, _arrowhead_length : f32, _color : Rgb<u8>) {
+        self.arrowheads += 1;
+    }
+
+}
+
+
+
+#[test]
+fn test_draw_on_custom_canvas() {
+    let ctx = GeneralContext{lf_names:vec!["a".to_string(),"b".to_string()],ms_names:vec!["m".to_string(),"n".to_string()]};
+    let input_text = "par(a -- m -> b, b -- n -> a)";
+    let internal_repr = parse_interaction::<MinimalLangCioII,GeneralContext>(
+        input_text,&ctx
+    ).unwrap();
+    let drawing_context = MinimalDrawingContext::new(ctx);
+    let canvas = draw_interaction_on_canvas::<MinimalLangCioII,usize,MinimalDrawingContext,MinimalDrawingContext,RecordingCanvas>(
+        &internal_repr,
+        &drawing_context,
+        &drawing_context,
+        |width,height| RecordingCanvas{width,height,lines:0,rects:0,texts:vec![],arrowheads:0}
+    ).unwrap();
+    assert!(canvas.width > 0.0);
+    assert!(canvas.height > 0.0);
+    // the background
+    assert_eq!(canvas.rects, 1);
+    // one arrowhead per message passing arrow
+    assert_eq!(canvas.arrowheads, 2);
+    // at least the two lifelines, the two arrows and the frame of the "par" operator
+    assert!(canvas.lines >= 8);
+    for expected_text in ["a","b","m","n","par"] {
+        assert!(canvas.texts.iter().any(|t| t == expected_text), "missing text '{}'", expected_text);
+    }
+}
+
+
+
+#[test]
+fn test_dashed_line_with_negative_gap() {
+    let mut canvas = RecordingCanvas{width:10.0,height:10.0,lines:0,rects:0,texts:vec![],arrowheads:0};
+    canvas.draw_dashed_line((0.0,0.0),(10.0,0.0),Rgb([0,0,0]),false,(2.0,-2.0));
+    canvas.draw_dashed_line((0.0,0.0),(10.0,0.0),Rgb([0,0,0]),false,(2.0,-5.0));
+    assert_eq!(canvas.lines, 0);
+    canvas.draw_dashed_line((0.0,0.0),(10.0,0.0),Rgb([0,0,0]),false,(2.0,0.0));
+    assert_eq!(canvas.lines, 5);
+}
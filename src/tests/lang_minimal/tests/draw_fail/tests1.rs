@@ -0,0 +1,60 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use std::path::Path;
+
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::GeneralContext;
+use crate::tests::lang_minimal::to_image::drawing_context::MinimalDrawingContext;
+use crate::from_text::parse::parse_interaction;
+use crate::to_image::error::DrawError;
+use crate::to_image::interface::try_draw_interaction_as_sequence_diagram;
+
+
+
+
+fn try_draw_to_path(file_path : &Path) -> Result<(),DrawError> {
+    let ctx = GeneralContext{lf_names:vec!["a".to_string(),"b".to_string()],ms_names:vec!["m".to_string()]};
+    let internal_repr = parse_interaction::<MinimalLangCioII,GeneralContext>(
+        "a -- m -> b",&ctx
+    ).unwrap();
+    let drawing_context = MinimalDrawingContext::new(ctx);
+    try_draw_interaction_as_sequence_diagram::<MinimalLangCioII,usize,MinimalDrawingContext,MinimalDrawingContext>(
+        &internal_repr,
+        &drawing_context,
+        &drawing_context,
+        file_path
+    )
+}
+
+
+#[test]
+fn test_draw_fail_unsupported_extension() {
+    match try_draw_to_path(Path::new("draw_fail_test.not_an_image_format")) {
+        Err(DrawError::Encoding(_)) => {},
+        other => panic!("expected an encoding error, got {:?}", other)
+    }
+}
+
+#[test]
+fn test_draw_fail_invalid_path() {
+    match try_draw_to_path(Path::new("this_directory_does_not_exist/draw_fail_test.png")) {
+        Err(DrawError::Io(_)) => {},
+        other => panic!("expected an I/O error, got {:?}", other)
+    }
+}
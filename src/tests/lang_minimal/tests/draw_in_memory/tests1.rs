@@ -0,0 +1,60 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use image::ImageFormat;
+
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::GeneralContext;
+use crate::tests::lang_minimal::to_image::drawing_context::MinimalDrawingContext;
+use crate::from_text::parse::parse_interaction;
+use crate::to_image::interface::{get_interaction_as_encoded_sequence_diagram_image, get_interaction_as_sequence_diagram_image};
+
+
+
+
+#[test]
+fn test_draw_in_memory() {
+    let ctx = GeneralContext{lf_names:vec!["a".to_string(),"b".to_string()],ms_names:vec!["m".to_string()]};
+    let internal_repr = parse_interaction::<MinimalLangCioII,GeneralContext>(
+        "strict(a -- m -> b, b -- m -> a)",&ctx
+    ).unwrap();
+    let drawing_context = MinimalDrawingContext::new(ctx);
+    let image = get_interaction_as_sequence_diagram_image::<MinimalLangCioII,usize,MinimalDrawingContext,MinimalDrawingContext>(
+        &internal_repr,
+        &drawing_context,
+        &drawing_context
+    ).unwrap();
+    assert!(image.width() > 0);
+    assert!(image.height() > 0);
+    // ***
+    for (format, expected_prefix) in [
+        (ImageFormat::Png, b"\x89PNG".as_slice()),
+        (ImageFormat::Jpeg, b"\xFF\xD8".as_slice()),
+        (ImageFormat::WebP, b"RIFF".as_slice())
+    ] {
+        let bytes = get_interaction_as_encoded_sequence_diagram_image::<MinimalLangCioII,usize,MinimalDrawingContext,MinimalDrawingContext>(
+            &internal_repr,
+            &drawing_context,
+            &drawing_context,
+            format
+        ).unwrap();
+        assert!(bytes.starts_with(expected_prefix), "unexpected encoding for {:?}", format);
+        let decoded = image::load_from_memory_with_format(&bytes, format).unwrap();
+        assert_eq!((decoded.width(),decoded.height()), (image.width(),image.height()));
+    }
+}
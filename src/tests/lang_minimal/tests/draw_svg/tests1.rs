@@ -0,0 +1,63 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::GeneralContext;
+use crate::tests::lang_minimal::to_image::drawing_context::MinimalDrawingContext;
+use crate::from_text::parse::parse_interaction;
+use crate::tests::common::DRAWING_GRAPHIC_FONT_FAMILY;
+use crate::to_svg::interface::get_interaction_as_svg_sequence_diagram;
+
+
+
+
+
+#[test]
+fn test_draw_svg_1() {
+    let ctx = GeneralContext{lf_names:vec!["a".to_string(),"b".to_string()],ms_names:vec!["m".to_string(),"n".to_string()]};
+    let input_text = 
+r#"seq(
+        a -- m -> b,
+        alt(
+                b -- n -> a,
+                0
+        )
+)"#;
+    let internal_repr = parse_interaction::<MinimalLangCioII,GeneralContext>(
+        input_text,&ctx
+    ).unwrap();
+    let drawing_context = MinimalDrawingContext::new(ctx);
+    let svg = get_interaction_as_svg_sequence_diagram::<MinimalLangCioII,usize,MinimalDrawingContext,MinimalDrawingContext>(
+        &internal_repr,
+        &drawing_context,
+        &drawing_context,
+        DRAWING_GRAPHIC_FONT_FAMILY
+    ).unwrap();
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    // the lifeline headers, the message names and the operator label are written as text
+    for expected_text in ["a","b","m","n","alt"] {
+        assert!(svg.contains(&format!(">{}</tspan>",expected_text)), "missing text '{}'", expected_text);
+    }
+    // the texts are written with the font family of the caller
+    assert!(svg.contains(r#"font-family="DejaVu Sans Mono""#));
+    assert!(!svg.contains("monospace"));
+    // the background is drawn as a filled rectangle
+    assert!(svg.contains("<rect "));
+    // the arrowheads of the two message passing arrows
+    assert!(svg.matches("<polyline ").count() >= 2);
+    assert!(svg.matches("<line ").count() >= 4);
+}
@@ -0,0 +1,156 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use crate::conversion::lang_to_repr::FromInteractionTermToInternalRepresentation;
+use crate::conversion::nesting::{AssociativeFoldStrategy, AssociativeNestingItem, AssociativeNestings};
+use crate::conversion::repr_to_lang::FromInternalRepresentationToInteractionTerm;
+use crate::internal_representation::InteractionInternalRepresentation;
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::{MinimalAction, MinimalActionKind, MinimalInteraction};
+
+
+
+fn emission(lf_id : usize) -> MinimalInteraction {
+    MinimalInteraction::Action(MinimalAction::new(lf_id, 0, MinimalActionKind::Emission))
+}
+
+fn reception(lf_id : usize) -> MinimalInteraction {
+    MinimalInteraction::Action(MinimalAction::new(lf_id, 0, MinimalActionKind::Reception))
+}
+
+fn seq(i1 : MinimalInteraction, i2 : MinimalInteraction) -> MinimalInteraction {
+    MinimalInteraction::Seq(Box::new(i1), Box::new(i2))
+}
+
+fn strict(i1 : MinimalInteraction, i2 : MinimalInteraction) -> MinimalInteraction {
+    MinimalInteraction::Strict(Box::new(i1), Box::new(i2))
+}
+
+fn par(i1 : MinimalInteraction, i2 : MinimalInteraction) -> MinimalInteraction {
+    MinimalInteraction::Par(Box::new(i1), Box::new(i2))
+}
+
+fn seq_of_emissions(num_operands : usize) -> InteractionInternalRepresentation<MinimalLangCioII> {
+    let operands = (0..num_operands).map(|lf_id| emission(lf_id).to_io_repr(false,false)).collect();
+    InteractionInternalRepresentation::Operator(MinimalOperators::Seq, operands)
+}
+
+
+
+#[test]
+fn test_fold_strategies() {
+    let int_repr = seq_of_emissions(5);
+    let (a,b,c,d,e) = (emission(0),emission(1),emission(2),emission(3),emission(4));
+    // ***
+    let right = seq(a.clone(),seq(b.clone(),seq(c.clone(),seq(d.clone(),e.clone()))));
+    assert_eq!(MinimalInteraction::from_io_repr(&int_repr), right);
+    assert_eq!(MinimalInteraction::from_io_repr_with_fold_strategy(&int_repr, &AssociativeFoldStrategy::Right), right);
+    // ***
+    let left = seq(seq(seq(seq(a.clone(),b.clone()),c.clone()),d.clone()),e.clone());
+    assert_eq!(MinimalInteraction::from_io_repr_with_fold_strategy(&int_repr, &AssociativeFoldStrategy::Left), left);
+    // ***
+    let balanced = seq(seq(seq(a,b),seq(c,d)),e);
+    assert_eq!(MinimalInteraction::from_io_repr_with_fold_strategy(&int_repr, &AssociativeFoldStrategy::Balanced), balanced);
+}
+
+
+#[test]
+fn test_restore_original_nestings() {
+    let (a,b,c,d,e) = (emission(0),emission(1),emission(2),emission(3),emission(4));
+    let term = par(
+        seq(seq(a.clone(),b.clone()),seq(c.clone(),seq(d.clone(),e.clone()))),
+        seq(seq(a,b),c)
+    );
+    let (int_repr,nestings) = term.to_io_repr_recording_nestings(false, true);
+    let expected_nestings : AssociativeNestings = vec![
+        (
+            vec![0],
+            vec![
+                AssociativeNestingItem::Application(2),
+                AssociativeNestingItem::Application(2),
+                AssociativeNestingItem::Operand,
+                AssociativeNestingItem::Operand,
+                AssociativeNestingItem::Application(2),
+                AssociativeNestingItem::Operand,
+                AssociativeNestingItem::Application(2),
+                AssociativeNestingItem::Operand,
+                AssociativeNestingItem::Operand
+            ]
+        ),
+        (
+            vec![1],
+            vec![
+                AssociativeNestingItem::Application(2),
+                AssociativeNestingItem::Application(2),
+                AssociativeNestingItem::Operand,
+                AssociativeNestingItem::Operand,
+                AssociativeNestingItem::Operand
+            ]
+        )
+    ].into_iter().collect();
+    assert_eq!(nestings, expected_nestings);
+    // the operands are flattened in the internal representation
+    assert_eq!(format!("{:?}",int_repr), format!("{:?}",term.to_io_repr(false, true)));
+    let restored = MinimalInteraction::from_io_repr_restoring_nestings(&int_repr, &nestings, &AssociativeFoldStrategy::Left);
+    assert_eq!(restored, term);
+}
+
+
+#[test]
+fn test_nesting_not_recorded_when_patterns_are_merged() {
+    // the emission and the reception are merged into a single pattern
+    let term = strict(strict(emission(0),reception(1)),emission(2));
+    let (int_repr,nestings) = term.to_io_repr_recording_nestings(true, true);
+    assert!(nestings.is_empty());
+    match &int_repr {
+        InteractionInternalRepresentation::Operator(MinimalOperators::Strict, operands) => {
+            assert_eq!(operands.len(), 2);
+        },
+        _ => {
+            panic!("expected strict at the root");
+        }
+    }
+    // without merging, the nesting is recorded and restored
+    let (int_repr,nestings) = term.to_io_repr_recording_nestings(false, true);
+    assert_eq!(nestings.len(), 1);
+    let restored = MinimalInteraction::from_io_repr_restoring_nestings(&int_repr, &nestings, &AssociativeFoldStrategy::Right);
+    assert_eq!(restored, term);
+}
+
+
+#[test]
+fn test_nesting_which_does_not_fit_is_ignored() {
+    let int_repr = seq_of_emissions(3);
+    let nestings : AssociativeNestings = vec![
+        (
+            vec![],
+            vec![
+                AssociativeNestingItem::Application(2),
+                AssociativeNestingItem::Application(2),
+                AssociativeNestingItem::Operand,
+                AssociativeNestingItem::Operand,
+                AssociativeNestingItem::Application(2),
+                AssociativeNestingItem::Operand,
+                AssociativeNestingItem::Operand
+            ]
+        )
+    ].into_iter().collect();
+    // the nesting describes 4 operands instead of 3 so the operands are folded with the given strategy
+    let restored = MinimalInteraction::from_io_repr_restoring_nestings(&int_repr, &nestings, &AssociativeFoldStrategy::Left);
+    assert_eq!(restored, seq(seq(emission(0),emission(1)),emission(2)));
+}
@@ -0,0 +1,91 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::GeneralContext;
+use crate::tests::lang_minimal::to_image::drawing_context::MinimalDrawingContext;
+use crate::from_text::parse::parse_interaction;
+use crate::to_image::interface::get_interaction_layout;
+use crate::to_image::layout::model::*;
+
+
+
+
+fn get_layout(input_text : &str) -> DiagramLayout<usize> {
+    let ctx = GeneralContext{lf_names:vec!["a".to_string(),"b".to_string()],ms_names:vec!["m".to_string(),"n".to_string()]};
+    let internal_repr = parse_interaction::<MinimalLangCioII,GeneralContext>(
+        input_text,&ctx
+    ).unwrap();
+    let drawing_context = MinimalDrawingContext::new(ctx);
+    get_interaction_layout::<MinimalLangCioII,usize,MinimalDrawingContext,MinimalDrawingContext>(
+        &internal_repr,
+        &drawing_context,
+        &drawing_context
+    ).unwrap()
+}
+
+
+#[test]
+fn test_layout_1() {
+    let layout = get_layout("alt(a -- m -> b, b -- n -> a)");
+    // lifelines from left to right
+    assert_eq!(layout.lifelines.iter().map(|l| l.lifeline).collect::<Vec<usize>>(), vec![0,1]);
+    let (a_x, b_x) = (layout.lifelines[0].x_pos, layout.lifelines[1].x_pos);
+    assert!(a_x < b_x);
+    for lf in &layout.lifelines {
+        assert!(lf.header.y_top + lf.header.height <= lf.y_top + 0.01);
+        assert!(lf.y_top < lf.y_bottom && lf.y_bottom <= layout.height);
+    }
+    // message passings
+    assert_eq!(layout.leaf_patterns.len(), 2);
+    let first = &layout.leaf_patterns[0];
+    assert!(first.y_top < first.midline_y && first.midline_y < first.y_bottom);
+    match &first.origin {
+        LeafPatternOriginLayout::Lifeline { lifeline, x_pos, .. } => {
+            assert_eq!((*lifeline,*x_pos), (0,a_x));
+        },
+        _ => panic!("expected a lifeline origin")
+    }
+    assert_eq!(first.lifeline_targets.len(), 1);
+    assert_eq!((first.lifeline_targets[0].lifeline,first.lifeline_targets[0].x_pos), (1,b_x));
+    assert_eq!(first.message.lines[0].segments[0].text, "m");
+    assert!(layout.leaf_patterns[1].y_top >= first.y_bottom);
+    // the "alt" frame encloses both lifelines and both operands
+    assert_eq!(layout.operators.len(), 1);
+    let operator = &layout.operators[0];
+    assert_eq!(operator.separators_y.len(), 1);
+    assert!(operator.y_top <= first.y_top && layout.leaf_patterns[1].y_bottom <= operator.y_bottom);
+    match &operator.shape {
+        OperatorShapeLayout::Framed { x_left, x_right, label } => {
+            assert!(*x_left < a_x && b_x < *x_right);
+            assert_eq!(label.lines[0].segments[0].text, "alt");
+        },
+        _ => panic!("expected a frame")
+    }
+}
+
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_layout_json() {
+    let layout = get_layout("seq(a -- m -> b, b -- n -> a)");
+    let json = serde_json::to_string(&layout).unwrap();
+    assert!(json.contains("\"leaf_patterns\""));
+    let deserialized : DiagramLayout<usize> = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, layout);
+}
@@ -0,0 +1,146 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use image::Rgb;
+use image_colored_text::text::line::ColoredTextLine;
+use image_colored_text::text::paragraph::{ColoredTextParagraph, MultiLineTextAlignment};
+
+use crate::tests::lang_minimal::to_image::drawing_context::{MinimalDrawingContext, MY_COLOR_BLACK};
+use crate::tests::lang_minimal::tests::tool_test_draw::{tool_get_drawing_context, tool_get_layout};
+#[cfg(feature = "to_svg")]
+use crate::tests::lang_minimal::tests::tool_test_draw::tool_get_svg;
+use crate::to_image::drawable::group::DrawableLifelineGroup;
+use crate::to_image::error::DrawError;
+use crate::to_image::layout::model::DiagramLayout;
+
+
+
+const MY_COLOR_GROUP : [u8;3] = [130u8, 22u8, 22u8];
+
+
+fn make_group(title : &str, lifelines : Vec<usize>, sub_groups : Vec<DrawableLifelineGroup<usize>>) -> DrawableLifelineGroup<usize> {
+    let title = ColoredTextParagraph::new(
+        vec![ColoredTextLine::new(vec![(title.to_owned(),Rgb(MY_COLOR_BLACK))])], 
+        MultiLineTextAlignment::Left,
+        None,
+        None
+    );
+    DrawableLifelineGroup::new(title, Rgb(MY_COLOR_GROUP), lifelines, sub_groups)
+}
+
+
+fn get_drawing_context(lifeline_groups : Vec<DrawableLifelineGroup<usize>>) -> MinimalDrawingContext {
+    tool_get_drawing_context(
+        &["a","b","c","d"], 
+        &["m"], 
+        |drawing_context| drawing_context.lifeline_groups = lifeline_groups
+    )
+}
+
+
+fn get_layout(input_text : &str, lifeline_groups : Vec<DrawableLifelineGroup<usize>>) -> Result<DiagramLayout<usize>,DrawError> {
+    tool_get_layout(input_text, &get_drawing_context(lifeline_groups))
+}
+
+
+
+#[test]
+fn test_lifeline_group_box() {
+    let input_text = "seq(a -- m -> b, b -- m -> c, c -- m -> d)";
+    let ungrouped = get_layout(input_text, vec![]).unwrap();
+    assert!(ungrouped.lifeline_groups.is_empty());
+    // the lifelines of the group are drawn next to each other
+    let layout = get_layout(input_text, vec![make_group("component", vec![3,1], vec![])]).unwrap();
+    assert_eq!(layout.lifelines.iter().map(|l| l.lifeline).collect::<Vec<usize>>(), vec![0,1,3,2]);
+    assert_eq!(layout.lifeline_groups.len(), 1);
+    let group = &layout.lifeline_groups[0];
+    assert_eq!(group.lifelines, vec![1,3]);
+    assert_eq!(group.color, MY_COLOR_GROUP);
+    assert_eq!(group.title.lines[0].segments[0].text, "component");
+    // the box encloses its lifelines only
+    for lf in &layout.lifelines {
+        let is_enclosed = group.x_left < lf.header.x_left && lf.header.x_left + lf.header.width < group.x_right;
+        assert_eq!(is_enclosed, group.lifelines.contains(&lf.lifeline));
+    }
+    // the title is drawn above the headers and the box runs down the full diagram height
+    for lf in &layout.lifelines {
+        assert!(group.y_top < group.title.y_top && group.title.y_top + group.title.height <= lf.header.y_top + 0.01);
+        assert!(lf.y_bottom < group.y_bottom && group.y_bottom <= layout.height);
+    }
+    assert!(layout.height > ungrouped.height);
+    assert!(layout.width > ungrouped.width);
+}
+
+
+#[test]
+fn test_nested_lifeline_groups() {
+    let input_text = "seq(a -- m -> b, b -- m -> c, c -- m -> d)";
+    let groups = vec![
+        make_group("system", vec![0], vec![make_group("subsystem", vec![2,3], vec![])]),
+        // groups which lifelines do not occur in the diagram are not drawn
+        make_group("unused", vec![5], vec![])
+    ];
+    let layout = get_layout(input_text, groups).unwrap();
+    assert_eq!(layout.lifelines.iter().map(|l| l.lifeline).collect::<Vec<usize>>(), vec![0,2,3,1]);
+    assert_eq!(layout.lifeline_groups.len(), 2);
+    let (outer, inner) = (&layout.lifeline_groups[0], &layout.lifeline_groups[1]);
+    assert_eq!(outer.lifelines, vec![0,2,3]);
+    assert_eq!(inner.lifelines, vec![2,3]);
+    assert!(outer.x_left < inner.x_left && inner.x_right < outer.x_right);
+    assert!(outer.y_top < inner.y_top && inner.y_bottom < outer.y_bottom);
+    // the title of the outer group is above that of the inner group
+    assert!(outer.title.y_top + outer.title.height <= inner.y_top + 0.01);
+}
+
+
+#[test]
+fn test_lifeline_in_several_groups() {
+    let groups = vec![make_group("first", vec![0,1], vec![]), make_group("second", vec![1], vec![])];
+    match get_layout("a -- m -> b", groups) {
+        Err(DrawError::InconsistentPattern(_)) => {},
+        _ => panic!("expected an inconsistent pattern error")
+    }
+}
+
+
+#[test]
+fn test_pinned_lifelines_in_groups() {
+    let input_text = "seq(a -- m -> b, b -- m -> c, c -- m -> d)";
+    let get_order = |pinned_lifelines : Vec<(usize,usize)>| {
+        let mut drawing_context = get_drawing_context(vec![make_group("component", vec![0,2], vec![])]);
+        drawing_context.pinned_lifelines = pinned_lifelines.into_iter().collect();
+        tool_get_layout(input_text, &drawing_context).map(|layout| layout.lifelines.iter().map(|l| l.lifeline).collect::<Vec<usize>>())
+    };
+    // pinned lifelines which the groups do not move are kept at their positions
+    assert_eq!(get_order(vec![(0,0),(3,3)]).unwrap(), vec![0,2,1,3]);
+    // grouping "a" with "c" would move "b" away from its position
+    match get_order(vec![(1,1)]) {
+        Err(DrawError::InconsistentLifelinePlacement(_)) => {},
+        _ => panic!("expected an inconsistent lifeline placement error")
+    }
+}
+
+
+#[cfg(feature = "to_svg")]
+#[test]
+fn test_draw_lifeline_groups_as_svg() {
+    let drawing_context = get_drawing_context(vec![make_group("component", vec![0,1], vec![])]);
+    let svg = tool_get_svg("a -- m -> b", &drawing_context);
+    assert!(svg.contains(r##"stroke="#821616""##));
+    assert!(svg.contains(">component</tspan>"));
+}
@@ -0,0 +1,87 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use crate::tests::lang_minimal::to_image::drawing_context::MinimalDrawingContext;
+use crate::tests::lang_minimal::tests::tool_test_draw::{tool_get_drawing_context, tool_get_layout};
+#[cfg(feature = "to_svg")]
+use crate::tests::lang_minimal::tests::tool_test_draw::tool_get_svg;
+use crate::to_image::error::DrawError;
+use crate::to_image::layout::model::DiagramLayout;
+
+
+
+fn get_drawing_context() -> MinimalDrawingContext {
+    tool_get_drawing_context(
+        &["client","session"], 
+        &["new","m","delete"], 
+        |drawing_context| {
+            drawing_context.creation_messages.insert(0);
+            drawing_context.destruction_messages.insert(2);
+        }
+    )
+}
+
+
+fn get_layout(input_text : &str) -> Result<DiagramLayout<usize>,DrawError> {
+    tool_get_layout(input_text, &get_drawing_context())
+}
+
+
+
+#[test]
+fn test_created_and_destroyed_lifeline() {
+    let layout = get_layout(
+        "seq(client -- new -> session, session -- m -> client, client -- delete -> session, client -- m -> |)"
+    ).unwrap();
+    let (client, session) = (&layout.lifelines[0], &layout.lifelines[1]);
+    let (creation, destruction) = (&layout.leaf_patterns[0], &layout.leaf_patterns[2]);
+    // the header of the created lifeline is drawn at the reception of the creating message
+    assert!(client.header.y_top + client.header.height <= creation.y_top);
+    assert!(session.header.y_top < creation.midline_y && creation.midline_y < session.header.y_top + session.header.height);
+    assert!((session.y_top - (session.header.y_top + session.header.height)).abs() < 0.01);
+    assert!(creation.lifeline_targets[0].creates_lifeline);
+    assert!(!destruction.lifeline_targets[0].creates_lifeline);
+    // the destroyed lifeline ends with a cross at the reception of the destroying message
+    assert_eq!(client.destruction_cross, None);
+    assert!(session.destruction_cross.is_some());
+    assert!((session.y_bottom - destruction.midline_y).abs() < 0.01);
+    assert!(session.y_bottom < client.y_bottom);
+    // the pattern which creates the lifeline is high enough for its header
+    assert!(creation.y_top <= session.header.y_top && session.header.y_top + session.header.height <= creation.y_bottom);
+}
+
+
+#[test]
+fn test_lifelines_without_creation_nor_destruction() {
+    // messages without lifeline targets neither create nor destroy lifelines
+    let layout = get_layout("seq(client -- new -> |, client -- m -> session, client -- delete -> |)").unwrap();
+    for lf in &layout.lifelines {
+        assert_eq!(lf.destruction_cross, None);
+        assert!((lf.header.y_top - layout.lifelines[0].header.y_top).abs() < 0.01);
+        assert!((lf.y_bottom - layout.lifelines[0].y_bottom).abs() < 0.01);
+    }
+    assert!(layout.leaf_patterns.iter().flat_map(|p| p.lifeline_targets.iter()).all(|t| !t.creates_lifeline));
+}
+
+
+#[cfg(feature = "to_svg")]
+#[test]
+fn test_draw_created_and_destroyed_lifeline_as_svg() {
+    let svg = tool_get_svg("seq(client -- new -> session, client -- delete -> session)", &get_drawing_context());
+    assert!(svg.contains(">session</tspan>"));
+}
@@ -0,0 +1,91 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use std::collections::HashMap;
+
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::GeneralContext;
+use crate::tests::lang_minimal::to_image::drawing_context::MinimalDrawingContext;
+use crate::from_text::parse::parse_interaction;
+use crate::to_image::extract::ordering::{LifelineOrderingCostWeights, LifelineOrderingStrategy};
+use crate::to_image::interface::get_interaction_layout;
+
+
+
+/** 
+ * Returns the lifelines of the diagram of the interaction from left to right.
+ * **/
+fn get_lifelines_order(
+        input_text : &str, 
+        strategy : LifelineOrderingStrategy, 
+        pinned_lifelines : Vec<(usize,usize)>
+    ) -> Vec<usize> {
+    let ctx = GeneralContext{
+        lf_names:vec!["a".to_string(),"b".to_string(),"c".to_string(),"d".to_string()],
+        ms_names:vec!["m".to_string()]
+    };
+    let internal_repr = parse_interaction::<MinimalLangCioII,GeneralContext>(
+        input_text,&ctx
+    ).unwrap();
+    let mut drawing_context = MinimalDrawingContext::new(ctx);
+    drawing_context.lifelines_ordering_strategy = strategy;
+    drawing_context.pinned_lifelines = pinned_lifelines.into_iter().collect::<HashMap<usize,usize>>();
+    let layout = get_interaction_layout::<MinimalLangCioII,usize,MinimalDrawingContext,MinimalDrawingContext>(
+        &internal_repr,
+        &drawing_context,
+        &drawing_context
+    ).unwrap();
+    layout.lifelines.iter().map(|l| l.lifeline).collect()
+}
+
+
+
+#[test]
+fn test_declaration_and_first_appearance_orders() {
+    let input_text = "seq(c -- m -> b, b -- m -> (a,d))";
+    assert_eq!(get_lifelines_order(input_text, LifelineOrderingStrategy::Declaration, vec![]), vec![0,1,2,3]);
+    assert_eq!(get_lifelines_order(input_text, LifelineOrderingStrategy::FirstAppearance, vec![]), vec![2,1,0,3]);
+}
+
+
+#[test]
+fn test_minimal_cost_order() {
+    let strategy = LifelineOrderingStrategy::MinimalCost(LifelineOrderingCostWeights::default());
+    // "a" exchanges with both "b" and "c" and is drawn between them
+    assert_eq!(
+        get_lifelines_order("seq(a -- m -> b, a -- m -> c, c -- m -> a, c -- m -> a)", strategy.clone(), vec![]), 
+        vec![1,0,2]
+    );
+    // "b" is not drawn within the frame of the "alt"
+    let order = get_lifelines_order("seq(a -- m -> b, alt(a -- m -> c, c -- m -> a))", strategy, vec![]);
+    assert_eq!(order[1], 0);
+}
+
+
+#[test]
+fn test_pinned_lifelines() {
+    let input_text = "seq(a -- m -> b, a -- m -> c, c -- m -> a, c -- m -> a)";
+    assert_eq!(get_lifelines_order(input_text, LifelineOrderingStrategy::Declaration, vec![(0,2)]), vec![1,2,0]);
+    // lifelines pinned beyond the rightmost position are drawn on the right
+    assert_eq!(get_lifelines_order(input_text, LifelineOrderingStrategy::Declaration, vec![(1,7)]), vec![0,2,1]);
+    // pinned lifelines which are not in the diagram are ignored
+    assert_eq!(get_lifelines_order(input_text, LifelineOrderingStrategy::FirstAppearance, vec![(3,0)]), vec![0,1,2]);
+    // the other lifelines are ordered around the pinned ones
+    let strategy = LifelineOrderingStrategy::MinimalCost(LifelineOrderingCostWeights::default());
+    assert_eq!(get_lifelines_order(input_text, strategy, vec![(2,0)]), vec![2,0,1]);
+}
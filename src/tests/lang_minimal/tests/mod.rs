@@ -16,5 +16,30 @@ limitations under the License.
 
 
 
+mod tool_test_draw;
+
 pub mod parse_fail;
-pub mod parse_and_convert;
\ No newline at end of file
+pub mod parse_recovery;
+pub mod parse_comments;
+pub mod parse_spans;
+pub mod parse_definitions;
+pub mod parse_includes;
+pub mod parse_signature;
+pub mod parse_infix;
+pub mod parse_operator_parameters;
+pub mod parse_annotations;
+pub mod parse_and_convert;
+pub mod deep_nesting;
+pub mod fold_strategies;
+pub mod conversion_fail;
+#[cfg(feature = "to_svg")]
+pub mod draw_svg;
+pub mod draw_canvas;
+pub mod draw_fail;
+pub mod draw_in_memory;
+pub mod layout;
+pub mod lifeline_ordering;
+pub mod lifeline_groups;
+pub mod lifeline_lifespan;
+pub mod activation_bars;
+pub mod arrow_styles;
@@ -18,3 +18,4 @@ limitations under the License.
 mod tool_test_parse_and_convert;
 mod tests1;
 mod tests2;
+mod tests3;
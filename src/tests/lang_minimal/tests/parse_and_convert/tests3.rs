@@ -0,0 +1,149 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use crate::conversion::repr_to_lang::FromInternalRepresentationToInteractionTerm;
+use crate::internal_representation::{CommonIoInteractionInterface, InteractionInternalRepresentation, InteractionOperatorRepresentation};
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::{GeneralContext, MinimalInteraction};
+use crate::from_text::error::ParseErrorKind;
+use crate::from_text::parse::parse_interaction_strict;
+
+
+
+fn get_context() -> GeneralContext {
+    GeneralContext{lf_names:vec!["a".to_string(),"b".to_string()],ms_names:vec!["m".to_string(),"n".to_string()]}
+}
+
+
+
+#[test]
+fn test_parse_operators_with_arity_ranges() {
+    let ctx = get_context();
+    for input_text in ["choice(a -- m -> b)", "choice(0, a -- m -> b, 0)"] {
+        assert!(parse_interaction_strict::<MinimalLangCioII,GeneralContext>(input_text,&ctx).is_ok());
+    }
+    // ***
+    let err = parse_interaction_strict::<MinimalLangCioII,GeneralContext>("seq(0, choice())",&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::ArityMismatch { operator: "choice".to_owned(), min_operands: 1, max_operands: None, got: 0 });
+    assert_eq!(err.offset, 7);
+    // ***
+    let err = parse_interaction_strict::<MinimalLangCioII,GeneralContext>("loop(0,0)",&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::ArityMismatch { operator: "loop".to_owned(), min_operands: 1, max_operands: Some(1), got: 2 });
+}
+
+
+#[test]
+fn test_convert_operators_with_arity_ranges() {
+    let ctx = get_context();
+    let int_repr = parse_interaction_strict::<MinimalLangCioII,GeneralContext>("choice(0, b -- m -> |, 0)",&ctx).unwrap();
+    let term = MinimalInteraction::from_io_repr(&int_repr);
+    let expected_term : String = r#"
+Alt(
+    Empty,
+    Alt(
+        Action(MinimalAction{lf_id:1,ms_id:0,kind:Emission}),
+        Empty
+    )
+)
+"#.chars().filter(|c| !c.is_whitespace()).collect();
+    assert_eq!(format!("{:?}", term).replace(' ', ""), expected_term);
+}
+
+
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+struct AtMostTernarySeq {}
+
+impl InteractionOperatorRepresentation for AtMostTernarySeq {
+
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn is_associative(&self) -> bool {
+        true
+    }
+
+    fn max_arity(&self) -> Option<usize> {
+        Some(3)
+    }
+}
+
+struct AtMostTernaryCioII {}
+
+impl CommonIoInteractionInterface for AtMostTernaryCioII {
+    type InteractionLeafPatternType = usize;
+    type InteractionOperatorType = AtMostTernarySeq;
+}
+
+#[derive(Clone)]
+enum AtMostTernaryTerm {
+    Leaf(usize),
+    Seq(Vec<AtMostTernaryTerm>)
+}
+
+impl AtMostTernaryTerm {
+
+    fn as_text(&self) -> String {
+        match self {
+            AtMostTernaryTerm::Leaf(x) => {
+                x.to_string()
+            },
+            AtMostTernaryTerm::Seq(sub_ints) => {
+                let sub_texts : Vec<String> = sub_ints.iter().map(|x| x.as_text()).collect();
+                format!("seq({})", sub_texts.join(","))
+            }
+        }
+    }
+
+}
+
+impl FromInternalRepresentationToInteractionTerm<AtMostTernaryCioII> for AtMostTernaryTerm {
+
+    fn get_empty_interaction() -> Self {
+        AtMostTernaryTerm::Leaf(0)
+    }
+
+    fn instantiate_interaction_under_operator(operator : &AtMostTernarySeq, sub_ints : &mut Vec<Self>) -> Option<Self> {
+        if sub_ints.len() >= operator.min_arity() && sub_ints.len() <= operator.max_arity().unwrap() {
+            Some(AtMostTernaryTerm::Seq(std::mem::take(sub_ints)))
+        } else {
+            None
+        }
+    }
+
+    fn transform_pattern_to_term(pattern : &usize) -> Self {
+        AtMostTernaryTerm::Leaf(*pattern)
+    }
+
+}
+
+
+#[test]
+fn test_fold_associative_operands_up_to_max_arity() {
+    for (num_operands,expected_term) in [
+        (3,"seq(1,2,3)"),
+        (4,"seq(1,2,seq(3,4))"),
+        (6,"seq(1,2,seq(3,4,seq(5,6)))")
+    ] {
+        let operands = (1..=num_operands).map(InteractionInternalRepresentation::LeafPattern).collect();
+        let int_repr = InteractionInternalRepresentation::<AtMostTernaryCioII>::Operator(AtMostTernarySeq{}, operands);
+        let term = AtMostTernaryTerm::from_io_repr(&int_repr);
+        assert_eq!(term.as_text(), expected_term);
+    }
+}
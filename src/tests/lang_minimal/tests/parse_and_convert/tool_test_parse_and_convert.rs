@@ -58,7 +58,7 @@ pub fn tool_test_verify_parsing_and_two_way_conversions(
     ) {
     // we parse the input text and verifify that the obtained internal representation is indeed the expected one
     let internal_repr = parse_interaction::<MinimalLangCioII,GeneralContext>(
-        input_text,&ctx
+        input_text,ctx
     ).unwrap();
     let got_internal_repr : String = format!("{:?}",internal_repr).chars().filter(|c| !c.is_whitespace()).collect();
     assert_eq!(expected_parsed_internal_repr, got_internal_repr);
@@ -0,0 +1,223 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use crate::conversion::lang_to_repr::FromInteractionTermToInternalRepresentation;
+use crate::conversion::repr_to_lang::FromInternalRepresentationToInteractionTerm;
+use crate::internal_representation::InteractionInternalRepresentation;
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::{GeneralContext, MinimalInteraction};
+use crate::tests::lang_minimal::to_image::drawing_context::MinimalDrawingContext;
+use crate::from_text::comments::parse_interaction_with_comments;
+use crate::from_text::error::ParseErrorKind;
+use crate::from_text::infix::parse_infix_interaction;
+use crate::from_text::parse::parse_interaction_strict;
+use crate::from_text::spans::parse_interaction_with_spans;
+use crate::to_image::interface::get_interaction_as_sequence_diagram_image;
+use crate::to_text::context_aware_printer::ContextAwareInteractionPrinter;
+use crate::to_text::print::{print_interaction, print_interaction_infix};
+use crate::syntax::InteractionLanguageSyntax;
+
+
+
+fn get_context() -> GeneralContext {
+    GeneralContext{lf_names:vec!["a".to_string(),"b".to_string()],ms_names:vec!["m".to_string()]}
+}
+
+
+fn annotated(
+        annotation : &str, 
+        int : InteractionInternalRepresentation<MinimalLangCioII>
+    ) -> InteractionInternalRepresentation<MinimalLangCioII> {
+    InteractionInternalRepresentation::Annotated(annotation.to_owned(), Box::new(int))
+}
+
+
+
+#[test]
+fn test_parse_annotations() {
+    let input_text = "@{ note } @{owner : {a}} seq(a -- m -> b, @{x} 0)";
+    let got = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(input_text,&get_context()).unwrap();
+    let expected = annotated(
+        "note",
+        annotated(
+            "owner : {a}",
+            InteractionInternalRepresentation::Operator(
+                MinimalOperators::Seq,
+                vec![
+                    InteractionInternalRepresentation::LeafPattern(
+                        MinimalLeafPattern::BROADCAST(MinimalBroadcastLeafPattern::new(Some(0), 0, vec![1]))
+                    ),
+                    annotated("x", InteractionInternalRepresentation::LeafPattern(MinimalLeafPattern::EMPTY))
+                ]
+            )
+        )
+    );
+    assert_eq!(format!("{:?}", got), format!("{:?}", expected));
+    // annotations are built outermost first
+    let built = InteractionInternalRepresentation::<MinimalLangCioII>::LeafPattern(MinimalLeafPattern::EMPTY)
+        .with_annotations(vec!["first".to_owned(),"second".to_owned()]);
+    assert_eq!(
+        format!("{:?}", built), 
+        format!("{:?}", annotated("first", annotated("second", InteractionInternalRepresentation::LeafPattern(MinimalLeafPattern::EMPTY))))
+    );
+}
+
+
+#[test]
+fn test_annotations_round_trip() {
+    let ctx = get_context();
+    let int_repr = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(
+        "@{root} alt(@{first} @{again} loop(a -- m -> b), @{x} 0)",
+        &ctx
+    ).unwrap();
+    let printed = print_interaction(&int_repr, &ctx);
+    assert!(printed.starts_with("@{root}\nalt("));
+    let reparsed = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(&printed,&ctx).unwrap();
+    assert_eq!(format!("{:?}", reparsed), format!("{:?}", int_repr));
+}
+
+
+#[test]
+fn test_infix_annotations() {
+    let ctx = get_context();
+    let int_repr = parse_infix_interaction::<MinimalLangCioII,GeneralContext>(
+        "@{n} (a -- m -> b ; 0) ; @{x} (0 + 0)",
+        &ctx
+    ).unwrap();
+    let expected = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(
+        "seq(@{n} seq(a -- m -> b, 0), @{x} alt(0,0))",
+        &ctx
+    ).unwrap();
+    assert_eq!(format!("{:?}", int_repr), format!("{:?}", expected));
+    let printed = print_interaction_infix(&int_repr, &ctx);
+    assert_eq!(printed, "@{n} (a -- m -> b ; 0) ; @{x} (0 + 0)");
+    let reparsed = parse_infix_interaction::<MinimalLangCioII,GeneralContext>(&printed,&ctx).unwrap();
+    assert_eq!(format!("{:?}", reparsed), format!("{:?}", int_repr));
+}
+
+
+#[test]
+fn test_annotations_in_spans_and_comments() {
+    let ctx = get_context();
+    let input_text = "seq(/* before */ @{x} /* after */ a -- m -> b, 0)";
+    let (_,spans) = parse_interaction_with_spans::<MinimalLangCioII,GeneralContext>(input_text,&ctx).unwrap();
+    assert_eq!(spans.get(&vec![0]).unwrap().get_text(input_text), "@{x} /* after */ a -- m -> b");
+    assert_eq!(spans.get(&vec![0,0]).unwrap().get_text(input_text), "a -- m -> b");
+    let (_,comments) = parse_interaction_with_comments::<MinimalLangCioII,GeneralContext>(input_text,&ctx).unwrap();
+    assert_eq!(comments.leading.get(&vec![0]).unwrap(), &vec!["/* before */".to_owned()]);
+    assert_eq!(comments.leading.get(&vec![0,0]).unwrap(), &vec!["/* after */".to_owned()]);
+}
+
+
+#[test]
+fn test_invalid_annotations() {
+    let ctx = get_context();
+    // empty annotations are not accepted by the minimal language
+    let input_text = "seq(a -- m -> b, @{  } 0)";
+    let err = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(input_text,&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidAnnotation { annotation: "@{  }".to_owned() });
+    assert_eq!(err.offset, 17);
+    assert!(err.to_string().contains("invalid annotation '@{  }'"));
+    // an unterminated annotation extends to the end of the input
+    let err = parse_interaction_strict::<MinimalLangCioII,GeneralContext>("@{x} seq(a -- m -> b, @{y 0)",&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::Lexical);
+}
+
+
+#[test]
+fn test_convert_and_draw_annotated_interaction() {
+    let ctx = get_context();
+    let annotated_repr = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(
+        "@{root} seq(a -- m -> b, @{x} loop(b -- m -> a))",
+        &ctx
+    ).unwrap();
+    let plain_repr = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(
+        "seq(a -- m -> b, loop(b -- m -> a))",
+        &ctx
+    ).unwrap();
+    // the minimal language has no place for annotations in its terms
+    let term = MinimalInteraction::from_io_repr(&annotated_repr);
+    assert_eq!(term, MinimalInteraction::from_io_repr(&plain_repr));
+    assert_eq!(format!("{:?}", term.to_io_repr(true, true)), format!("{:?}", plain_repr));
+    // annotations are drawn as frames
+    let drawing_context = MinimalDrawingContext::new(ctx);
+    let annotated_image = get_interaction_as_sequence_diagram_image::<MinimalLangCioII,usize,MinimalDrawingContext,MinimalDrawingContext>(
+        &annotated_repr,
+        &drawing_context,
+        &drawing_context
+    ).unwrap();
+    let plain_image = get_interaction_as_sequence_diagram_image::<MinimalLangCioII,usize,MinimalDrawingContext,MinimalDrawingContext>(
+        &plain_repr,
+        &drawing_context,
+        &drawing_context
+    ).unwrap();
+    assert!(annotated_image.height() > plain_image.height());
+}
+
+
+// the printer of the minimal language, with the default printing of annotations
+struct PrinterWithoutAnnotations(GeneralContext);
+
+impl ContextAwareInteractionPrinter<MinimalLangCioII> for PrinterWithoutAnnotations {
+
+    fn left_parenthesis(&self) -> &str {
+        self.0.left_parenthesis()
+    }
+
+    fn right_parenthesis(&self) -> &str {
+        self.0.right_parenthesis()
+    }
+
+    fn operand_separator(&self) -> &str {
+        self.0.operand_separator()
+    }
+
+    fn syntax(&self) -> &dyn InteractionLanguageSyntax {
+        &self.0
+    }
+
+    fn print_operator(
+        &self, 
+        operator : &MinimalOperators,
+        sub_ints : &[InteractionInternalRepresentation<MinimalLangCioII>]
+    ) -> String {
+        self.0.print_operator(operator, sub_ints)
+    }
+
+    fn print_explicit_pattern(&self, leaf_pattern : &MinimalLeafPattern) -> String {
+        self.0.print_explicit_pattern(leaf_pattern)
+    }
+
+}
+
+#[test]
+fn test_annotations_not_printed_by_default() {
+    let ctx = get_context();
+    let int_repr = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(
+        "@{root} alt(@{first} @{again} loop(a -- m -> b), @{x} 0)",
+        &ctx
+    ).unwrap();
+    let expected = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(
+        "alt(loop(a -- m -> b), 0)",
+        &ctx
+    ).unwrap();
+    assert_eq!(
+        print_interaction(&int_repr, &PrinterWithoutAnnotations(get_context())), 
+        print_interaction(&expected, &ctx)
+    );
+}
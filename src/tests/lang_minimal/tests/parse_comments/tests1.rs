@@ -0,0 +1,111 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::GeneralContext;
+use crate::from_text::comments::parse_interaction_with_comments;
+use crate::from_text::error::ParseErrorKind;
+use crate::from_text::parse::parse_interaction_strict;
+use crate::to_text::print::print_interaction_with_comments;
+
+
+
+fn get_context() -> GeneralContext {
+    GeneralContext{lf_names:vec!["a".to_string(),"b".to_string(),"c".to_string()],ms_names:vec!["m".to_string(),"n".to_string(),"p".to_string()]}
+}
+
+
+
+#[test]
+fn test_comments_as_whitespace() {
+    let with_comments = 
+r#"// the whole interaction
+seq( /* first */
+        a /* origin */ -- m /* a
+        multiline comment */ -> ( b , // first target
+            c ),
+        // an alternative
+        alt(b -- n -> a,0/* empty */)
+) // trailing comment
+/* another one */"#;
+    let without_comments = "seq(a -- m -> (b,c), alt(b -- n -> a, 0))";
+    let ctx = get_context();
+    let got = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(with_comments,&ctx).unwrap();
+    let expected = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(without_comments,&ctx).unwrap();
+    assert_eq!(format!("{:?}", got), format!("{:?}", expected));
+}
+
+
+#[test]
+fn test_unterminated_comment() {
+    let input_text = "seq(a -- m -> b, /* unterminated 0)";
+    let err = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(input_text,&get_context()).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::Lexical);
+    assert_eq!(err.offset, input_text.len());
+    assert_eq!(err.expected, vec!["the end of the comment".to_owned()]);
+}
+
+
+#[test]
+fn test_comments_round_trip() {
+    let input_text = 
+r#"// the whole interaction
+seq(
+        a -- m -> b,
+        /* the alternative */
+        alt(
+                // first branch
+                b -- n -> a,
+                0 // empty
+        )
+)
+// at the end"#;
+    let ctx = get_context();
+    let (int_repr,comments) = parse_interaction_with_comments::<MinimalLangCioII,GeneralContext>(input_text,&ctx).unwrap();
+    assert_eq!(comments.leading.get(&vec![]).unwrap(), &vec!["// the whole interaction".to_owned()]);
+    assert_eq!(comments.leading.get(&vec![1]).unwrap(), &vec!["/* the alternative */".to_owned()]);
+    assert_eq!(comments.leading.get(&vec![1,0]).unwrap(), &vec!["// first branch".to_owned()]);
+    // the comment before the right parenthesis follows the last operand
+    assert_eq!(comments.trailing.get(&vec![1,1]).unwrap(), &vec!["// empty".to_owned()]);
+    // and the comment at the end of the input follows the root
+    assert_eq!(comments.trailing.get(&vec![]).unwrap(), &vec!["// at the end".to_owned()]);
+    // ***
+    let printed = print_interaction_with_comments(&int_repr, &comments, &ctx);
+    assert!(printed.starts_with("// the whole interaction\nseq("));
+    assert!(printed.contains("\t/* the alternative */\n\talt("));
+    assert!(printed.contains("\t\t0\n\t\t// empty\n\t)"));
+    assert!(printed.ends_with(")\n// at the end"));
+    let (reparsed,recomments) = parse_interaction_with_comments::<MinimalLangCioII,GeneralContext>(&printed,&ctx).unwrap();
+    assert_eq!(format!("{:?}", reparsed), format!("{:?}", int_repr));
+    assert_eq!(recomments, comments);
+}
+
+
+#[test]
+fn test_trailing_comments_of_operands_before_separators() {
+    let input_text = "seq(a -- m -> b, alt(b -- n -> a, 0 /* empty */))";
+    let ctx = get_context();
+    let (int_repr,mut comments) = parse_interaction_with_comments::<MinimalLangCioII,GeneralContext>(input_text,&ctx).unwrap();
+    assert_eq!(comments.trailing.get(&vec![1,1]).unwrap(), &vec!["/* empty */".to_owned()]);
+    // a trailing line comment on an operand which is not the last one is printed after the separator
+    comments.trailing.insert(vec![0], vec!["// after the first".to_owned()]);
+    let printed = print_interaction_with_comments(&int_repr, &comments, &ctx);
+    assert!(printed.contains("\ta -- m -> b,\n\t// after the first\n"));
+    let reparsed = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(&printed,&ctx).unwrap();
+    assert_eq!(format!("{:?}", reparsed), format!("{:?}", int_repr));
+}
@@ -0,0 +1,172 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::GeneralContext;
+use crate::from_text::definitions::parse_interaction_with_definitions;
+use crate::from_text::error::ParseErrorKind;
+use crate::from_text::parse::parse_interaction_strict;
+use crate::to_text::print::print_interaction_with_definitions;
+
+
+
+fn get_context() -> GeneralContext {
+    GeneralContext{
+        lf_names:vec!["a".to_string(),"b".to_string(),"c".to_string()],
+        ms_names:vec!["req".to_string(),"ack".to_string(),"m".to_string()]
+    }
+}
+
+
+
+#[test]
+fn test_definitions_expansion() {
+    let with_definitions = 
+r#"// a handshake between two lifelines
+let handshake(x,y) = seq(x -- req -> y, y -- ack -> x);
+let ping = a -- m -> (b,c);
+let twice(i) = seq(i, i);
+par(
+    handshake(a,b),
+    twice(ping),
+    loop(handshake(b, c))
+)"#;
+    let expanded = 
+r#"par(
+    seq(a -- req -> b, b -- ack -> a),
+    seq(a -- m -> (b,c), a -- m -> (b,c)),
+    loop(seq(b -- req -> c, c -- ack -> b))
+)"#;
+    let ctx = get_context();
+    let got = parse_interaction_with_definitions::<MinimalLangCioII,GeneralContext>(with_definitions,&ctx).unwrap();
+    let expected = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(expanded,&ctx).unwrap();
+    assert_eq!(format!("{:?}", got), format!("{:?}", expected));
+}
+
+
+#[test]
+fn test_definitions_errors() {
+    let ctx = get_context();
+    // cycle
+    let input_text = "let f = seq(a -- m -> b, g);\nlet g = alt(f, 0);\nf";
+    let err = parse_interaction_with_definitions::<MinimalLangCioII,GeneralContext>(input_text,&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::CyclicDefinition { cycle: vec!["f".to_owned(),"g".to_owned(),"f".to_owned()] });
+    assert_eq!((err.line,err.column), (1,5));
+    // wrong number of arguments
+    let input_text = "let f(x,y) = x -- m -> y;\nseq(f(a,b),f(a))";
+    let err = parse_interaction_with_definitions::<MinimalLangCioII,GeneralContext>(input_text,&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::DefinitionArityMismatch { name: "f".to_owned(), expected: 2, got: 1 });
+    assert_eq!((err.line,err.column), (2,12));
+    // duplicate
+    let input_text = "let f = 0;\nlet f = 0;\nf";
+    let err = parse_interaction_with_definitions::<MinimalLangCioII,GeneralContext>(input_text,&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::DuplicateDefinition { name: "f".to_owned() });
+    // errors within the body of a definition are located in the definition
+    let input_text = "let f(x) = x -- zz -> b;\nseq(f(a),0)";
+    let err = parse_interaction_with_definitions::<MinimalLangCioII,GeneralContext>(input_text,&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::UnknownName { name: "zz".to_owned() });
+    assert_eq!((err.line,err.column), (1,17));
+    // missing terminator
+    let input_text = "let f = seq(a -- m -> b, 0)\nf";
+    let err = parse_interaction_with_definitions::<MinimalLangCioII,GeneralContext>(input_text,&ctx).unwrap_err();
+    assert_eq!(err.expected, vec!["';'".to_owned()]);
+}
+
+
+#[test]
+fn test_print_with_definitions() {
+    let input_text = 
+r#"par(
+    seq(a -- req -> b, b -- ack -> a),
+    alt(
+        seq(a -- req -> b, b -- ack -> a),
+        loop(seq(a -- m -> c, seq(a -- req -> b, b -- ack -> a)))
+    ),
+    loop(seq(a -- m -> c, seq(a -- req -> b, b -- ack -> a)))
+)"#;
+    let ctx = get_context();
+    let int_repr = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(input_text,&ctx).unwrap();
+    let printed = print_interaction_with_definitions(&int_repr, &ctx);
+    assert!(printed.starts_with("let def1 = seq(\n\ta -- req -> b,\n\tb -- ack -> a\n);\nlet def2 = loop(\n\tseq(\n\t\ta -- m -> c,\n\t\tdef1\n\t)\n);\n"));
+    let reparsed = parse_interaction_with_definitions::<MinimalLangCioII,GeneralContext>(&printed,&ctx).unwrap();
+    assert_eq!(format!("{:?}", reparsed), format!("{:?}", int_repr));
+}
+
+
+#[test]
+fn test_definitions_with_reserved_names() {
+    let ctx = get_context();
+    let input_text = "let loop = a -- m -> b;\nloop";
+    let err = parse_interaction_with_definitions::<MinimalLangCioII,GeneralContext>(input_text,&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::ReservedName { name: "loop".to_owned() });
+    assert_eq!((err.line,err.column), (1,5));
+    let input_text = "let f(seq) = seq;\nf(0)";
+    let err = parse_interaction_with_definitions::<MinimalLangCioII,GeneralContext>(input_text,&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::ReservedName { name: "seq".to_owned() });
+    // names which merely start with a keyword are not reserved
+    let input_text = "let sequence = a -- m -> b;\nseq(sequence,sequence)";
+    assert!(parse_interaction_with_definitions::<MinimalLangCioII,GeneralContext>(input_text,&ctx).is_ok());
+}
+
+
+#[test]
+fn test_definitions_expansion_too_large() {
+    let ctx = get_context();
+    // each definition refers twice to the previous one, so that the expansion doubles at each level
+    let mut input_text = "let d0 = a -- m -> b;\n".to_owned();
+    for index in 1..=30 {
+        input_text.push_str(&format!("let d{} = seq(d{},d{});\n", index, index - 1, index - 1));
+    }
+    input_text.push_str("d30");
+    let err = parse_interaction_with_definitions::<MinimalLangCioII,GeneralContext>(&input_text,&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::ExpansionTooLarge { max_length: 1 << 16 });
+}
+
+
+#[test]
+fn test_definitions_with_names_of_lifelines_and_messages() {
+    let ctx = get_context();
+    // names within patterns do not refer to definitions, which are only expanded where a sub-interaction may occur
+    let input_text = "let m = a -- req -> b;\nlet b(x) = seq(x, x);\nseq(m, a -- m -> b, b(m))";
+    let expanded = "seq(a -- req -> b, a -- m -> b, seq(a -- req -> b, a -- req -> b))";
+    let got = parse_interaction_with_definitions::<MinimalLangCioII,GeneralContext>(input_text,&ctx).unwrap();
+    let expected = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(expanded,&ctx).unwrap();
+    assert_eq!(format!("{:?}", got), format!("{:?}", expected));
+    // nor do they create cycles
+    let input_text = "let a = seq(a -- m -> b, 0);\na";
+    let got = parse_interaction_with_definitions::<MinimalLangCioII,GeneralContext>(input_text,&ctx).unwrap();
+    let expected = parse_interaction_strict::<MinimalLangCioII,GeneralContext>("seq(a -- m -> b, 0)",&ctx).unwrap();
+    assert_eq!(format!("{:?}", got), format!("{:?}", expected));
+}
+
+
+#[test]
+fn test_definitions_long_chain_of_references() {
+    let ctx = get_context();
+    // each definition refers to the previous one, which does not overflow the call stack
+    // (the bodies have no whitespace, which would be kept in the expansion)
+    let num_definitions = 100_000;
+    let mut input_text = "let d0 = a -- m -> b;\n".to_owned();
+    for index in 1..num_definitions {
+        input_text.push_str(&format!("let d{} =d{};\n", index, index - 1));
+    }
+    input_text.push_str(&format!("d{}", num_definitions - 1));
+    let got = parse_interaction_with_definitions::<MinimalLangCioII,GeneralContext>(&input_text,&ctx).unwrap();
+    let expected = parse_interaction_strict::<MinimalLangCioII,GeneralContext>("a -- m -> b",&ctx).unwrap();
+    assert_eq!(format!("{:?}", got), format!("{:?}", expected));
+}
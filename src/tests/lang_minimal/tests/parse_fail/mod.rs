@@ -17,3 +17,5 @@ limitations under the License.
 
 mod tool_test_parse_fail;
 mod tests1;
+mod tests2;
+mod tests3;
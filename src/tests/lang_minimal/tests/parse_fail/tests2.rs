@@ -0,0 +1,68 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::GeneralContext;
+use crate::from_text::error::ParseErrorKind;
+use crate::from_text::parse::{parse_interaction, parse_interaction_strict};
+
+
+
+
+
+
+
+#[test]
+fn test_fail_strict_trailing_input() {
+    let ctx = GeneralContext{lf_names:vec!["a".to_string(),"b".to_string()],ms_names:vec!["m".to_string(),"n".to_string()]};
+    let input_text = 
+r#"seq(
+        a -- m -> b,
+        0
+) 
+  garbage"#;
+    // the non-strict mode silently ignores the trailing input
+    assert!(parse_interaction::<MinimalLangCioII,GeneralContext>(input_text,&ctx).is_ok());
+    // ***
+    match parse_interaction_strict::<MinimalLangCioII,GeneralContext>(input_text,&ctx) {
+        Err(err) => {
+            assert_eq!(&input_text[err.offset..], "garbage");
+            assert_eq!((err.line,err.column), (5,3));
+            assert_eq!(err.kind, ParseErrorKind::TrailingInput { trailing: "garbage".to_owned() });
+        },
+        other => {
+            panic!("expected a trailing input error, got {:?}", other);
+        }
+    }
+}
+
+
+#[test]
+fn test_strict_trailing_whitespace() {
+    let ctx = GeneralContext{lf_names:vec!["a".to_string(),"b".to_string()],ms_names:vec!["m".to_string(),"n".to_string()]};
+    let input_text = "seq(a -- m -> b, 0)  \n\t \n";
+    assert!(parse_interaction_strict::<MinimalLangCioII,GeneralContext>(input_text,&ctx).is_ok());
+}
+
+
+#[test]
+fn test_strict_trailing_comments() {
+    let ctx = GeneralContext{lf_names:vec!["a".to_string(),"b".to_string()],ms_names:vec!["m".to_string(),"n".to_string()]};
+    let input_text = "seq(a -- m -> b, 0) // done\n/* nothing\nelse */  \n";
+    assert!(parse_interaction_strict::<MinimalLangCioII,GeneralContext>(input_text,&ctx).is_ok());
+}
@@ -0,0 +1,123 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::GeneralContext;
+use crate::from_text::error::{ParseError, ParseErrorKind};
+use crate::from_text::parse::parse_interaction;
+
+
+
+fn get_parse_error(input_text : &str) -> ParseError {
+    let ctx = GeneralContext{lf_names:vec!["a".to_string(),"b".to_string(),"c".to_string()],ms_names:vec!["m".to_string(),"n".to_string(),"p".to_string()]};
+    match parse_interaction::<MinimalLangCioII,GeneralContext>(input_text,&ctx) {
+        Err(err) => {
+            err
+        },
+        Ok(_) => {
+            panic!("expected a parse error for {}", input_text);
+        }
+    }
+}
+
+
+
+#[test]
+fn test_error_lexical() {
+    let input_text = 
+r#"seq(
+        a -- m -> b,
+        alt(
+                b -- m -> a;
+                0
+        )
+)"#;
+    let err = get_parse_error(input_text);
+    assert_eq!(err.kind, ParseErrorKind::Lexical);
+    assert_eq!(&input_text[err.offset..(err.offset+1)], ";");
+    assert_eq!((err.line,err.column), (4,28));
+    assert_eq!(err.expected, vec!["','".to_owned(), "')'".to_owned()]);
+    let context : Vec<(String,usize)> = err.context.iter().map(|f| (f.operator.clone(),f.operand_index)).collect();
+    assert_eq!(context, vec![("seq".to_owned(),1),("alt".to_owned(),0)]);
+    assert_eq!((err.context[1].line,err.context[1].column), (3,9));
+}
+
+
+#[test]
+fn test_error_unknown_name() {
+    let input_text = 
+r#"seq(
+        a -- m -> b,
+        par(
+                b -- m -> a,
+                b -- zz -> (a,c)
+        )
+)"#;
+    let err = get_parse_error(input_text);
+    assert_eq!(err.kind, ParseErrorKind::UnknownName { name: "zz".to_owned() });
+    assert_eq!((err.line,err.column), (5,22));
+    let context : Vec<(String,usize)> = err.context.iter().map(|f| (f.operator.clone(),f.operand_index)).collect();
+    assert_eq!(context, vec![("seq".to_owned(),1),("par".to_owned(),1)]);
+}
+
+
+#[test]
+fn test_error_unknown_lifeline_in_targets() {
+    let input_text = "strict(a -- m -> (b,d), 0)";
+    let err = get_parse_error(input_text);
+    assert_eq!(err.kind, ParseErrorKind::UnknownName { name: "d".to_owned() });
+    assert_eq!(err.offset, 20);
+    // the list of targets is not an operator
+    let context : Vec<(String,usize)> = err.context.iter().map(|f| (f.operator.clone(),f.operand_index)).collect();
+    assert_eq!(context, vec![("strict".to_owned(),0)]);
+}
+
+
+#[test]
+fn test_error_arity() {
+    let input_text = 
+r#"seq(
+        a -- m -> b,
+        a -- n -> c,
+        loop(
+            a -- p -> (b,c),
+            b -- m -> c
+        )
+)"#;
+    let err = get_parse_error(input_text);
+    assert_eq!(
+        err.kind, 
+        ParseErrorKind::ArityMismatch { operator: "loop".to_owned(), min_operands: 1, max_operands: Some(1), got: 2 }
+    );
+    assert_eq!((err.line,err.column), (4,9));
+    let context : Vec<(String,usize)> = err.context.iter().map(|f| (f.operator.clone(),f.operand_index)).collect();
+    assert_eq!(context, vec![("seq".to_owned(),2)]);
+    assert!(err.to_string().contains("operator 'loop' expects 1 operands but got 2"));
+}
+
+
+#[test]
+fn test_error_arity_associative() {
+    let err = get_parse_error("alt(a -- m -> b)");
+    assert_eq!(
+        err.kind, 
+        ParseErrorKind::ArityMismatch { operator: "alt".to_owned(), min_operands: 2, max_operands: None, got: 1 }
+    );
+    assert_eq!(err.offset, 0);
+    assert!(err.context.is_empty());
+}
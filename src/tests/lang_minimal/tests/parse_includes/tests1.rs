@@ -0,0 +1,111 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use std::collections::HashMap;
+
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::GeneralContext;
+use crate::from_text::error::ParseErrorKind;
+use crate::from_text::includes::{parse_interaction_with_includes, InteractionSourceResolver};
+use crate::from_text::parse::parse_interaction_strict;
+
+
+
+struct InMemoryResolver {
+    files : HashMap<&'static str,&'static str>
+}
+
+impl InteractionSourceResolver for InMemoryResolver {
+    fn resolve(&self, path : &str, _including_source : Option<&str>) -> Result<(String,String),String> {
+        match self.files.get(path) {
+            Some(text) => {
+                Ok((path.to_owned(),text.to_string()))
+            },
+            None => {
+                Err("no such file".to_owned())
+            }
+        }
+    }
+}
+
+
+
+fn get_context() -> GeneralContext {
+    GeneralContext{
+        lf_names:vec!["a".to_string(),"b".to_string(),"c".to_string()],
+        ms_names:vec!["req".to_string(),"ack".to_string(),"m".to_string()]
+    }
+}
+
+
+
+#[test]
+fn test_includes() {
+    let resolver = InMemoryResolver{
+        files:HashMap::from(
+            [
+                ("main.txt", "include \"handshake.txt\";\ninclude \"ping.txt\";\nseq(handshake(a,b), ping)"),
+                ("handshake.txt", "include \"common.txt\";\nlet handshake(x,y) = seq(x -- req -> y, y -- ack -> x);"),
+                ("ping.txt", "include \"common.txt\"; // included twice\nlet ping = alt(a -- m -> c, nothing);"),
+                ("common.txt", "let nothing = 0;")
+            ]
+        )
+    };
+    let ctx = get_context();
+    let got = parse_interaction_with_includes::<MinimalLangCioII,GeneralContext,_>("main.txt",&ctx,&resolver).unwrap();
+    let expected = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(
+        "seq(seq(a -- req -> b, b -- ack -> a), alt(a -- m -> c, 0))",
+        &ctx
+    ).unwrap();
+    assert_eq!(format!("{:?}", got), format!("{:?}", expected));
+}
+
+
+#[test]
+fn test_includes_errors() {
+    let resolver = InMemoryResolver{
+        files:HashMap::from(
+            [
+                ("cycle1.txt", "include \"cycle2.txt\";\n0"),
+                ("cycle2.txt", "include \"cycle1.txt\";"),
+                ("missing.txt", "include \"nowhere.txt\";\n0"),
+                ("wrong.txt", "include \"defs.txt\";\nseq(f,0)"),
+                ("defs.txt", "// definitions\nlet f = a -- zz -> b;")
+            ]
+        )
+    };
+    let ctx = get_context();
+    // ***
+    let err = parse_interaction_with_includes::<MinimalLangCioII,GeneralContext,_>("cycle1.txt",&ctx,&resolver).unwrap_err();
+    assert_eq!(
+        err.kind, 
+        ParseErrorKind::CyclicInclude { cycle: vec!["cycle1.txt".to_owned(),"cycle2.txt".to_owned(),"cycle1.txt".to_owned()] }
+    );
+    assert_eq!(err.source, Some("cycle2.txt".to_owned()));
+    // ***
+    let err = parse_interaction_with_includes::<MinimalLangCioII,GeneralContext,_>("missing.txt",&ctx,&resolver).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::UnresolvedInclude { path: "nowhere.txt".to_owned(), reason: "no such file".to_owned() });
+    assert_eq!(err.source, Some("missing.txt".to_owned()));
+    // ***
+    let err = parse_interaction_with_includes::<MinimalLangCioII,GeneralContext,_>("wrong.txt",&ctx,&resolver).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::UnknownName { name: "zz".to_owned() });
+    assert_eq!(err.source, Some("defs.txt".to_owned()));
+    assert_eq!((err.line,err.column), (2,14));
+    assert_eq!(err.context[0].source, Some("wrong.txt".to_owned()));
+    assert!(err.to_string().starts_with("in 'defs.txt' at line 2 column 14 : unknown name 'zz'"));
+}
@@ -0,0 +1,209 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use nom::IResult;
+
+use crate::internal_representation::{InfixAssociativity, InfixNotationTable, InteractionInternalRepresentation, OperatorNotation};
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::GeneralContext;
+use crate::from_text::context_aware_parser::ContextAwareInteractionParser;
+use crate::from_text::error::{InteractionParseError, ParseErrorKind};
+use crate::from_text::infix::parse_infix_interaction;
+use crate::from_text::parse::parse_interaction_strict;
+use crate::from_text::util::delimited_lang_parser::DelimitedInteractionLanguageParser;
+use crate::syntax::InteractionLanguageSyntax;
+use crate::to_text::print::print_interaction_infix;
+
+
+
+fn get_context() -> GeneralContext {
+    GeneralContext{
+        lf_names:vec!["a".to_owned(),"b".to_owned()],
+        ms_names:vec!["m".to_owned(),"n".to_owned()]
+    }
+}
+
+
+fn assert_same_as_prefix_syntax(infix_text : &str, prefix_text : &str) {
+    let ctx = get_context();
+    let from_infix = parse_infix_interaction::<MinimalLangCioII,GeneralContext>(infix_text,&ctx).unwrap();
+    let from_prefix = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(prefix_text,&ctx).unwrap();
+    assert_eq!(format!("{:?}", from_infix), format!("{:?}", from_prefix));
+}
+
+
+
+#[test]
+fn test_infix_same_as_prefix_syntax() {
+    assert_same_as_prefix_syntax(
+        "a -- m -> b ; b -- n -> a ; (0 + a -- m -> |) || loop*(m -> a << n -> b)",
+        "par(seq(a -- m -> b, b -- n -> a, alt(0, a -- m -> |)), loop(strict(m -> a, n -> b)))"
+    );
+    // parentheses prevent flattening
+    assert_same_as_prefix_syntax(
+        "(a -- m -> b ; b -- n -> a) ; 0",
+        "seq(seq(a -- m -> b, b -- n -> a), 0)"
+    );
+    // operators can still be written in the usual form, with operands in the infix syntax
+    assert_same_as_prefix_syntax(
+        "seq(0 + 0, /* comment */ 0) || 0",
+        "par(seq(alt(0,0),0),0)"
+    );
+}
+
+
+#[test]
+fn test_infix_print_minimal_parentheses() {
+    let ctx = get_context();
+    let cases = [
+        ("alt(seq(0,0),0)", "0 ; 0 + 0"),
+        ("seq(alt(0,0),0)", "(0 + 0) ; 0"),
+        ("seq(0,0,0)", "0 ; 0 ; 0"),
+        ("seq(seq(0,0),0)", "(0 ; 0) ; 0"),
+        ("seq(0,seq(0,0))", "0 ; (0 ; 0)"),
+        ("loop(par(a -- m -> b, n -> a))", "loop*(a -- m -> b || n -> a)"),
+        ("strict(loop(0),alt(0,0))", "loop*(0) << (0 + 0)")
+    ];
+    for (prefix_text,expected) in cases {
+        let int_repr = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(prefix_text,&ctx).unwrap();
+        let printed = print_interaction_infix(&int_repr, &ctx);
+        assert_eq!(printed, expected);
+        let reparsed = parse_infix_interaction::<MinimalLangCioII,GeneralContext>(&printed,&ctx).unwrap();
+        assert_eq!(format!("{:?}", reparsed), format!("{:?}", int_repr));
+    }
+}
+
+
+#[test]
+fn test_infix_errors() {
+    let ctx = get_context();
+    let err = parse_infix_interaction::<MinimalLangCioII,GeneralContext>("0 ; ",&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::Lexical);
+    assert_eq!(err.offset, 4);
+    // ***
+    let err = parse_infix_interaction::<MinimalLangCioII,GeneralContext>("0 ; 0 )",&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::TrailingInput { trailing: ")".to_owned() });
+    // ***
+    let err = parse_infix_interaction::<MinimalLangCioII,GeneralContext>("0 + seq(0)",&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::ArityMismatch { operator: "seq".to_owned(), min_operands: 2, max_operands: None, got: 1 });
+    // ***
+    let err = parse_infix_interaction::<MinimalLangCioII,GeneralContext>("a -- m -> c",&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::UnknownName { name: "c".to_owned() });
+}
+
+
+#[test]
+fn test_infix_long_chains() {
+    let ctx = get_context();
+    let operands = 50_000;
+    // a chain of the same associative operator is flattened into a single application
+    let input_text = vec!["a -- m -> b"; operands].join(" ; ");
+    let got = parse_infix_interaction::<MinimalLangCioII,GeneralContext>(&input_text,&ctx).unwrap();
+    match &got {
+        InteractionInternalRepresentation::Operator(MinimalOperators::Seq, sub_ints) => {
+            assert_eq!(sub_ints.len(), operands);
+        },
+        _ => {
+            panic!("expected a flattened seq");
+        }
+    }
+    // right-associative operators are not nested through calls either
+    // and chains of operators do not count towards the maximal nesting depth
+    let ctx = RightAssociativeContext(get_context());
+    let input_text = vec!["a -- m -> b"; operands].join(" + ");
+    let got = parse_infix_interaction::<MinimalLangCioII,RightAssociativeContext>(&input_text,&ctx).unwrap();
+    let InteractionInternalRepresentation::Operator(MinimalOperators::Alt, sub_ints) = &got else {
+        panic!("expected a flattened alt");
+    };
+    assert_eq!(sub_ints.len(), operands);
+}
+
+
+fn make_nested_infix(depth : usize) -> String {
+    // groups, prefix operators and operator applications in turn
+    let openings = ["(\n", "loop*(\n", "loop(\n"];
+    let mut input_text = String::new();
+    for i in 0..depth {
+        input_text.push_str(openings[i % openings.len()]);
+    }
+    input_text.push_str("a -- m -> b");
+    input_text.push_str(&")".repeat(depth));
+    input_text
+}
+
+
+#[test]
+fn test_infix_deep_nesting() {
+    let ctx = get_context();
+    // as in the prefix syntax, the maximal nesting depth of the minimal language is 10000
+    parse_infix_interaction::<MinimalLangCioII,GeneralContext>(&make_nested_infix(10_000),&ctx).unwrap();
+    let err = parse_infix_interaction::<MinimalLangCioII,GeneralContext>(&make_nested_infix(10_001),&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::NestingTooDeep { max_depth: 10_000 });
+    assert_eq!((err.line,err.column), (10_001,1));
+}
+
+
+
+// the minimal language in which the alternative is right-associative
+struct RightAssociativeContext(GeneralContext);
+
+impl DelimitedInteractionLanguageParser for RightAssociativeContext {
+
+    fn left_parenthesis_char(&self) -> char {
+        self.0.left_parenthesis_char()
+    }
+
+    fn right_parenthesis_char(&self) -> char {
+        self.0.right_parenthesis_char()
+    }
+
+    fn separator_char(&self) -> char {
+        self.0.separator_char()
+    }
+
+    fn syntax(&self) -> &dyn InteractionLanguageSyntax {
+        &self.0
+    }
+
+    fn max_nesting_depth(&self) -> Option<usize> {
+        self.0.max_nesting_depth()
+    }
+
+}
+
+impl ContextAwareInteractionParser<MinimalLangCioII> for RightAssociativeContext {
+
+    fn parse_operator<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<&'a str, MinimalOperators, E> {
+        self.0.parse_operator(input)
+    }
+
+    fn parse_explicit_pattern<'a, E: InteractionParseError<'a>>(&self, input : &'a str) -> IResult<&'a str, MinimalLeafPattern, E> {
+        self.0.parse_explicit_pattern(input)
+    }
+
+}
+
+impl InfixNotationTable<MinimalLangCioII> for RightAssociativeContext {
+
+    fn get_operator_notations(&self) -> Vec<OperatorNotation<MinimalOperators>> {
+        self.0.get_operator_notations().into_iter()
+            .map(|notation| OperatorNotation{associativity:InfixAssociativity::Right,..notation})
+            .collect()
+    }
+
+}
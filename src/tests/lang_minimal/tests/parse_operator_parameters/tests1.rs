@@ -0,0 +1,100 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use crate::internal_representation::{InteractionInternalRepresentation, OperatorParameters};
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::GeneralContext;
+use crate::tests::lang_minimal::to_image::drawing_context::MinimalDrawingContext;
+use crate::from_text::error::ParseErrorKind;
+use crate::from_text::infix::parse_infix_interaction;
+use crate::from_text::parse::parse_interaction_strict;
+use crate::to_image::drawable::operator::builtin_operator::DrawableOperatorKind;
+use crate::to_image::extract::context_aware_extractor::ContextAwareInteractionDrawingInstructionsExtractor;
+use crate::to_text::print::{print_interaction, print_interaction_infix};
+
+
+
+fn get_context() -> GeneralContext {
+    GeneralContext{
+        lf_names:vec!["a".to_owned(),"b".to_owned()],
+        ms_names:vec!["m".to_owned(),"n".to_owned()]
+    }
+}
+
+
+
+#[test]
+fn test_operator_parameters_parse_and_print() {
+    let ctx = get_context();
+    let int_repr = parse_interaction_strict::<MinimalLangCioII,GeneralContext>("loop [ 2 , 5 ] (seq(a -- m -> b, loop[3](n -> a)))",&ctx).unwrap();
+    let InteractionInternalRepresentation::Operator(operator,_) = &int_repr else {
+        panic!("expected an operator at the root");
+    };
+    assert_eq!(
+        operator, 
+        &MinimalOperators::BoundedLoop(OperatorParameters::new('[', ']', vec!["2".to_owned(),"5".to_owned()]))
+    );
+    // ***
+    let printed = print_interaction(&int_repr, &ctx);
+    assert!(printed.starts_with("loop[2,5]("));
+    let reparsed = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(&printed,&ctx).unwrap();
+    assert_eq!(format!("{:?}", reparsed), format!("{:?}", int_repr));
+    // ***
+    let printed = print_interaction_infix(&int_repr, &ctx);
+    assert_eq!(printed, "loop[2,5](a -- m -> b ; loop[3](n -> a))");
+    let reparsed = parse_infix_interaction::<MinimalLangCioII,GeneralContext>(&printed,&ctx).unwrap();
+    assert_eq!(format!("{:?}", reparsed), format!("{:?}", int_repr));
+}
+
+
+#[test]
+fn test_operator_parameters_in_frame_label() {
+    let ctx = get_context();
+    let int_repr = parse_interaction_strict::<MinimalLangCioII,GeneralContext>("loop[2,5](a -- m -> b)",&ctx).unwrap();
+    let InteractionInternalRepresentation::Operator(operator,sub_ints) = &int_repr else {
+        panic!("expected an operator at the root");
+    };
+    let drawing_context = MinimalDrawingContext::new(ctx.clone());
+    match drawing_context.to_drawable_operator(operator, sub_ints).kind {
+        DrawableOperatorKind::Framed(label) => {
+            assert_eq!(label.lines[0].colored_segments[0].0, "loop[2,5]");
+        },
+        DrawableOperatorKind::CoRegionLike(_) => {
+            panic!("expected a framed operator");
+        }
+    }
+}
+
+
+#[test]
+fn test_operator_parameters_errors() {
+    let ctx = get_context();
+    let err = parse_interaction_strict::<MinimalLangCioII,GeneralContext>("alt(0, seq[2](0,0))",&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidOperatorParameters { operator: "seq".to_owned(), parameters: Some("[2]".to_owned()) });
+    assert_eq!(err.offset, 7);
+    // ***
+    let err = parse_interaction_strict::<MinimalLangCioII,GeneralContext>("loop{2}(0)",&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidOperatorParameters { operator: "loop".to_owned(), parameters: Some("{2}".to_owned()) });
+    // ***
+    let err = parse_interaction_strict::<MinimalLangCioII,GeneralContext>("loop[5,2](0)",&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidOperatorParameters { operator: "loop".to_owned(), parameters: Some("[5,2]".to_owned()) });
+    // ***
+    let err = parse_interaction_strict::<MinimalLangCioII,GeneralContext>("loop[2,5(0)",&ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::Lexical);
+    assert_eq!(err.expected, vec!["']'".to_owned()]);
+}
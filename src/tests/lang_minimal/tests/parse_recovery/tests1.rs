@@ -0,0 +1,170 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use std::rc::Rc;
+
+use crate::internal_representation::InteractionInternalRepresentation;
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::{GeneralContext, SignatureContext};
+use crate::from_text::error::{get_line_and_column, ParseErrorKind};
+use crate::from_text::parse::parse_interaction_strict;
+use crate::from_text::recovery::parse_interaction_with_recovery;
+use crate::from_text::signature::{InteractionSignature, SharedSignature, SignatureBasedInteractionParser};
+
+
+
+fn get_context() -> GeneralContext {
+    GeneralContext{lf_names:vec!["a".to_string(),"b".to_string(),"c".to_string()],ms_names:vec!["m".to_string(),"n".to_string(),"p".to_string()]}
+}
+
+
+
+#[test]
+fn test_recovery_reports_all_errors() {
+    let input_text = 
+r#"seq(
+        a -- zz -> b,
+        alt(
+            b -- m -> a ; 0,
+            a -- n -> b
+        ),
+        loop(a -- m -> b, 0),
+        par(a -- p -> c, 0)
+)"#;
+    let (int_repr,errors) = parse_interaction_with_recovery::<MinimalLangCioII,GeneralContext>(input_text,&get_context());
+    // ***
+    let kinds : Vec<ParseErrorKind> = errors.iter().map(|err| err.kind.clone()).collect();
+    assert_eq!(
+        kinds, 
+        vec![
+            ParseErrorKind::UnknownName { name: "zz".to_owned() },
+            ParseErrorKind::Lexical,
+            ParseErrorKind::ArityMismatch { operator: "loop".to_owned(), min_operands: 1, max_operands: Some(1), got: 2 }
+        ]
+    );
+    assert_eq!((errors[0].line,errors[0].column), (2,14));
+    assert_eq!((errors[1].line,errors[1].column), (4,25));
+    assert_eq!((errors[2].line,errors[2].column), (7,9));
+    // ***
+    match &int_repr {
+        InteractionInternalRepresentation::Operator(MinimalOperators::Seq, operands) => {
+            assert_eq!(operands.len(), 4);
+            // the unknown name and the loop with two operands are replaced by placeholders
+            assert!(matches!(operands[0], InteractionInternalRepresentation::LeafPattern(MinimalLeafPattern::EMPTY)));
+            assert!(matches!(operands[2], InteractionInternalRepresentation::LeafPattern(MinimalLeafPattern::EMPTY)));
+            // the alt resynchronises at the separator after the unexpected ';'
+            match &operands[1] {
+                InteractionInternalRepresentation::Operator(MinimalOperators::Alt, alt_operands) => {
+                    assert_eq!(alt_operands.len(), 2);
+                },
+                other => {
+                    panic!("expected an alt, got {:?}", other);
+                }
+            }
+            assert!(matches!(operands[3], InteractionInternalRepresentation::Operator(MinimalOperators::Par, _)));
+        },
+        other => {
+            panic!("expected a seq, got {:?}", other);
+        }
+    }
+}
+
+
+#[test]
+fn test_recovery_unclosed_and_trailing() {
+    let (_,errors) = parse_interaction_with_recovery::<MinimalLangCioII,GeneralContext>("seq(a -- m -> b, 0", &get_context());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ParseErrorKind::Lexical);
+    assert_eq!(errors[0].offset, 18);
+    // ***
+    let (_,errors) = parse_interaction_with_recovery::<MinimalLangCioII,GeneralContext>("seq(a -- m -> b, 0)) x", &get_context());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ParseErrorKind::TrailingInput { trailing: ") x".to_owned() });
+    // ***
+    // the end of the input within nested operators is only reported within the innermost one
+    let (_,errors) = parse_interaction_with_recovery::<MinimalLangCioII,GeneralContext>("seq(a -- m -> b, alt(0, loop(0", &get_context());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].offset, 30);
+    assert_eq!(errors[0].context.len(), 3);
+}
+
+
+#[test]
+fn test_recovery_many_errors() {
+    // each error only costs the input up to the next synchronisation point
+    let num_operands = 50_000;
+    let input_text = format!("seq({})", vec!["a -- zz -> b"; num_operands].join(","));
+    let (_,errors) = parse_interaction_with_recovery::<MinimalLangCioII,GeneralContext>(&input_text, &get_context());
+    assert_eq!(errors.len(), num_operands);
+    // ***
+    // the lines and columns of the errors are the same as when they are computed from the start of the input
+    let input_text = format!("seq({})", vec!["a -- zz -> b /* é */"; num_operands].join(",\n"));
+    let (_,errors) = parse_interaction_with_recovery::<MinimalLangCioII,GeneralContext>(&input_text, &get_context());
+    assert_eq!(errors.len(), num_operands);
+    for error in errors.iter().step_by(997) {
+        assert_eq!((error.line,error.column), get_line_and_column(&input_text, error.offset));
+    }
+}
+
+
+#[test]
+fn test_recovery_without_errors() {
+    let input_text = "seq(a -- m -> b, alt(b -- n -> (a,c), 0), loop(c -- p -> |))";
+    let (int_repr,errors) = parse_interaction_with_recovery::<MinimalLangCioII,GeneralContext>(input_text,&get_context());
+    assert!(errors.is_empty());
+    let expected = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(input_text,&get_context()).unwrap();
+    assert_eq!(format!("{:?}", int_repr), format!("{:?}", expected));
+}
+
+
+#[test]
+fn test_recovery_with_comments_in_operator_header() {
+    let input_text = "seq /* c1 */ ( /* c2 */ a -- m -> b, loop // c3\n (b -- n -> a))";
+    let (int_repr,errors) = parse_interaction_with_recovery::<MinimalLangCioII,GeneralContext>(input_text,&get_context());
+    assert!(errors.is_empty());
+    let expected = parse_interaction_strict::<MinimalLangCioII,GeneralContext>(input_text,&get_context()).unwrap();
+    assert_eq!(format!("{:?}", int_repr), format!("{:?}", expected));
+}
+
+
+#[test]
+fn test_recovery_nesting_too_deep() {
+    // the innermost operator exceeds the maximal nesting depth of the parser (10 000)
+    let depth = 10_001;
+    let input_text = format!("{}a -- m -> b{}", "loop(".repeat(depth), ")".repeat(depth));
+    let (_,errors) = parse_interaction_with_recovery::<MinimalLangCioII,GeneralContext>(&input_text,&get_context());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ParseErrorKind::NestingTooDeep { max_depth: 10_000 });
+    assert_eq!(errors[0].offset, 10_000 * "loop(".len());
+    assert_eq!(errors[0].context.len(), 10_000);
+}
+
+
+#[test]
+fn test_recovery_discards_names_of_skipped_text() {
+    let signature = Rc::new(SharedSignature::new(InteractionSignature::default(), true));
+    let parser = SignatureContext::from_signature(signature.clone());
+    let input_text = "seq(x -- y, a -- m -> b, loop(c -- n -> d, 0), b -- m -> a)";
+    let (_,errors) = parse_interaction_with_recovery::<MinimalLangCioII,SignatureContext>(input_text,&parser);
+    assert_eq!(errors.len(), 2);
+    // the names within the erroneous leaf and within the loop with two operands are not interned
+    assert_eq!(
+        signature.get_signature(), 
+        InteractionSignature{lifelines:vec!["a".to_string(),"b".to_string()],messages:vec!["m".to_string()],gates:vec![]}
+    );
+}
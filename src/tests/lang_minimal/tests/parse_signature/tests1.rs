@@ -0,0 +1,98 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::{GeneralContext, SignatureContext};
+use crate::from_text::error::ParseErrorKind;
+use crate::from_text::parse::parse_interaction_strict;
+use crate::from_text::signature::{parse_interaction_with_interning, parse_interaction_with_signature, parse_signature_header, InteractionSignature};
+
+
+
+fn to_strings(names : &[&str]) -> Vec<String> {
+    names.iter().map(|x| x.to_string()).collect()
+}
+
+
+
+#[test]
+fn test_signature_header() {
+    let input_text = 
+r#"@lifelines{a, b, c} // the lifelines
+@messages{m,n}
+@gates{}
+seq(a -- m -> (b,c), n -> a)"#;
+    let (int_repr,signature) = parse_interaction_with_signature::<MinimalLangCioII,SignatureContext>(input_text).unwrap();
+    assert_eq!(
+        signature, 
+        InteractionSignature{lifelines:to_strings(&["a","b","c"]),messages:to_strings(&["m","n"]),gates:vec![]}
+    );
+    let ctx = GeneralContext{lf_names:signature.lifelines.clone(),ms_names:signature.messages.clone()};
+    let expected = parse_interaction_strict::<MinimalLangCioII,GeneralContext>("seq(a -- m -> (b,c), n -> a)",&ctx).unwrap();
+    assert_eq!(format!("{:?}", int_repr), format!("{:?}", expected));
+    // ***
+    let err = parse_interaction_with_signature::<MinimalLangCioII,SignatureContext>("@lifelines{a,b}\n@messages{m}\na -- m -> x").unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::UnknownName { name: "x".to_owned() });
+    assert_eq!((err.line,err.column), (3,11));
+    // ***
+    let err = parse_interaction_with_signature::<MinimalLangCioII,SignatureContext>("@lifelines{a;b}\n0").unwrap_err();
+    assert_eq!(err.expected, vec!["',' or '}'".to_owned()]);
+    // ***
+    let err = parse_interaction_with_signature::<MinimalLangCioII,SignatureContext>("@lifelines{a,b}\n@lifelines{a}\n0").unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::DuplicateDeclaration { name: "a".to_owned() });
+    assert_eq!((err.line,err.column), (2,12));
+    // ***
+    // a keyword followed by a character which continues a name does not start a section
+    let ctx = GeneralContext{lf_names:vec![],ms_names:vec![]};
+    let (signature,offset) = parse_signature_header("@lifelines {a} @lifelinesX{b}", &ctx).unwrap();
+    assert_eq!(signature.lifelines, to_strings(&["a"]));
+    assert_eq!(offset, 15);
+    let (signature,offset) = parse_signature_header("@gates_{g}", &ctx).unwrap();
+    assert_eq!(signature.gates, Vec::<String>::new());
+    assert_eq!(offset, 0);
+}
+
+
+#[test]
+fn test_signature_interning() {
+    let input_text = 
+r#"@lifelines{c}
+seq(
+    a -- m -> (b,c),
+    n -> a,
+    d -- n -> |
+)"#;
+    let (int_repr,signature) = parse_interaction_with_interning::<MinimalLangCioII,SignatureContext>(input_text).unwrap();
+    assert_eq!(
+        signature, 
+        InteractionSignature{lifelines:to_strings(&["c","a","b","d"]),messages:to_strings(&["m","n"]),gates:vec![]}
+    );
+    let ctx = GeneralContext{lf_names:signature.lifelines.clone(),ms_names:signature.messages.clone()};
+    let expected = parse_interaction_strict::<MinimalLangCioII,GeneralContext>("seq(a -- m -> (b,c), n -> a, d -- n -> |)",&ctx).unwrap();
+    assert_eq!(format!("{:?}", int_repr), format!("{:?}", expected));
+    // names which are only tried in alternatives that fail (e.g. operators tried as leaf patterns) are not interned
+    let (_,signature) = parse_interaction_with_interning::<MinimalLangCioII,SignatureContext>("loop(alt(x -- y -> z, 0))").unwrap();
+    assert_eq!(
+        signature, 
+        InteractionSignature{lifelines:to_strings(&["x","z"]),messages:to_strings(&["y"]),gates:vec![]}
+    );
+    // errors which are not about unknown names are still reported
+    let err = parse_interaction_with_interning::<MinimalLangCioII,SignatureContext>("seq(a -- m -> b; 0)").unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::Lexical);
+    assert_eq!(err.offset, 15);
+}
@@ -0,0 +1,79 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::GeneralContext;
+use crate::from_text::spans::parse_interaction_with_spans;
+use crate::from_text::comments::parse_interaction_with_comments;
+
+
+
+#[test]
+fn test_spans() {
+    let ctx = GeneralContext{lf_names:vec!["a".to_string(),"b".to_string(),"c".to_string()],ms_names:vec!["m".to_string(),"n".to_string(),"p".to_string()]};
+    let input_text = 
+r#"// comments are not part of spans
+seq(
+        a -- m -> (b,c)  ,
+        /* before alt */ alt(
+                b -- n -> a,
+                0
+        )
+)  "#;
+    let (_,spans) = parse_interaction_with_spans::<MinimalLangCioII,GeneralContext>(input_text,&ctx).unwrap();
+    assert_eq!(spans.len(), 5);
+    let root = spans.get(&vec![]).unwrap();
+    assert!(root.get_text(input_text).starts_with("seq("));
+    assert!(root.get_text(input_text).ends_with("\n)"));
+    assert_eq!(root.get_start_line_and_column(input_text), (2,1));
+    assert_eq!(spans.get(&vec![0]).unwrap().get_text(input_text), "a -- m -> (b,c)");
+    let alt = spans.get(&vec![1]).unwrap();
+    assert_eq!(alt.get_start_line_and_column(input_text), (4,26));
+    assert_eq!(alt.get_end_line_and_column(input_text), (7,10));
+    assert_eq!(spans.get(&vec![1,0]).unwrap().get_text(input_text), "b -- n -> a");
+    assert_eq!(spans.get(&vec![1,1]).unwrap().get_text(input_text), "0");
+}
+
+
+
+#[test]
+fn test_spans_of_annotated_nodes() {
+    let ctx = GeneralContext{lf_names:vec!["a".to_string(),"b".to_string()],ms_names:vec!["m".to_string()]};
+    let input_text = "seq(@{x} /* inner */ @{y} a -- m -> b, 0)";
+    let (_,spans) = parse_interaction_with_spans::<MinimalLangCioII,GeneralContext>(input_text,&ctx).unwrap();
+    assert_eq!(spans.len(), 5);
+    assert_eq!(spans.get(&vec![0]).unwrap().get_text(input_text), "@{x} /* inner */ @{y} a -- m -> b");
+    assert_eq!(spans.get(&vec![0,0]).unwrap().get_text(input_text), "@{y} a -- m -> b");
+    assert_eq!(spans.get(&vec![0,0,0]).unwrap().get_text(input_text), "a -- m -> b");
+    assert_eq!(spans.get(&vec![1]).unwrap().get_text(input_text), "0");
+    let (_,comments) = parse_interaction_with_comments::<MinimalLangCioII,GeneralContext>(input_text,&ctx).unwrap();
+    assert_eq!(comments.leading.get(&vec![0,0]).unwrap(), &vec!["/* inner */".to_owned()]);
+}
+
+
+
+#[test]
+fn test_spans_of_deeply_nested_interaction() {
+    let ctx = GeneralContext{lf_names:vec!["a".to_string(),"b".to_string()],ms_names:vec!["m".to_string()]};
+    let depth = 2_000;
+    let input_text = format!("{}a -- m -> b{}", "loop(".repeat(depth), ")".repeat(depth));
+    let (_,spans) = parse_interaction_with_spans::<MinimalLangCioII,GeneralContext>(&input_text,&ctx).unwrap();
+    assert_eq!(spans.len(), depth + 1);
+    assert_eq!(spans.get(&vec![0;depth]).unwrap().get_text(&input_text), "a -- m -> b");
+    assert_eq!(spans.get(&vec![0;depth - 1]).unwrap().get_text(&input_text), "loop(a -- m -> b)");
+}
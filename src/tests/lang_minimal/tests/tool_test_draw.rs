@@ -0,0 +1,101 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use crate::internal_representation::InteractionInternalRepresentation;
+use crate::tests::lang_minimal::core::internal_representation::*;
+use crate::tests::lang_minimal::minimal_lang::GeneralContext;
+use crate::tests::lang_minimal::to_image::drawing_context::MinimalDrawingContext;
+use crate::from_text::parse::parse_interaction;
+use crate::to_image::error::DrawError;
+use crate::to_image::interface::get_interaction_layout;
+use crate::to_image::layout::model::DiagramLayout;
+#[cfg(feature = "to_svg")]
+use crate::tests::common::DRAWING_GRAPHIC_FONT_FAMILY;
+#[cfg(feature = "to_svg")]
+use crate::to_svg::interface::get_interaction_as_svg_sequence_diagram;
+
+
+
+/**
+ * Builds a drawing context for the given lifeline and message names, 
+ * which is then adapted to the feature under test by *configure*.
+ * **/
+pub fn tool_get_drawing_context(
+        lf_names : &[&str],
+        ms_names : &[&str],
+        configure : impl FnOnce(&mut MinimalDrawingContext)
+    ) -> MinimalDrawingContext {
+    let ctx = GeneralContext{
+        lf_names:lf_names.iter().map(|name| name.to_string()).collect(),
+        ms_names:ms_names.iter().map(|name| name.to_string()).collect()
+    };
+    let mut drawing_context = MinimalDrawingContext::new(ctx);
+    configure(&mut drawing_context);
+    drawing_context
+}
+
+
+
+/**
+ * Parses the input text with knowledge of the context of the drawing context.
+ * Here the parsing is expected to succeed.
+ * **/
+pub fn tool_parse_for_drawing(
+        input_text : &str,
+        drawing_context : &MinimalDrawingContext
+    ) -> InteractionInternalRepresentation<MinimalLangCioII> {
+    parse_interaction::<MinimalLangCioII,GeneralContext>(
+        input_text,&drawing_context.general_context
+    ).unwrap()
+}
+
+
+
+/**
+ * Parses the input text and computes the layout of the corresponding sequence diagram.
+ * **/
+pub fn tool_get_layout(
+        input_text : &str,
+        drawing_context : &MinimalDrawingContext
+    ) -> Result<DiagramLayout<usize>,DrawError> {
+    let internal_repr = tool_parse_for_drawing(input_text, drawing_context);
+    get_interaction_layout::<MinimalLangCioII,usize,MinimalDrawingContext,MinimalDrawingContext>(
+        &internal_repr,
+        drawing_context,
+        drawing_context
+    )
+}
+
+
+
+/**
+ * Parses the input text and draws the corresponding sequence diagram as an SVG document.
+ * **/
+#[cfg(feature = "to_svg")]
+pub fn tool_get_svg(
+        input_text : &str,
+        drawing_context : &MinimalDrawingContext
+    ) -> String {
+    let internal_repr = tool_parse_for_drawing(input_text, drawing_context);
+    get_interaction_as_svg_sequence_diagram::<MinimalLangCioII,usize,MinimalDrawingContext,MinimalDrawingContext>(
+        &internal_repr,
+        drawing_context,
+        drawing_context,
+        DRAWING_GRAPHIC_FONT_FAMILY
+    ).unwrap()
+}
@@ -28,12 +28,15 @@ use crate::tests::common::*;
 use crate::tests::lang_minimal::minimal_lang::GeneralContext;
 use crate::to_image::common_interaction_drawer::CommonInteractionDrawerTrait;
 use crate::to_image::draw::context_aware_drawer::ContextAwareInteractionDrawer;
-use crate::to_image::drawable::leaf::util::MessageExchangeLineStyle;
+use crate::to_image::draw::canvas::DiagramCanvas;
+use crate::to_image::drawable::leaf::util::{MessageArrowTailDecoration, MessageArrowheadKind, MessageExchangeLineStyle, MessageLineDashPattern};
 use crate::to_image::extract::context_aware_extractor::ContextAwareInteractionDrawingInstructionsExtractor;
+use crate::to_image::extract::ordering::LifelineOrderingStrategy;
 
 use crate::tests::lang_minimal::core::internal_representation::{MinimalOperators, MinimalLangCioII, MinimalLeafPattern};
 use crate::to_image::drawable::leaf::broadcast::*;
 use crate::to_image::drawable::operator::builtin_operator::{DrawableOperator, DrawableOperatorKind};
+use crate::to_image::drawable::group::DrawableLifelineGroup;
 
 
 use crate::to_image::draw::util::draw_uniform_colored_background;
@@ -50,7 +53,20 @@ pub struct MinimalDrawingContext {
     pub y_margin_between_seq_operands : f32,
     pub margin_between_items : f32,
     pub border_padding : f32,
-    pub arrowhead_length : f32
+    pub arrowhead_length : f32,
+    pub lifelines_ordering_strategy : LifelineOrderingStrategy,
+    pub pinned_lifelines : HashMap<usize,usize>,
+    pub lifeline_groups : Vec<DrawableLifelineGroup<usize>>,
+    // the messages that create (resp. destroy) the lifelines which receive them
+    pub creation_messages : HashSet<usize>,
+    pub destruction_messages : HashSet<usize>,
+    // the messages that activate the lifelines which receive them (calls)
+    // and those that deactivate the lifelines which emit them (returns)
+    pub activation_messages : HashSet<usize>,
+    pub deactivation_messages : HashSet<usize>,
+    pub activation_bar_width : f32,
+    // how the lines of specific messages are drawn (solid lines with open arrowheads by default)
+    pub messages_line_styles : HashMap<usize,(MessageLineDashPattern,MessageArrowheadKind,MessageArrowTailDecoration)>
 }
 
 impl MinimalDrawingContext {
@@ -67,7 +83,16 @@ impl MinimalDrawingContext {
             y_margin_between_seq_operands,
             margin_between_items,
             border_padding,
-            arrowhead_length
+            arrowhead_length,
+            lifelines_ordering_strategy : LifelineOrderingStrategy::Declaration,
+            pinned_lifelines : HashMap::new(),
+            lifeline_groups : vec![],
+            creation_messages : HashSet::new(),
+            destruction_messages : HashSet::new(),
+            activation_messages : HashSet::new(),
+            deactivation_messages : HashSet::new(),
+            activation_bar_width : margin_between_items,
+            messages_line_styles : HashMap::new()
         }
     }
 }
@@ -102,6 +127,22 @@ impl ContextAwareInteractionDrawingInstructionsExtractor<MinimalLangCioII,usize>
         l1.cmp(l2)
     }
 
+    fn get_lifelines_ordering_strategy(&self) -> LifelineOrderingStrategy {
+        self.lifelines_ordering_strategy.clone()
+    }
+
+    fn get_pinned_lifelines(&self) -> HashMap<usize,usize> {
+        self.pinned_lifelines.clone()
+    }
+
+    fn get_lifeline_groups(&self) -> Vec<DrawableLifelineGroup<usize>> {
+        self.lifeline_groups.clone()
+    }
+
+    fn get_activation_bar_width(&self) -> f32 {
+        self.activation_bar_width
+    }
+
     fn get_involved_lifelines(&self, pattern : &MinimalLeafPattern) -> HashSet<usize> {
         match pattern{
             MinimalLeafPattern::BROADCAST(ref brd) => {
@@ -141,12 +182,18 @@ impl ContextAwareInteractionDrawingInstructionsExtractor<MinimalLangCioII,usize>
                     None, 
                     None
                 );
-                let line_style = MessageExchangeLineStyle::new(
+                let mut line_style = MessageExchangeLineStyle::new(
                     false, 
                     false, 
                     Rgb(MY_COLOR_BLACK), 
                     self.arrowhead_length
                 );
+                if let Some((dash_pattern,arrowhead_kind,tail_decoration)) = self.messages_line_styles.get(&brd.msg_id) {
+                    line_style = line_style
+                        .with_dash_pattern(*dash_pattern)
+                        .with_arrowhead_kind(*arrowhead_kind)
+                        .with_tail_decoration(*tail_decoration);
+                }
                 let origin = match brd.origin_lf_id {
                     None => {
                         DrawableBroadcastLeafPatternOrigin::Empty
@@ -168,7 +215,20 @@ impl ContextAwareInteractionDrawingInstructionsExtractor<MinimalLangCioII,usize>
                         TargetLifelineBroadcastDrawInstruction::Centered(CenteredDrawableActionItem::new(empty_paragraph))
                     );
                 }
-                Some(DrawableBroadcastLeafPattern::new(message,line_style,origin,targets,vec![]))
+                let mut drawable_pattern = DrawableBroadcastLeafPattern::new(message,line_style,origin,targets,vec![]);
+                if self.creation_messages.contains(&brd.msg_id) {
+                    drawable_pattern = drawable_pattern.with_created_lifelines(brd.targets.iter().cloned().collect());
+                }
+                if self.destruction_messages.contains(&brd.msg_id) {
+                    drawable_pattern = drawable_pattern.with_destroyed_lifelines(brd.targets.iter().cloned().collect());
+                }
+                if self.activation_messages.contains(&brd.msg_id) {
+                    drawable_pattern = drawable_pattern.with_activated_lifelines(brd.targets.iter().cloned().collect());
+                }
+                if self.deactivation_messages.contains(&brd.msg_id) {
+                    drawable_pattern = drawable_pattern.with_deactivated_lifelines(brd.origin_lf_id.into_iter().collect());
+                }
+                Some(drawable_pattern)
             },
             MinimalLeafPattern::EMPTY => {
                 None
@@ -184,8 +244,17 @@ impl ContextAwareInteractionDrawingInstructionsExtractor<MinimalLangCioII,usize>
         if op == &MinimalOperators::Seq {
             DrawableOperator::new(Rgb(MY_COLOR_BLACK),DrawableOperatorKind::CoRegionLike(HashSet::new()))
         } else {
+            // the bounds of loops are shown in the label of the frame
+            let label_text = match op {
+                MinimalOperators::BoundedLoop(bounds) => {
+                    format!("{}{}", op.as_lowercase_string(), bounds)
+                },
+                _ => {
+                    op.as_lowercase_string()
+                }
+            };
             let op_label = ColoredTextParagraph::new(
-                vec![ColoredTextLine::new(vec![(op.as_lowercase_string(),Rgb(MY_COLOR_BLACK))])], 
+                vec![ColoredTextLine::new(vec![(label_text,Rgb(MY_COLOR_BLACK))])], 
                 MultiLineTextAlignment::Center,
                 None,
                 None
@@ -194,14 +263,28 @@ impl ContextAwareInteractionDrawingInstructionsExtractor<MinimalLangCioII,usize>
         }
     }
 
+    fn to_drawable_annotation(
+        &self, 
+        annotation : &String,
+        _annotated : &InteractionInternalRepresentation<MinimalLangCioII>
+    ) -> Option<DrawableOperator<usize>> {
+        let annotation_label = ColoredTextParagraph::new(
+            vec![ColoredTextLine::new(vec![(annotation.clone(),Rgb(MY_COLOR_BLACK))])], 
+            MultiLineTextAlignment::Center,
+            None,
+            None
+        );
+        Some(DrawableOperator::new(Rgb(MY_COLOR_BLACK),DrawableOperatorKind::Framed(annotation_label)))
+    }
+
 }
 
 
 
 
 impl ContextAwareInteractionDrawer<usize> for MinimalDrawingContext {
-    fn draw_background(&self, image : &mut image::RgbImage, img_width : f32, img_height : f32) {
-        draw_uniform_colored_background(image,&img_width,&img_height,Rgb(MY_COLOR_WHITE));
+    fn draw_background<Canvas : DiagramCanvas>(&self, canvas : &mut Canvas, img_width : f32, img_height : f32) {
+        draw_uniform_colored_background(canvas,&img_width,&img_height,Rgb(MY_COLOR_WHITE));
     }
     
     fn get_lifelines_colors(&self, involved_lifelines : &[usize]) -> HashMap<usize,Rgb<u8>> {
@@ -19,6 +19,7 @@ limitations under the License.
 
 use crate::internal_representation::InteractionInternalRepresentation;
 use crate::to_text::context_aware_printer::ContextAwareInteractionPrinter;
+use crate::syntax::InteractionLanguageSyntax;
 
 use crate::tests::lang_minimal::minimal_lang::*;
 use crate::tests::lang_minimal::core::internal_representation::*;
@@ -38,12 +39,27 @@ impl ContextAwareInteractionPrinter<MinimalLangCioII> for GeneralContext {
         ","
     }
 
+    fn syntax(&self) -> &dyn InteractionLanguageSyntax {
+        self
+    }
+
     fn print_operator(
         &self, 
         operator : &MinimalOperators,
         _sub_ints : &[InteractionInternalRepresentation<MinimalLangCioII>]
     ) -> String {
-        operator.as_lowercase_string()
+        match operator {
+            MinimalOperators::BoundedLoop(bounds) => {
+                format!("{}{}", operator.as_lowercase_string(), self.print_operator_parameters(bounds))
+            },
+            _ => {
+                operator.as_lowercase_string()
+            }
+        }
+    }
+
+    fn print_annotation(&self, annotation : &String) -> Option<String> {
+        Some(annotation.clone())
     }
 
     fn print_explicit_pattern(&self, leaf_pattern : &MinimalLeafPattern) -> String {
@@ -64,7 +80,7 @@ impl ContextAwareInteractionPrinter<MinimalLangCioII> for GeneralContext {
                 let end : String = match targs_num {
                     0 => {"|".to_owned()},
                     1 => {
-                        let targ_lf_id = brd.targets.get(0).unwrap();
+                        let targ_lf_id = brd.targets.first().unwrap();
                         self.lf_names.get(*targ_lf_id).unwrap().clone()
                     },
                     _ => {
@@ -0,0 +1,180 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use ab_glyph::{Font, PxScale};
+use image::{Rgb, RgbImage};
+use imageproc::drawing::{
+    draw_filled_circle_mut,
+    draw_filled_rect_mut,
+    draw_line_segment_mut
+};
+use imageproc::rect::Rect;
+
+use image_colored_text::draw::coord::DrawCoord;
+use image_colored_text::draw::multi_line::draw_multiline_colored_text;
+use image_colored_text::text::paragraph::ColoredTextParagraph;
+
+
+
+/**
+ * A drawing surface on which sequence diagrams can be drawn.
+ * All the drawing functions of the *to_image::draw* and *to_image::drawable* modules are generic over this trait
+ * so that other renderers (SVG, PDF, GUI canvas etc.) may be plugged in.
+ * Only *draw_line*, *draw_filled_rect* and *draw_text_paragraph* are required.
+ * The other primitives have default implementations in terms of the required ones.
+ **/
+pub trait DiagramCanvas {
+
+    fn draw_line(&mut self, start : (f32,f32), end : (f32,f32), color : Rgb<u8>);
+
+    fn draw_filled_rect(&mut self, x_left : f32, y_top : f32, width : f32, height : f32, color : Rgb<u8>);
+
+    /** 
+     * Draws a paragraph of colored text (with its background and border if any) at the given coordinates.
+     * The *font* and *scale* are those that have been used to compute the size of the paragraph during the extraction.
+     * **/
+    fn draw_text_paragraph(
+        &mut self,
+        x_pos : &DrawCoord,
+        y_pos : &DrawCoord,
+        paragraph : &ColoredTextParagraph,
+        font : &impl Font,
+        scale : impl Into<PxScale> + Copy
+    );
+
+    /** 
+     * Draws a line that is thicker than the lines drawn with *draw_line* (e.g., for bold message arrows).
+     * **/
+    fn draw_thick_line(&mut self, start : (f32,f32), end : (f32,f32), color : Rgb<u8>) {
+        self.draw_line((start.0, start.1 - 0.5),(end.0, end.1 - 0.5),color);
+        self.draw_line((start.0, start.1 + 0.5),(end.0, end.1 + 0.5),color);
+    }
+
+    /** 
+     * Draws an open arrowhead which tip is at the given position and which points either rightward or leftward.
+     * **/
+    fn draw_arrowhead(&mut self, tip : (f32,f32), pointing_rightward : bool, arrowhead_length : f32, color : Rgb<u8>) {
+        let (x_pos,y_pos) = tip;
+        let x_back = if pointing_rightward {
+            x_pos - arrowhead_length
+        } else {
+            x_pos + arrowhead_length
+        };
+        self.draw_line(tip,(x_back, y_pos - arrowhead_length),color);
+        self.draw_line(tip,(x_back, y_pos + arrowhead_length),color);
+    }
+
+    /** 
+     * Draws a filled triangular arrowhead which tip is at the given position and which points either rightward or leftward.
+     * It is filled with vertical lines.
+     * **/
+    fn draw_filled_arrowhead(&mut self, tip : (f32,f32), pointing_rightward : bool, arrowhead_length : f32, color : Rgb<u8>) {
+        let (x_pos,y_pos) = tip;
+        let direction = if pointing_rightward {
+            -1.0
+        } else {
+            1.0
+        };
+        let mut shift = 0.0_f32;
+        while shift <= arrowhead_length {
+            let x = x_pos + direction*shift;
+            self.draw_line((x, y_pos - shift),(x, y_pos + shift),color);
+            shift += 0.5;
+        }
+    }
+
+    /** 
+     * Draws a (possibly thick) line made of dashes separated by gaps, starting with a dash.
+     * Nothing is drawn if the dash length is not positive or if the gap length is negative.
+     * **/
+    fn draw_dashed_line(&mut self, start : (f32,f32), end : (f32,f32), color : Rgb<u8>, thick : bool, (dash_length,gap_length) : (f32,f32)) {
+        let (dx,dy) = (end.0 - start.0, end.1 - start.1);
+        let length = (dx*dx + dy*dy).sqrt();
+        if length == 0.0 || dash_length <= 0.0 || gap_length < 0.0 {
+            return;
+        }
+        let point_at = |dist : f32| (start.0 + dx*dist/length, start.1 + dy*dist/length);
+        let mut dist = 0.0_f32;
+        while dist < length {
+            let dash_start = point_at(dist);
+            let dash_end = point_at(f32::min(dist + dash_length, length));
+            if thick {
+                self.draw_thick_line(dash_start, dash_end, color);
+            } else {
+                self.draw_line(dash_start, dash_end, color);
+            }
+            dist += dash_length + gap_length;
+        }
+    }
+
+    /** 
+     * Draws a filled circle with horizontal lines.
+     * **/
+    fn draw_filled_circle(&mut self, center : (f32,f32), radius : f32, color : Rgb<u8>) {
+        let (x_pos,y_pos) = center;
+        let mut shift = -radius;
+        while shift <= radius {
+            let half_width = (radius*radius - shift*shift).max(0.0).sqrt();
+            self.draw_line((x_pos - half_width, y_pos + shift),(x_pos + half_width, y_pos + shift),color);
+            shift += 0.5;
+        }
+    }
+
+}
+
+
+
+/**
+ * The default canvas, which rasterizes the diagram with *imageproc*.
+ **/
+impl DiagramCanvas for RgbImage {
+
+    fn draw_line(&mut self, start : (f32,f32), end : (f32,f32), color : Rgb<u8>) {
+        draw_line_segment_mut(self, start, end, color);
+    }
+
+    fn draw_filled_rect(&mut self, x_left : f32, y_top : f32, width : f32, height : f32, color : Rgb<u8>) {
+        // rectangles smaller than a pixel (or with negative sizes) cover no pixel
+        let (width,height) = (width as u32, height as u32);
+        if width == 0 || height == 0 {
+            return;
+        }
+        draw_filled_rect_mut(
+            self, 
+            Rect::at(x_left as i32, y_top as i32).of_size(width, height), 
+            color
+        );
+    }
+
+    fn draw_text_paragraph(
+        &mut self,
+        x_pos : &DrawCoord,
+        y_pos : &DrawCoord,
+        paragraph : &ColoredTextParagraph,
+        font : &impl Font,
+        scale : impl Into<PxScale> + Copy
+    ) {
+        draw_multiline_colored_text(self, x_pos, y_pos, paragraph, font, scale);
+    }
+
+    fn draw_filled_circle(&mut self, center : (f32,f32), radius : f32, color : Rgb<u8>) {
+        draw_filled_circle_mut(self, (center.0.round() as i32, center.1.round() as i32), radius.round() as i32, color);
+    }
+
+}
+
@@ -21,15 +21,16 @@ limitations under the License.
 use std::hash::Hash;
 use std::collections::HashMap;
 
-use image::{Rgb, RgbImage};
+use image::Rgb;
 
 use crate::to_image::common_interaction_drawer::CommonInteractionDrawerTrait;
+use crate::to_image::draw::canvas::DiagramCanvas;
 
 pub trait ContextAwareInteractionDrawer<
         LI : Eq + Hash + Copy + Clone
     > : CommonInteractionDrawerTrait {  
 
-    fn draw_background(&self, image : &mut RgbImage, img_width : f32, img_height : f32);    
+    fn draw_background<Canvas : DiagramCanvas>(&self, canvas : &mut Canvas, img_width : f32, img_height : f32);    
 
     fn get_lifelines_colors(&self, involved_lifelines : &[LI]) -> HashMap<LI,Rgb<u8>>;
 
@@ -37,6 +38,14 @@ pub trait ContextAwareInteractionDrawer<
 
     fn get_nest_padding_unit(&self) -> f32;
 
+    /** 
+     * Returns the color with which activation bars are filled (their borders having the color of their lifeline).
+     * By default, they are filled in white.
+     * **/
+    fn get_activation_bar_fill_color(&self) -> Rgb<u8> {
+        Rgb([255u8, 255u8, 255u8])
+    }
+
 }
 
 
@@ -19,12 +19,16 @@ limitations under the License.
 use std::hash::Hash;
 use image::RgbImage;
 
-use image_colored_text::draw::multi_line::draw_multiline_colored_text;
 use image_colored_text::draw::coord::DrawCoord;
 
 use crate::to_image::extract::instructions::*;
+use crate::to_image::draw::canvas::DiagramCanvas;
 use crate::to_image::draw::context_aware_drawer::ContextAwareInteractionDrawer;
-use crate::to_image::draw::util::draw_lifelines_vertical_spans;
+use crate::to_image::draw::util::{draw_destruction_cross, draw_lifelines_vertical_spans};
+use crate::to_image::drawable::activation::draw_activation_bar;
+use crate::to_image::drawable::group::draw_lifeline_group_frame;
+use crate::to_image::drawable::util::get_lifeline_horizontal_position;
+use crate::to_image::error::DrawError;
 
 pub(crate) fn make_image_from_display_information<
     LI : Eq + Hash + Copy + Clone,
@@ -32,30 +36,78 @@ pub(crate) fn make_image_from_display_information<
   >(
   palette : &Drawer,
   draw_instruction : &CompleteInteractionDrawInstruction<LI>
-) -> RgbImage {
-
+) -> Result<RgbImage,DrawError> {
   let mut image = RgbImage::new( draw_instruction.width as u32, draw_instruction.height as u32);
+  draw_display_information_on_canvas::<LI,Drawer,RgbImage>(palette, draw_instruction, &mut image)?;
+  Ok(image)
+}
+
+/** 
+ * Draws the interaction on any canvas which size is at least that given in the draw instruction.
+ * **/
+pub(crate) fn draw_display_information_on_canvas<
+    LI : Eq + Hash + Copy + Clone,
+    Drawer : ContextAwareInteractionDrawer<LI>,
+    Canvas : DiagramCanvas
+  >(
+  palette : &Drawer,
+  draw_instruction : &CompleteInteractionDrawInstruction<LI>,
+  canvas : &mut Canvas
+) -> Result<(),DrawError> {
 
   // Draw Background
-  palette.draw_background(&mut image, draw_instruction.width, draw_instruction.height);
+  palette.draw_background(canvas, draw_instruction.width, draw_instruction.height);
+
+  // Draw the boxes of the lifeline groups
+  for group_instruct in &draw_instruction.lifeline_groups_to_draw {
+    draw_lifeline_group_frame(
+      canvas,
+      &group_instruct.title,
+      (group_instruct.x_left,group_instruct.x_right),
+      (group_instruct.y_top,group_instruct.y_bottom),
+      group_instruct.frame_color,
+      palette.get_margin_between_items(),
+      palette.get_font(),
+      palette.get_scale()
+    );
+  }
 
   // Draw vertical spans for each lifeline
   let involved_lifelines : Vec<LI> = draw_instruction.lifelines_horizontal_positions.keys().cloned().collect();
+  let lifelines_colors = palette.get_lifelines_colors(&involved_lifelines);
   draw_lifelines_vertical_spans(
-    &mut image, 
-    draw_instruction.y_shift_to_absolute, 
+    canvas, 
     &draw_instruction.lifelines_horizontal_positions,
-    draw_instruction.height - palette.get_border_padding(),
-    &palette.get_lifelines_colors(&involved_lifelines)
-  );
+    &draw_instruction.lifelines_vertical_spans,
+    &lifelines_colors
+  )?;
+
+  // Draw the activation bars over the vertical spans
+  for bar_instruct in &draw_instruction.activation_bars_to_draw {
+    let color = lifelines_colors.get(&bar_instruct.lifeline).ok_or(DrawError::MissingLifelineColor)?;
+    draw_activation_bar(
+      canvas,
+      (bar_instruct.x_left,bar_instruct.x_right),
+      (bar_instruct.y_top,bar_instruct.y_bottom),
+      palette.get_activation_bar_fill_color(),
+      *color
+    );
+  }
 
-  // Draw static lifelines header
+  // Draw the crosses which end destroyed lifelines
+  for lf in &draw_instruction.destroyed_lifelines {
+    let lf_x_pos = get_lifeline_horizontal_position(&draw_instruction.lifelines_horizontal_positions, lf)?;
+    let (_,lf_y_bottom) = draw_instruction.get_lifeline_vertical_span(lf)?;
+    let color = lifelines_colors.get(lf).ok_or(DrawError::MissingLifelineColor)?;
+    draw_destruction_cross(canvas, (lf_x_pos,lf_y_bottom), palette.get_margin_between_items(), *color);
+  }
+
+  // Draw lifelines headers (either at the top or at the creation of the lifeline)
   for (lf,header) in &draw_instruction.lifelines_headers {
-    let lf_x_pos = *draw_instruction.lifelines_horizontal_positions.get(lf).unwrap();
-    draw_multiline_colored_text(
-      &mut image,
+    let lf_x_pos = get_lifeline_horizontal_position(&draw_instruction.lifelines_horizontal_positions, lf)?;
+    canvas.draw_text_paragraph(
       &DrawCoord::CenteredAround(lf_x_pos),
-      &DrawCoord::EndingAt(draw_instruction.y_shift_to_absolute),
+      &draw_instruction.get_lifeline_header_vertical_coord(lf),
       header,
       palette.get_font(),
       palette.get_scale()
@@ -66,7 +118,7 @@ pub(crate) fn make_image_from_display_information<
   for leaf_instruct in &draw_instruction.patterns_to_draw {
     leaf_instruct.pattern.draw(
       &leaf_instruct.intermediate_info,
-      &mut image, 
+      canvas, 
       palette.get_font(),
       palette.get_scale(),
       draw_instruction.y_shift_to_absolute + leaf_instruct.relative_y_pos,
@@ -77,7 +129,7 @@ pub(crate) fn make_image_from_display_information<
       palette.get_margin_between_items(),
       palette.get_y_margin_between_seq_operands(),
       palette.get_arrow_length()
-    );
+    )?;
   }
 
   // Draw operators
@@ -87,7 +139,7 @@ pub(crate) fn make_image_from_display_information<
       horizontal_seps_absolute_vertical_positions.push(x + draw_instruction.y_shift_to_absolute);
     }
     operator_instruct.drawable_op.draw(
-      &mut image, 
+      canvas, 
       palette.get_font(),
       palette.get_scale(),
       &horizontal_seps_absolute_vertical_positions,
@@ -95,10 +147,10 @@ pub(crate) fn make_image_from_display_information<
       &draw_instruction.lifelines_horizontal_positions,
        (operator_instruct.nest_depth as f32) * palette.get_nest_padding_unit(),
       palette.get_margin_between_items()
-    );
+    )?;
   }
 
-  image
+  Ok(())
 }
 
 
@@ -15,6 +15,7 @@ limitations under the License.
 */
 
 
+pub mod canvas;
 pub mod draw;
 pub mod context_aware_drawer;
 pub mod util;
\ No newline at end of file
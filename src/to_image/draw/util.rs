@@ -19,36 +19,47 @@ limitations under the License.
 use std::hash::Hash;
 use std::collections::HashMap;
 
-use image::{Rgb, RgbImage};
-use imageproc::drawing::{
-    draw_filled_rect_mut,
-    draw_line_segment_mut
-};
-use imageproc::rect::Rect;
+use image::Rgb;
+
+use crate::to_image::draw::canvas::DiagramCanvas;
+use crate::to_image::error::DrawError;
 
 
 
 // **********
 
-pub fn draw_uniform_colored_background(image : &mut RgbImage, img_width : &f32, img_height : &f32, color : Rgb<u8>) {
-    draw_filled_rect_mut(image, Rect::at(0,0).of_size(*img_width as u32,*img_height as u32), color);
+pub fn draw_uniform_colored_background<Canvas : DiagramCanvas>(canvas : &mut Canvas, img_width : &f32, img_height : &f32, color : Rgb<u8>) {
+    canvas.draw_filled_rect(0.0, 0.0, *img_width, *img_height, color);
 }
 
-pub fn draw_lifelines_vertical_spans<LI : Eq + Hash + Copy + Clone>(
-    image : &mut RgbImage, 
-    absolute_top_y_pos : f32,
+/** 
+ * Draws the vertical line of each lifeline between the top and bottom positions given by *lifelines_vertical_spans*.
+ * **/
+pub fn draw_lifelines_vertical_spans<LI : Eq + Hash + Copy + Clone, Canvas : DiagramCanvas>(
+    canvas : &mut Canvas, 
     lifelines_horizontal_positions : &HashMap<LI,f32>,
-    absolute_bottom_y_pos : f32,
+    lifelines_vertical_spans : &HashMap<LI,(f32,f32)>,
     lifelines_colors : &HashMap<LI,Rgb<u8>>,
-) {
+) -> Result<(),DrawError> {
     for (lf_id,lf_x_middle) in lifelines_horizontal_positions {
-        let color = lifelines_colors.get(lf_id).unwrap();
-        draw_line_segment_mut(image,
-                              (*lf_x_middle, absolute_top_y_pos),
-                              (*lf_x_middle, absolute_bottom_y_pos),
-                              *color
+        let color = lifelines_colors.get(lf_id).ok_or(DrawError::MissingLifelineColor)?;
+        let (absolute_top_y_pos,absolute_bottom_y_pos) = lifelines_vertical_spans.get(lf_id).ok_or(DrawError::MissingLifelinePosition)?;
+        canvas.draw_line(
+            (*lf_x_middle, *absolute_top_y_pos),
+            (*lf_x_middle, *absolute_bottom_y_pos),
+            *color
         );
     }
+    Ok(())
+}
+
+/** 
+ * Draws the cross which ends a destroyed lifeline.
+ * **/
+pub fn draw_destruction_cross<Canvas : DiagramCanvas>(canvas : &mut Canvas, center : (f32,f32), half_size : f32, color : Rgb<u8>) {
+    let (x_pos,y_pos) = center;
+    canvas.draw_line((x_pos - half_size, y_pos - half_size),(x_pos + half_size, y_pos + half_size),color);
+    canvas.draw_line((x_pos - half_size, y_pos + half_size),(x_pos + half_size, y_pos - half_size),color);
 }
 
 
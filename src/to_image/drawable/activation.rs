@@ -0,0 +1,66 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use image::Rgb;
+
+use crate::to_image::draw::canvas::DiagramCanvas;
+
+
+
+/** 
+ * Returns the horizontal space occupied by *activation_depth* nested activation bars 
+ * on the left and on the right of their lifeline.
+ * The outermost bar is centered on the lifeline and each nested bar is shifted towards the right by half the width of a bar.
+ * **/
+pub fn get_activation_bars_horizontal_extents(activation_depth : u32, activation_bar_width : f32) -> (f32,f32) {
+    if activation_depth == 0 {
+        (0.0,0.0)
+    } else {
+        let half_width = activation_bar_width/2.0;
+        (half_width, half_width + ((activation_depth - 1) as f32)*half_width)
+    }
+}
+
+
+/** 
+ * Returns the horizontal positions of the left and right sides of an activation bar 
+ * which is nested within *nest_depth* other bars on the same lifeline.
+ * **/
+pub fn get_activation_bar_horizontal_bounds(lifeline_x_pos : f32, nest_depth : u32, activation_bar_width : f32) -> (f32,f32) {
+    let x_left = lifeline_x_pos - activation_bar_width/2.0 + (nest_depth as f32)*activation_bar_width/2.0;
+    (x_left, x_left + activation_bar_width)
+}
+
+
+
+pub(crate) fn draw_activation_bar<Canvas : DiagramCanvas>(    
+    canvas : &mut Canvas,
+    (x_left,x_right) : (f32,f32),
+    (y_top,y_bottom) : (f32,f32),
+    fill_color : Rgb<u8>,
+    border_color : Rgb<u8>
+) {
+    // a bar without width or height is only drawn by its borders
+    if x_right > x_left && y_bottom > y_top {
+        canvas.draw_filled_rect(x_left, y_top, x_right - x_left, y_bottom - y_top, fill_color);
+    }
+    canvas.draw_line((x_left, y_top), (x_left, y_bottom), border_color);
+    canvas.draw_line((x_right, y_top), (x_right, y_bottom), border_color);
+    canvas.draw_line((x_left, y_top), (x_right, y_top), border_color);
+    canvas.draw_line((x_left, y_bottom), (x_right, y_bottom), border_color);
+}
@@ -0,0 +1,80 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use std::hash::Hash;
+use ab_glyph::{Font, PxScale};
+
+use image::Rgb;
+use image_colored_text::draw::coord::DrawCoord;
+use image_colored_text::text::paragraph::ColoredTextParagraph;
+
+use crate::to_image::draw::canvas::DiagramCanvas;
+
+
+
+/** 
+ * Instructions to draw a group of lifelines (e.g., those of a component or of a subsystem).
+ * The lifelines of a group are drawn next to each other within a box 
+ * which runs down the full height of the diagram and which title is drawn above the headers of the lifelines.
+ * **/
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrawableLifelineGroup<LI : Eq + Hash + Copy + Clone> {
+    pub title : ColoredTextParagraph,
+    pub frame_color : Rgb<u8>,
+    // the lifelines that belong to the group but not to any of its sub-groups
+    pub lifelines : Vec<LI>,
+    // groups nested within this group
+    pub sub_groups : Vec<DrawableLifelineGroup<LI>>
+}
+
+impl<LI : Eq + Hash + Copy + Clone> DrawableLifelineGroup<LI> {
+
+    pub fn new(
+        title : ColoredTextParagraph,
+        frame_color : Rgb<u8>,
+        lifelines : Vec<LI>,
+        sub_groups : Vec<DrawableLifelineGroup<LI>>) -> Self {
+        Self { title, frame_color, lifelines, sub_groups }
+    }
+
+}
+
+
+
+pub(crate) fn draw_lifeline_group_frame<Canvas : DiagramCanvas>(    
+    canvas : &mut Canvas,
+    title : &ColoredTextParagraph,
+    (x_left,x_right) : (f32,f32),
+    (y_top,y_bottom) : (f32,f32),
+    color : Rgb<u8>,
+    margin_between_items : f32,
+    font: &impl Font,
+    scale: impl Into<PxScale> + Copy,
+) {
+    canvas.draw_line((x_left, y_top), (x_left, y_bottom), color);
+    canvas.draw_line((x_right, y_top), (x_right, y_bottom), color);
+    canvas.draw_line((x_left, y_top), (x_right, y_top), color);
+    canvas.draw_line((x_left, y_bottom), (x_right, y_bottom), color);
+    canvas.draw_text_paragraph(
+        &DrawCoord::StartingAt(x_left + margin_between_items),
+        &DrawCoord::StartingAt(y_top + margin_between_items/2.0),
+        title,
+        font,
+        scale
+    );
+}
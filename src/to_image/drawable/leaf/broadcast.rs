@@ -18,9 +18,10 @@ limitations under the License.
 
 
 use std::hash::Hash;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use ab_glyph::{Font, PxScale};
 
+use image_colored_text::draw::coord::DrawCoord;
 use image_colored_text::text::paragraph::ColoredTextParagraph;
 use crate::to_image::drawable::leaf::util::MessageExchangeLineStyle;
 
@@ -115,6 +116,66 @@ impl TargetLifelineBroadcastDrawInstruction {
         }
     }
 
+    /** 
+     * Same as *get_size_around_midline* for a reception on a lifeline that is created by the message,
+     * the header of that lifeline (of the given size) being drawn centered around the midline.
+     * Preambles are drawn above the header while postambles and centered contents are drawn below it.
+     * **/
+    pub fn get_size_around_midline_of_created_lifeline(
+        &self, 
+        scale: impl Into<PxScale> + Copy,
+        font: &impl Font,
+        (header_width,header_height) : (f32,f32)) -> (f32,f32,f32,f32) 
+    {
+        let (pre_w,pre_h,post_w,post_h) = match &self {
+            TargetLifelineBroadcastDrawInstruction::TwoParts(ref act) => {
+                act.get_size_around_midline(scale, font)
+            },
+            TargetLifelineBroadcastDrawInstruction::Centered(ref act) => {
+                let (width, height, _) = act.content.paragraph_size(scale, font);
+                (0.0,0.0,width,height)
+            }
+        };
+        (
+            f32::max(pre_w,header_width),
+            pre_h + header_height/2.0,
+            f32::max(post_w,header_width),
+            post_h + header_height/2.0
+        )
+    }
+
+    /** 
+     * Returns the texts to draw around the reception (centered around the target lifeline) 
+     * together with their vertical coordinates.
+     * *created_lifeline_header_height* is the height of the header of the target lifeline if it is created by the message.
+     * **/
+    pub(crate) fn get_texts_with_vertical_coords(
+        &self,
+        target_y_midline : f32,
+        created_lifeline_header_height : Option<f32>
+    ) -> Vec<(&ColoredTextParagraph,DrawCoord)> {
+        let half_header_height = created_lifeline_header_height.unwrap_or(0.0)/2.0;
+        let mut texts = vec![];
+        match &self {
+            TargetLifelineBroadcastDrawInstruction::TwoParts(ref act) => {
+                if let Some(preamble) = &act.preamble {
+                    texts.push((preamble,DrawCoord::EndingAt(target_y_midline - half_header_height)));
+                }
+                if let Some(postamble) = &act.postamble {
+                    texts.push((postamble,DrawCoord::StartingAt(target_y_midline + half_header_height)));
+                }
+            },
+            TargetLifelineBroadcastDrawInstruction::Centered(ref act) => {
+                if created_lifeline_header_height.is_some() {
+                    texts.push((&act.content,DrawCoord::StartingAt(target_y_midline + half_header_height)));
+                } else {
+                    texts.push((&act.content,DrawCoord::CenteredAround(target_y_midline)));
+                }
+            }
+        }
+        texts
+    }
+
 }
 
 
@@ -141,7 +202,19 @@ pub struct DrawableBroadcastLeafPattern<LifelineIdentifier : Eq + Hash + Copy +
     // instructions to draw the lifeline targets of the horizontal line
     pub lifeline_targets : HashMap<LifelineIdentifier,TargetLifelineBroadcastDrawInstruction>,
     // instructions to draw the targets that are not lifelines but output gates
-    pub output_outside_gates_targets : Vec<ColoredTextParagraph>
+    pub output_outside_gates_targets : Vec<ColoredTextParagraph>,
+    // the lifeline targets that are created by the message : 
+    // the header of such a lifeline is drawn at the reception and the lifeline starts there
+    pub created_lifelines : HashSet<LifelineIdentifier>,
+    // the lifelines (origin or targets) that are destroyed : 
+    // such a lifeline ends at the emission or reception with a cross
+    pub destroyed_lifelines : HashSet<LifelineIdentifier>,
+    // the lifeline targets that are activated by the message : 
+    // an activation bar starts at the reception
+    pub activated_lifelines : HashSet<LifelineIdentifier>,
+    // the lifelines (origin or targets) that are deactivated : 
+    // the innermost activation bar of such a lifeline ends at the emission or reception
+    pub deactivated_lifelines : HashSet<LifelineIdentifier>
 }
 
 impl<LI : Eq + Hash + Copy + Clone>  DrawableBroadcastLeafPattern<LI> {
@@ -152,7 +225,63 @@ impl<LI : Eq + Hash + Copy + Clone>  DrawableBroadcastLeafPattern<LI> {
         origin : DrawableBroadcastLeafPatternOrigin<LI>,
         lifeline_targets : HashMap<LI,TargetLifelineBroadcastDrawInstruction>,
         output_outside_gates_targets : Vec<ColoredTextParagraph>) -> Self {
-        Self { message, line_style, origin, lifeline_targets, output_outside_gates_targets }
+        Self { 
+            message, 
+            line_style, 
+            origin, 
+            lifeline_targets, 
+            output_outside_gates_targets, 
+            created_lifelines : HashSet::new(), 
+            destroyed_lifelines : HashSet::new(), 
+            activated_lifelines : HashSet::new(), 
+            deactivated_lifelines : HashSet::new() 
+        }
+    }
+
+    pub fn with_created_lifelines(mut self, created_lifelines : HashSet<LI>) -> Self {
+        self.created_lifelines = created_lifelines;
+        self
+    }
+
+    pub fn with_destroyed_lifelines(mut self, destroyed_lifelines : HashSet<LI>) -> Self {
+        self.destroyed_lifelines = destroyed_lifelines;
+        self
+    }
+
+    pub fn with_activated_lifelines(mut self, activated_lifelines : HashSet<LI>) -> Self {
+        self.activated_lifelines = activated_lifelines;
+        self
+    }
+
+    pub fn with_deactivated_lifelines(mut self, deactivated_lifelines : HashSet<LI>) -> Self {
+        self.deactivated_lifelines = deactivated_lifelines;
+        self
+    }
+
+    /** 
+     * Returns the activation depths of the lifelines at the reception of the message (i.e., including the activations it performs)
+     * from their activation depths before the pattern.
+     * **/
+    pub(crate) fn get_activation_depths_at_reception(&self, activation_depths : &HashMap<LI,u32>) -> HashMap<LI,u32> {
+        let mut depths_at_reception = activation_depths.clone();
+        for lf in &self.activated_lifelines {
+            *depths_at_reception.entry(*lf).or_insert(0) += 1;
+        }
+        depths_at_reception
+    }
+
+    /** 
+     * Returns the activation depths of the lifelines after the pattern from their activation depths before the pattern.
+     * Deactivating a lifeline that is not active has no effect.
+     * **/
+    pub(crate) fn get_activation_depths_after(&self, activation_depths : &HashMap<LI,u32>) -> HashMap<LI,u32> {
+        let mut depths_after = self.get_activation_depths_at_reception(activation_depths);
+        for lf in &self.deactivated_lifelines {
+            if let Some(depth) = depths_after.get_mut(lf) {
+                *depth = depth.saturating_sub(1);
+            }
+        }
+        depths_after
     }
 
 }
@@ -18,25 +18,113 @@ limitations under the License.
 use std::hash::Hash;
 use std::collections::HashMap;
 
-use image::RgbImage;
 use ab_glyph::{Font, PxScale};
 
-use image_colored_text::draw::multi_line::draw_multiline_colored_text;
 use image_colored_text::draw::coord::DrawCoord;
 
+use crate::to_image::draw::canvas::DiagramCanvas;
 use crate::to_image::drawable::leaf::broadcast::*;
 use crate::to_image::drawable::leaf::info::*;
 use crate::to_image::drawable::leaf::util::*;
+use crate::to_image::drawable::util::get_lifeline_horizontal_position;
+use crate::to_image::error::DrawError;
 
 
 
 
 impl<LifelineIdentifier : Eq + Hash + Copy + Clone> DrawableBroadcastLeafPattern<LifelineIdentifier> {
 
-pub fn draw(
+    /** 
+     * Returns the vertical position of the midline on which the reception on the given target lifeline is drawn.
+     * It is the midline of the pattern except if the target is the same lifeline as the origin, 
+     * in which case the reception is drawn underneath the emission.
+     * **/
+    pub(crate) fn get_target_y_midline(
+        info : &BroadcastLeafPatternIntermediateInformation<LifelineIdentifier>,
+        tar_lf : &LifelineIdentifier,
+        tar_act : &TargetLifelineBroadcastDrawInstruction,
+        font: &impl Font,
+        scale: impl Into<PxScale> + Copy,
+        y_pos_top_of_pattern : f32, 
+        y_margin_between_seq_operands : f32
+    ) -> f32 {
+        match &info.required_space_under_emission {
+            Some((orig_lf,req_space)) if tar_lf == orig_lf => {
+                let (_,pre_h,_,_) = tar_act.get_size_around_midline(scale, font);
+                y_pos_top_of_pattern + info.y_space_top_to_midline + req_space + y_margin_between_seq_operands + pre_h
+            },
+            _ => {
+                y_pos_top_of_pattern + info.y_space_top_to_midline
+            }
+        }
+    }
+
+    /** 
+     * Returns the width and height of the header of the target lifeline if it is created by the message.
+     * **/
+    pub(crate) fn get_created_lifeline_header_size(
+        &self,
+        info : &BroadcastLeafPatternIntermediateInformation<LifelineIdentifier>,
+        tar_lf : &LifelineIdentifier
+    ) -> Option<(f32,f32)> {
+        if self.created_lifelines.contains(tar_lf) {
+            info.created_lifelines_headers_sizes.get(tar_lf).copied()
+        } else {
+            None
+        }
+    }
+
+    /** 
+     * Returns the horizontal position at which an arrow coming from the left (or right) ends on the target lifeline.
+     * Arrows which create lifelines end on the side of their headers 
+     * and arrows which reach active lifelines end on the side of their activation bars.
+     * **/
+    fn get_target_arrow_tip_x_pos(
+        &self,
+        info : &BroadcastLeafPatternIntermediateInformation<LifelineIdentifier>,
+        tar_lf : &LifelineIdentifier,
+        tar_lf_x_pos : f32,
+        from_the_left : bool
+    ) -> f32 {
+        match self.get_created_lifeline_header_size(info, tar_lf) {
+            Some((header_width,_)) if from_the_left => {
+                tar_lf_x_pos - header_width/2.0
+            },
+            Some((header_width,_)) => {
+                tar_lf_x_pos + header_width/2.0
+            },
+            None => {
+                let (bars_on_the_left,bars_on_the_right) = info.targets_activation_bars_extents.get(tar_lf).copied().unwrap_or((0.0,0.0));
+                if from_the_left {
+                    tar_lf_x_pos - bars_on_the_left
+                } else {
+                    tar_lf_x_pos + bars_on_the_right
+                }
+            }
+        }
+    }
+
+    /** 
+     * Returns the horizontal position at which an arrow leaving towards the left (or right) starts on the origin lifeline 
+     * i.e., on the side of its activation bars if it is active.
+     * **/
+    fn get_origin_arrow_start_x_pos(
+        info : &BroadcastLeafPatternIntermediateInformation<LifelineIdentifier>,
+        orig_lf_x_pos : f32,
+        towards_the_left : bool
+    ) -> f32 {
+        let (bars_on_the_left,bars_on_the_right) = info.origin_activation_bars_extents;
+        if towards_the_left {
+            orig_lf_x_pos - bars_on_the_left
+        } else {
+            orig_lf_x_pos + bars_on_the_right
+        }
+    }
+
+pub fn draw<Canvas : DiagramCanvas>(
         &self, 
         info : &BroadcastLeafPatternIntermediateInformation<LifelineIdentifier>,
-        image : &mut RgbImage,
+        canvas : &mut Canvas,
         font: &impl Font,
         scale: impl Into<PxScale> + Copy,
         // this gives the vertical shift to add to the relative vertical positions given in *info*
@@ -50,7 +138,7 @@ pub fn draw(
         x_margin_between_items : f32,
         y_margin_between_items : f32,
         y_margin_between_seq_operands : f32, 
-        x_arrow_length : f32) 
+        x_arrow_length : f32) -> Result<(),DrawError>
     {
         // start by drawing the origin
         match &self.origin {
@@ -58,8 +146,7 @@ pub fn draw(
                 // ***
             },
             DrawableBroadcastLeafPatternOrigin::InputOutsideGate(ref gate_parag) => {
-                draw_multiline_colored_text(
-                    image,
+                canvas.draw_text_paragraph(
                     &DrawCoord::EndingAt(left_side_of_diagram_x_pos),
                     &DrawCoord::CenteredAround(y_pos_top_of_pattern + info.y_space_top_to_midline),
                     gate_parag,
@@ -68,11 +155,10 @@ pub fn draw(
                 );
             },
             DrawableBroadcastLeafPatternOrigin::Lifeline(ref orig_lf,ref orig_act) => {
-                let orig_lf_x_pos = *lifelines_horizontal_positions.get(orig_lf).unwrap();
+                let orig_lf_x_pos = get_lifeline_horizontal_position(lifelines_horizontal_positions, orig_lf)?;
                 // ***
                 if let Some(preamble) = &orig_act.preamble {
-                    draw_multiline_colored_text(
-                        image,
+                    canvas.draw_text_paragraph(
                         &DrawCoord::CenteredAround(orig_lf_x_pos),
                         &DrawCoord::EndingAt(y_pos_top_of_pattern + info.y_space_top_to_midline),
                         preamble,
@@ -82,8 +168,7 @@ pub fn draw(
                 }
                 // ***
                 if let Some(postamble) = &orig_act.postamble {
-                    draw_multiline_colored_text(
-                        image,
+                    canvas.draw_text_paragraph(
                         &DrawCoord::CenteredAround(orig_lf_x_pos),
                         &DrawCoord::StartingAt(y_pos_top_of_pattern + info.y_space_top_to_midline),
                         postamble,
@@ -96,56 +181,20 @@ pub fn draw(
 
         // then draw the lifeline targets
         for (tar_lf, tar_act) in &self.lifeline_targets {
-            let tar_lf_x_pos = *lifelines_horizontal_positions.get(tar_lf).unwrap();
+            let tar_lf_x_pos = get_lifeline_horizontal_position(lifelines_horizontal_positions, tar_lf)?;
             // in case the target is the same lifeline as the origin, we need to define a custom midline vertical
-            let target_y_midline = match &info.required_space_under_emission {
-                Some((orig_lf,req_space)) => {
-                    if tar_lf == orig_lf {
-                        let (_,pre_h,_,_) = tar_act.get_size_around_midline(scale, font);
-                        y_pos_top_of_pattern + info.y_space_top_to_midline + req_space + y_margin_between_seq_operands + pre_h
-                    } else {
-                        y_pos_top_of_pattern + info.y_space_top_to_midline
-                    }
-                },
-                None => {
-                    y_pos_top_of_pattern + info.y_space_top_to_midline
-                }
-            };
-            match &tar_act {
-                TargetLifelineBroadcastDrawInstruction::TwoParts(ref lf_act) => {
-                    // ***
-                    if let Some(preamble) = &lf_act.preamble {
-                        draw_multiline_colored_text(
-                            image,
-                            &DrawCoord::CenteredAround(tar_lf_x_pos),
-                            &DrawCoord::EndingAt(target_y_midline),
-                            preamble,
-                            font,
-                            scale
-                        );
-                    }
-                    // ***
-                    if let Some(postamble) = &lf_act.postamble {
-                        draw_multiline_colored_text(
-                            image,
-                            &DrawCoord::CenteredAround(tar_lf_x_pos),
-                            &DrawCoord::StartingAt(target_y_midline),
-                            postamble,
-                            font,
-                            scale
-                        );
-                    }
-                },
-                TargetLifelineBroadcastDrawInstruction::Centered(ref lf_act) => {
-                    draw_multiline_colored_text(
-                        image,
-                        &DrawCoord::CenteredAround(tar_lf_x_pos),
-                        &DrawCoord::CenteredAround(target_y_midline),
-                        &lf_act.content,
-                        font,
-                        scale
-                    );
-                }
+            let target_y_midline = Self::get_target_y_midline(
+                info, tar_lf, tar_act, font, scale, y_pos_top_of_pattern, y_margin_between_seq_operands
+            );
+            let created_lifeline_header_height = self.get_created_lifeline_header_size(info, tar_lf).map(|(_,height)| height);
+            for (text,y_coord) in tar_act.get_texts_with_vertical_coords(target_y_midline, created_lifeline_header_height) {
+                canvas.draw_text_paragraph(
+                    &DrawCoord::CenteredAround(tar_lf_x_pos),
+                    &y_coord,
+                    text,
+                    font,
+                    scale
+                );
             }
         }
 
@@ -153,8 +202,7 @@ pub fn draw(
         {
             let mut y = y_pos_top_of_pattern + info.y_space_top_to_midline - info.y_shift_above_midline_for_output_gates;
             for target_output_gate in &self.output_outside_gates_targets {
-                draw_multiline_colored_text(
-                    image,
+                canvas.draw_text_paragraph(
                     &DrawCoord::StartingAt(right_side_of_diagram_x_pos),
                     &DrawCoord::StartingAt(y),
                     target_output_gate,
@@ -168,10 +216,9 @@ pub fn draw(
         
         // then draw the message
         {
-            let anchor_lifeline_x_pos = *lifelines_horizontal_positions.get(&info.message_drawing_location.0.anchor_lifeline).unwrap();
+            let anchor_lifeline_x_pos = get_lifeline_horizontal_position(lifelines_horizontal_positions, &info.message_drawing_location.0.anchor_lifeline)?;
             let message_x_pos = anchor_lifeline_x_pos + info.message_drawing_location.1;
-            draw_multiline_colored_text(
-                image,
+            canvas.draw_text_paragraph(
                 &DrawCoord::CenteredAround(message_x_pos),
                 &DrawCoord::EndingAt(y_pos_top_of_pattern + info.y_space_top_to_midline - y_margin_between_items),
                 &self.message,
@@ -189,7 +236,7 @@ pub fn draw(
                 (Some(left_side_of_diagram_x_pos),None)
             },
             DrawableBroadcastLeafPatternOrigin::Lifeline(ref orig_lf,_) => {
-                let orig_lf_x_pos = *lifelines_horizontal_positions.get(orig_lf).unwrap();
+                let orig_lf_x_pos = get_lifeline_horizontal_position(lifelines_horizontal_positions, orig_lf)?;
                 (Some(orig_lf_x_pos),Some(*orig_lf))
             }
         };
@@ -209,36 +256,35 @@ pub fn draw(
             };
             single_target = self.lifeline_targets.len() + output_gates_modifier == 1;
             // ***
-            if single_target && has_origin.is_some() {
-                let origin_x_pos = has_origin.unwrap();
-                let (target_x_pos,target_as_lifeline) = {
-                    match self.lifeline_targets.keys().next() {
-                        Some(targ_lf) => {
-                            let targ_x_pos = *lifelines_horizontal_positions.get(targ_lf).unwrap();
-                            (targ_x_pos,Some(*targ_lf))
-                        },
-                        None => {
-                            // then target is an output gate
-                            (right_side_of_diagram_x_pos,None)
+            match has_origin {
+                Some(origin_x_pos) if single_target => {
+                    let (target_x_pos,target_as_lifeline,from_the_left) = {
+                        match self.lifeline_targets.keys().next() {
+                            Some(targ_lf) => {
+                                let targ_x_pos = get_lifeline_horizontal_position(lifelines_horizontal_positions, targ_lf)?;
+                                let from_the_left = origin_x_pos < targ_x_pos;
+                                (self.get_target_arrow_tip_x_pos(info, targ_lf, targ_x_pos, from_the_left),Some(*targ_lf),from_the_left)
+                            },
+                            None => {
+                                // then target is an output gate
+                                (right_side_of_diagram_x_pos,None,true)
+                            }
                         }
-                    }
-                };
-                // origin and target must not be both the same lifeline
-                match (origin_as_lifeline,target_as_lifeline) {
-                    (Some(orig_lf),Some(targ_lf)) => {
-                        if orig_lf == targ_lf {
+                    };
+                    // origin and target must not be both the same lifeline
+                    match (origin_as_lifeline,target_as_lifeline) {
+                        (Some(orig_lf),Some(targ_lf)) if orig_lf == targ_lf => {
                             sends_message_to_self = true;
                             None 
-                        } else {
-                            Some((origin_x_pos,target_x_pos))
+                        },
+                        _ => {
+                            Some((Self::get_origin_arrow_start_x_pos(info, origin_x_pos, !from_the_left),target_x_pos))
                         }
-                    },
-                    _ => {
-                        Some((origin_x_pos,target_x_pos))
                     }
+                },
+                _ => {
+                    None 
                 }
-            } else {
-                None 
             }
         };
 
@@ -246,7 +292,7 @@ pub fn draw(
             Some((x_start,x_end)) => {
                 // we draw a single continuous horizontal arrow
                 draw_message_exchange_horizontal_arrow(
-                    image,
+                    canvas,
                     x_start,
                     x_end,
                     y_pos_top_of_pattern + info.y_space_top_to_midline,
@@ -262,7 +308,7 @@ pub fn draw(
                         None => {
                             // the origin is an input gate
                             draw_message_exchange_horizontal_arrow(
-                                image,
+                                canvas,
                                 origin_x_pos - x_margin_between_items,
                                 origin_x_pos + x_margin_between_items,
                                 y_pos_top_of_pattern + info.y_space_top_to_midline,
@@ -271,25 +317,28 @@ pub fn draw(
                         },
                         Some(_) => {
                             // the origin is a lifeline
+                            let start_x_pos = Self::get_origin_arrow_start_x_pos(
+                                info, *origin_x_pos, info.message_drawing_location.0.draw_message_on_left
+                            );
                             let end_x_pos = if info.message_drawing_location.0.draw_message_on_left {
-                                origin_x_pos - x_arrow_length
+                                start_x_pos - x_arrow_length
                             } else {
-                                origin_x_pos + x_arrow_length
+                                start_x_pos + x_arrow_length
                             };
                             if sends_message_to_self && single_target {
                                 // here it is a message to self with a single target.
                                 // we do not draw the arrowhead on the top midline 
                                 draw_styled_horizontal_line_mut(
-                                    image,
-                                    *origin_x_pos,
+                                    canvas,
+                                    start_x_pos,
                                     end_x_pos,
                                     y_pos_top_of_pattern + info.y_space_top_to_midline,
                                     &self.line_style
                                 );
                             } else {
                                 draw_message_exchange_horizontal_arrow(
-                                    image,
-                                    *origin_x_pos,
+                                    canvas,
+                                    start_x_pos,
                                     end_x_pos,
                                     y_pos_top_of_pattern + info.y_space_top_to_midline,
                                     &self.line_style
@@ -301,7 +350,7 @@ pub fn draw(
 
                 // incoming arrows for the lifeline targets
                 for (tar_lf,tar_act) in &self.lifeline_targets {
-                    let tar_lf_x_pos = *lifelines_horizontal_positions.get(tar_lf).unwrap();
+                    let tar_lf_x_pos = get_lifeline_horizontal_position(lifelines_horizontal_positions, tar_lf)?;
                     let from_the_left = if let Some(orig_x_pos) = &has_origin {
                         // here there is a specific origin that is specified
                         if let Some(orig_lf) = &origin_as_lifeline {
@@ -321,51 +370,45 @@ pub fn draw(
                         // here the origin is the environment so the message comes from the left
                         true
                     };
+                    let tip_x_pos = self.get_target_arrow_tip_x_pos(info, tar_lf, tar_lf_x_pos, from_the_left);
                     let start_x_pos = if from_the_left {
-                        tar_lf_x_pos - x_arrow_length
+                        tip_x_pos - x_arrow_length
                     } else {
-                        tar_lf_x_pos + x_arrow_length
+                        tip_x_pos + x_arrow_length
                     };
                     // in case the target is the same lifeline as the origin, we need to define a custom midline vertical
-                    let (target_y_midline,draw_self_link) = match &info.required_space_under_emission {
-                        Some((orig_lf,req_space)) => {
-                            if tar_lf == orig_lf {
-                                let (_,pre_h,_,_) = tar_act.get_size_around_midline(scale, font);
-                                ( 
-                                    y_pos_top_of_pattern + info.y_space_top_to_midline + req_space + y_margin_between_seq_operands + pre_h,
-                                    Some(y_pos_top_of_pattern + info.y_space_top_to_midline)
-                                )
-                            } else {
-                                ( 
-                                    y_pos_top_of_pattern + info.y_space_top_to_midline,
-                                    None 
-                                )
-                            }
+                    let target_y_midline = Self::get_target_y_midline(
+                        info, tar_lf, tar_act, font, scale, y_pos_top_of_pattern, y_margin_between_seq_operands
+                    );
+                    let draw_self_link = match &info.required_space_under_emission {
+                        Some((orig_lf,_)) if tar_lf == orig_lf => {
+                            Some(y_pos_top_of_pattern + info.y_space_top_to_midline)
                         },
-                        None => {
-                            (
-                                y_pos_top_of_pattern + info.y_space_top_to_midline,
-                                None 
-                            )
+                        _ => {
+                            None 
                         }
                     };
                     // draw the arrow
                     draw_message_exchange_horizontal_arrow(
-                        image,
+                        canvas,
                         start_x_pos,
-                        tar_lf_x_pos,
+                        tip_x_pos,
                         target_y_midline,
                         &self.line_style
                     );
                     // here the target lifeline is the same as the emiting lifeline so we need to add a recurved arrow from top to bottom on the same lifeline
                     if let Some(top_y_midline) = draw_self_link {
-                        // the horizontal part of the top midline
+                        // the horizontal part of the top midline (which starts on the activation bars of the emission)
                         draw_styled_horizontal_line_mut(
-                            image,start_x_pos,tar_lf_x_pos,top_y_midline,&self.line_style
+                            canvas,
+                            start_x_pos,
+                            Self::get_origin_arrow_start_x_pos(info, tar_lf_x_pos, from_the_left),
+                            top_y_midline,
+                            &self.line_style
                         );
                         // the vertical part linking the top midline (on the emitting action) to the bottom midline (on the receiving action)
                         draw_styled_vertical_line_mut(
-                            image, top_y_midline, target_y_midline, start_x_pos, &self.line_style
+                            canvas, top_y_midline, target_y_midline, start_x_pos, &self.line_style
                         );
                     }
                 }
@@ -373,7 +416,7 @@ pub fn draw(
                 // finally incoming arrow for the target output gates if there are any
                 if !self.output_outside_gates_targets.is_empty() {
                     draw_message_exchange_horizontal_arrow(
-                        image,
+                        canvas,
                         right_side_of_diagram_x_pos - x_margin_between_items,
                         right_side_of_diagram_x_pos + x_margin_between_items,
                         y_pos_top_of_pattern + info.y_space_top_to_midline,
@@ -384,7 +427,7 @@ pub fn draw(
             }
         }
         
-
+        Ok(())
     }
 
 }
\ No newline at end of file
@@ -19,8 +19,11 @@ use std::hash::Hash;
 use std::collections::{HashSet,HashMap};
 use ab_glyph::{Font, PxScale};
 
+use crate::to_image::drawable::activation::get_activation_bars_horizontal_extents;
 use crate::to_image::drawable::leaf::broadcast::*;
 use crate::to_image::drawable::leaf::info::*;
+use crate::to_image::drawable::util::*;
+use crate::to_image::error::DrawError;
 
 
 
@@ -34,7 +37,62 @@ impl<LifelineIdentifier : Eq + Hash + Copy + Clone> DrawableBroadcastLeafPattern
         y_margin_between_seq_operands : f32,
         x_margin_between_items : f32,
         y_margin_between_items : f32,
-    ) -> BroadcastLeafPatternIntermediateInformation<LifelineIdentifier> {
+        // the width and height of the headers of the lifelines that are created by the message
+        created_lifelines_headers_sizes : HashMap<LifelineIdentifier,(f32,f32)>,
+        // the activation depths of the lifelines before the pattern
+        activation_depths : &HashMap<LifelineIdentifier,u32>,
+        activation_bar_width : f32
+    ) -> Result<BroadcastLeafPatternIntermediateInformation<LifelineIdentifier>,DrawError> {
+        // created lifelines must be targets and destroyed lifelines either the origin or targets
+        let origin_lifeline = match &self.origin {
+            DrawableBroadcastLeafPatternOrigin::Lifeline(ref orig_lf,_) => Some(orig_lf),
+            _ => None
+        };
+        for lf in &self.created_lifelines {
+            if origin_lifeline == Some(lf) || !self.lifeline_targets.contains_key(lf) {
+                return Err(DrawError::InconsistentPattern(
+                    "a lifeline that is created by a broadcast pattern must be one of its targets and not its origin".to_owned()
+                ));
+            }
+        }
+        for lf in &self.destroyed_lifelines {
+            if origin_lifeline != Some(lf) && !self.lifeline_targets.contains_key(lf) {
+                return Err(DrawError::InconsistentPattern(
+                    "a lifeline that is destroyed by a broadcast pattern must be its origin or one of its targets".to_owned()
+                ));
+            }
+        }
+        // likewise, activated lifelines must be targets and deactivated lifelines either the origin or targets
+        for lf in &self.activated_lifelines {
+            if !self.lifeline_targets.contains_key(lf) {
+                return Err(DrawError::InconsistentPattern(
+                    "a lifeline that is activated by a broadcast pattern must be one of its targets".to_owned()
+                ));
+            }
+        }
+        for lf in &self.deactivated_lifelines {
+            if origin_lifeline != Some(lf) && !self.lifeline_targets.contains_key(lf) {
+                return Err(DrawError::InconsistentPattern(
+                    "a lifeline that is deactivated by a broadcast pattern must be its origin or one of its targets".to_owned()
+                ));
+            }
+        }
+        // the emission is drawn on the activation bars of the origin before the pattern
+        // while the receptions are drawn on those of the targets including the activations performed by the pattern
+        let origin_activation_bars_extents = match origin_lifeline {
+            Some(orig_lf) => {
+                get_activation_bars_horizontal_extents(activation_depths.get(orig_lf).copied().unwrap_or(0), activation_bar_width)
+            },
+            None => {
+                (0.0,0.0)
+            }
+        };
+        let targets_activation_bars_extents : HashMap<LifelineIdentifier,(f32,f32)> = {
+            let depths_at_reception = self.get_activation_depths_at_reception(activation_depths);
+            self.lifeline_targets.keys().map(
+                |tar_lf| (*tar_lf,get_activation_bars_horizontal_extents(depths_at_reception.get(tar_lf).copied().unwrap_or(0), activation_bar_width))
+            ).collect()
+        };
         let minimum_horizontal_space_for_involved_lifeliens = LifelineRequiredHorizontalSpaceInDiagram::new(
             2.5*x_margin_between_items, 
             2.5*x_margin_between_items
@@ -60,27 +118,21 @@ impl<LifelineIdentifier : Eq + Hash + Copy + Clone> DrawableBroadcastLeafPattern
         // we start by dealing with the origin
         match &self.origin {
             DrawableBroadcastLeafPatternOrigin::Empty => {
-                let leftmost_lf_id = *self.lifeline_targets.keys()
-                    .min_by(|l1,l2|
-                        {
-                            let l1_idx = all_lifelines_in_diagram.iter().position(|l| l == *l1).unwrap();
-                            let l2_idx = all_lifelines_in_diagram.iter().position(|l| l == *l2).unwrap();
-                            l1_idx.cmp(&l2_idx)
-                        }
-                    )
-                    .unwrap();
+                let leftmost_lf_id = get_leftmost_lifeline_in_diagram(
+                    all_lifelines_in_diagram, 
+                    self.lifeline_targets.keys()
+                )?.ok_or(DrawError::InconsistentPattern(
+                    "a broadcast pattern without a lifeline origin must have at least one lifeline target".to_owned()
+                ))?;
                 message_drawing_location = MessageDrawingLocation::new(leftmost_lf_id,true);
             },
             DrawableBroadcastLeafPatternOrigin::InputOutsideGate(ref gate_parag) => {
-                let leftmost_lf_id = *self.lifeline_targets.keys()
-                    .min_by(|l1,l2|
-                        {
-                            let l1_idx = all_lifelines_in_diagram.iter().position(|l| l == *l1).unwrap();
-                            let l2_idx = all_lifelines_in_diagram.iter().position(|l| l == *l2).unwrap();
-                            l1_idx.cmp(&l2_idx)
-                        }
-                    )
-                    .unwrap();
+                let leftmost_lf_id = get_leftmost_lifeline_in_diagram(
+                    all_lifelines_in_diagram, 
+                    self.lifeline_targets.keys()
+                )?.ok_or(DrawError::InconsistentPattern(
+                    "a broadcast pattern without a lifeline origin must have at least one lifeline target".to_owned()
+                ))?;
                 message_drawing_location = MessageDrawingLocation::new(leftmost_lf_id,true);
                 // ***
                 let (gate_width, gate_height, _) = gate_parag.paragraph_size(scale, font);
@@ -92,6 +144,7 @@ impl<LifelineIdentifier : Eq + Hash + Copy + Clone> DrawableBroadcastLeafPattern
             DrawableBroadcastLeafPatternOrigin::Lifeline(ref orig_lf,ref orig_act) => {
                 let (pre_w,pre_h,post_w,post_h) = orig_act.get_size_around_midline(scale, font);
                 let half_max_act_width = (f32::max(pre_w,post_w) + x_margin_between_items) /2.0;
+                let (bars_on_the_left,bars_on_the_right) = origin_activation_bars_extents;
                 // memorize that for later
                 required_space_under_emission = Some((*orig_lf,f32::max(post_h,2.0*y_margin_between_items)));
                 // ***
@@ -100,26 +153,29 @@ impl<LifelineIdentifier : Eq + Hash + Copy + Clone> DrawableBroadcastLeafPattern
                 // by default the message is written on the right of the emitting lifeline
                 // except if there are lifeline targets and these targets are on its left
                 // and there is no output gate
+                let orig_lf_idx = get_lifeline_index_in_diagram(all_lifelines_in_diagram, orig_lf)?;
+                let mut all_targets_on_the_left = true;
+                for tar_lf in self.lifeline_targets.keys() {
+                    if get_lifeline_index_in_diagram(all_lifelines_in_diagram, tar_lf)? > orig_lf_idx {
+                        all_targets_on_the_left = false;
+                    }
+                }
                 let draw_message_on_left = 
                     (!self.lifeline_targets.is_empty())
                     &&
-                    self.lifeline_targets.iter()
-                    .all(
-                        |(tar_lf,_)| {
-                            let orig_lf_idx = all_lifelines_in_diagram.iter().position(|l| l == orig_lf).unwrap();
-                            let tar_lf_idx = all_lifelines_in_diagram.iter().position(|l| l == tar_lf).unwrap();
-                            tar_lf_idx <= orig_lf_idx
-                         }
-                    )
+                    all_targets_on_the_left
                     &&
                     self.output_outside_gates_targets.is_empty();
                 // ***
                 message_drawing_location = MessageDrawingLocation::new(*orig_lf, draw_message_on_left);
-                message_drawing_location_lf_horizontal_reqs_if_emission = Some(
-                    LifelineRequiredHorizontalSpaceInDiagram::new(half_max_act_width, half_max_act_width)
+                // the message is drawn beside the activation bars of the origin
+                let reqs_of_emission = LifelineRequiredHorizontalSpaceInDiagram::new(
+                    f32::max(half_max_act_width, bars_on_the_left), 
+                    f32::max(half_max_act_width, bars_on_the_right)
                 );
+                message_drawing_location_lf_horizontal_reqs_if_emission = Some(reqs_of_emission.clone());
                 // ***
-                let mut reqs = LifelineRequiredHorizontalSpaceInDiagram::new(half_max_act_width, half_max_act_width);
+                let mut reqs = reqs_of_emission;
                 reqs.update_to_max(minimum_horizontal_space_for_involved_lifeliens.clone());
                 lifelines_horizontal_requirements.insert(*orig_lf, reqs);
                 // ***
@@ -129,9 +185,21 @@ impl<LifelineIdentifier : Eq + Hash + Copy + Clone> DrawableBroadcastLeafPattern
         // ***
         // now let us deal with the lifeline targets
         for (tar_lf, tar_act) in &self.lifeline_targets {
-            let (pre_w,pre_h,post_w,post_h) = tar_act.get_size_around_midline(scale, font);
+            let (pre_w,pre_h,post_w,post_h) = match created_lifelines_headers_sizes.get(tar_lf) {
+                Some(header_size) if self.created_lifelines.contains(tar_lf) => {
+                    // the header of the created lifeline is drawn at the reception
+                    tar_act.get_size_around_midline_of_created_lifeline(scale, font, *header_size)
+                },
+                _ => {
+                    tar_act.get_size_around_midline(scale, font)
+                }
+            };
             let half_max_act_width = (f32::max(pre_w,post_w) + x_margin_between_items) /2.0;
-            let mut new_reqs = LifelineRequiredHorizontalSpaceInDiagram::new(half_max_act_width, half_max_act_width);
+            let (bars_on_the_left,bars_on_the_right) = targets_activation_bars_extents.get(tar_lf).copied().unwrap_or((0.0,0.0));
+            let mut new_reqs = LifelineRequiredHorizontalSpaceInDiagram::new(
+                f32::max(half_max_act_width, bars_on_the_left), 
+                f32::max(half_max_act_width, bars_on_the_right)
+            );
             // ***
             let mut default_upd_y_space_top_to_midline = pre_h;
             let mut default_upd_y_space_midline_to_bottom = post_h;
@@ -142,7 +210,9 @@ impl<LifelineIdentifier : Eq + Hash + Copy + Clone> DrawableBroadcastLeafPattern
                     default_upd_y_space_midline_to_bottom = req_space_under_emission + y_margin_between_seq_operands + pre_h + post_h + y_margin_between_items;
                     // also this means that we have already added a horizontal requirements for that lifeline so we need to retrieve it
                     // and keep the maximum horizontal space requirement
-                    new_reqs.update_to_max(lifelines_horizontal_requirements.remove(orig_lf).unwrap());
+                    if let Some(orig_reqs) = lifelines_horizontal_requirements.remove(orig_lf) {
+                        new_reqs.update_to_max(orig_reqs);
+                    }
                 }
             }
             // ***
@@ -160,11 +230,11 @@ impl<LifelineIdentifier : Eq + Hash + Copy + Clone> DrawableBroadcastLeafPattern
         {
             // the lifeline close to which to draw the message
             let msg_anchor_lf = message_drawing_location.anchor_lifeline;
-            let msg_anchor_lf_idx = all_lifelines_in_diagram.iter().position(|l| *l == msg_anchor_lf).unwrap();
+            let msg_anchor_lf_idx = get_lifeline_index_in_diagram(all_lifelines_in_diagram, &msg_anchor_lf)?;
             let mut msg_anchor_lf_previous_horizontal_reqs = if let Some(x) = message_drawing_location_lf_horizontal_reqs_if_emission {
                 x 
             } else {
-                lifelines_horizontal_requirements.get(&msg_anchor_lf).unwrap().clone()
+                lifelines_horizontal_requirements.get(&msg_anchor_lf).cloned().unwrap_or_else(LifelineRequiredHorizontalSpaceInDiagram::new_empty)
             };
             // ***
             if message_drawing_location.draw_message_on_left {
@@ -173,7 +243,7 @@ impl<LifelineIdentifier : Eq + Hash + Copy + Clone> DrawableBroadcastLeafPattern
                 // ***
                 if msg_anchor_lf_idx > 0 {
                     // if there is a neighbor to its left then the width of the message may be shared between the two
-                    let left_neighbor_lf = all_lifelines_in_diagram.get(msg_anchor_lf_idx - 1).unwrap();
+                    let left_neighbor_lf = &all_lifelines_in_diagram[msg_anchor_lf_idx - 1];
                     let mut left_neighbor_req = if let Some(x) = lifelines_horizontal_requirements.remove(left_neighbor_lf) {
                         x
                     } else {
@@ -181,9 +251,9 @@ impl<LifelineIdentifier : Eq + Hash + Copy + Clone> DrawableBroadcastLeafPattern
                     };
                     // half of the width is for the left side of the anchor lifeline
                     msg_anchor_lf_previous_horizontal_reqs.on_the_left += x_margin_between_items + msg_txt_width/2.0;
-                    lifelines_horizontal_requirements.get_mut(&msg_anchor_lf).unwrap().update_to_max(
-                        msg_anchor_lf_previous_horizontal_reqs 
-                    );
+                    lifelines_horizontal_requirements.entry(msg_anchor_lf)
+                        .or_insert_with(LifelineRequiredHorizontalSpaceInDiagram::new_empty)
+                        .update_to_max(msg_anchor_lf_previous_horizontal_reqs);
                     // the other half of the width is for the right side of the left neighbor of the anchor lifeline
                     // with added margin
                     left_neighbor_req.on_the_right += x_margin_between_items + msg_txt_width/2.0;
@@ -191,9 +261,9 @@ impl<LifelineIdentifier : Eq + Hash + Copy + Clone> DrawableBroadcastLeafPattern
                 } else {
                     // otherwise the width of the message must be added to the required space on the left
                     msg_anchor_lf_previous_horizontal_reqs.on_the_left += x_margin_between_items + msg_txt_width;
-                    lifelines_horizontal_requirements.get_mut(&msg_anchor_lf).unwrap().update_to_max(
-                        msg_anchor_lf_previous_horizontal_reqs 
-                    );
+                    lifelines_horizontal_requirements.entry(msg_anchor_lf)
+                        .or_insert_with(LifelineRequiredHorizontalSpaceInDiagram::new_empty)
+                        .update_to_max(msg_anchor_lf_previous_horizontal_reqs);
                 }
             } else {
                 precise_message_drawing_location_x_shift_wrt_anchor_lifeline = msg_anchor_lf_previous_horizontal_reqs.on_the_right + msg_txt_width/2.0 + x_margin_between_items;
@@ -202,7 +272,7 @@ impl<LifelineIdentifier : Eq + Hash + Copy + Clone> DrawableBroadcastLeafPattern
                 // ***
                 if msg_anchor_lf_idx < (all_lifelines_in_diagram.len() - 1) {
                     // if there is a neighbor to its right then the width of the message may be shared between the two
-                    let right_neighbor_lf = all_lifelines_in_diagram.get(msg_anchor_lf_idx + 1).unwrap();
+                    let right_neighbor_lf = &all_lifelines_in_diagram[msg_anchor_lf_idx + 1];
                     let mut right_neighbor_req = if let Some(x) = lifelines_horizontal_requirements.remove(right_neighbor_lf) {
                         x
                     } else {
@@ -210,9 +280,9 @@ impl<LifelineIdentifier : Eq + Hash + Copy + Clone> DrawableBroadcastLeafPattern
                     };
                     // half of the width is for the right side of the anchor lifeline
                     msg_anchor_lf_previous_horizontal_reqs.on_the_right += x_margin_between_items + msg_txt_width/2.0;
-                    lifelines_horizontal_requirements.get_mut(&msg_anchor_lf).unwrap().update_to_max(
-                        msg_anchor_lf_previous_horizontal_reqs 
-                    );
+                    lifelines_horizontal_requirements.entry(msg_anchor_lf)
+                        .or_insert_with(LifelineRequiredHorizontalSpaceInDiagram::new_empty)
+                        .update_to_max(msg_anchor_lf_previous_horizontal_reqs);
                     // the other half of the width is for the left side of the right neighbor of the anchor lifeline
                     // with added margin
                     right_neighbor_req.on_the_left += x_margin_between_items + msg_txt_width/2.0;
@@ -220,9 +290,9 @@ impl<LifelineIdentifier : Eq + Hash + Copy + Clone> DrawableBroadcastLeafPattern
                 } else {
                     // otherwise the width of the message must be added to the required space on the right
                     msg_anchor_lf_previous_horizontal_reqs.on_the_right += x_margin_between_items + msg_txt_width;
-                    lifelines_horizontal_requirements.get_mut(&msg_anchor_lf).unwrap().update_to_max(
-                        msg_anchor_lf_previous_horizontal_reqs 
-                    );
+                    lifelines_horizontal_requirements.entry(msg_anchor_lf)
+                        .or_insert_with(LifelineRequiredHorizontalSpaceInDiagram::new_empty)
+                        .update_to_max(msg_anchor_lf_previous_horizontal_reqs);
                 }
             }
         }
@@ -245,7 +315,7 @@ impl<LifelineIdentifier : Eq + Hash + Copy + Clone> DrawableBroadcastLeafPattern
         };
         // ***
         let y_space_top_to_bottom = y_space_top_to_midline + y_space_midline_to_bottom;
-        BroadcastLeafPatternIntermediateInformation::new(
+        Ok(BroadcastLeafPatternIntermediateInformation::new(
             y_space_top_to_bottom, 
             y_space_top_to_midline,
             (message_drawing_location,precise_message_drawing_location_x_shift_wrt_anchor_lifeline),
@@ -254,8 +324,11 @@ impl<LifelineIdentifier : Eq + Hash + Copy + Clone> DrawableBroadcastLeafPattern
             required_space_under_emission,
             input_gate_width,
             output_gates_max_width,
-            y_shift_above_midline_for_output_gates
-        )
+            y_shift_above_midline_for_output_gates,
+            created_lifelines_headers_sizes,
+            origin_activation_bars_extents,
+            targets_activation_bars_extents
+        ))
     }
 }
 
@@ -46,7 +46,13 @@ use std::collections::{HashMap, HashSet};
     // max width of the output gates (0.0 if there are none)
     pub output_gates_max_width : f32,
     // vertical space above the midline on which to start drawing the output gates from top to bottom
-    pub y_shift_above_midline_for_output_gates : f32
+    pub y_shift_above_midline_for_output_gates : f32,
+    // the width and height of the headers of the lifelines that are created by the message
+    pub created_lifelines_headers_sizes : HashMap<LifelineIdentifier,(f32,f32)>,
+    // the horizontal space occupied on the left and on the right of the origin lifeline by its activation bars at the emission
+    pub origin_activation_bars_extents : (f32,f32),
+    // the horizontal space occupied on the left and on the right of each target lifeline by its activation bars at the reception
+    pub targets_activation_bars_extents : HashMap<LifelineIdentifier,(f32,f32)>
 }
 
 impl<LifelineIdentifier : Eq + Hash + Copy + Clone> BroadcastLeafPatternIntermediateInformation<LifelineIdentifier> {
@@ -60,7 +66,10 @@ impl<LifelineIdentifier : Eq + Hash + Copy + Clone> BroadcastLeafPatternIntermed
         required_space_under_emission : Option<(LifelineIdentifier, f32)>,
         input_gate_width : f32,
         output_gates_max_width : f32,
-        y_shift_above_midline_for_output_gates : f32
+        y_shift_above_midline_for_output_gates : f32,
+        created_lifelines_headers_sizes : HashMap<LifelineIdentifier,(f32,f32)>,
+        origin_activation_bars_extents : (f32,f32),
+        targets_activation_bars_extents : HashMap<LifelineIdentifier,(f32,f32)>
     ) -> BroadcastLeafPatternIntermediateInformation<LifelineIdentifier> {
         BroadcastLeafPatternIntermediateInformation{
             y_space_top_to_bottom,
@@ -71,7 +80,10 @@ impl<LifelineIdentifier : Eq + Hash + Copy + Clone> BroadcastLeafPatternIntermed
             required_space_under_emission,
             input_gate_width,
             output_gates_max_width,
-            y_shift_above_midline_for_output_gates
+            y_shift_above_midline_for_output_gates,
+            created_lifelines_headers_sizes,
+            origin_activation_bars_extents,
+            targets_activation_bars_extents
         }
         }
 }
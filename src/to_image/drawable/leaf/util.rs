@@ -14,133 +14,210 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
-use image::{Rgb, RgbImage};
-use imageproc::drawing::draw_line_segment_mut;
+use image::Rgb;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::to_image::draw::canvas::DiagramCanvas;
 
 
 // **********
 
 
+/** 
+ * The pattern with which the lines of a message exchange are drawn (e.g., dashed lines for replies).
+ * **/
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MessageLineDashPattern {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted
+}
+
+impl MessageLineDashPattern {
+
+    /** 
+     * Returns the lengths of the dashes and of the gaps between them, if the line is not solid.
+     * These lengths are proportional to the length of the arrowheads so that the pattern follows the scale of the drawing
+     * (without arrowheads to scale against, the line is drawn solid).
+     * **/
+    pub fn get_dash_and_gap_lengths(&self, arrowhead_length : f32) -> Option<(f32,f32)> {
+        match self {
+            _ if arrowhead_length <= 0.0 => None,
+            MessageLineDashPattern::Solid => None,
+            MessageLineDashPattern::Dashed => Some((0.6*arrowhead_length,0.4*arrowhead_length)),
+            MessageLineDashPattern::Dotted => Some((0.2*arrowhead_length,0.3*arrowhead_length))
+        }
+    }
+
+}
+
+
+/** 
+ * The arrowhead drawn at the tip of a message exchange : 
+ * an open arrowhead (e.g., for replies), a filled triangle (e.g., for synchronous calls), 
+ * the upper half of an open arrowhead (e.g., for asynchronous signals) or no arrowhead at all.
+ * **/
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MessageArrowheadKind {
+    #[default]
+    Open,
+    Filled,
+    Half,
+    None
+}
+
+
+/** 
+ * The decoration drawn at the tail of a message exchange, 
+ * e.g., a filled circle for a found message (which sender is unknown).
+ * **/
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MessageArrowTailDecoration {
+    #[default]
+    None,
+    Circle
+}
+
+
+
 pub struct MessageExchangeLineStyle {
     pub bold : bool,
     pub doubled : bool,
     pub color : Rgb<u8>,
-    pub arrowhead_length : f32
+    pub arrowhead_length : f32,
+    pub dash_pattern : MessageLineDashPattern,
+    pub arrowhead_kind : MessageArrowheadKind,
+    pub tail_decoration : MessageArrowTailDecoration
 }
 
 
 impl MessageExchangeLineStyle {
+    /** 
+     * Creates a solid line style with an open arrowhead and no tail decoration.
+     * **/
     pub fn new(bold : bool,doubled : bool,color : Rgb<u8>,arrowhead_length : f32) -> MessageExchangeLineStyle {
         MessageExchangeLineStyle{
-            bold,doubled,color,arrowhead_length
+            bold,
+            doubled,
+            color,
+            arrowhead_length,
+            dash_pattern : MessageLineDashPattern::default(),
+            arrowhead_kind : MessageArrowheadKind::default(),
+            tail_decoration : MessageArrowTailDecoration::default()
         }
     }
+
+    pub fn with_dash_pattern(mut self, dash_pattern : MessageLineDashPattern) -> Self {
+        self.dash_pattern = dash_pattern;
+        self
+    }
+
+    pub fn with_arrowhead_kind(mut self, arrowhead_kind : MessageArrowheadKind) -> Self {
+        self.arrowhead_kind = arrowhead_kind;
+        self
+    }
+
+    pub fn with_tail_decoration(mut self, tail_decoration : MessageArrowTailDecoration) -> Self {
+        self.tail_decoration = tail_decoration;
+        self
+    }
 }
 
 
-pub fn draw_message_exchange_horizontal_arrow(image : &mut RgbImage, x_start : f32, x_end : f32, y_pos : f32, style : &MessageExchangeLineStyle) {
-    draw_styled_horizontal_line_mut(image,x_start,x_end,y_pos,style);
-    if x_start < x_end {
-        draw_arrowhead_rightward(image,x_end,y_pos,style.arrowhead_length,style.color);
-    } else {
-        draw_arrowhead_leftward(image,x_end,y_pos,style.arrowhead_length,style.color);
+pub fn draw_message_exchange_horizontal_arrow<Canvas : DiagramCanvas>(canvas : &mut Canvas, x_start : f32, x_end : f32, y_pos : f32, style : &MessageExchangeLineStyle) {
+    draw_styled_horizontal_line_mut(canvas,x_start,x_end,y_pos,style);
+    let pointing_rightward = x_start < x_end;
+    match style.arrowhead_kind {
+        MessageArrowheadKind::Open => {
+            canvas.draw_arrowhead((x_end,y_pos),pointing_rightward,style.arrowhead_length,style.color);
+        },
+        MessageArrowheadKind::Filled => {
+            canvas.draw_filled_arrowhead((x_end,y_pos),pointing_rightward,style.arrowhead_length,style.color);
+        },
+        MessageArrowheadKind::Half => {
+            let x_back = if pointing_rightward {
+                x_end - style.arrowhead_length
+            } else {
+                x_end + style.arrowhead_length
+            };
+            canvas.draw_line((x_end,y_pos),(x_back,y_pos - style.arrowhead_length),style.color);
+        },
+        MessageArrowheadKind::None => {
+            // ***
+        }
+    }
+    if let MessageArrowTailDecoration::Circle = style.tail_decoration {
+        canvas.draw_filled_circle((x_start,y_pos),style.arrowhead_length/2.0,style.color);
     }
 }
 
 
 
-pub fn draw_styled_horizontal_line_mut(image : &mut RgbImage, x_left : f32, x_right : f32, y_pos : f32, style : &MessageExchangeLineStyle) {
+pub fn draw_styled_horizontal_line_mut<Canvas : DiagramCanvas>(canvas : &mut Canvas, x_left : f32, x_right : f32, y_pos : f32, style : &MessageExchangeLineStyle) {
     if style.doubled {
         let doubling_y_shift = if style.bold {
             2.5
         } else {
             1.5
         };
-        draw_thick_line_segment_mut(image,
+        draw_thick_line_segment_mut(canvas,
             (x_left, y_pos - doubling_y_shift),
             (x_right, y_pos - doubling_y_shift),
-            style.color, 
-            style.bold);
-        draw_thick_line_segment_mut(image,
+            style);
+        draw_thick_line_segment_mut(canvas,
             (x_left, y_pos + doubling_y_shift),
             (x_right, y_pos + doubling_y_shift),
-            style.color, 
-            style.bold);
+            style);
     } else {
-        draw_thick_line_segment_mut(image,
+        draw_thick_line_segment_mut(canvas,
             (x_left, y_pos),
             (x_right, y_pos),
-            style.color, 
-            style.bold);
+            style);
     }
 }
 
 
-pub fn draw_styled_vertical_line_mut(image : &mut RgbImage, y_top : f32, y_bot : f32, x_pos : f32, style : &MessageExchangeLineStyle) {
+pub fn draw_styled_vertical_line_mut<Canvas : DiagramCanvas>(canvas : &mut Canvas, y_top : f32, y_bot : f32, x_pos : f32, style : &MessageExchangeLineStyle) {
     if style.doubled {
         let doubling_x_shift = if style.bold {
             2.5
         } else {
             1.5
         };
-        draw_thick_line_segment_mut(image,
+        draw_thick_line_segment_mut(canvas,
             (x_pos - doubling_x_shift, y_top),
             (x_pos - doubling_x_shift, y_bot),
-            style.color, 
-            style.bold);
-        draw_thick_line_segment_mut(image,
+            style);
+        draw_thick_line_segment_mut(canvas,
             (x_pos + doubling_x_shift, y_top),
             (x_pos + doubling_x_shift, y_bot),
-            style.color, 
-            style.bold);
+            style);
     } else {
-        draw_thick_line_segment_mut(image,
+        draw_thick_line_segment_mut(canvas,
             (x_pos, y_top),
             (x_pos, y_bot),
-            style.color, 
-            style.bold);
+            style);
     }
 }
 
 
 
 
-fn draw_thick_line_segment_mut(image : &mut RgbImage, start: (f32, f32), end: (f32, f32), color: Rgb<u8>, is_thick : bool) {
-    if is_thick {
-        draw_line_segment_mut(image,
-            (start.0, start.1 - 0.5),
-            (end.0, end.1 - 0.5),
-            color);
-        draw_line_segment_mut(image,
-            (start.0, start.1 + 0.5),
-            (end.0, end.1 + 0.5),
-            color);
-    } else {
-        draw_line_segment_mut(image,
-            start,
-            end,
-            color);
+fn draw_thick_line_segment_mut<Canvas : DiagramCanvas>(canvas : &mut Canvas, start: (f32, f32), end: (f32, f32), style : &MessageExchangeLineStyle) {
+    match style.dash_pattern.get_dash_and_gap_lengths(style.arrowhead_length) {
+        Some(dash_and_gap_lengths) => {
+            canvas.draw_dashed_line(start, end, style.color, style.bold, dash_and_gap_lengths);
+        },
+        None if style.bold => {
+            canvas.draw_thick_line(start, end, style.color);
+        },
+        None => {
+            canvas.draw_line(start, end, style.color);
+        }
     }
 }
-
-fn draw_arrowhead_rightward(image : &mut RgbImage, x_pos : f32, y_pos : f32, arrowhead_length : f32, color : Rgb<u8>) {
-    draw_line_segment_mut(image,
-                          (x_pos, y_pos),
-                          (x_pos - arrowhead_length, y_pos - arrowhead_length),
-                          color);
-    draw_line_segment_mut(image,
-                          (x_pos, y_pos),
-                          (x_pos - arrowhead_length, y_pos + arrowhead_length),
-                          color);
-}
-
-fn draw_arrowhead_leftward(image : &mut RgbImage, x_pos : f32, y_pos : f32, arrowhead_length : f32, color : Rgb<u8>) {
-    draw_line_segment_mut(image,
-                          (x_pos, y_pos),
-                          (x_pos + arrowhead_length, y_pos - arrowhead_length),
-                          color);
-    draw_line_segment_mut(image,
-                          (x_pos, y_pos),
-                          (x_pos + arrowhead_length, y_pos + arrowhead_length),
-                          color);
-}
\ No newline at end of file
@@ -15,5 +15,8 @@ limitations under the License.
 */
 
 
+pub mod activation;
+pub mod group;
 pub mod leaf;
-pub mod operator;
\ No newline at end of file
+pub mod operator;
+pub(crate) mod util;
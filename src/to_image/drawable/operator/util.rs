@@ -21,17 +21,98 @@ use std::hash::Hash;
 use std::collections::{HashSet, HashMap};
 use ab_glyph::{Font, PxScale};
 
-use image::{Rgb, RgbImage};
+use image::Rgb;
 use image_colored_text::draw::coord::DrawCoord;
-use image_colored_text::draw::multi_line::draw_multiline_colored_text;
 use image_colored_text::text::paragraph::ColoredTextParagraph;
-use imageproc::drawing::draw_line_segment_mut;
 
+use crate::to_image::draw::canvas::DiagramCanvas;
 use crate::to_image::drawable::leaf::info::LifelineRequiredHorizontalSpaceInDiagram;
+use crate::to_image::drawable::util::get_lifeline_horizontal_position;
+use crate::to_image::error::DrawError;
+
+
+/** 
+ * Returns the vertical positions of the top and the bottom of the operator 
+ * and, in between, those of the separations between its operands.
+ * **/
+pub(crate) fn get_operator_vertical_positions(
+    horizontal_seps_vertical_positions : &[f32]
+) -> Result<(f32,Vec<f32>,f32),DrawError> {
+    match horizontal_seps_vertical_positions {
+        [y_start, y_coords @ .., y_end] => {
+            Ok((*y_start, y_coords.to_vec(), *y_end))
+        },
+        _ => {
+            Err(DrawError::InconsistentPattern("an operator must have at least one operand".to_owned()))
+        }
+    }
+}
+
+
+/** 
+ * Returns the leftmost and rightmost lifelines enclosed in an operator (if there are any).
+ * **/
+pub(crate) fn get_leftmost_and_rightmost_enclosed_lifelines<LI : Eq + Hash + Copy + Clone>(
+    enclosed_lfs_reqs : &HashMap<LI,LifelineRequiredHorizontalSpaceInDiagram>,
+    lifelines_horizontal_positions : &HashMap<LI,f32>
+) -> Result<Option<(LI,LI)>,DrawError> {
+    let mut enclosed_lfs_positions = vec![];
+    for lf in enclosed_lfs_reqs.keys() {
+        enclosed_lfs_positions.push(
+            (get_lifeline_horizontal_position(lifelines_horizontal_positions, lf)?,*lf)
+        );
+    }
+    enclosed_lfs_positions.sort_by(|(x1,_),(x2,_)| x1.total_cmp(x2));
+    match (enclosed_lfs_positions.first(),enclosed_lfs_positions.last()) {
+        (Some((_,leftmost_lf)),Some((_,rightmost_lf))) => {
+            Ok(Some((*leftmost_lf,*rightmost_lf)))
+        },
+        _ => {
+            Ok(None)
+        }
+    }
+}
+
+/** 
+ * Returns the horizontal positions of the left and right sides of the frame of a combined fragment.
+ * **/
+pub(crate) fn get_combined_fragment_frame_horizontal_bounds<LI : Eq + Hash + Copy + Clone>(
+    enclosed_lfs_reqs : &HashMap<LI,LifelineRequiredHorizontalSpaceInDiagram>,
+    nest_padding : f32,
+    leftmost_lf : LI,
+    rightmost_lf : LI,
+    lifelines_horizontal_positions : &HashMap<LI,f32>
+) -> Result<(f32,f32),DrawError> {
+    let x_left = {
+        let leftmost_lf_x_pos = get_lifeline_horizontal_position(lifelines_horizontal_positions, &leftmost_lf)?;
+        let leftmost_lf_hor_reqs = enclosed_lfs_reqs.get(&leftmost_lf).ok_or(DrawError::MissingLifelinePosition)?;
+        leftmost_lf_x_pos + nest_padding - leftmost_lf_hor_reqs.on_the_left
+    };
+    let x_right  = {
+        let rightmost_lf_x_pos = get_lifeline_horizontal_position(lifelines_horizontal_positions, &rightmost_lf)?;
+        let rightmost_lf_hor_reqs = enclosed_lfs_reqs.get(&rightmost_lf).ok_or(DrawError::MissingLifelinePosition)?;
+        rightmost_lf_x_pos + rightmost_lf_hor_reqs.on_the_right - nest_padding
+    };
+    Ok((x_left,x_right))
+}
+
+/** 
+ * Returns the horizontal positions of the left and right sides of the bracket of a co-region on a lifeline.
+ * **/
+pub(crate) fn get_coregion_bracket_horizontal_bounds(
+    lf_x_pos : f32,
+    nest_padding : f32,
+    margin_between_items : f32
+) -> (f32,f32) {
+    (
+        lf_x_pos + nest_padding - 2.0*margin_between_items,
+        lf_x_pos + 2.0*margin_between_items - nest_padding
+    )
+}
 
 
-pub(crate) fn draw_combined_fragment_frame<LI : Eq + Hash + Copy + Clone>(    
-    image : &mut RgbImage,
+pub(crate) fn draw_combined_fragment_frame<LI : Eq + Hash + Copy + Clone, Canvas : DiagramCanvas>(    
+    canvas : &mut Canvas,
     label : &ColoredTextParagraph,
     enclosed_lfs_reqs : &HashMap<LI,LifelineRequiredHorizontalSpaceInDiagram>,
     nest_padding : f32,
@@ -43,116 +124,108 @@ pub(crate) fn draw_combined_fragment_frame<LI : Eq + Hash + Copy + Clone>(
     x_margin_between_items : f32,
     font: &impl Font,
     scale: impl Into<PxScale> + Copy,
-) 
+) -> Result<(),DrawError>
 {
-    let x_left = {
-        let leftmost_lf_x_pos = lifelines_horizontal_positions.get(&leftmost_lf).unwrap();
-        let leftmost_lf_hor_reqs = enclosed_lfs_reqs.get(&leftmost_lf).unwrap();
-        leftmost_lf_x_pos + nest_padding - leftmost_lf_hor_reqs.on_the_left
-    };
-    let x_right  = {
-        let rightmost_lf_x_pos = lifelines_horizontal_positions.get(&rightmost_lf).unwrap();
-        let rightmost_lf_hor_reqs = enclosed_lfs_reqs.get(&rightmost_lf).unwrap();
-        rightmost_lf_x_pos + rightmost_lf_hor_reqs.on_the_right - nest_padding
-    };
-
-    let mut y_coords : Vec<f32> = horizontal_seps_vertical_positions.to_vec();
-    let y_start : f32 = y_coords.remove(0);
-    let y_end : f32 = y_coords.pop().unwrap();
-    draw_line_segment_mut(image,
+    let (x_left,x_right) = get_combined_fragment_frame_horizontal_bounds(
+        enclosed_lfs_reqs, 
+        nest_padding, 
+        leftmost_lf, 
+        rightmost_lf, 
+        lifelines_horizontal_positions
+    )?;
+
+    let (y_start, y_coords, y_end) = get_operator_vertical_positions(horizontal_seps_vertical_positions)?;
+    canvas.draw_line(
                             (x_left, y_start),
                             (x_left, y_end),
                             color);
-    draw_line_segment_mut(image,
+    canvas.draw_line(
                             (x_right, y_start),
                             (x_right, y_end),
                             color);
-    draw_line_segment_mut(image,
+    canvas.draw_line(
                             (x_left, y_start),
                             (x_right, y_start),
                             color);
-    draw_line_segment_mut(image,
+    canvas.draw_line(
                             (x_left, y_end),
                             (x_right, y_end),
                             color);
     for y_coord in y_coords {
-        draw_line_segment_mut(image,
+        canvas.draw_line(
                                 (x_left, y_coord),
                                 (x_right, y_coord),
                                 color);
     }
-    draw_multiline_colored_text(
-        image,
+    canvas.draw_text_paragraph(
         &DrawCoord::StartingAt(x_left + x_margin_between_items),
         &DrawCoord::StartingAt(y_start),
         label,
         font,
         scale
     );
+    Ok(())
 }
 
 
-pub(crate) fn draw_coregion_frame<LI : Eq + Hash + Copy + Clone>(    
-    image : &mut RgbImage,
+pub(crate) fn draw_coregion_frame<LI : Eq + Hash + Copy + Clone, Canvas : DiagramCanvas>(    
+    canvas : &mut Canvas,
     framed_lfs : &HashSet<LI>,
     nest_padding : f32,
     horizontal_seps_vertical_positions : &[f32],
     lifelines_horizontal_positions : &HashMap<LI,f32>,
     color : Rgb<u8>,
     margin_between_items : f32
-)
+) -> Result<(),DrawError>
 {
-
-    let mut y_coords : Vec<f32> = horizontal_seps_vertical_positions.to_vec();
-    let y_start : f32 = y_coords.remove(0);
+    let (y_start, y_coords, y_end) = get_operator_vertical_positions(horizontal_seps_vertical_positions)?;
     // ***
-    let y_end : f32 = y_coords.pop().unwrap();
     for lf in framed_lfs {
-        let lf_x_pos = lifelines_horizontal_positions.get(lf).unwrap();
-        let x_left = lf_x_pos + nest_padding - 2.0*margin_between_items ;
-        let x_right = lf_x_pos + 2.0*margin_between_items - nest_padding;
+        let lf_x_pos = get_lifeline_horizontal_position(lifelines_horizontal_positions, lf)?;
+        let (x_left,x_right) = get_coregion_bracket_horizontal_bounds(lf_x_pos, nest_padding, margin_between_items);
         // ***
-        draw_line_segment_mut(image,
+        canvas.draw_line(
                               (x_left, y_start),
                               (x_right, y_start),
                               color);
-        draw_line_segment_mut(image,
+        canvas.draw_line(
                               (x_left, y_start),
                               (x_left, y_start + margin_between_items),
                               color);
-        draw_line_segment_mut(image,
+        canvas.draw_line(
                               (x_right, y_start),
                               (x_right, y_start + margin_between_items),
                               color);
         // ***
-        draw_line_segment_mut(image,
+        canvas.draw_line(
                               (x_left, y_end),
                               (x_right, y_end),
                               color);
-        draw_line_segment_mut(image,
+        canvas.draw_line(
                               (x_left, y_end),
                               (x_left, y_end - margin_between_items),
                               color);
-        draw_line_segment_mut(image,
+        canvas.draw_line(
                               (x_right, y_end),
                               (x_right, y_end - margin_between_items),
                               color);
         // ***
         for y_coord in &y_coords {
-            draw_line_segment_mut(image,
+            canvas.draw_line(
                                   (x_left, *y_coord),
                                   (x_right, *y_coord),
                                   color);
-            draw_line_segment_mut(image,
+            canvas.draw_line(
                                   (x_left, *y_coord + margin_between_items/2.0),
                                   (x_left, *y_coord - margin_between_items/2.0),
                                   color);
-            draw_line_segment_mut(image,
+            canvas.draw_line(
                                   (x_right, *y_coord + margin_between_items/2.0),
                                   (x_right, *y_coord - margin_between_items/2.0),
                                   color);
         }
     }
+    Ok(())
 }
 
 
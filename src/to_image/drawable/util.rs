@@ -0,0 +1,68 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use std::hash::Hash;
+use std::collections::HashMap;
+
+use crate::to_image::error::DrawError;
+
+
+
+/** 
+ * Returns the index of the lifeline in the (ordered) list of all the lifelines of the diagram.
+ * **/
+pub(crate) fn get_lifeline_index_in_diagram<LI : Eq + Hash + Copy + Clone>(
+    all_lifelines_in_diagram : &[LI],
+    lf : &LI
+) -> Result<usize,DrawError> {
+    all_lifelines_in_diagram.iter().position(|l| l == lf).ok_or(
+        DrawError::InconsistentPattern(
+            "a pattern involves a lifeline that is not among the lifelines of the diagram".to_owned()
+        )
+    )
+}
+
+/** 
+ * Returns, among the given lifelines, the one that is the most on the left of the diagram (if any).
+ * **/
+pub(crate) fn get_leftmost_lifeline_in_diagram<'a, LI : Eq + Hash + Copy + Clone + 'a>(
+    all_lifelines_in_diagram : &[LI],
+    lifelines : impl Iterator<Item = &'a LI>
+) -> Result<Option<LI>,DrawError> {
+    let mut leftmost : Option<(usize,LI)> = None;
+    for lf in lifelines {
+        let lf_idx = get_lifeline_index_in_diagram(all_lifelines_in_diagram, lf)?;
+        match leftmost {
+            Some((min_idx,_)) if min_idx <= lf_idx => {},
+            _ => {
+                leftmost = Some((lf_idx,*lf));
+            }
+        }
+    }
+    Ok(leftmost.map(|(_,lf)| lf))
+}
+
+/** 
+ * Returns the horizontal position at which the lifeline is drawn.
+ * **/
+pub(crate) fn get_lifeline_horizontal_position<LI : Eq + Hash + Copy + Clone>(
+    lifelines_horizontal_positions : &HashMap<LI,f32>,
+    lf : &LI
+) -> Result<f32,DrawError> {
+    lifelines_horizontal_positions.get(lf).copied().ok_or(DrawError::MissingLifelinePosition)
+}
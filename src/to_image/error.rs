@@ -0,0 +1,95 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use std::fmt;
+
+use image::ImageError;
+
+
+
+/** 
+ * Errors that may occur when drawing an interaction as a sequence diagram.
+ * Those related to lifelines or patterns indicate an inconsistency between what the extractor
+ * (*ContextAwareInteractionDrawingInstructionsExtractor*) and the drawer (*ContextAwareInteractionDrawer*)
+ * provide for a given interaction.
+ * **/
+#[derive(Debug)]
+pub enum DrawError {
+    // the diagram could not be written to the file system
+    Io(std::io::Error),
+    // the image could not be encoded (e.g., the file extension does not correspond to a supported format)
+    Encoding(ImageError),
+    // the drawer did not provide a color for one of the lifelines of the diagram
+    MissingLifelineColor,
+    // one of the lifelines that is drawn has not been given a horizontal position in the diagram
+    MissingLifelinePosition,
+    // a pattern or operator cannot be drawn (the reason is given as a description)
+    InconsistentPattern(String),
+    // the pinned lifelines and the lifeline groups cannot be drawn together (the reason is given as a description)
+    InconsistentLifelinePlacement(String)
+}
+
+impl fmt::Display for DrawError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DrawError::Io(e) => {
+                write!(f, "could not write the diagram : {}", e)
+            },
+            DrawError::Encoding(e) => {
+                write!(f, "could not encode the diagram : {}", e)
+            },
+            DrawError::MissingLifelineColor => {
+                write!(f, "no color is provided for a lifeline of the diagram")
+            },
+            DrawError::MissingLifelinePosition => {
+                write!(f, "a lifeline that is drawn has no horizontal position in the diagram")
+            },
+            DrawError::InconsistentPattern(description) => {
+                write!(f, "inconsistent pattern : {}", description)
+            },
+            DrawError::InconsistentLifelinePlacement(description) => {
+                write!(f, "inconsistent lifeline placement : {}", description)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DrawError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DrawError::Io(e) => Some(e),
+            DrawError::Encoding(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<std::io::Error> for DrawError {
+    fn from(e: std::io::Error) -> Self {
+        DrawError::Io(e)
+    }
+}
+
+impl From<ImageError> for DrawError {
+    fn from(e: ImageError) -> Self {
+        match e {
+            ImageError::IoError(io_error) => DrawError::Io(io_error),
+            _ => DrawError::Encoding(e)
+        }
+    }
+}
@@ -19,23 +19,63 @@ limitations under the License.
 
 
 use std::hash::Hash;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 
 use image_colored_text::text::paragraph::ColoredTextParagraph;
 
-use crate::internal_representation::{CommonIoInteractionInterface, InteractionInternalRepresentation};
+use crate::internal_representation::{AnnotatedCommonIoInteractionInterface, InteractionInternalRepresentation};
 use crate::to_image::common_interaction_drawer::CommonInteractionDrawerTrait;
+use crate::to_image::drawable::group::DrawableLifelineGroup;
 use crate::to_image::drawable::operator::builtin_operator::DrawableOperator;
 use crate::to_image::drawable::leaf::broadcast::DrawableBroadcastLeafPattern;
+use crate::to_image::extract::ordering::LifelineOrderingStrategy;
 
 pub trait ContextAwareInteractionDrawingInstructionsExtractor<
-        CioII : CommonIoInteractionInterface, 
+        CioII : AnnotatedCommonIoInteractionInterface, 
         LI : Eq + Hash + Copy + Clone
         > : CommonInteractionDrawerTrait {
 
+    /** 
+     * Compares lifelines according to their order of declaration.
+     * This order is used by the *Declaration* ordering strategy and to break ties in the other strategies.
+     * **/
     fn lifelines_compare(&self, l1 : &LI, l2 : &LI) -> std::cmp::Ordering;
 
+    /** 
+     * Returns how the lifelines of the diagram are ordered from left to right.
+     * By default, they are ordered according to *lifelines_compare*.
+     * **/
+    fn get_lifelines_ordering_strategy(&self) -> LifelineOrderingStrategy {
+        LifelineOrderingStrategy::Declaration
+    }
+
+    /** 
+     * Returns the lifelines which are drawn at fixed positions, whatever the ordering strategy.
+     * Positions are indices (starting at 0 on the left) among the lifelines that occur in the diagram.
+     * By default, no lifeline is pinned.
+     * **/
+    fn get_pinned_lifelines(&self) -> HashMap<LI,usize> {
+        HashMap::new()
+    }
+
+    /** 
+     * Returns the groups of lifelines (which may be nested) that are drawn as boxes around their lifelines.
+     * A lifeline belongs to at most one group (and to its enclosing groups).
+     * By default, there are no groups.
+     * **/
+    fn get_lifeline_groups(&self) -> Vec<DrawableLifelineGroup<LI>> {
+        vec![]
+    }
+
+    /** 
+     * Returns the width of the activation bars drawn on active lifelines.
+     * By default, it is the margin between items.
+     * **/
+    fn get_activation_bar_width(&self) -> f32 {
+        self.get_margin_between_items()
+    }
+
     fn get_involved_lifelines(&self, pattern : &CioII::InteractionLeafPatternType) -> HashSet<LI>;      
 
     fn get_lifeline_header(&self, l : &LI) -> ColoredTextParagraph;
@@ -55,6 +95,19 @@ pub trait ContextAwareInteractionDrawingInstructionsExtractor<
         sub_ints : &[InteractionInternalRepresentation<CioII>]
     ) -> DrawableOperator<LI>;
 
+    /** 
+     * Returns how to draw an annotated sub-interaction, e.g. as a frame whose color and label depend on the annotation.
+     * The *None* case corresponds to an annotation which is not drawn, the annotated sub-interaction being drawn as is.
+     * By default, annotations are not drawn.
+     * **/
+    fn to_drawable_annotation(
+        &self, 
+        _annotation : &CioII::InteractionAnnotationType,
+        _annotated : &InteractionInternalRepresentation<CioII>
+    ) -> Option<DrawableOperator<LI>> {
+        None
+    }
+
 }
 
 
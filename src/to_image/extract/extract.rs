@@ -18,61 +18,83 @@ limitations under the License.
 
 
 use std::hash::Hash;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 
 use image_colored_text::text::paragraph::ColoredTextParagraph;
 
 
 use crate::internal_representation::*;
+use crate::to_image::common_interaction_drawer::CommonInteractionDrawerTrait;
 use crate::to_image::extract::context_aware_extractor::ContextAwareInteractionDrawingInstructionsExtractor;
 use crate::to_image::drawable::leaf::info::LifelineRequiredHorizontalSpaceInDiagram;
 use crate::to_image::extract::instructions::*;
 use crate::to_image::extract::extract_rec::*;
+use crate::to_image::extract::ordering::{get_ordered_lifelines_in_diagram, make_lifeline_groups_contiguous};
+use crate::to_image::drawable::activation::get_activation_bar_horizontal_bounds;
+use crate::to_image::drawable::group::DrawableLifelineGroup;
+use crate::to_image::drawable::leaf::broadcast::{DrawableBroadcastLeafPattern, DrawableBroadcastLeafPatternOrigin};
+use crate::to_image::drawable::util::get_lifeline_horizontal_position;
+use crate::to_image::error::DrawError;
 
 
 
-fn get_all_involved_lifelines_rec<
-    CioII : CommonIoInteractionInterface,
+/** 
+ * A group of lifelines that occurs in the diagram, with its lifelines (including those of its sub-groups) from left to right.
+ * **/
+struct LifelineGroupInDiagram<'a, LI : Eq + Hash + Copy + Clone> {
+    group : &'a DrawableLifelineGroup<LI>,
+    nest_depth : usize,
+    lifelines : Vec<LI>,
+    title_width : f32,
+    title_height : f32
+}
+
+/** 
+ * Lists the groups that occur in the diagram, each group before its sub-groups, and returns their lifelines.
+ * **/
+fn get_lifeline_groups_in_diagram_rec<'a, LI, Context>(
+    context : &Context,
+    positions : &HashMap<LI,usize>,
+    groups : &'a [DrawableLifelineGroup<LI>],
+    nest_depth : usize,
+    groups_in_diagram : &mut Vec<LifelineGroupInDiagram<'a,LI>>
+) -> Vec<LI> 
+where 
     LI : Eq + Hash + Copy + Clone,
-    Context : ContextAwareInteractionDrawingInstructionsExtractor<CioII,LI>
-    > (
-        context : &Context,
-        int_repr : &InteractionInternalRepresentation<CioII>
-    ) -> HashSet<LI> {
-        match int_repr {
-            InteractionInternalRepresentation::LeafPattern(leaf) => {
-                context.get_involved_lifelines(leaf)
-            },
-            InteractionInternalRepresentation::Operator(_, sub_ints) => {
-                sub_ints.iter().fold(
-                    HashSet::new(), 
-                    |mut p,x| {
-                        p.extend(get_all_involved_lifelines_rec(context,x).iter());
-                        p 
-                    }
-                )
-            }
+    Context : CommonInteractionDrawerTrait
+{
+    let mut all_lifelines = vec![];
+    for group in groups {
+        let group_idx = groups_in_diagram.len();
+        let mut lifelines : Vec<LI> = group.lifelines.iter().filter(|lf| positions.contains_key(lf)).cloned().collect();
+        lifelines.extend(get_lifeline_groups_in_diagram_rec(context, positions, &group.sub_groups, nest_depth + 1, groups_in_diagram));
+        if !lifelines.is_empty() {
+            lifelines.sort_by_key(|lf| positions.get(lf));
+            let (title_width, title_height, _) = group.title.paragraph_size(context.get_scale(), context.get_font());
+            all_lifelines.extend(lifelines.iter().cloned());
+            groups_in_diagram.insert(
+                group_idx, 
+                LifelineGroupInDiagram{group,nest_depth,lifelines,title_width,title_height}
+            );
         }
     }
+    all_lifelines
+}
 
 pub(crate) fn extract_drawing_information<CioII,LI,Context> (
     context : &Context,
     int_repr : &InteractionInternalRepresentation<CioII>
-) -> CompleteInteractionDrawInstruction<LI> 
+) -> Result<CompleteInteractionDrawInstruction<LI>,DrawError>
 where 
-    CioII : CommonIoInteractionInterface,
+    CioII : AnnotatedCommonIoInteractionInterface,
     LI : Eq + Hash + Copy + Clone,
     Context : ContextAwareInteractionDrawingInstructionsExtractor<CioII,LI>
 {
 
-let all_lifelines_in_diagram = {
-    let mut all_lifelines : Vec<LI> = get_all_involved_lifelines_rec(context,int_repr).into_iter().collect();
-    all_lifelines.sort_by(
-        |l1,l2| context.lifelines_compare(l1, l2)
-    );
-    all_lifelines
-};
+let lifeline_groups = context.get_lifeline_groups();
+let (ordered_lifelines,mut drawables) = get_ordered_lifelines_in_diagram(context,int_repr);
+let all_lifelines_in_diagram = make_lifeline_groups_contiguous(ordered_lifelines, &lifeline_groups, &context.get_pinned_lifelines())?;
 
 let mut patterns_to_draw = vec![];
 let mut operators_to_draw = vec![];
@@ -81,14 +103,71 @@ let interaction_intermediate_information = extract_intermediate_drawing_informat
     context,
     &all_lifelines_in_diagram,
     int_repr,
+    &mut vec![],
+    &mut drawables,
     &mut relative_y_pos,
     0,
+    &mut HashMap::new(),
     &mut patterns_to_draw,
     &mut operators_to_draw
-);
+)?;
+// the lifelines that are created or destroyed by messages, with the relative vertical positions of their creation or destruction
+// if a lifeline is created (resp. destroyed) several times (e.g., in different alternatives), 
+// its header is drawn at the first creation (resp. it ends at the last destruction)
+let mut lifelines_creations : HashMap<LI,f32> = HashMap::new();
+let mut lifelines_destructions : HashMap<LI,f32> = HashMap::new();
+// the activation bars of the lifelines, given by their lifeline, nesting depth and relative vertical positions of their top and bottom
+// activations and deactivations are matched in the order in which the patterns are drawn
+let mut activations_in_progress : HashMap<LI,Vec<f32>> = HashMap::new();
+let mut activation_bars : Vec<(LI,u32,f32,Option<f32>)> = vec![];
+for leaf_instruct in &patterns_to_draw {
+    let pattern = &leaf_instruct.pattern;
+    let info = &leaf_instruct.intermediate_info;
+    if let DrawableBroadcastLeafPatternOrigin::Lifeline(orig_lf,_) = &pattern.origin {
+        if pattern.destroyed_lifelines.contains(orig_lf) {
+            let y_midline = leaf_instruct.relative_y_pos + info.y_space_top_to_midline;
+            let destruction = lifelines_destructions.entry(*orig_lf).or_insert(y_midline);
+            *destruction = f32::max(*destruction, y_midline);
+        }
+    }
+    let mut targets_y_midlines : HashMap<LI,f32> = HashMap::new();
+    for (tar_lf,tar_act) in &pattern.lifeline_targets {
+        let target_y_midline = DrawableBroadcastLeafPattern::get_target_y_midline(
+            info, tar_lf, tar_act, context.get_font(), context.get_scale(), leaf_instruct.relative_y_pos, context.get_y_margin_between_seq_operands()
+        );
+        if pattern.created_lifelines.contains(tar_lf) {
+            let creation = lifelines_creations.entry(*tar_lf).or_insert(target_y_midline);
+            *creation = f32::min(*creation, target_y_midline);
+        }
+        if pattern.destroyed_lifelines.contains(tar_lf) {
+            let destruction = lifelines_destructions.entry(*tar_lf).or_insert(target_y_midline);
+            *destruction = f32::max(*destruction, target_y_midline);
+        }
+        if pattern.activated_lifelines.contains(tar_lf) {
+            activations_in_progress.entry(*tar_lf).or_default().push(target_y_midline);
+        }
+        targets_y_midlines.insert(*tar_lf, target_y_midline);
+    }
+    for lf in &pattern.deactivated_lifelines {
+        let y_midline = targets_y_midlines.get(lf).copied().unwrap_or(leaf_instruct.relative_y_pos + info.y_space_top_to_midline);
+        if let Some(lf_activations) = activations_in_progress.get_mut(lf) {
+            if let Some(activation_y_pos) = lf_activations.pop() {
+                activation_bars.push((*lf,lf_activations.len() as u32,activation_y_pos,Some(y_midline)));
+            }
+        }
+    }
+}
+// the bars that are not closed by a deactivation end at the bottom of their lifelines
+for (lf,lf_activations) in activations_in_progress {
+    for (nest_depth,activation_y_pos) in lf_activations.into_iter().enumerate() {
+        activation_bars.push((lf,nest_depth as u32,activation_y_pos,None));
+    }
+}
 // let us calculate the height
 // for that we need at first the header height
+// (that of the lifelines which are not created by messages and which headers are drawn at the top of the diagram)
 let mut header_height = 0.0_f32;
+let mut lifelines_headers_heights : HashMap<LI,f32> = HashMap::new();
 // we compute it by iterating the involved lifelines
 // as the same time, we update the horizontal requirements of each lifelines with the width of the header
 // and store the header texts
@@ -99,17 +178,41 @@ for lf in interaction_intermediate_information.involved_lifelines {
     let (text_width, text_height,_) = para.paragraph_size(context.get_scale(), context.get_font());
     {
         let mid_hor_space_req = text_width/2.0;
-        lfs_hor_reqs.get_mut(&lf).unwrap().update_to_max(
+        lfs_hor_reqs.entry(lf).or_insert_with(LifelineRequiredHorizontalSpaceInDiagram::new_empty).update_to_max(
             LifelineRequiredHorizontalSpaceInDiagram::new(mid_hor_space_req, mid_hor_space_req)
         );
     }
-    header_height = f32::max(header_height, text_height);
+    if !lifelines_creations.contains_key(&lf) {
+        header_height = f32::max(header_height, text_height);
+    }
+    lifelines_headers_heights.insert(lf, text_height);
     lifelines_headers.insert(lf, para);
 }
+// the titles of the lifeline groups are drawn above the headers, with one row per nesting depth
+let groups_in_diagram = {
+    let positions : HashMap<LI,usize> = all_lifelines_in_diagram.iter().enumerate().map(|(idx,lf)| (*lf,idx)).collect();
+    let mut groups_in_diagram = vec![];
+    get_lifeline_groups_in_diagram_rec(context, &positions, &lifeline_groups, 0, &mut groups_in_diagram);
+    groups_in_diagram
+};
+let mut groups_rows_heights : Vec<f32> = vec![];
+for group_in_diagram in &groups_in_diagram {
+    if groups_rows_heights.len() <= group_in_diagram.nest_depth {
+        groups_rows_heights.resize(group_in_diagram.nest_depth + 1, 0.0);
+    }
+    groups_rows_heights[group_in_diagram.nest_depth] = f32::max(
+        groups_rows_heights[group_in_diagram.nest_depth], 
+        group_in_diagram.title_height + context.get_margin_between_items()
+    );
+}
+let groups_titles_height : f32 = groups_rows_heights.iter().sum();
+// each nesting depth also requires some space below the lifelines so that the bottoms of nested boxes do not overlap
+let groups_bottoms_height = (groups_rows_heights.len() as f32)*context.get_margin_between_items();
 // the vertical space occupied by the top of the diagram
-let y_shift_to_absolute = context.get_border_padding() + header_height;
+let y_shift_to_absolute = context.get_border_padding() + groups_titles_height + header_height;
 // we can now compute the total image height
-let height = (2.0_f32)*context.get_border_padding() + header_height + relative_y_pos + context.get_margin_between_items();
+let height = (2.0_f32)*context.get_border_padding() + groups_titles_height + header_height + relative_y_pos + context.get_margin_between_items() + groups_bottoms_height;
+let lifelines_y_bottom = height - context.get_border_padding() - groups_bottoms_height;
 
 
 // let us now compute the horizontal positions that we need to know
@@ -119,6 +222,8 @@ let left_side_of_diagram_x_pos : f32;
 let mut lifelines_horizontal_positions : HashMap<LI,f32> = HashMap::new();
 // the right side of the diagram, on the right of which to draw output gates
 let right_side_of_diagram_x_pos : f32;
+// the left and right sides of the boxes of the lifeline groups
+let mut groups_horizontal_bounds : Vec<(f32,f32)> = vec![(0.0,0.0);groups_in_diagram.len()];
 // and finally the total width of the image
 let width : f32;
 
@@ -135,10 +240,31 @@ let mut current_x_pos = 0.0_f32;
     current_x_pos += context.get_margin_between_items();
     // ***
     for lf in &all_lifelines_in_diagram {
-        let lf_req = lfs_hor_reqs.get(lf).unwrap();
+        // the boxes of the groups which leftmost lifeline is this one (from the outermost to the innermost)
+        for (group_idx,group_in_diagram) in groups_in_diagram.iter().enumerate() {
+            if group_in_diagram.lifelines.first() == Some(lf) {
+                groups_horizontal_bounds[group_idx].0 = current_x_pos;
+                current_x_pos += context.get_margin_between_items();
+            }
+        }
+        let lf_req = lfs_hor_reqs.get(lf).ok_or(DrawError::InconsistentPattern(
+            "a lifeline that is declared as involved by the extractor does not occur in any of the drawable patterns".to_owned()
+        ))?;
         current_x_pos += lf_req.on_the_left;
         lifelines_horizontal_positions.insert(*lf, current_x_pos);
         current_x_pos += lf_req.on_the_right;
+        // the boxes of the groups which rightmost lifeline is this one (from the innermost to the outermost)
+        for (group_idx,group_in_diagram) in groups_in_diagram.iter().enumerate().rev() {
+            if group_in_diagram.lifelines.last() == Some(lf) {
+                current_x_pos += context.get_margin_between_items();
+                // the box must be wide enough for its title
+                current_x_pos = f32::max(
+                    current_x_pos, 
+                    groups_horizontal_bounds[group_idx].0 + group_in_diagram.title_width + (2.0_f32)*context.get_margin_between_items()
+                );
+                groups_horizontal_bounds[group_idx].1 = current_x_pos;
+            }
+        }
         current_x_pos += context.get_margin_between_items();
     }
     //
@@ -149,17 +275,92 @@ let mut current_x_pos = 0.0_f32;
     current_x_pos += context.get_border_padding();
     width = current_x_pos;
 }
+// the vertical line of a created lifeline starts under its header and that of a destroyed lifeline ends at its destruction
+let mut lifelines_vertical_spans : HashMap<LI,(f32,f32)> = HashMap::new();
+let mut created_lifelines_headers_y_pos : HashMap<LI,f32> = HashMap::new();
+for lf in &all_lifelines_in_diagram {
+    let y_top = match lifelines_creations.get(lf) {
+        Some(creation_y_pos) => {
+            let header_y_pos = y_shift_to_absolute + creation_y_pos;
+            created_lifelines_headers_y_pos.insert(*lf, header_y_pos);
+            header_y_pos + lifelines_headers_heights.get(lf).copied().unwrap_or(0.0)/2.0
+        },
+        None => {
+            y_shift_to_absolute
+        }
+    };
+    let y_bottom = match lifelines_destructions.get(lf) {
+        Some(destruction_y_pos) => {
+            y_shift_to_absolute + destruction_y_pos
+        },
+        None => {
+            lifelines_y_bottom
+        }
+    };
+    lifelines_vertical_spans.insert(*lf, (y_top,y_bottom));
+}
+// the activation bars are drawn from the outermost to the innermost, then from left to right and from top to bottom
+{
+    let positions : HashMap<LI,usize> = all_lifelines_in_diagram.iter().enumerate().map(|(idx,lf)| (*lf,idx)).collect();
+    activation_bars.sort_by(
+        |(lf1,depth1,y1,_),(lf2,depth2,y2,_)| depth1.cmp(depth2).then(positions.get(lf1).cmp(&positions.get(lf2))).then(y1.total_cmp(y2))
+    );
+}
+let mut activation_bars_to_draw = vec![];
+for (lf,nest_depth,activation_y_pos,deactivation_y_pos) in activation_bars {
+    let lf_x_pos = get_lifeline_horizontal_position(&lifelines_horizontal_positions, &lf)?;
+    let (x_left,x_right) = get_activation_bar_horizontal_bounds(lf_x_pos, nest_depth, context.get_activation_bar_width());
+    let y_bottom = match deactivation_y_pos {
+        Some(deactivation_y_pos) => {
+            y_shift_to_absolute + deactivation_y_pos
+        },
+        None => {
+            lifelines_vertical_spans.get(&lf).ok_or(DrawError::MissingLifelinePosition)?.1
+        }
+    };
+    activation_bars_to_draw.push(
+        CompleteActivationBarDrawInstruction{
+            lifeline:lf,
+            x_left,
+            x_right,
+            y_top:y_shift_to_absolute + activation_y_pos,
+            y_bottom
+        }
+    );
+}
+//
+let mut lifeline_groups_to_draw = vec![];
+for (group_in_diagram,(x_left,x_right)) in groups_in_diagram.into_iter().zip(groups_horizontal_bounds) {
+    let y_top = context.get_border_padding() + groups_rows_heights[..group_in_diagram.nest_depth].iter().sum::<f32>();
+    let y_bottom = lifelines_y_bottom + ((groups_rows_heights.len() - group_in_diagram.nest_depth) as f32)*context.get_margin_between_items();
+    lifeline_groups_to_draw.push(
+        CompleteLifelineGroupDrawInstruction{
+            title:group_in_diagram.group.title.clone(),
+            frame_color:group_in_diagram.group.frame_color,
+            lifelines:group_in_diagram.lifelines,
+            x_left,
+            x_right,
+            y_top,
+            y_bottom
+        }
+    );
+}
 // 
-CompleteInteractionDrawInstruction{
+Ok(CompleteInteractionDrawInstruction{
     width,
     height,
     y_shift_to_absolute,
     left_side_of_diagram_x_pos,
     right_side_of_diagram_x_pos,
     lifelines_horizontal_positions,
+    lifelines_vertical_spans,
     lifelines_headers,
+    created_lifelines_headers_y_pos,
+    destroyed_lifelines : lifelines_destructions.into_keys().collect(),
+    lifeline_groups_to_draw,
+    activation_bars_to_draw,
     patterns_to_draw,
     operators_to_draw
-}
+})
 }
 
@@ -18,14 +18,19 @@ limitations under the License.
 
 
 use std::hash::Hash;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 
 
 use crate::internal_representation::*;
 use crate::to_image::extract::context_aware_extractor::ContextAwareInteractionDrawingInstructionsExtractor;
+use crate::to_image::drawable::activation::get_activation_bars_horizontal_extents;
+use crate::to_image::drawable::leaf::broadcast::DrawableBroadcastLeafPattern;
 use crate::to_image::drawable::leaf::info::LifelineRequiredHorizontalSpaceInDiagram;
 use crate::to_image::extract::instructions::*;
+use crate::to_image::drawable::operator::builtin_operator::DrawableOperator;
+use crate::to_image::drawable::util::get_leftmost_lifeline_in_diagram;
+use crate::to_image::error::DrawError;
 
 
 
@@ -51,48 +56,97 @@ impl<LI : Eq + Hash + Copy + Clone> InteractionIntermediateInformation<LI> {
 
 
 
+/** 
+ * The drawable patterns and operators (or annotations) of an interaction, keyed by the paths of their nodes
+ * (patterns and annotations which are not drawn have no entry).
+ * They are obtained once from the extractor, when ordering the lifelines, and then taken by the extraction.
+ * **/
+pub(crate) struct DrawableInteractionNodes<LI : Eq + Hash + Copy + Clone> {
+    pub patterns : BTreeMap<Vec<usize>,DrawableBroadcastLeafPattern<LI>>,
+    pub frames : BTreeMap<Vec<usize>,DrawableOperator<LI>>
+}
+
+impl<LI : Eq + Hash + Copy + Clone> DrawableInteractionNodes<LI> {
+    pub fn new() -> Self {
+        Self{patterns:BTreeMap::new(),frames:BTreeMap::new()}
+    }
+}
+
+
+
+
 
 /** 
  * Recursively extracts intermediate drawing information from the structure of the Internal Representation of the interaction.
  * This consists in:
  * - updating the *ypos* vertical position as the term is traversed
+ * - updating the *activation_depths* of the lifelines as the term is traversed, in the order in which the patterns are drawn
  * - filling the *encountered_leaves* with all the patterns that are encountered at the leaves at the correct relative *ypos* position
  * - filling the *encountered_operators* with all the operators that are encountered with the correct information on the *ypos* of their operands
  * - returning information about the lifelines that are encountered in the term structure
+ * 
+ * The patterns and operators are drawn as given by *drawables*, from which they are taken using the *path* of their node.
  * **/
 pub fn extract_intermediate_drawing_information_rec<CioII,LI,Context> 
     (   context : &Context,
         all_lifelines_in_diagram : &Vec<LI>,
         int_repr : &InteractionInternalRepresentation<CioII>,
+        path : &mut Vec<usize>,
+        drawables : &mut DrawableInteractionNodes<LI>,
         ypos : &mut f32,
         nest_shift : u32,
+        activation_depths : &mut HashMap<LI,u32>,
         encountered_leaves : &mut Vec<CompleteBroadcastLeafPatternDrawInstruction<LI>>,
         encountered_operators : &mut Vec<CompleteOperatorDrawInstruction<LI>>
-    ) -> InteractionIntermediateInformation<LI>
+    ) -> Result<InteractionIntermediateInformation<LI>,DrawError>
     where 
-        CioII : CommonIoInteractionInterface,
+        CioII : AnnotatedCommonIoInteractionInterface,
         LI : Eq + Hash + Copy + Clone,
         Context : ContextAwareInteractionDrawingInstructionsExtractor<CioII,LI>
     {
         match int_repr {
-            InteractionInternalRepresentation::LeafPattern(leaf) => {
-                match context.to_drawable_pattern(leaf) {
+            InteractionInternalRepresentation::LeafPattern(_) => {
+                match drawables.patterns.remove(path) {
                     Some(drawable_pattern) => {
+                        let created_lifelines_headers_sizes = drawable_pattern.created_lifelines.iter().map(
+                            |lf| {
+                                let (header_width, header_height, _) = context.get_lifeline_header(lf).paragraph_size(context.get_scale(), context.get_font());
+                                (*lf,(header_width,header_height))
+                            }
+                        ).collect();
                         let leaf_info = drawable_pattern.get_intermediate_information(
                             context.get_scale(),
                             context.get_font(),
                             all_lifelines_in_diagram,
                             context.get_y_margin_between_seq_operands(),
                             context.get_margin_between_items(),
-                            context.get_margin_between_items()
-                        );
+                            context.get_margin_between_items(),
+                            created_lifelines_headers_sizes,
+                            activation_depths,
+                            context.get_activation_bar_width()
+                        )?;
                         // ***
                         // recursive information gathering
+                        let mut lfs_horizontal_reqs = leaf_info.lifelines_horizontal_requirements.clone();
+                        // the activation bars of all the lifelines that are active during the pattern 
+                        // (and not only of those that are involved) must fit between the lifelines
+                        for (lf,depth) in drawable_pattern.get_activation_depths_at_reception(activation_depths) {
+                            if depth > 0 {
+                                let (bars_on_the_left,bars_on_the_right) = get_activation_bars_horizontal_extents(depth, context.get_activation_bar_width());
+                                lfs_horizontal_reqs.entry(lf).or_insert_with(LifelineRequiredHorizontalSpaceInDiagram::new_empty).update_to_max(
+                                    LifelineRequiredHorizontalSpaceInDiagram::new(
+                                        bars_on_the_left + context.get_margin_between_items(), 
+                                        bars_on_the_right + context.get_margin_between_items()
+                                    )
+                                );
+                            }
+                        }
+                        *activation_depths = drawable_pattern.get_activation_depths_after(activation_depths);
                         let rec_info = InteractionIntermediateInformation::new(
                             leaf_info.input_gate_width, 
                             leaf_info.output_gates_max_width, 
                             leaf_info.involved_lifelines.clone(), 
-                            leaf_info.lifelines_horizontal_requirements.clone()
+                            lfs_horizontal_reqs
                         );
                         let leaf_height = leaf_info.y_space_top_to_bottom;
                         // keeps track of encountered leaf
@@ -100,111 +154,187 @@ pub fn extract_intermediate_drawing_information_rec<CioII,LI,Context>
                         // increments mutable vertical position reference
                         *ypos += leaf_height;
                         // ***
-                        rec_info
+                        Ok(rec_info)
                     },
                     None => {
-                        InteractionIntermediateInformation::new(
-                            0.0, 
-                            0.0, 
-                            HashSet::new(), 
-                            HashMap::new()
+                        Ok(
+                            InteractionIntermediateInformation::new(
+                                0.0, 
+                                0.0, 
+                                HashSet::new(), 
+                                HashMap::new()
+                            )
                         )
                     }
                 }
             },
-            InteractionInternalRepresentation::Operator(operator, sub_ints) => {
-                let mut op_operands_positions = vec![*ypos];
-                let drawable_operator = context.to_drawable_operator(
-                    operator,
-                    sub_ints
-                );
-                let operator_info = drawable_operator.get_intermediate_information(
-                    context.get_scale(),
-                    context.get_font(),
-                    context.get_margin_between_items(),
-                    context.get_margin_between_items()
-                );
-                *ypos += operator_info.required_vertical_space_at_the_top;
-                let rec_nest_shift = if operator_info.requires_nest_shift {
-                    nest_shift + 1
-                } else {
-                    nest_shift
-                };
-                // recursive information gathering
-                let mut max_input_gate_width = 0.0_f32;
-                let mut max_output_gate_width = 0.0_f32;
-                let mut op_included_lifelines = HashSet::new();
-                let mut lifelines_reqs = HashMap::new();
-                // iter operands
-                let num_operands = sub_ints.len();
-                for (count,sub_int) in sub_ints.iter().enumerate() {
-                    let sub_rec_info = extract_intermediate_drawing_information_rec::<CioII,LI,Context>(
-                        context,
-                        all_lifelines_in_diagram,
-                        sub_int,
-                        ypos,
-                        rec_nest_shift,
-                        encountered_leaves,
-                        encountered_operators);
-                    // ***
-                    // recursive information update
-                    op_included_lifelines.extend(sub_rec_info.involved_lifelines);
-                    LifelineRequiredHorizontalSpaceInDiagram::update_all_to_max(&mut lifelines_reqs, sub_rec_info.lfs_horizontal_reqs);
-                    max_input_gate_width = f32::max(max_input_gate_width, sub_rec_info.max_input_gate_width);
-                    max_output_gate_width = f32::max(max_output_gate_width, sub_rec_info.max_output_gate_width);
-                    // updates the ypos
-                    {
-                        *ypos += operator_info.required_vertical_space_between_operands/2.0;
-                        op_operands_positions.push(*ypos);
-                        if count < num_operands - 1 {
-                            *ypos += operator_info.required_vertical_space_between_operands/2.0;
-                        } 
+            InteractionInternalRepresentation::Operator(_, sub_ints) => {
+                let drawable_operator = drawables.frames.remove(path).ok_or(
+                    DrawError::InconsistentPattern("an operator of the interaction has no drawable counterpart".to_owned())
+                )?;
+                extract_intermediate_drawing_information_of_frame::<CioII,LI,Context>(
+                    context, 
+                    all_lifelines_in_diagram, 
+                    drawable_operator, 
+                    sub_ints, 
+                    path, 
+                    drawables, 
+                    ypos, 
+                    nest_shift, 
+                    activation_depths, 
+                    encountered_leaves, 
+                    encountered_operators
+                )
+            },
+            InteractionInternalRepresentation::Annotated(_, annotated) => {
+                match drawables.frames.remove(path) {
+                    Some(drawable_annotation) => {
+                        // the annotation is drawn as an operator framing the annotated sub-interaction
+                        extract_intermediate_drawing_information_of_frame::<CioII,LI,Context>(
+                            context, 
+                            all_lifelines_in_diagram, 
+                            drawable_annotation, 
+                            std::slice::from_ref(annotated.as_ref()), 
+                            path, 
+                            drawables, 
+                            ypos, 
+                            nest_shift, 
+                            activation_depths, 
+                            encountered_leaves, 
+                            encountered_operators
+                        )
+                    },
+                    None => {
+                        path.push(0);
+                        let rec_info = extract_intermediate_drawing_information_rec::<CioII,LI,Context>(
+                            context,
+                            all_lifelines_in_diagram,
+                            annotated,
+                            path,
+                            drawables,
+                            ypos,
+                            nest_shift,
+                            activation_depths,
+                            encountered_leaves,
+                            encountered_operators
+                        );
+                        path.pop();
+                        rec_info
                     }
                 }
-                // the left most inner lifeline may require additional space on its left for drawing the operator label
-                if let Some(leftmost_lf_id) = op_included_lifelines.iter()
-                    .min_by(|l1,l2|
-                        {
-                            let l1_idx = all_lifelines_in_diagram.iter().position(|l| l == *l1).unwrap();
-                            let l2_idx = all_lifelines_in_diagram.iter().position(|l| l == *l2).unwrap();
-                            l1_idx.cmp(&l2_idx)
-                        }
-                    ) {
-                    let letftmost_lf_reqs = lifelines_reqs.get_mut(leftmost_lf_id).unwrap();
-                    letftmost_lf_reqs.on_the_left = f32::max(
-                        letftmost_lf_reqs.on_the_left, 
-                        operator_info.required_horizontal_space_at_left_most_lifeline
-                    );
-                }
-                //
-                // we make a copy of the internal lifelines horizontal requirements
-                let mut enclosed_lfs_reqs = HashMap::new();
-                for lf in &op_included_lifelines {
-                    enclosed_lfs_reqs.insert(*lf, lifelines_reqs.get(lf).unwrap().clone());
-                }
-                // finalize recursive information gathering
-                let rec_info = InteractionIntermediateInformation::new(
-                    max_input_gate_width, 
-                    max_output_gate_width, 
-                    op_included_lifelines, 
-                    lifelines_reqs
-                );
-                // keepts track of encountered operator instruction
-                encountered_operators.push(
-                    CompleteOperatorDrawInstruction::new(
-                        drawable_operator, 
-                        enclosed_lfs_reqs,
-                        nest_shift, 
-                        op_operands_positions
-                    )
-                );
-                rec_info
             }
         }
 }
 
 
 
+/** 
+ * Extracts intermediate drawing information for an operator (or an annotation drawn as an operator) 
+ * which frames the given sub-interactions.
+ * **/
+fn extract_intermediate_drawing_information_of_frame<CioII,LI,Context> 
+    (   context : &Context,
+        all_lifelines_in_diagram : &Vec<LI>,
+        drawable_operator : DrawableOperator<LI>,
+        sub_ints : &[InteractionInternalRepresentation<CioII>],
+        path : &mut Vec<usize>,
+        drawables : &mut DrawableInteractionNodes<LI>,
+        ypos : &mut f32,
+        nest_shift : u32,
+        activation_depths : &mut HashMap<LI,u32>,
+        encountered_leaves : &mut Vec<CompleteBroadcastLeafPatternDrawInstruction<LI>>,
+        encountered_operators : &mut Vec<CompleteOperatorDrawInstruction<LI>>
+    ) -> Result<InteractionIntermediateInformation<LI>,DrawError>
+    where 
+        CioII : AnnotatedCommonIoInteractionInterface,
+        LI : Eq + Hash + Copy + Clone,
+        Context : ContextAwareInteractionDrawingInstructionsExtractor<CioII,LI>
+    {
+        let mut op_operands_positions = vec![*ypos];
+        let operator_info = drawable_operator.get_intermediate_information(
+            context.get_scale(),
+            context.get_font(),
+            context.get_margin_between_items(),
+            context.get_margin_between_items()
+        );
+        *ypos += operator_info.required_vertical_space_at_the_top;
+        let rec_nest_shift = if operator_info.requires_nest_shift {
+            nest_shift + 1
+        } else {
+            nest_shift
+        };
+        // recursive information gathering
+        let mut max_input_gate_width = 0.0_f32;
+        let mut max_output_gate_width = 0.0_f32;
+        let mut op_included_lifelines = HashSet::new();
+        let mut lifelines_reqs = HashMap::new();
+        // iter operands
+        let num_operands = sub_ints.len();
+        for (count,sub_int) in sub_ints.iter().enumerate() {
+            path.push(count);
+            let sub_rec_info = extract_intermediate_drawing_information_rec::<CioII,LI,Context>(
+                context,
+                all_lifelines_in_diagram,
+                sub_int,
+                path,
+                drawables,
+                ypos,
+                rec_nest_shift,
+                activation_depths,
+                encountered_leaves,
+                encountered_operators)?;
+            path.pop();
+            // ***
+            // recursive information update
+            op_included_lifelines.extend(sub_rec_info.involved_lifelines);
+            LifelineRequiredHorizontalSpaceInDiagram::update_all_to_max(&mut lifelines_reqs, sub_rec_info.lfs_horizontal_reqs);
+            max_input_gate_width = f32::max(max_input_gate_width, sub_rec_info.max_input_gate_width);
+            max_output_gate_width = f32::max(max_output_gate_width, sub_rec_info.max_output_gate_width);
+            // updates the ypos
+            {
+                *ypos += operator_info.required_vertical_space_between_operands/2.0;
+                op_operands_positions.push(*ypos);
+                if count < num_operands - 1 {
+                    *ypos += operator_info.required_vertical_space_between_operands/2.0;
+                } 
+            }
+        }
+        // the left most inner lifeline may require additional space on its left for drawing the operator label
+        if let Some(leftmost_lf_id) = get_leftmost_lifeline_in_diagram(all_lifelines_in_diagram, op_included_lifelines.iter())? {
+            let letftmost_lf_reqs = lifelines_reqs.entry(leftmost_lf_id).or_insert_with(LifelineRequiredHorizontalSpaceInDiagram::new_empty);
+            letftmost_lf_reqs.on_the_left = f32::max(
+                letftmost_lf_reqs.on_the_left, 
+                operator_info.required_horizontal_space_at_left_most_lifeline
+            );
+        }
+        //
+        // we make a copy of the internal lifelines horizontal requirements
+        let mut enclosed_lfs_reqs = HashMap::new();
+        for lf in &op_included_lifelines {
+            let lf_reqs = lifelines_reqs.get(lf).cloned().unwrap_or_else(LifelineRequiredHorizontalSpaceInDiagram::new_empty);
+            enclosed_lfs_reqs.insert(*lf, lf_reqs);
+        }
+        // finalize recursive information gathering
+        let rec_info = InteractionIntermediateInformation::new(
+            max_input_gate_width, 
+            max_output_gate_width, 
+            op_included_lifelines, 
+            lifelines_reqs
+        );
+        // keepts track of encountered operator instruction
+        encountered_operators.push(
+            CompleteOperatorDrawInstruction::new(
+                drawable_operator, 
+                enclosed_lfs_reqs,
+                nest_shift, 
+                op_operands_positions
+            )
+        );
+        Ok(rec_info)
+}
+
+
+
 
 
 
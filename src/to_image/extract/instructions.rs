@@ -19,16 +19,19 @@ limitations under the License.
 
 
 use std::hash::Hash;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 
 
 
+use image::Rgb;
+use image_colored_text::draw::coord::DrawCoord;
 use image_colored_text::text::paragraph::ColoredTextParagraph;
 
 use crate::to_image::drawable::operator::builtin_operator::DrawableOperator;
 use crate::to_image::drawable::leaf::broadcast::DrawableBroadcastLeafPattern;
 use crate::to_image::drawable::leaf::info::{BroadcastLeafPatternIntermediateInformation, LifelineRequiredHorizontalSpaceInDiagram};
+use crate::to_image::error::DrawError;
 
 
 
@@ -72,6 +75,35 @@ impl <LI : Eq + Hash + Copy + Clone>  CompleteOperatorDrawInstruction<LI> {
 
 
 
+/** 
+ * The box of a group of lifelines, with absolute coordinates.
+ * *lifelines* are all the lifelines of the group (including those of its sub-groups) from left to right.
+ * **/
+pub struct CompleteLifelineGroupDrawInstruction<LI : Eq + Hash + Copy + Clone> {
+    pub title : ColoredTextParagraph,
+    pub frame_color : Rgb<u8>,
+    pub lifelines : Vec<LI>,
+    pub x_left : f32,
+    pub x_right : f32,
+    pub y_top : f32,
+    pub y_bottom : f32
+}
+
+
+
+/** 
+ * An activation bar on a lifeline, with absolute coordinates.
+ * **/
+pub struct CompleteActivationBarDrawInstruction<LI : Eq + Hash + Copy + Clone> {
+    pub lifeline : LI,
+    pub x_left : f32,
+    pub x_right : f32,
+    pub y_top : f32,
+    pub y_bottom : f32
+}
+
+
+
 /** 
  * This holds all the information required to draw an interaction term as a sequence diagram.
  * **/
@@ -81,7 +113,7 @@ impl <LI : Eq + Hash + Copy + Clone>  CompleteOperatorDrawInstruction<LI> {
     // the total height of the image
     pub height : f32,
     // the value to add to the relative y positions of the leaf pattern and operator instructions to get their absolute position
-    // this amounts to the top padding + the height of the headers
+    // this amounts to the top padding + the height of the titles of the lifeline groups + the height of the headers
     pub y_shift_to_absolute : f32,
     // this gives the horizontal position on the left of which to draw input outside gates
     pub left_side_of_diagram_x_pos : f32,
@@ -89,11 +121,46 @@ impl <LI : Eq + Hash + Copy + Clone>  CompleteOperatorDrawInstruction<LI> {
     pub right_side_of_diagram_x_pos : f32,
     // for each lifeline, the distance between the left side of the image and the horizontal line corresponding to the lifeline
     pub lifelines_horizontal_positions : HashMap<LI,f32>,
+    // for each lifeline, the vertical positions of the top and of the bottom of its vertical line
+    pub lifelines_vertical_spans : HashMap<LI,(f32,f32)>,
     // ***
     pub lifelines_headers : HashMap<LI,ColoredTextParagraph>,
+    // the vertical positions around which the headers of the lifelines that are created by messages are centered
+    // (the headers of the other lifelines end at *y_shift_to_absolute*)
+    pub created_lifelines_headers_y_pos : HashMap<LI,f32>,
+    // the lifelines which end with a cross
+    pub destroyed_lifelines : HashSet<LI>,
+    // all the instructions to draw the boxes of the lifeline groups, from the outermost to the innermost
+    pub lifeline_groups_to_draw : Vec<CompleteLifelineGroupDrawInstruction<LI>>,
+    // all the instructions to draw the activation bars on the lifelines, from the outermost to the innermost
+    pub activation_bars_to_draw : Vec<CompleteActivationBarDrawInstruction<LI>>,
     // all the instructions to draw all the patterns
     pub patterns_to_draw : Vec<CompleteBroadcastLeafPatternDrawInstruction<LI>>,
     // all the instructions to draw all the operators
     pub operators_to_draw : Vec<CompleteOperatorDrawInstruction<LI>>
 }
 
+impl<LI : Eq + Hash + Copy + Clone> CompleteInteractionDrawInstruction<LI> {
+
+    /** 
+     * Returns the vertical coordinate at which the header of the lifeline is drawn.
+     * **/
+    pub fn get_lifeline_header_vertical_coord(&self, lf : &LI) -> DrawCoord {
+        match self.created_lifelines_headers_y_pos.get(lf) {
+            Some(y_pos) => {
+                DrawCoord::CenteredAround(*y_pos)
+            },
+            None => {
+                DrawCoord::EndingAt(self.y_shift_to_absolute)
+            }
+        }
+    }
+
+    /** 
+     * Returns the vertical positions of the top and of the bottom of the vertical line of the lifeline.
+     * **/
+    pub fn get_lifeline_vertical_span(&self, lf : &LI) -> Result<(f32,f32),DrawError> {
+        self.lifelines_vertical_spans.get(lf).copied().ok_or(DrawError::MissingLifelinePosition)
+    }
+
+}
@@ -17,6 +17,7 @@ limitations under the License.
 
 
 pub mod context_aware_extractor;
+pub mod ordering;
 mod extract_rec;
 pub(crate) mod instructions;
 pub(crate) mod extract;
\ No newline at end of file
@@ -0,0 +1,606 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use std::hash::Hash;
+use std::collections::{HashMap, HashSet};
+
+
+use crate::internal_representation::*;
+use crate::to_image::extract::context_aware_extractor::ContextAwareInteractionDrawingInstructionsExtractor;
+use crate::to_image::extract::extract_rec::DrawableInteractionNodes;
+use crate::to_image::drawable::group::DrawableLifelineGroup;
+use crate::to_image::drawable::leaf::broadcast::DrawableBroadcastLeafPatternOrigin;
+use crate::to_image::drawable::operator::builtin_operator::DrawableOperatorKind;
+use crate::to_image::error::DrawError;
+
+
+
+/** 
+ * How the lifelines of a sequence diagram are ordered from left to right.
+ * In any case, lifelines which are pinned by the extractor (see *get_pinned_lifelines*) are drawn at their fixed positions
+ * and the ordering only concerns the other lifelines.
+ * The lifelines of each group (see *get_lifeline_groups*) are then moved next to each other, 
+ * groups being ordered according to their leftmost lifeline.
+ * Drawing fails if this moves a pinned lifeline (see *DrawError::InconsistentLifelinePlacement*).
+ * **/
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum LifelineOrderingStrategy {
+    // the order given by *lifelines_compare*
+    #[default]
+    Declaration,
+    // lifelines are ordered according to their first occurrence in the diagram, from top to bottom
+    // within a pattern, the origin comes first and then the targets (ordered by *lifelines_compare*)
+    FirstAppearance,
+    // starting from the first appearance order, lifelines are moved so as to minimise the cost given by the weights
+    MinimalCost(LifelineOrderingCostWeights)
+}
+
+/** 
+ * The cost of an order of lifelines, which is minimised by the *MinimalCost* strategy, is the sum of :
+ * - *arrow_length* times the total length of the arrows, the distance between two neighbouring lifelines being 1 
+ *   (and gates being at distance 1 from the leftmost and rightmost lifelines)
+ * - *frame_crossing* times the total number of lifelines which cross the frame of a combined fragment they are not involved in
+ * **/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LifelineOrderingCostWeights {
+    pub arrow_length : u32,
+    pub frame_crossing : u32
+}
+
+impl LifelineOrderingCostWeights {
+    pub fn new(arrow_length : u32, frame_crossing : u32) -> Self {
+        Self{arrow_length,frame_crossing}
+    }
+}
+
+impl Default for LifelineOrderingCostWeights {
+    fn default() -> Self {
+        Self::new(1, 4)
+    }
+}
+
+
+
+/** 
+ * The horizontal line of a pattern as far as ordering lifelines is concerned.
+ * **/
+struct ArrowToOrder<LI : Eq + Hash + Copy + Clone> {
+    lifelines : Vec<LI>,
+    from_input_gate : bool,
+    to_output_gates : bool
+}
+
+/** 
+ * What is gathered from the interaction to order its lifelines.
+ * **/
+struct LifelineOrderingInformation<LI : Eq + Hash + Copy + Clone> {
+    // all the lifelines of the diagram, by order of first appearance
+    first_appearances : Vec<LI>,
+    // the same lifelines, to check whether a lifeline has already appeared
+    appeared : HashSet<LI>,
+    arrows : Vec<ArrowToOrder<LI>>,
+    // the lifelines enclosed by each framed operator
+    frames : Vec<HashSet<LI>>
+}
+
+
+
+/** 
+ * A step of the traversal which gathers the ordering information.
+ * **/
+enum GatheringStep<'a,CioII : AnnotatedCommonIoInteractionInterface> {
+    // visits a sub-interaction, located at the given path
+    Visit(&'a InteractionInternalRepresentation<CioII>,Vec<usize>),
+    // closes an operator (or an annotation drawn as an operator), once all its sub-interactions have been visited
+    CloseFrame(bool)
+}
+
+
+/** 
+ * Gathers the ordering information of the interaction 
+ * as well as its drawable patterns and operators, which are kept in *drawables* for the extraction.
+ * The interaction is traversed using an explicit stack so that deeply nested interactions do not overflow the call stack.
+ * **/
+fn gather_ordering_information<
+    CioII : AnnotatedCommonIoInteractionInterface,
+    LI : Eq + Hash + Copy + Clone,
+    Context : ContextAwareInteractionDrawingInstructionsExtractor<CioII,LI>
+    > (
+        context : &Context,
+        int_repr : &InteractionInternalRepresentation<CioII>,
+        info : &mut LifelineOrderingInformation<LI>,
+        drawables : &mut DrawableInteractionNodes<LI>
+    ) {
+        let mut steps = vec![GatheringStep::Visit(int_repr,vec![])];
+        // the lifelines involved within each frame being traversed (and within the whole interaction at the bottom)
+        let mut enclosed_stack : Vec<HashSet<LI>> = vec![HashSet::new()];
+        while let Some(step) = steps.pop() {
+            match step {
+                GatheringStep::Visit(InteractionInternalRepresentation::LeafPattern(leaf),path) => {
+                    let involved = context.get_involved_lifelines(leaf);
+                    let mut appearances = vec![];
+                    if let Some(drawable_pattern) = context.to_drawable_pattern(leaf) {
+                        if let DrawableBroadcastLeafPatternOrigin::Lifeline(orig_lf,_) = &drawable_pattern.origin {
+                            appearances.push(*orig_lf);
+                        }
+                        let mut targets : Vec<LI> = drawable_pattern.lifeline_targets.keys().cloned().collect();
+                        targets.sort_by(|l1,l2| context.lifelines_compare(l1, l2));
+                        appearances.extend(targets);
+                        info.arrows.push(
+                            ArrowToOrder{
+                                lifelines:appearances.clone(),
+                                from_input_gate:matches!(drawable_pattern.origin, DrawableBroadcastLeafPatternOrigin::InputOutsideGate(_)),
+                                to_output_gates:!drawable_pattern.output_outside_gates_targets.is_empty()
+                            }
+                        );
+                        drawables.patterns.insert(path, drawable_pattern);
+                    }
+                    let mut others : Vec<LI> = involved.iter().filter(|lf| !appearances.contains(lf)).cloned().collect();
+                    others.sort_by(|l1,l2| context.lifelines_compare(l1, l2));
+                    appearances.extend(others);
+                    // only the lifelines declared as involved are lifelines of the diagram
+                    for lf in appearances {
+                        if involved.contains(&lf) && info.appeared.insert(lf) {
+                            info.first_appearances.push(lf);
+                        }
+                    }
+                    enclosed_stack.last_mut().unwrap().extend(involved);
+                },
+                GatheringStep::Visit(InteractionInternalRepresentation::Operator(operator, sub_ints),path) => {
+                    let drawable_operator = context.to_drawable_operator(operator, sub_ints);
+                    let is_framed = matches!(drawable_operator.kind, DrawableOperatorKind::Framed(_));
+                    steps.push(GatheringStep::CloseFrame(is_framed));
+                    for (index,sub_int) in sub_ints.iter().enumerate().rev() {
+                        let mut sub_path = path.clone();
+                        sub_path.push(index);
+                        steps.push(GatheringStep::Visit(sub_int,sub_path));
+                    }
+                    enclosed_stack.push(HashSet::new());
+                    drawables.frames.insert(path, drawable_operator);
+                },
+                GatheringStep::Visit(InteractionInternalRepresentation::Annotated(annotation, annotated),path) => {
+                    let mut sub_path = path.clone();
+                    sub_path.push(0);
+                    if let Some(drawable_annotation) = context.to_drawable_annotation(annotation, annotated) {
+                        let is_framed = matches!(drawable_annotation.kind, DrawableOperatorKind::Framed(_));
+                        steps.push(GatheringStep::CloseFrame(is_framed));
+                        enclosed_stack.push(HashSet::new());
+                        drawables.frames.insert(path, drawable_annotation);
+                    }
+                    steps.push(GatheringStep::Visit(annotated,sub_path));
+                },
+                GatheringStep::CloseFrame(is_framed) => {
+                    let enclosed = enclosed_stack.pop().unwrap();
+                    if is_framed && !enclosed.is_empty() {
+                        info.frames.push(enclosed.clone());
+                    }
+                    enclosed_stack.last_mut().unwrap().extend(enclosed);
+                }
+            }
+        }
+    }
+
+
+
+/** 
+ * Inserts the pinned lifelines (sorted by position) at their positions among the other lifelines.
+ * Lifelines pinned at the same position are drawn next to each other 
+ * and those pinned beyond the rightmost position are drawn on the right of the diagram.
+ * **/
+fn insert_pinned_lifelines<LI : Eq + Hash + Copy + Clone>(
+    free_lifelines : &[LI],
+    pinned_lifelines : &[(usize,LI)]
+) -> Vec<LI> {
+    let mut order = Vec::with_capacity(free_lifelines.len() + pinned_lifelines.len());
+    let mut free_iter = free_lifelines.iter().peekable();
+    let mut pinned_iter = pinned_lifelines.iter().peekable();
+    loop {
+        let place_pinned = match (pinned_iter.peek(), free_iter.peek()) {
+            (None,None) => {
+                break;
+            },
+            (Some((pos,_)),Some(_)) => {
+                *pos <= order.len()
+            },
+            (Some(_),None) => {
+                true
+            },
+            (None,Some(_)) => {
+                false
+            }
+        };
+        if place_pinned {
+            order.push(pinned_iter.next().unwrap().1);
+        } else {
+            order.push(*free_iter.next().unwrap());
+        }
+    }
+    order
+}
+
+
+/** 
+ * The maximal number of moves made by the *MinimalCost* strategy, 
+ * so that the time spent ordering the lifelines of large diagrams remains bounded.
+ * **/
+const MAX_MOVES_WHEN_MINIMISING_ORDERING_COST : usize = 256;
+
+
+/** 
+ * The ordering information in which lifelines are identified by their position in the initial order,
+ * with, for each lifeline, the arrows and frames in which it occurs.
+ * **/
+struct IndexedOrderingInformation {
+    arrows : Vec<ArrowToOrder<usize>>,
+    frames : Vec<Vec<usize>>,
+    arrows_of_lifelines : Vec<Vec<usize>>,
+    frames_of_lifelines : Vec<Vec<usize>>
+}
+
+impl IndexedOrderingInformation {
+
+    fn new<LI : Eq + Hash + Copy + Clone>(info : &LifelineOrderingInformation<LI>, order : &[LI]) -> Self {
+        let indices : HashMap<LI,usize> = order.iter().enumerate().map(|(idx,lf)| (*lf,idx)).collect();
+        let mut arrows_of_lifelines = vec![vec![];order.len()];
+        let mut frames_of_lifelines = vec![vec![];order.len()];
+        let mut arrows = Vec::with_capacity(info.arrows.len());
+        for (arrow_idx,arrow) in info.arrows.iter().enumerate() {
+            let mut lifelines : Vec<usize> = arrow.lifelines.iter().filter_map(|lf| indices.get(lf).copied()).collect();
+            lifelines.sort_unstable();
+            lifelines.dedup();
+            for lf_idx in &lifelines {
+                arrows_of_lifelines[*lf_idx].push(arrow_idx);
+            }
+            arrows.push(ArrowToOrder{lifelines,from_input_gate:arrow.from_input_gate,to_output_gates:arrow.to_output_gates});
+        }
+        let mut frames = Vec::with_capacity(info.frames.len());
+        for (frame_idx,frame) in info.frames.iter().enumerate() {
+            let lifelines : Vec<usize> = frame.iter().filter_map(|lf| indices.get(lf).copied()).collect();
+            for lf_idx in &lifelines {
+                frames_of_lifelines[*lf_idx].push(frame_idx);
+            }
+            frames.push(lifelines);
+        }
+        Self{arrows,frames,arrows_of_lifelines,frames_of_lifelines}
+    }
+
+    fn get_arrow_length(&self, arrow_idx : usize, positions : &[i64]) -> u64 {
+        let arrow = &self.arrows[arrow_idx];
+        // gates are on either side of the diagram
+        let mut extremities = arrow.lifelines.iter().map(|lf_idx| positions[*lf_idx]);
+        let (mut min,mut max) = match extremities.next() {
+            Some(pos) => (pos,pos),
+            None => (i64::MAX,i64::MIN)
+        };
+        for pos in extremities {
+            min = min.min(pos);
+            max = max.max(pos);
+        }
+        if arrow.from_input_gate {
+            min = -1;
+            max = max.max(-1);
+        }
+        if arrow.to_output_gates {
+            min = min.min(positions.len() as i64);
+            max = positions.len() as i64;
+        }
+        if min <= max {
+            (max - min) as u64
+        } else {
+            0
+        }
+    }
+
+    fn get_frame_crossings(&self, frame_idx : usize, positions : &[i64]) -> u64 {
+        let frame = &self.frames[frame_idx];
+        let enclosed_positions = frame.iter().map(|lf_idx| positions[*lf_idx]);
+        let (min,max) = enclosed_positions.fold((i64::MAX,i64::MIN), |(min,max),pos| (min.min(pos),max.max(pos)));
+        if frame.is_empty() {
+            0
+        } else {
+            (max - min + 1) as u64 - frame.len() as u64
+        }
+    }
+
+    /** 
+     * The part of the cost due to the arrows and frames in which either of the two lifelines occurs.
+     * **/
+    fn get_cost_around(
+        &self, 
+        weights : &LifelineOrderingCostWeights, 
+        positions : &[i64], 
+        lf_idx1 : usize, 
+        lf_idx2 : usize
+    ) -> i64 {
+        let arrows = merge_sorted_indices(&self.arrows_of_lifelines[lf_idx1], &self.arrows_of_lifelines[lf_idx2]);
+        let frames = merge_sorted_indices(&self.frames_of_lifelines[lf_idx1], &self.frames_of_lifelines[lf_idx2]);
+        let arrows_length : u64 = arrows.map(|arrow_idx| self.get_arrow_length(arrow_idx, positions)).sum();
+        let frame_crossings : u64 = frames.map(|frame_idx| self.get_frame_crossings(frame_idx, positions)).sum();
+        ((weights.arrow_length as u64)*arrows_length + (weights.frame_crossing as u64)*frame_crossings) as i64
+    }
+
+}
+
+
+/** 
+ * The indices that occur in either of two increasing lists of indices, each of them once and in increasing order.
+ * **/
+fn merge_sorted_indices<'a>(indices1 : &'a [usize], indices2 : &'a [usize]) -> impl Iterator<Item = usize> + 'a {
+    let mut iter1 = indices1.iter().copied().peekable();
+    let mut iter2 = indices2.iter().copied().peekable();
+    std::iter::from_fn(move || {
+        match (iter1.peek().copied(), iter2.peek().copied()) {
+            (Some(idx1),Some(idx2)) => {
+                if idx1 <= idx2 {
+                    iter1.next();
+                }
+                if idx2 <= idx1 {
+                    iter2.next();
+                }
+                Some(idx1.min(idx2))
+            },
+            (Some(_),None) => {
+                iter1.next()
+            },
+            (None,_) => {
+                iter2.next()
+            }
+        }
+    })
+}
+
+
+/** 
+ * The free lifelines of an order, which may be moved while the pinned lifelines keep their positions.
+ * The positions of the free slots in the order do not depend on which free lifeline is in which slot.
+ * **/
+struct FreeLifelinesInOrder<'a> {
+    info : &'a IndexedOrderingInformation,
+    weights : &'a LifelineOrderingCostWeights,
+    // the position of each free slot in the order
+    slots_positions : Vec<i64>,
+    // the lifeline in each free slot
+    slots_lifelines : Vec<usize>,
+    // the position of each lifeline in the order
+    positions : Vec<i64>
+}
+
+impl FreeLifelinesInOrder<'_> {
+
+    /** 
+     * Swaps the lifelines of the free slots *slot* and *slot + 1* and returns the variation of the cost,
+     * which only concerns the arrows and frames in which either of the two lifelines occurs.
+     * **/
+    fn swap_with_next(&mut self, slot : usize) -> i64 {
+        let (lf_idx1,lf_idx2) = (self.slots_lifelines[slot],self.slots_lifelines[slot + 1]);
+        let cost_before = self.info.get_cost_around(self.weights, &self.positions, lf_idx1, lf_idx2);
+        self.slots_lifelines.swap(slot, slot + 1);
+        self.positions[lf_idx1] = self.slots_positions[slot + 1];
+        self.positions[lf_idx2] = self.slots_positions[slot];
+        let cost_after = self.info.get_cost_around(self.weights, &self.positions, lf_idx1, lf_idx2);
+        cost_after - cost_before
+    }
+
+    /** 
+     * Moves the lifeline of the free slot *from* to the free slot *to* (shifting the lifelines in between) 
+     * and returns the variation of the cost.
+     * **/
+    fn move_lifeline(&mut self, from : usize, to : usize) -> i64 {
+        let mut variation = 0;
+        if from < to {
+            for slot in from..to {
+                variation += self.swap_with_next(slot);
+            }
+        } else {
+            for slot in (to..from).rev() {
+                variation += self.swap_with_next(slot);
+            }
+        }
+        variation
+    }
+
+    /** 
+     * The variations of the cost when moving the lifeline of the free slot *from* to each free slot, 
+     * the lifelines being left in place.
+     * **/
+    fn get_variations_of_moves(&mut self, from : usize) -> Vec<i64> {
+        let mut variations = vec![0;self.slots_lifelines.len()];
+        let mut variation = 0;
+        for slot in from..self.slots_lifelines.len() - 1 {
+            variation += self.swap_with_next(slot);
+            variations[slot + 1] = variation;
+        }
+        self.move_lifeline(self.slots_lifelines.len() - 1, from);
+        let mut variation = 0;
+        for slot in (0..from).rev() {
+            variation += self.swap_with_next(slot);
+            variations[slot] = variation;
+        }
+        self.move_lifeline(0, from);
+        variations
+    }
+
+}
+
+
+/** 
+ * Repeatedly moves the single free lifeline (to any other position) which most decreases the cost, 
+ * until no such move decreases it or *MAX_MOVES_WHEN_MINIMISING_ORDERING_COST* moves have been made.
+ * Each move only changes the positions of the lifelines between its origin and its destination, 
+ * so that its cost is computed incrementally, from the arrows and frames in which these lifelines occur.
+ * **/
+fn minimise_ordering_cost<LI : Eq + Hash + Copy + Clone>(
+    info : &LifelineOrderingInformation<LI>,
+    weights : &LifelineOrderingCostWeights,
+    free_lifelines : Vec<LI>,
+    pinned_lifelines : &[(usize,LI)]
+) -> Vec<LI> {
+    if free_lifelines.len() < 2 {
+        return free_lifelines;
+    }
+    // the lifelines are identified by their positions in the initial order
+    let initial_order = insert_pinned_lifelines(&free_lifelines, pinned_lifelines);
+    let pinned : HashSet<LI> = pinned_lifelines.iter().map(|(_,lf)| *lf).collect();
+    let indexed_info = IndexedOrderingInformation::new(info, &initial_order);
+    let slots_lifelines : Vec<usize> = initial_order.iter().enumerate()
+        .filter(|(_,lf)| !pinned.contains(lf))
+        .map(|(idx,_)| idx)
+        .collect();
+    let mut free_order = FreeLifelinesInOrder{
+        info : &indexed_info,
+        weights,
+        slots_positions : slots_lifelines.iter().map(|idx| *idx as i64).collect(),
+        slots_lifelines,
+        positions : (0..initial_order.len() as i64).collect()
+    };
+    for _ in 0..MAX_MOVES_WHEN_MINIMISING_ORDERING_COST {
+        let mut improvement : Option<(usize,usize,i64)> = None;
+        for from in 0..free_order.slots_lifelines.len() {
+            for (to,variation) in free_order.get_variations_of_moves(from).into_iter().enumerate() {
+                let current_min = improvement.map_or(0, |(_,_,variation)| variation);
+                if to != from && variation < current_min {
+                    improvement = Some((from,to,variation));
+                }
+            }
+        }
+        match improvement {
+            None => {
+                break;
+            },
+            Some((from,to,_)) => {
+                free_order.move_lifeline(from, to);
+            }
+        }
+    }
+    free_order.slots_lifelines.into_iter().map(|idx| initial_order[idx]).collect()
+}
+
+
+
+/** 
+ * Returns all the lifelines of the diagram ordered from left to right,
+ * according to the ordering strategy and pinned lifelines of the extractor,
+ * along with the drawable patterns and operators of the interaction (see *DrawableInteractionNodes*).
+ * **/
+pub(crate) fn get_ordered_lifelines_in_diagram<CioII,LI,Context> (
+    context : &Context,
+    int_repr : &InteractionInternalRepresentation<CioII>
+) -> (Vec<LI>,DrawableInteractionNodes<LI>)
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    LI : Eq + Hash + Copy + Clone,
+    Context : ContextAwareInteractionDrawingInstructionsExtractor<CioII,LI>
+{
+    let mut info = LifelineOrderingInformation{first_appearances:vec![],appeared:HashSet::new(),arrows:vec![],frames:vec![]};
+    let mut drawables = DrawableInteractionNodes::new();
+    gather_ordering_information(context, int_repr, &mut info, &mut drawables);
+    // pinned lifelines which do not occur in the diagram are ignored
+    let pins = context.get_pinned_lifelines();
+    let mut pinned_lifelines : Vec<(usize,LI)> = info.first_appearances.iter()
+        .filter_map(|lf| pins.get(lf).map(|pos| (*pos,*lf)))
+        .collect();
+    pinned_lifelines.sort_by(|(pos1,l1),(pos2,l2)| pos1.cmp(pos2).then_with(|| context.lifelines_compare(l1, l2)));
+    let free_lifelines_by_appearance : Vec<LI> = info.first_appearances.iter()
+        .filter(|lf| !pins.contains_key(lf))
+        .cloned()
+        .collect();
+    let free_lifelines = match context.get_lifelines_ordering_strategy() {
+        LifelineOrderingStrategy::Declaration => {
+            let mut free_lifelines = free_lifelines_by_appearance;
+            free_lifelines.sort_by(|l1,l2| context.lifelines_compare(l1, l2));
+            free_lifelines
+        },
+        LifelineOrderingStrategy::FirstAppearance => {
+            free_lifelines_by_appearance
+        },
+        LifelineOrderingStrategy::MinimalCost(weights) => {
+            minimise_ordering_cost(&info, &weights, free_lifelines_by_appearance, &pinned_lifelines)
+        }
+    };
+    (insert_pinned_lifelines(&free_lifelines, &pinned_lifelines),drawables)
+}
+
+
+
+fn order_lifelines_of_group<LI : Eq + Hash + Copy + Clone>(
+    positions : &HashMap<LI,usize>,
+    lifelines : &[LI],
+    sub_groups : &[DrawableLifelineGroup<LI>]
+) -> Vec<LI> {
+    // each item is either a single lifeline or the lifelines of a sub-group, and is placed according to its leftmost lifeline
+    let mut items : Vec<(usize,Vec<LI>)> = lifelines.iter()
+        .filter_map(|lf| positions.get(lf).map(|pos| (*pos,vec![*lf])))
+        .collect();
+    for sub_group in sub_groups {
+        let sub_group_lifelines = order_lifelines_of_group(positions, &sub_group.lifelines, &sub_group.sub_groups);
+        if let Some(leftmost_pos) = sub_group_lifelines.iter().filter_map(|lf| positions.get(lf)).min() {
+            items.push((*leftmost_pos,sub_group_lifelines));
+        }
+    }
+    items.sort_by_key(|(pos,_)| *pos);
+    items.into_iter().flat_map(|(_,lfs)| lfs).collect()
+}
+
+
+fn collect_grouped_lifelines<LI : Eq + Hash + Copy + Clone>(
+    groups : &[DrawableLifelineGroup<LI>],
+    grouped : &mut HashSet<LI>
+) -> Result<(),DrawError> {
+    for group in groups {
+        for lf in &group.lifelines {
+            if !grouped.insert(*lf) {
+                return Err(DrawError::InconsistentPattern("a lifeline belongs to more than one lifeline group".to_owned()));
+            }
+        }
+        collect_grouped_lifelines(&group.sub_groups, grouped)?;
+    }
+    Ok(())
+}
+
+
+/** 
+ * Reorders the lifelines so that those of each group (and of each nested group) are next to each other.
+ * Lifelines of the groups which do not occur in the diagram are ignored.
+ * Pinned lifelines must keep the positions they have in *ordered_lifelines*, otherwise an error is returned.
+ * **/
+pub(crate) fn make_lifeline_groups_contiguous<LI : Eq + Hash + Copy + Clone>(
+    ordered_lifelines : Vec<LI>,
+    groups : &[DrawableLifelineGroup<LI>],
+    pinned_lifelines : &HashMap<LI,usize>
+) -> Result<Vec<LI>,DrawError> {
+    if groups.is_empty() {
+        return Ok(ordered_lifelines);
+    }
+    let mut grouped = HashSet::new();
+    collect_grouped_lifelines(groups, &mut grouped)?;
+    let positions : HashMap<LI,usize> = ordered_lifelines.iter().enumerate().map(|(idx,lf)| (*lf,idx)).collect();
+    let ungrouped : Vec<LI> = ordered_lifelines.iter().filter(|lf| !grouped.contains(lf)).cloned().collect();
+    let grouped_order = order_lifelines_of_group(&positions, &ungrouped, groups);
+    for (lf,pinned_pos) in pinned_lifelines {
+        if let Some(pos) = positions.get(lf) {
+            if grouped_order[*pos] != *lf {
+                return Err(
+                    DrawError::InconsistentLifelinePlacement(
+                        format!("a lifeline pinned at position {} is moved by the lifeline groups", pinned_pos)
+                    )
+                );
+            }
+        }
+    }
+    Ok(grouped_order)
+}
@@ -18,6 +18,9 @@ limitations under the License.
 
 use std::path::Path;
 use std::hash::Hash;
+use std::io::Cursor;
+
+use image::{ImageFormat, RgbImage};
 
 
 use crate::internal_representation::*;
@@ -25,8 +28,98 @@ use crate::to_image::extract::context_aware_extractor::ContextAwareInteractionDr
 use crate::to_image::draw::context_aware_drawer::ContextAwareInteractionDrawer;
 
 use crate::to_image::extract::extract::extract_drawing_information;
-use crate::to_image::draw::draw::make_image_from_display_information;
+use crate::to_image::draw::draw::{draw_display_information_on_canvas, make_image_from_display_information};
+use crate::to_image::draw::canvas::DiagramCanvas;
+use crate::to_image::error::DrawError;
+use crate::to_image::layout::model::DiagramLayout;
+use crate::to_image::layout::build::make_layout_from_display_information;
+
+
+/** 
+ * Returns an image in which the interaction is drawn as a sequence diagram.
+ * **/
+pub fn get_interaction_as_sequence_diagram_image<CioII,LI,Extractor,Drawer> (
+    int_repr : &InteractionInternalRepresentation<CioII>,
+    extractor : &Extractor,
+    palette : &Drawer
+) -> Result<RgbImage,DrawError>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    LI : Eq + Hash + Copy + Clone,
+    Extractor : ContextAwareInteractionDrawingInstructionsExtractor<CioII,LI>,
+    Drawer : ContextAwareInteractionDrawer<LI>
+{
+    let display_info = extract_drawing_information::<CioII,LI,Extractor>(
+        extractor,
+        int_repr
+    )?;
+    make_image_from_display_information::<LI,Drawer>(
+        palette,
+        &display_info
+    )
+}
+
+
+/** 
+ * Returns the bytes of an image in which the interaction is drawn as a sequence diagram,
+ * encoded in the given format (e.g., *ImageFormat::Png*, *ImageFormat::Jpeg* or *ImageFormat::WebP*).
+ * **/
+pub fn get_interaction_as_encoded_sequence_diagram_image<CioII,LI,Extractor,Drawer> (
+    int_repr : &InteractionInternalRepresentation<CioII>,
+    extractor : &Extractor,
+    palette : &Drawer,
+    format : ImageFormat
+) -> Result<Vec<u8>,DrawError>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    LI : Eq + Hash + Copy + Clone,
+    Extractor : ContextAwareInteractionDrawingInstructionsExtractor<CioII,LI>,
+    Drawer : ContextAwareInteractionDrawer<LI>
+{
+    let image = get_interaction_as_sequence_diagram_image::<CioII,LI,Extractor,Drawer>(
+        int_repr,
+        extractor,
+        palette
+    )?;
+    let mut bytes : Vec<u8> = Vec::new();
+    image.write_to(&mut Cursor::new(&mut bytes), format)?;
+    Ok(bytes)
+}
+
+
+/** 
+ * Draws the interaction as a sequence diagram and saves it as an image at the given path.
+ * The image format is deduced from the extension of the file.
+ * Any error (e.g., an invalid path or an inconsistency between the extractor and the drawer) is returned.
+ * **/
+pub fn try_draw_interaction_as_sequence_diagram<CioII,LI,Extractor,Drawer> (
+    int_repr : &InteractionInternalRepresentation<CioII>,
+    extractor : &Extractor,
+    palette : &Drawer,
+    file_path : &Path,
+) -> Result<(),DrawError>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    LI : Eq + Hash + Copy + Clone,
+    Extractor : ContextAwareInteractionDrawingInstructionsExtractor<CioII,LI>,
+    Drawer : ContextAwareInteractionDrawer<LI>
+{
+    let image = get_interaction_as_sequence_diagram_image::<CioII,LI,Extractor,Drawer>(
+        int_repr,
+        extractor,
+        palette
+    )?;
+    image.save(file_path)?;
+    Ok(())
+}
 
+
+/** 
+ * Draws the interaction as a sequence diagram and saves it as an image at the given path.
+ * Any error is silently discarded, so that nothing may be drawn without the caller noticing.
+ * Use *try_draw_interaction_as_sequence_diagram* instead, which returns the errors.
+ * **/
+#[deprecated(note = "use try_draw_interaction_as_sequence_diagram")]
 pub fn draw_interaction_as_sequence_diagram<CioII,LI,Extractor,Drawer> (
     int_repr : &InteractionInternalRepresentation<CioII>,
     extractor : &Extractor,
@@ -34,23 +127,75 @@ pub fn draw_interaction_as_sequence_diagram<CioII,LI,Extractor,Drawer> (
     file_path : &Path,
 )
 where 
-    CioII : CommonIoInteractionInterface,
+    CioII : AnnotatedCommonIoInteractionInterface,
     LI : Eq + Hash + Copy + Clone,
     Extractor : ContextAwareInteractionDrawingInstructionsExtractor<CioII,LI>,
     Drawer : ContextAwareInteractionDrawer<LI>
 {
+    let _ = try_draw_interaction_as_sequence_diagram::<CioII,LI,Extractor,Drawer>(
+        int_repr,
+        extractor,
+        palette,
+        file_path
+    );
+}
+
+
 
+/** 
+ * Draws the interaction as a sequence diagram on a custom canvas.
+ * The canvas is created by *make_canvas* from the width and height of the diagram
+ * (which are only known once the drawing instructions have been extracted) and is then returned.
+ * **/
+pub fn draw_interaction_on_canvas<CioII,LI,Extractor,Drawer,Canvas> (
+    int_repr : &InteractionInternalRepresentation<CioII>,
+    extractor : &Extractor,
+    palette : &Drawer,
+    make_canvas : impl FnOnce(f32,f32) -> Canvas
+) -> Result<Canvas,DrawError>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    LI : Eq + Hash + Copy + Clone,
+    Extractor : ContextAwareInteractionDrawingInstructionsExtractor<CioII,LI>,
+    Drawer : ContextAwareInteractionDrawer<LI>,
+    Canvas : DiagramCanvas
+{
     let display_info = extract_drawing_information::<CioII,LI,Extractor>(
         extractor,
         int_repr
-    );
-    let image = make_image_from_display_information::<LI,Drawer>(
+    )?;
+    let mut canvas = make_canvas(display_info.width, display_info.height);
+    draw_display_information_on_canvas::<LI,Drawer,Canvas>(
         palette,
-        &display_info
-    );
-    let _ = image.save(file_path);
+        &display_info,
+        &mut canvas
+    )?;
+    Ok(canvas)
 }
 
 
 
-
+/** 
+ * Returns the layout of the sequence diagram of the interaction, 
+ * i.e., the absolute coordinates of all its elements, without drawing it.
+ * **/
+pub fn get_interaction_layout<CioII,LI,Extractor,Drawer> (
+    int_repr : &InteractionInternalRepresentation<CioII>,
+    extractor : &Extractor,
+    palette : &Drawer
+) -> Result<DiagramLayout<LI>,DrawError>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    LI : Eq + Hash + Copy + Clone,
+    Extractor : ContextAwareInteractionDrawingInstructionsExtractor<CioII,LI>,
+    Drawer : ContextAwareInteractionDrawer<LI>
+{
+    let display_info = extract_drawing_information::<CioII,LI,Extractor>(
+        extractor,
+        int_repr
+    )?;
+    make_layout_from_display_information::<LI,Drawer>(
+        palette,
+        &display_info
+    )
+}
@@ -0,0 +1,419 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use std::hash::Hash;
+
+use ab_glyph::{Font, PxScale};
+use image::Rgb;
+
+use image_colored_text::draw::coord::DrawCoord;
+use image_colored_text::text::paragraph::{ColoredTextParagraph, MultiLineTextAlignment};
+
+use crate::to_image::draw::context_aware_drawer::ContextAwareInteractionDrawer;
+use crate::to_image::drawable::leaf::broadcast::*;
+use crate::to_image::drawable::leaf::util::MessageExchangeLineStyle;
+use crate::to_image::drawable::operator::builtin_operator::*;
+use crate::to_image::drawable::operator::util::*;
+use crate::to_image::drawable::util::get_lifeline_horizontal_position;
+use crate::to_image::error::DrawError;
+use crate::to_image::extract::instructions::*;
+use crate::to_image::layout::model::*;
+
+
+
+
+fn to_layout_color(color : &Rgb<u8>) -> LayoutColor {
+    color.0
+}
+
+
+impl TextBoxLayout {
+
+    /** 
+     * Computes the layout of a paragraph of colored text that is drawn at the given coordinates.
+     * The layout (alignment, background, border and the 2 pixels of padding inside the border)
+     * is the same as that of *image_colored_text::draw::multi_line::draw_multiline_colored_text*.
+     * **/
+    pub(crate) fn from_paragraph(
+        x_pos : &DrawCoord,
+        y_pos : &DrawCoord,
+        paragraph : &ColoredTextParagraph,
+        font : &impl Font,
+        scale : impl Into<PxScale> + Copy
+    ) -> TextBoxLayout {
+        let (para_width, para_height, line_height) = paragraph.paragraph_size(scale, font);
+        let (x_left, y_top) = DrawCoord::get_adjusted_object_top_left_corner(x_pos, y_pos, para_width, para_height);
+        // ***
+        let (mut current_y_pos,startline_x_pos, inner_para_width) = match paragraph.border_color {
+            None => (y_top,x_left,para_width),
+            Some(_) => (y_top + 2.0, x_left + 2.0,para_width - 4.0)
+        };
+        let mut lines = vec![];
+        for line in &paragraph.lines {
+            let (line_width,_) = line.line_size(scale, font);
+            let line_x_pos = match &paragraph.alignment {
+                MultiLineTextAlignment::Left => {
+                    startline_x_pos
+                },
+                MultiLineTextAlignment::Right => {
+                    startline_x_pos + inner_para_width - line_width
+                },
+                MultiLineTextAlignment::Center => {
+                    startline_x_pos + (inner_para_width - line_width)/2.0
+                }
+            };
+            let segments = line.colored_segments.iter().map(
+                |(text,color)| TextSegmentLayout{text:text.clone(),color:to_layout_color(color)}
+            ).collect();
+            lines.push(
+                TextLineLayout{
+                    x_left:line_x_pos,
+                    y_top:current_y_pos,
+                    width:line_width,
+                    height:line_height,
+                    segments
+                }
+            );
+            current_y_pos += line_height;
+        }
+        // ***
+        TextBoxLayout{
+            x_left,
+            y_top,
+            width:para_width,
+            height:para_height,
+            background_color:paragraph.background_color.as_ref().map(to_layout_color),
+            border_color:paragraph.border_color.as_ref().map(to_layout_color),
+            lines
+        }
+    }
+
+}
+
+
+fn get_message_line_layout(style : &MessageExchangeLineStyle) -> MessageLineLayout {
+    MessageLineLayout{
+        bold:style.bold,
+        doubled:style.doubled,
+        color:to_layout_color(&style.color),
+        arrowhead_length:style.arrowhead_length,
+        dash_pattern:style.dash_pattern,
+        arrowhead_kind:style.arrowhead_kind,
+        tail_decoration:style.tail_decoration
+    }
+}
+
+
+fn get_leaf_pattern_layout<LI, Drawer>(
+    palette : &Drawer,
+    draw_instruction : &CompleteInteractionDrawInstruction<LI>,
+    leaf_instruct : &CompleteBroadcastLeafPatternDrawInstruction<LI>
+) -> Result<LeafPatternLayout<LI>,DrawError>
+where 
+    LI : Eq + Hash + Copy + Clone,
+    Drawer : ContextAwareInteractionDrawer<LI>
+{
+    let font = palette.get_font();
+    let scale = palette.get_scale();
+    let pattern = &leaf_instruct.pattern;
+    let info = &leaf_instruct.intermediate_info;
+    let y_top = draw_instruction.y_shift_to_absolute + leaf_instruct.relative_y_pos;
+    let midline_y = y_top + info.y_space_top_to_midline;
+    // ***
+    let origin = match &pattern.origin {
+        DrawableBroadcastLeafPatternOrigin::Empty => {
+            LeafPatternOriginLayout::Empty
+        },
+        DrawableBroadcastLeafPatternOrigin::InputOutsideGate(gate_parag) => {
+            LeafPatternOriginLayout::InputGate { 
+                x_pos: draw_instruction.left_side_of_diagram_x_pos, 
+                gate: TextBoxLayout::from_paragraph(
+                    &DrawCoord::EndingAt(draw_instruction.left_side_of_diagram_x_pos),
+                    &DrawCoord::CenteredAround(midline_y),
+                    gate_parag,
+                    font,
+                    scale
+                ) 
+            }
+        },
+        DrawableBroadcastLeafPatternOrigin::Lifeline(orig_lf, orig_act) => {
+            let x_pos = get_lifeline_horizontal_position(&draw_instruction.lifelines_horizontal_positions, orig_lf)?;
+            let mut texts = vec![];
+            if let Some(preamble) = &orig_act.preamble {
+                texts.push(
+                    TextBoxLayout::from_paragraph(&DrawCoord::CenteredAround(x_pos), &DrawCoord::EndingAt(midline_y), preamble, font, scale)
+                );
+            }
+            if let Some(postamble) = &orig_act.postamble {
+                texts.push(
+                    TextBoxLayout::from_paragraph(&DrawCoord::CenteredAround(x_pos), &DrawCoord::StartingAt(midline_y), postamble, font, scale)
+                );
+            }
+            LeafPatternOriginLayout::Lifeline { lifeline: *orig_lf, x_pos, texts }
+        }
+    };
+    // ***
+    let mut lifeline_targets = vec![];
+    for (tar_lf, tar_act) in &pattern.lifeline_targets {
+        let x_pos = get_lifeline_horizontal_position(&draw_instruction.lifelines_horizontal_positions, tar_lf)?;
+        let target_midline_y = DrawableBroadcastLeafPattern::get_target_y_midline(
+            info, tar_lf, tar_act, font, scale, y_top, palette.get_y_margin_between_seq_operands()
+        );
+        let created_lifeline_header_height = pattern.get_created_lifeline_header_size(info, tar_lf).map(|(_,height)| height);
+        let texts = tar_act.get_texts_with_vertical_coords(target_midline_y, created_lifeline_header_height).into_iter().map(
+            |(text,y_coord)| TextBoxLayout::from_paragraph(&DrawCoord::CenteredAround(x_pos), &y_coord, text, font, scale)
+        ).collect();
+        lifeline_targets.push(
+            LeafPatternTargetLayout{
+                lifeline:*tar_lf, 
+                x_pos, 
+                midline_y:target_midline_y, 
+                creates_lifeline:pattern.created_lifelines.contains(tar_lf),
+                texts
+            }
+        );
+    }
+    lifeline_targets.sort_by(|t1,t2| t1.x_pos.total_cmp(&t2.x_pos));
+    // ***
+    let mut output_gates = vec![];
+    {
+        let mut y = midline_y - info.y_shift_above_midline_for_output_gates;
+        for target_output_gate in &pattern.output_outside_gates_targets {
+            let gate = TextBoxLayout::from_paragraph(
+                &DrawCoord::StartingAt(draw_instruction.right_side_of_diagram_x_pos),
+                &DrawCoord::StartingAt(y),
+                target_output_gate,
+                font,
+                scale
+            );
+            y += gate.height + palette.get_margin_between_items();
+            output_gates.push(gate);
+        }
+    }
+    // ***
+    let message = {
+        let anchor_lifeline_x_pos = get_lifeline_horizontal_position(
+            &draw_instruction.lifelines_horizontal_positions, 
+            &info.message_drawing_location.0.anchor_lifeline
+        )?;
+        TextBoxLayout::from_paragraph(
+            &DrawCoord::CenteredAround(anchor_lifeline_x_pos + info.message_drawing_location.1),
+            &DrawCoord::EndingAt(midline_y - palette.get_margin_between_items()),
+            &pattern.message,
+            font,
+            scale
+        )
+    };
+    // ***
+    Ok(
+        LeafPatternLayout{
+            y_top,
+            y_bottom:y_top + info.y_space_top_to_bottom,
+            midline_y,
+            message,
+            line:get_message_line_layout(&pattern.line_style),
+            origin,
+            lifeline_targets,
+            output_gates_x_pos:draw_instruction.right_side_of_diagram_x_pos,
+            output_gates
+        }
+    )
+}
+
+
+fn get_operator_layout<LI, Drawer>(
+    palette : &Drawer,
+    draw_instruction : &CompleteInteractionDrawInstruction<LI>,
+    operator_instruct : &CompleteOperatorDrawInstruction<LI>
+) -> Result<Option<OperatorLayout<LI>>,DrawError>
+where 
+    LI : Eq + Hash + Copy + Clone,
+    Drawer : ContextAwareInteractionDrawer<LI>
+{
+    let horizontal_seps_absolute_vertical_positions : Vec<f32> = operator_instruct.horizontal_seps_relative_vertical_positions.iter()
+        .map(|y| y + draw_instruction.y_shift_to_absolute)
+        .collect();
+    let (y_top, separators_y, y_bottom) = get_operator_vertical_positions(&horizontal_seps_absolute_vertical_positions)?;
+    let nest_padding = (operator_instruct.nest_depth as f32) * palette.get_nest_padding_unit();
+    let shape = match &operator_instruct.drawable_op.kind {
+        DrawableOperatorKind::CoRegionLike(framed_lfs) => {
+            if framed_lfs.is_empty() {
+                return Ok(None);
+            }
+            let mut brackets = vec![];
+            for lf in framed_lfs {
+                let lf_x_pos = get_lifeline_horizontal_position(&draw_instruction.lifelines_horizontal_positions, lf)?;
+                let (x_left,x_right) = get_coregion_bracket_horizontal_bounds(lf_x_pos, nest_padding, palette.get_margin_between_items());
+                brackets.push(CoRegionBracketLayout{lifeline:*lf, x_left, x_right});
+            }
+            brackets.sort_by(|b1,b2| b1.x_left.total_cmp(&b2.x_left));
+            OperatorShapeLayout::CoRegionLike { brackets }
+        },
+        DrawableOperatorKind::Framed(label) => {
+            let (leftmost_lf,rightmost_lf) = match get_leftmost_and_rightmost_enclosed_lifelines(
+                &operator_instruct.enclosed_lfs_reqs, 
+                &draw_instruction.lifelines_horizontal_positions
+            )? {
+                None => {
+                    return Ok(None);
+                },
+                Some(extremities) => extremities
+            };
+            let (x_left,x_right) = get_combined_fragment_frame_horizontal_bounds(
+                &operator_instruct.enclosed_lfs_reqs, 
+                nest_padding, 
+                leftmost_lf, 
+                rightmost_lf, 
+                &draw_instruction.lifelines_horizontal_positions
+            )?;
+            let label = TextBoxLayout::from_paragraph(
+                &DrawCoord::StartingAt(x_left + palette.get_margin_between_items()),
+                &DrawCoord::StartingAt(y_top),
+                label,
+                palette.get_font(),
+                palette.get_scale()
+            );
+            OperatorShapeLayout::Framed { x_left, x_right, label }
+        }
+    };
+    Ok(
+        Some(
+            OperatorLayout{
+                y_top,
+                y_bottom,
+                separators_y,
+                color:to_layout_color(&operator_instruct.drawable_op.frame_color),
+                shape
+            }
+        )
+    )
+}
+
+
+/** 
+ * Computes the layout of the diagram with absolute coordinates, 
+ * using the same positions as those used when drawing on a canvas.
+ * **/
+pub(crate) fn make_layout_from_display_information<LI, Drawer>(
+    palette : &Drawer,
+    draw_instruction : &CompleteInteractionDrawInstruction<LI>
+) -> Result<DiagramLayout<LI>,DrawError>
+where 
+    LI : Eq + Hash + Copy + Clone,
+    Drawer : ContextAwareInteractionDrawer<LI>
+{
+    let involved_lifelines : Vec<LI> = draw_instruction.lifelines_horizontal_positions.keys().cloned().collect();
+    let lifelines_colors = palette.get_lifelines_colors(&involved_lifelines);
+    let mut lifelines = vec![];
+    for (lf,x_pos) in &draw_instruction.lifelines_horizontal_positions {
+        let color = lifelines_colors.get(lf).ok_or(DrawError::MissingLifelineColor)?;
+        let header = match draw_instruction.lifelines_headers.get(lf) {
+            None => {
+                return Err(DrawError::InconsistentPattern("a lifeline of the diagram has no header".to_owned()));
+            },
+            Some(header) => {
+                TextBoxLayout::from_paragraph(
+                    &DrawCoord::CenteredAround(*x_pos),
+                    &draw_instruction.get_lifeline_header_vertical_coord(lf),
+                    header,
+                    palette.get_font(),
+                    palette.get_scale()
+                )
+            }
+        };
+        let (y_top,y_bottom) = draw_instruction.get_lifeline_vertical_span(lf)?;
+        let destruction_cross = if draw_instruction.destroyed_lifelines.contains(lf) {
+            Some(palette.get_margin_between_items())
+        } else {
+            None
+        };
+        lifelines.push(
+            LifelineLayout{
+                lifeline:*lf,
+                x_pos:*x_pos,
+                y_top,
+                y_bottom,
+                destruction_cross,
+                color:to_layout_color(color),
+                header
+            }
+        );
+    }
+    lifelines.sort_by(|l1,l2| l1.x_pos.total_cmp(&l2.x_pos));
+    // ***
+    let mut lifeline_groups = vec![];
+    for group_instruct in &draw_instruction.lifeline_groups_to_draw {
+        lifeline_groups.push(
+            LifelineGroupLayout{
+                lifelines:group_instruct.lifelines.clone(),
+                x_left:group_instruct.x_left,
+                x_right:group_instruct.x_right,
+                y_top:group_instruct.y_top,
+                y_bottom:group_instruct.y_bottom,
+                color:to_layout_color(&group_instruct.frame_color),
+                title:TextBoxLayout::from_paragraph(
+                    &DrawCoord::StartingAt(group_instruct.x_left + palette.get_margin_between_items()),
+                    &DrawCoord::StartingAt(group_instruct.y_top + palette.get_margin_between_items()/2.0),
+                    &group_instruct.title,
+                    palette.get_font(),
+                    palette.get_scale()
+                )
+            }
+        );
+    }
+    // ***
+    let mut activation_bars = vec![];
+    for bar_instruct in &draw_instruction.activation_bars_to_draw {
+        let color = lifelines_colors.get(&bar_instruct.lifeline).ok_or(DrawError::MissingLifelineColor)?;
+        activation_bars.push(
+            ActivationBarLayout{
+                lifeline:bar_instruct.lifeline,
+                x_left:bar_instruct.x_left,
+                x_right:bar_instruct.x_right,
+                y_top:bar_instruct.y_top,
+                y_bottom:bar_instruct.y_bottom,
+                color:to_layout_color(color),
+                fill_color:to_layout_color(&palette.get_activation_bar_fill_color())
+            }
+        );
+    }
+    // ***
+    let mut leaf_patterns = vec![];
+    for leaf_instruct in &draw_instruction.patterns_to_draw {
+        leaf_patterns.push(get_leaf_pattern_layout(palette, draw_instruction, leaf_instruct)?);
+    }
+    // ***
+    let mut operators = vec![];
+    for operator_instruct in &draw_instruction.operators_to_draw {
+        if let Some(operator_layout) = get_operator_layout(palette, draw_instruction, operator_instruct)? {
+            operators.push(operator_layout);
+        }
+    }
+    // ***
+    Ok(
+        DiagramLayout{
+            width:draw_instruction.width,
+            height:draw_instruction.height,
+            lifelines,
+            lifeline_groups,
+            activation_bars,
+            leaf_patterns,
+            operators
+        }
+    )
+}
@@ -0,0 +1,26 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+/**
+ * A public and renderer-independent model of the layout of a sequence diagram.
+ * It gives the absolute coordinates of all the elements of the diagram 
+ * so that it can be rendered by other means (e.g., client-side in a web browser).
+ * With the *serde* feature, it can be serialized (e.g., to JSON).
+ **/
+pub mod model;
+pub(crate) mod build;
@@ -0,0 +1,258 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::to_image::drawable::leaf::util::{MessageArrowTailDecoration, MessageArrowheadKind, MessageLineDashPattern};
+
+
+
+/** 
+ * A color given as its red, green and blue components.
+ * **/
+pub type LayoutColor = [u8;3];
+
+
+/** 
+ * A segment of text of a single color.
+ * **/
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TextSegmentLayout {
+    pub text : String,
+    pub color : LayoutColor
+}
+
+/** 
+ * A line of text, the *x_left* and *y_top* coordinates being those of its top left corner.
+ * **/
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TextLineLayout {
+    pub x_left : f32,
+    pub y_top : f32,
+    pub width : f32,
+    pub height : f32,
+    pub segments : Vec<TextSegmentLayout>
+}
+
+/** 
+ * A box containing lines of text (e.g., a lifeline header, a message, an operator label etc.).
+ * **/
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TextBoxLayout {
+    pub x_left : f32,
+    pub y_top : f32,
+    pub width : f32,
+    pub height : f32,
+    pub background_color : Option<LayoutColor>,
+    pub border_color : Option<LayoutColor>,
+    pub lines : Vec<TextLineLayout>
+}
+
+
+
+/** 
+ * A lifeline, drawn as a vertical line at *x_pos* from *y_top* to *y_bottom* under its header.
+ * The header of a lifeline that is created by a message is drawn at the reception of that message.
+ * A lifeline that is destroyed ends at *y_bottom* with a cross which half-size is given by *destruction_cross*.
+ * **/
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LifelineLayout<LI> {
+    pub lifeline : LI,
+    pub x_pos : f32,
+    pub y_top : f32,
+    pub y_bottom : f32,
+    pub destruction_cross : Option<f32>,
+    pub color : LayoutColor,
+    pub header : TextBoxLayout
+}
+
+
+
+/** 
+ * The box of a group of lifelines, which title is drawn at its top left corner, above the headers of the lifelines.
+ * *lifelines* are all the lifelines of the group (including those of its sub-groups) from left to right.
+ * **/
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LifelineGroupLayout<LI> {
+    pub lifelines : Vec<LI>,
+    pub x_left : f32,
+    pub x_right : f32,
+    pub y_top : f32,
+    pub y_bottom : f32,
+    pub color : LayoutColor,
+    pub title : TextBoxLayout
+}
+
+
+
+/** 
+ * An activation bar on a lifeline, filled with *fill_color* and which borders have the color of the lifeline.
+ * **/
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ActivationBarLayout<LI> {
+    pub lifeline : LI,
+    pub x_left : f32,
+    pub x_right : f32,
+    pub y_top : f32,
+    pub y_bottom : f32,
+    pub color : LayoutColor,
+    pub fill_color : LayoutColor
+}
+
+
+
+/** 
+ * How the horizontal lines of a message exchange are drawn.
+ * **/
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MessageLineLayout {
+    pub bold : bool,
+    pub doubled : bool,
+    pub color : LayoutColor,
+    pub arrowhead_length : f32,
+    pub dash_pattern : MessageLineDashPattern,
+    pub arrowhead_kind : MessageArrowheadKind,
+    pub tail_decoration : MessageArrowTailDecoration
+}
+
+/** 
+ * Where the message of a leaf pattern comes from.
+ * **/
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LeafPatternOriginLayout<LI> {
+    // the message comes from the environment
+    Empty,
+    // the message comes from an input gate, drawn at the left of *x_pos*
+    InputGate{
+        x_pos : f32,
+        gate : TextBoxLayout
+    },
+    // the message is emitted by a lifeline, the texts being those that are drawn around the emission
+    Lifeline{
+        lifeline : LI,
+        x_pos : f32,
+        texts : Vec<TextBoxLayout>
+    }
+}
+
+/** 
+ * The reception of the message of a leaf pattern on a lifeline.
+ * The *midline_y* may differ from that of the pattern if the target lifeline is also the emitting lifeline.
+ * If the message creates the target lifeline, its arrow ends on the side of the header of that lifeline.
+ * **/
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LeafPatternTargetLayout<LI> {
+    pub lifeline : LI,
+    pub x_pos : f32,
+    pub midline_y : f32,
+    pub creates_lifeline : bool,
+    pub texts : Vec<TextBoxLayout>
+}
+
+/** 
+ * A leaf pattern (message passing, broadcast etc.) occupying the vertical space between *y_top* and *y_bottom*.
+ * The horizontal line(s) of the message exchange are drawn at *midline_y*.
+ * Output gates are drawn at the right of *output_gates_x_pos*.
+ * **/
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LeafPatternLayout<LI> {
+    pub y_top : f32,
+    pub y_bottom : f32,
+    pub midline_y : f32,
+    pub message : TextBoxLayout,
+    pub line : MessageLineLayout,
+    pub origin : LeafPatternOriginLayout<LI>,
+    pub lifeline_targets : Vec<LeafPatternTargetLayout<LI>>,
+    pub output_gates_x_pos : f32,
+    pub output_gates : Vec<TextBoxLayout>
+}
+
+
+
+/** 
+ * The bracket drawn on a single lifeline for a co-region-like operator.
+ * **/
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CoRegionBracketLayout<LI> {
+    pub lifeline : LI,
+    pub x_left : f32,
+    pub x_right : f32
+}
+
+/** 
+ * How an operator is drawn.
+ * **/
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum OperatorShapeLayout<LI> {
+    // a rectangular frame with a label at its top left corner
+    Framed{
+        x_left : f32,
+        x_right : f32,
+        label : TextBoxLayout
+    },
+    // brackets on individual lifelines
+    CoRegionLike{
+        brackets : Vec<CoRegionBracketLayout<LI>>
+    }
+}
+
+/** 
+ * An operator which operands are separated by horizontal lines at the *separators_y* vertical positions.
+ * **/
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OperatorLayout<LI> {
+    pub y_top : f32,
+    pub y_bottom : f32,
+    pub separators_y : Vec<f32>,
+    pub color : LayoutColor,
+    pub shape : OperatorShapeLayout<LI>
+}
+
+
+
+/** 
+ * The complete layout of a sequence diagram.
+ * Lifelines are ordered from left to right.
+ * Lifeline groups and activation bars are given from the outermost to the innermost.
+ * Leaf patterns and operators are given in the order in which they are drawn.
+ * **/
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DiagramLayout<LI> {
+    pub width : f32,
+    pub height : f32,
+    pub lifelines : Vec<LifelineLayout<LI>>,
+    pub lifeline_groups : Vec<LifelineGroupLayout<LI>>,
+    pub activation_bars : Vec<ActivationBarLayout<LI>>,
+    pub leaf_patterns : Vec<LeafPatternLayout<LI>>,
+    pub operators : Vec<OperatorLayout<LI>>
+}
@@ -18,6 +18,8 @@ pub mod common_interaction_drawer;
 pub mod drawable;
 pub mod extract;
 pub mod draw;
+pub mod error;
+pub mod layout;
 pub mod interface;
 
 
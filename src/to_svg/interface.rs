@@ -0,0 +1,85 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use std::path::Path;
+use std::hash::Hash;
+
+
+use crate::internal_representation::*;
+use crate::to_image::extract::context_aware_extractor::ContextAwareInteractionDrawingInstructionsExtractor;
+use crate::to_image::draw::context_aware_drawer::ContextAwareInteractionDrawer;
+
+use crate::to_image::interface::draw_interaction_on_canvas;
+use crate::to_image::error::DrawError;
+use crate::to_svg::svg_document::SvgDocument;
+
+
+/** 
+ * Returns the text of an SVG document in which the interaction is drawn as a sequence diagram.
+ * Texts are positioned using the font of the drawer, 
+ * so *font_family* should name that same font for the viewer to render them at the right size.
+ * **/
+pub fn get_interaction_as_svg_sequence_diagram<CioII,LI,Extractor,Drawer> (
+    int_repr : &InteractionInternalRepresentation<CioII>,
+    extractor : &Extractor,
+    palette : &Drawer,
+    font_family : &str
+) -> Result<String,DrawError>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    LI : Eq + Hash + Copy + Clone,
+    Extractor : ContextAwareInteractionDrawingInstructionsExtractor<CioII,LI>,
+    Drawer : ContextAwareInteractionDrawer<LI>
+{
+    let document = draw_interaction_on_canvas::<CioII,LI,Extractor,Drawer,SvgDocument>(
+        int_repr,
+        extractor,
+        palette,
+        |width,height| SvgDocument::new(width,height,font_family)
+    )?;
+    Ok(document.to_svg_string())
+}
+
+
+/** 
+ * Draws the interaction as a sequence diagram in an SVG file at the given path.
+ * See *get_interaction_as_svg_sequence_diagram* for the meaning of *font_family*.
+ * **/
+pub fn draw_interaction_as_svg_sequence_diagram<CioII,LI,Extractor,Drawer> (
+    int_repr : &InteractionInternalRepresentation<CioII>,
+    extractor : &Extractor,
+    palette : &Drawer,
+    font_family : &str,
+    file_path : &Path,
+) -> Result<(),DrawError>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    LI : Eq + Hash + Copy + Clone,
+    Extractor : ContextAwareInteractionDrawingInstructionsExtractor<CioII,LI>,
+    Drawer : ContextAwareInteractionDrawer<LI>
+{
+    let svg = get_interaction_as_svg_sequence_diagram::<CioII,LI,Extractor,Drawer>(
+        int_repr,
+        extractor,
+        palette,
+        font_family
+    )?;
+    std::fs::write(file_path, svg)?;
+    Ok(())
+}
+
@@ -0,0 +1,251 @@
+/*
+Copyright 2024 Erwan Mahe (github.com/erwanM974)
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+
+use std::fmt::Write;
+
+use ab_glyph::{Font, PxScale, ScaleFont};
+use image::Rgb;
+
+use image_colored_text::draw::coord::DrawCoord;
+use image_colored_text::text::paragraph::ColoredTextParagraph;
+
+use crate::to_image::draw::canvas::DiagramCanvas;
+use crate::to_image::layout::model::TextBoxLayout;
+
+
+
+/** 
+ * An SVG document that is built incrementally by appending elements.
+ * Elements are written in the order in which they are added so that
+ * later elements are drawn on top of earlier ones (as is the case when drawing on an *RgbImage*).
+ * **/
+pub struct SvgDocument {
+    pub width : f32,
+    pub height : f32,
+    /** 
+     * The font family written on the text elements.
+     * It should name the font used to measure the texts so that they fit in the layout.
+     * **/
+    pub font_family : String,
+    elements : Vec<String>
+}
+
+impl SvgDocument {
+
+    pub fn new(width : f32, height : f32, font_family : &str) -> SvgDocument {
+        SvgDocument{width,height,font_family:font_family.to_owned(),elements:vec![]}
+    }
+
+    fn add_line(&mut self, start : (f32,f32), end : (f32,f32), color : Rgb<u8>, stroke_width : f32) {
+        self.elements.push(
+            format!(
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}"/>"#,
+                start.0, start.1, end.0, end.1, svg_color(color), stroke_width
+            )
+        );
+    }
+
+    fn add_dashed_line(&mut self, start : (f32,f32), end : (f32,f32), color : Rgb<u8>, stroke_width : f32, (dash_length,gap_length) : (f32,f32)) {
+        self.elements.push(
+            format!(
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" stroke-dasharray="{} {}"/>"#,
+                start.0, start.1, end.0, end.1, svg_color(color), stroke_width, dash_length, gap_length
+            )
+        );
+    }
+
+    fn add_polyline(&mut self, points : &[(f32,f32)], color : Rgb<u8>, stroke_width : f32) {
+        let points_str : Vec<String> = points.iter().map(|(x,y)| format!("{},{}",x,y)).collect();
+        self.elements.push(
+            format!(
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+                points_str.join(" "), svg_color(color), stroke_width
+            )
+        );
+    }
+
+    fn add_polygon(&mut self, points : &[(f32,f32)], color : Rgb<u8>) {
+        let points_str : Vec<String> = points.iter().map(|(x,y)| format!("{},{}",x,y)).collect();
+        self.elements.push(
+            format!(
+                r#"<polygon points="{}" fill="{}" stroke="{}" stroke-width="1"/>"#,
+                points_str.join(" "), svg_color(color), svg_color(color)
+            )
+        );
+    }
+
+    fn add_circle(&mut self, center : (f32,f32), radius : f32, color : Rgb<u8>) {
+        self.elements.push(
+            format!(
+                r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+                center.0, center.1, radius, svg_color(color)
+            )
+        );
+    }
+
+    fn add_rect(&mut self, x : f32, y : f32, width : f32, height : f32, fill : Option<Rgb<u8>>, stroke : Option<Rgb<u8>>) {
+        let fill_str = match fill {
+            None => "none".to_owned(),
+            Some(col) => svg_color(col)
+        };
+        let stroke_str = match stroke {
+            None => "".to_owned(),
+            Some(col) => format!(r#" stroke="{}" stroke-width="1""#, svg_color(col))
+        };
+        self.elements.push(
+            format!(
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"{}/>"#,
+                x, y, width, height, fill_str, stroke_str
+            )
+        );
+    }
+
+    pub fn to_svg_string(&self) -> String {
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+            self.width, self.height, self.width, self.height
+        );
+        svg.push('\n');
+        for element in &self.elements {
+            svg.push_str(element);
+            svg.push('\n');
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+}
+
+/**
+ * Unlike the rasterized image, SVG natively supports stroke widths, dash arrays, polylines, polygons and circles
+ * so that thick or dashed lines, arrowheads and circles are drawn as single elements.
+ **/
+impl DiagramCanvas for SvgDocument {
+
+    fn draw_line(&mut self, start : (f32,f32), end : (f32,f32), color : Rgb<u8>) {
+        self.add_line(start, end, color, 1.0);
+    }
+
+    fn draw_filled_rect(&mut self, x_left : f32, y_top : f32, width : f32, height : f32, color : Rgb<u8>) {
+        self.add_rect(x_left, y_top, width, height, Some(color), None);
+    }
+
+    /** 
+     * The position of each line is given by the *TextBoxLayout* of the paragraph.
+     * Each line is written as a single *text* element so that the content of the diagram remains searchable.
+     * **/
+    fn draw_text_paragraph(
+        &mut self,
+        x_pos : &DrawCoord,
+        y_pos : &DrawCoord,
+        to_print : &ColoredTextParagraph,
+        font : &impl Font,
+        scale : impl Into<PxScale> + Copy
+    ) {
+        let text_box = TextBoxLayout::from_paragraph(x_pos, y_pos, to_print, font, scale);
+        // ***
+        if to_print.background_color.is_some() || to_print.border_color.is_some() {
+            self.add_rect(
+                text_box.x_left, 
+                text_box.y_top, 
+                text_box.width, 
+                text_box.height, 
+                to_print.background_color, 
+                to_print.border_color
+            );
+        }
+        // ***
+        let px_scale : PxScale = scale.into();
+        let ascent = font.as_scaled(px_scale).ascent();
+        // ***
+        for line in &text_box.lines {
+            let mut text_element = format!(
+                r#"<text x="{}" y="{}" font-family="{}" font-size="{}" xml:space="preserve">"#,
+                line.x_left, line.y_top + ascent, escape_xml(&self.font_family), px_scale.y
+            );
+            for segment in &line.segments {
+                let _ = write!(text_element, r#"<tspan fill="{}">{}</tspan>"#, svg_color(Rgb(segment.color)), escape_xml(&segment.text));
+            }
+            text_element.push_str("</text>");
+            self.elements.push(text_element);
+        }
+    }
+
+    fn draw_thick_line(&mut self, start : (f32,f32), end : (f32,f32), color : Rgb<u8>) {
+        self.add_line(start, end, color, 2.0);
+    }
+
+    fn draw_arrowhead(&mut self, tip : (f32,f32), pointing_rightward : bool, arrowhead_length : f32, color : Rgb<u8>) {
+        let (x_pos,y_pos) = tip;
+        let x_back = if pointing_rightward {
+            x_pos - arrowhead_length
+        } else {
+            x_pos + arrowhead_length
+        };
+        self.add_polyline(
+            &[(x_back, y_pos - arrowhead_length), tip, (x_back, y_pos + arrowhead_length)], 
+            color, 
+            1.0
+        );
+    }
+
+    fn draw_filled_arrowhead(&mut self, tip : (f32,f32), pointing_rightward : bool, arrowhead_length : f32, color : Rgb<u8>) {
+        let (x_pos,y_pos) = tip;
+        let x_back = if pointing_rightward {
+            x_pos - arrowhead_length
+        } else {
+            x_pos + arrowhead_length
+        };
+        self.add_polygon(&[(x_back, y_pos - arrowhead_length), tip, (x_back, y_pos + arrowhead_length)], color);
+    }
+
+    fn draw_dashed_line(&mut self, start : (f32,f32), end : (f32,f32), color : Rgb<u8>, thick : bool, dash_and_gap_lengths : (f32,f32)) {
+        let stroke_width = if thick {
+            2.0
+        } else {
+            1.0
+        };
+        self.add_dashed_line(start, end, color, stroke_width, dash_and_gap_lengths);
+    }
+
+    fn draw_filled_circle(&mut self, center : (f32,f32), radius : f32, color : Rgb<u8>) {
+        self.add_circle(center, radius, color);
+    }
+
+}
+
+
+fn svg_color(color : Rgb<u8>) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.0[0], color.0[1], color.0[2])
+}
+
+fn escape_xml(text : &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c)
+        }
+    }
+    escaped
+}
+
@@ -15,16 +15,29 @@ limitations under the License.
 */
 
 
-use crate::internal_representation::CommonIoInteractionInterface;
-use crate::internal_representation::InteractionInternalRepresentation;
+use crate::internal_representation::AnnotatedCommonIoInteractionInterface;
+use crate::internal_representation::{InteractionComments, InteractionInternalRepresentation, OperatorParameters};
+use crate::syntax::{DefaultInteractionLanguageSyntax, InteractionLanguageSyntax};
 
 
-pub trait ContextAwareInteractionPrinter<CioII : CommonIoInteractionInterface> {
+/** 
+ * How the interactions of a language are printed 
+ * (the delimiters of annotations and the keyword of definitions being given by its *syntax*).
+ * **/
+pub trait ContextAwareInteractionPrinter<CioII : AnnotatedCommonIoInteractionInterface> {
 
     fn left_parenthesis(&self) -> &str;
     fn right_parenthesis(&self) -> &str;
     fn operand_separator(&self) -> &str;
 
+    /** 
+     * The syntax of annotations and definitions, which the parser of the language should share.
+     * By default, the language has no annotations and definitions are written as in *let name = interaction;*.
+     * **/
+    fn syntax(&self) -> &dyn InteractionLanguageSyntax {
+        &DefaultInteractionLanguageSyntax
+    }
+
     /** 
      * Returns how to print the given operator, also taking into account the sub-interactions underneath.
      * **/
@@ -34,40 +47,147 @@ pub trait ContextAwareInteractionPrinter<CioII : CommonIoInteractionInterface> {
         sub_ints : &[InteractionInternalRepresentation<CioII>]
     ) -> String;
 
+    /** 
+     * Prints the argument list of an operator (e.g. to be appended to its name by *print_operator*),
+     * the arguments being separated by the *operand_separator*.
+     * **/
+    fn print_operator_parameters(&self, parameters : &OperatorParameters) -> String {
+        format!("{}{}{}", parameters.opening, parameters.arguments.join(self.operand_separator()), parameters.closing)
+    }
+
     fn print_explicit_pattern(&self, leaf_pattern : &CioII::InteractionLeafPatternType) -> String;
 
+    /** 
+     * Prints the content of an annotation, without its delimiters, or *None* if it is not to be printed.
+     * By default, annotations are not printed, only the sub-interactions they annotate.
+     * They are also not printed if the *syntax* has no *annotation_delimiters*.
+     * **/
+    fn print_annotation(&self, _annotation : &CioII::InteractionAnnotationType) -> Option<String> {
+        None
+    }
+
+    /** 
+     * The name given to the *index*-th (starting at 1) definition created when printing with definitions.
+     * It must not clash with the names of lifelines, messages or operators.
+     * **/
+    fn make_definition_name(&self, index : usize) -> String {
+        format!("def{}", index)
+    }
+
     fn print_interaction_inner(&self,
         depth : usize,
         int : &InteractionInternalRepresentation<CioII>
     ) -> String {
-        match int {
-            InteractionInternalRepresentation::LeafPattern(leaf) => {
-                format!("{}{}", "\t".repeat(depth), self.print_explicit_pattern(leaf))
-            },
-            InteractionInternalRepresentation::Operator(op, sub_ints ) => {
-                let first_line : &str = &format!("{}{}{}", "\t".repeat(depth), self.print_operator(op,sub_ints), self.left_parenthesis());
-                let inner_lines : Vec<String> = sub_ints.iter()
-                .map(|sub_int| self.print_interaction_inner(depth + 1, sub_int))
-                .collect();
-                let inner_lines_sep = format!("{}\n", self.operand_separator());
-                let last_line : &str = &format!("{}{}", "\t".repeat(depth), self.right_parenthesis());
-                format!("{}\n{}\n{}", first_line.to_owned(), inner_lines.join(&inner_lines_sep), last_line)
+        self.print_interaction_inner_with_comments(depth, &mut vec![], int, &InteractionComments::new())
+    }
+
+    /** 
+     * Prints the interaction with, before (resp. after) each sub-interaction, its leading (resp. trailing) comments
+     * (the sub-interaction at *path* being *int*).
+     * Each comment is printed on its own line.
+     * **/
+    fn print_interaction_inner_with_comments(&self,
+        depth : usize,
+        path : &mut Vec<usize>,
+        int : &InteractionInternalRepresentation<CioII>,
+        comments : &InteractionComments
+    ) -> String {
+        // the text is printed in order using an explicit stack so that deeply nested interactions do not overflow the call stack
+        let mut printed = String::new();
+        let mut steps = vec![PrintingStep::Trailing(depth),PrintingStep::Print(int,depth)];
+        while let Some(step) = steps.pop() {
+            match step {
+                PrintingStep::Print(int, depth) => {
+                    let indent = "\t".repeat(depth);
+                    printed.push_str(&print_leading_comment_lines(comments, path, depth));
+                    match int {
+                        InteractionInternalRepresentation::LeafPattern(leaf) => {
+                            printed.push_str(&format!("{}{}", indent, self.print_explicit_pattern(leaf)));
+                        },
+                        InteractionInternalRepresentation::Operator(op, sub_ints) => {
+                            printed.push_str(&format!("{}{}{}\n", indent, self.print_operator(op,sub_ints), self.left_parenthesis()));
+                            if sub_ints.is_empty() {
+                                printed.push('\n');
+                            }
+                            steps.push(PrintingStep::Text(format!("{}{}", indent, self.right_parenthesis())));
+                            for (operand_index,sub_int) in sub_ints.iter().enumerate().rev() {
+                                steps.push(PrintingStep::Text("\n".to_owned()));
+                                steps.push(PrintingStep::Leave);
+                                steps.push(PrintingStep::Trailing(depth + 1));
+                                // the separator is printed right after the operand, before its trailing comments
+                                if operand_index + 1 < sub_ints.len() {
+                                    steps.push(PrintingStep::Text(self.operand_separator().to_owned()));
+                                }
+                                steps.push(PrintingStep::Print(sub_int,depth + 1));
+                                steps.push(PrintingStep::Enter(operand_index));
+                            }
+                        },
+                        InteractionInternalRepresentation::Annotated(annotation, annotated) => {
+                            // the annotation is printed on its own line, before the annotated sub-interaction
+                            if let (Some((opening,closing)),Some(printed_annotation)) = (self.syntax().annotation_delimiters(),self.print_annotation(annotation)) {
+                                printed.push_str(&format!("{}{}{}{}\n", indent, opening, printed_annotation, closing));
+                            }
+                            steps.push(PrintingStep::Leave);
+                            steps.push(PrintingStep::Trailing(depth));
+                            steps.push(PrintingStep::Print(annotated,depth));
+                            steps.push(PrintingStep::Enter(0));
+                        }
+                    }
+                },
+                PrintingStep::Trailing(depth) => {
+                    printed.push_str(&print_trailing_comment_lines(comments, path, depth));
+                },
+                PrintingStep::Text(text) => {
+                    printed.push_str(&text);
+                },
+                PrintingStep::Enter(operand_index) => {
+                    path.push(operand_index);
+                },
+                PrintingStep::Leave => {
+                    path.pop();
+                }
             }
-            
         }
+        printed
     }
 
-}
-
-
-
 
+}
 
 
 
+enum PrintingStep<'a,CioII : AnnotatedCommonIoInteractionInterface> {
+    // prints the node at the given depth without its trailing comments, the current path being that of the node
+    Print(&'a InteractionInternalRepresentation<CioII>,usize),
+    // prints the trailing comments of the node at the current path
+    Trailing(usize),
+    Text(String),
+    // moves the current path to the child at the given index
+    Enter(usize),
+    // moves the current path back to the parent
+    Leave
+}
 
 
 
+/** 
+ * Prints the leading comments of the sub-interaction at *path*, each on its own line.
+ * **/
+fn print_leading_comment_lines(comments : &InteractionComments, path : &[usize], depth : usize) -> String {
+    let indent = "\t".repeat(depth);
+    comments.leading.get(path)
+        .map(|got| got.iter().map(|comment| format!("{}{}\n", indent, comment)).collect())
+        .unwrap_or_default()
+}
 
 
 
+/** 
+ * Prints the trailing comments of the sub-interaction at *path*, each on a new line.
+ * **/
+fn print_trailing_comment_lines(comments : &InteractionComments, path : &[usize], depth : usize) -> String {
+    let indent = "\t".repeat(depth);
+    comments.trailing.get(path)
+        .map(|got| got.iter().map(|comment| format!("\n{}{}", indent, comment)).collect())
+        .unwrap_or_default()
+}
@@ -16,7 +16,9 @@ limitations under the License.
 
 
 
-use crate::internal_representation::{CommonIoInteractionInterface, InteractionInternalRepresentation};
+use std::collections::HashMap;
+
+use crate::internal_representation::{AnnotatedCommonIoInteractionInterface, InfixAssociativity, InfixNotationTable, InteractionComments, InteractionInternalRepresentation, InteractionOperatorRepresentation, OperatorNotation};
 use crate::to_text::context_aware_printer::ContextAwareInteractionPrinter;
 
 
@@ -27,9 +29,462 @@ pub fn print_interaction<CioII,Printer>
     ) -> 
         String
 where 
-    CioII : CommonIoInteractionInterface,
+    CioII : AnnotatedCommonIoInteractionInterface,
     Printer : ContextAwareInteractionPrinter<CioII>
 {
     printer.print_interaction_inner(0, int)
 }
 
+
+
+/** 
+ * Prints the interaction together with the comments attached to its sub-interactions
+ * (e.g., those collected by *parse_interaction_with_comments*).
+ * Each comment is printed on its own line, before (or after, for trailing comments) the sub-interaction it is attached to.
+ * **/
+pub fn print_interaction_with_comments<CioII,Printer>
+    (
+        int : &InteractionInternalRepresentation<CioII>,
+        comments : &InteractionComments,
+        printer : &Printer
+    ) -> 
+        String
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Printer : ContextAwareInteractionPrinter<CioII>
+{
+    printer.print_interaction_inner_with_comments(0, &mut vec![], int, comments)
+}
+
+
+
+/** 
+ * Prints the interaction, factoring the sub-interactions which occur several times into definitions of named sub-interactions
+ * (which can be parsed back with *parse_interaction_with_definitions*).
+ * Only sub-interactions with an operator at their root are factored.
+ * **/
+pub fn print_interaction_with_definitions<CioII,Printer>
+    (
+        int : &InteractionInternalRepresentation<CioII>,
+        printer : &Printer
+    ) -> 
+        String
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Printer : ContextAwareInteractionPrinter<CioII>
+{
+    let (keys,occurrences) = get_keys_of_sub_interactions(int, printer);
+    // sub-interactions which only occur within the occurrences of a larger factored sub-interaction
+    // would only be referred to once, in the body of the definition of the larger one
+    let references = count_references_to_sub_interactions(int, &keys, &occurrences);
+    let (main,definitions) = print_interaction_with_references(int, printer, &keys, &references);
+    let mut printed = String::new();
+    for (name,body) in definitions {
+        printed.push_str(
+            &format!(
+                "{} {} {} {}{}\n", 
+                printer.syntax().definition_keyword(), 
+                name, 
+                printer.syntax().definition_assignment_char(), 
+                body, 
+                printer.syntax().definition_terminator_char()
+            )
+        );
+    }
+    printed.push_str(&main);
+    printed
+}
+
+
+
+/** 
+ * Prints the interaction on a single line in the infix syntax described by the *InfixNotationTable* of the printer
+ * (which can be parsed back with *parse_infix_interaction*).
+ * Only the parentheses that are required to preserve the structure of the interaction are printed.
+ * **/
+pub fn print_interaction_infix<CioII,Printer>
+    (
+        int : &InteractionInternalRepresentation<CioII>,
+        printer : &Printer
+    ) -> 
+        String
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Printer : ContextAwareInteractionPrinter<CioII> + InfixNotationTable<CioII>
+{
+    let notations = printer.get_operator_notations();
+    print_interaction_infix_inner(int, printer, &notations)
+}
+
+
+
+/** 
+ * Identifies a sub-interaction up to its printed text :
+ * leaf patterns by their printed text and the other nodes by their printed operator or annotation and the keys of their children.
+ * **/
+#[derive(PartialEq, Eq, Hash)]
+enum SubInteractionKey {
+    Leaf(String),
+    Operator(String,Vec<usize>),
+    Annotated(Option<String>,usize)
+}
+
+
+
+/** 
+ * Sub-interactions with the same printed text are given the same key, so that the key of each operator is computed 
+ * once from the keys of its operands. 
+ * Nodes are identified by their address.
+ * Returns the key of each node and the number of occurrences of the key of each operator.
+ * **/
+fn get_keys_of_sub_interactions<CioII,Printer>
+    (
+        int : &InteractionInternalRepresentation<CioII>,
+        printer : &Printer
+    ) -> 
+        (HashMap<*const InteractionInternalRepresentation<CioII>,usize>,HashMap<usize,usize>)
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Printer : ContextAwareInteractionPrinter<CioII>
+{
+    let mut interned : HashMap<SubInteractionKey,usize> = HashMap::new();
+    let mut keys = HashMap::new();
+    let mut occurrences = HashMap::new();
+    // post-order traversal : a node is visited a second time once all its children have been
+    let mut to_visit = vec![(int,false)];
+    while let Some((int,children_visited)) = to_visit.pop() {
+        if !children_visited {
+            to_visit.push((int,true));
+            match int {
+                InteractionInternalRepresentation::LeafPattern(_) => {},
+                InteractionInternalRepresentation::Operator(_, sub_ints) => {
+                    to_visit.extend(sub_ints.iter().map(|sub_int| (sub_int,false)));
+                },
+                InteractionInternalRepresentation::Annotated(_, annotated) => {
+                    to_visit.push((annotated,false));
+                }
+            }
+            continue;
+        }
+        let key = match int {
+            InteractionInternalRepresentation::LeafPattern(leaf) => {
+                SubInteractionKey::Leaf(printer.print_explicit_pattern(leaf))
+            },
+            InteractionInternalRepresentation::Operator(op, sub_ints) => {
+                let sub_keys = sub_ints.iter().map(|sub_int| keys[&(sub_int as *const _)]).collect();
+                SubInteractionKey::Operator(printer.print_operator(op,sub_ints), sub_keys)
+            },
+            InteractionInternalRepresentation::Annotated(annotation, annotated) => {
+                SubInteractionKey::Annotated(printer.print_annotation(annotation), keys[&(annotated.as_ref() as *const _)])
+            }
+        };
+        let is_operator = matches!(key, SubInteractionKey::Operator(..));
+        let next_key = interned.len();
+        let key = *interned.entry(key).or_insert(next_key);
+        if is_operator {
+            *occurrences.entry(key).or_default() += 1;
+        }
+        keys.insert(int as *const _, key);
+    }
+    (keys,occurrences)
+}
+
+
+
+/** 
+ * Counts how many times each sub-interaction would be referred to if all those which occur several times were factored.
+ * **/
+fn count_references_to_sub_interactions<CioII>
+    (
+        int : &InteractionInternalRepresentation<CioII>,
+        keys : &HashMap<*const InteractionInternalRepresentation<CioII>,usize>,
+        occurrences : &HashMap<usize,usize>
+    ) -> 
+        HashMap<usize,usize>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface
+{
+    let mut references = HashMap::new();
+    // each node is given with whether it is the body of a definition
+    let mut to_visit = vec![(int,false)];
+    while let Some((int,is_definition_body)) = to_visit.pop() {
+        match int {
+            InteractionInternalRepresentation::LeafPattern(_) => {},
+            InteractionInternalRepresentation::Operator(_, sub_ints) => {
+                let key = keys[&(int as *const _)];
+                if !is_definition_body && occurrences[&key] >= 2 {
+                    let count = references.entry(key).or_default();
+                    *count += 1;
+                    // the body of the definition is only visited once
+                    if *count == 1 {
+                        to_visit.push((int,true));
+                    }
+                    continue;
+                }
+                to_visit.extend(sub_ints.iter().map(|sub_int| (sub_int,false)));
+            },
+            InteractionInternalRepresentation::Annotated(_, annotated) => {
+                to_visit.push((annotated,false));
+            }
+        }
+    }
+    references
+}
+
+
+
+enum PrintingStep<'a,CioII : AnnotatedCommonIoInteractionInterface> {
+    // prints the node at the given depth, possibly as the body of a definition
+    Print(&'a InteractionInternalRepresentation<CioII>,usize,bool),
+    // assembles the node from the texts of its children, which have been printed
+    Assemble(&'a InteractionInternalRepresentation<CioII>,usize),
+    // names the definition whose body has been printed and refers to it at the given depth
+    Define(usize,usize)
+}
+
+
+
+/** 
+ * Prints the interaction, replacing sub-interactions which are referred to several times by references to definitions.
+ * The definitions on which a definition depends are created (and printed) before it.
+ * Returns the printed interaction and the names and bodies of the definitions.
+ * **/
+fn print_interaction_with_references<CioII,Printer>
+    (
+        int : &InteractionInternalRepresentation<CioII>,
+        printer : &Printer,
+        keys : &HashMap<*const InteractionInternalRepresentation<CioII>,usize>,
+        references : &HashMap<usize,usize>
+    ) -> 
+        (String,Vec<(String,String)>)
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Printer : ContextAwareInteractionPrinter<CioII>
+{
+    let mut names : HashMap<usize,String> = HashMap::new();
+    let mut definitions = vec![];
+    let mut printed : Vec<String> = vec![];
+    let mut steps = vec![PrintingStep::Print(int,0,false)];
+    while let Some(step) = steps.pop() {
+        match step {
+            PrintingStep::Print(int, depth, is_definition_body) => {
+                let indent = "\t".repeat(depth);
+                match int {
+                    InteractionInternalRepresentation::LeafPattern(leaf) => {
+                        printed.push(format!("{}{}", indent, printer.print_explicit_pattern(leaf)));
+                    },
+                    InteractionInternalRepresentation::Operator(_, sub_ints) => {
+                        let key = keys[&(int as *const _)];
+                        if !is_definition_body && references.get(&key).cloned().unwrap_or_default() >= 2 {
+                            match names.get(&key) {
+                                Some(name) => {
+                                    printed.push(format!("{}{}", indent, name));
+                                },
+                                None => {
+                                    steps.push(PrintingStep::Define(key,depth));
+                                    steps.push(PrintingStep::Print(int,0,true));
+                                }
+                            }
+                            continue;
+                        }
+                        steps.push(PrintingStep::Assemble(int,depth));
+                        steps.extend(sub_ints.iter().rev().map(|sub_int| PrintingStep::Print(sub_int,depth + 1,false)));
+                    },
+                    InteractionInternalRepresentation::Annotated(_, annotated) => {
+                        steps.push(PrintingStep::Assemble(int,depth));
+                        steps.push(PrintingStep::Print(annotated,depth,false));
+                    }
+                }
+            },
+            PrintingStep::Assemble(int, depth) => {
+                let indent = "\t".repeat(depth);
+                match int {
+                    InteractionInternalRepresentation::LeafPattern(_) => {},
+                    InteractionInternalRepresentation::Operator(op, sub_ints) => {
+                        let inner_lines = printed.split_off(printed.len() - sub_ints.len());
+                        let first_line = format!("{}{}{}", indent, printer.print_operator(op,sub_ints), printer.left_parenthesis());
+                        let inner_lines_sep = format!("{}\n", printer.operand_separator());
+                        let last_line = format!("{}{}", indent, printer.right_parenthesis());
+                        printed.push(format!("{}\n{}\n{}", first_line, inner_lines.join(&inner_lines_sep), last_line));
+                    },
+                    InteractionInternalRepresentation::Annotated(annotation, _) => {
+                        let annotated_lines = printed.pop().unwrap();
+                        match (printer.syntax().annotation_delimiters(),printer.print_annotation(annotation)) {
+                            (Some((opening,closing)),Some(printed_annotation)) => {
+                                printed.push(format!("{}{}{}{}\n{}", indent, opening, printed_annotation, closing, annotated_lines));
+                            },
+                            _ => {
+                                printed.push(annotated_lines);
+                            }
+                        }
+                    }
+                }
+            },
+            PrintingStep::Define(key, depth) => {
+                let body = printed.pop().unwrap();
+                let name = printer.make_definition_name(definitions.len() + 1);
+                definitions.push((name.clone(),body));
+                printed.push(format!("{}{}", "\t".repeat(depth), name));
+                names.insert(key, name);
+            }
+        }
+    }
+    (printed.pop().unwrap(),definitions)
+}
+
+
+
+enum InfixPrintingStep<'a,CioII : AnnotatedCommonIoInteractionInterface> {
+    Print(&'a InteractionInternalRepresentation<CioII>),
+    Text(String)
+}
+
+
+
+/** 
+ * Prints the interaction in the infix syntax in order, using an explicit stack 
+ * so that deeply nested interactions do not overflow the call stack.
+ * **/
+fn print_interaction_infix_inner<CioII,Printer>
+    (
+        int : &InteractionInternalRepresentation<CioII>,
+        printer : &Printer,
+        notations : &[OperatorNotation<CioII::InteractionOperatorType>]
+    ) -> 
+        String
+where 
+    CioII : AnnotatedCommonIoInteractionInterface,
+    Printer : ContextAwareInteractionPrinter<CioII>
+{
+    let mut printed = String::new();
+    let mut steps = vec![InfixPrintingStep::Print(int)];
+    // the steps printing the given sub-interaction, within parentheses if required, in reverse order
+    let parenthesised = |sub_int, requires_parentheses : bool| {
+        if requires_parentheses {
+            vec![
+                InfixPrintingStep::Text(printer.right_parenthesis().to_owned()),
+                InfixPrintingStep::Print(sub_int),
+                InfixPrintingStep::Text(printer.left_parenthesis().to_owned())
+            ]
+        } else {
+            vec![InfixPrintingStep::Print(sub_int)]
+        }
+    };
+    while let Some(step) = steps.pop() {
+        let int = match step {
+            InfixPrintingStep::Print(int) => {
+                int
+            },
+            InfixPrintingStep::Text(text) => {
+                printed.push_str(&text);
+                continue;
+            }
+        };
+        match int {
+            InteractionInternalRepresentation::LeafPattern(leaf) => {
+                printed.push_str(&printer.print_explicit_pattern(leaf));
+            },
+            InteractionInternalRepresentation::Operator(op, sub_ints) => {
+                let notation = notations.iter().find(|x| x.operator == *op);
+                match notation {
+                    Some(notation) if op.arity() == 2 && (sub_ints.len() == 2 || op.is_associative()) => {
+                        for (operand_index,sub_int) in sub_ints.iter().enumerate().rev() {
+                            let requires_parentheses = infix_operand_requires_parentheses(notation, operand_index, sub_ints.len(), sub_int, notations);
+                            steps.extend(parenthesised(sub_int, requires_parentheses));
+                            if operand_index > 0 {
+                                steps.push(InfixPrintingStep::Text(format!(" {} ", notation.symbol)));
+                            }
+                        }
+                    },
+                    Some(notation) if op.arity() == 1 && sub_ints.len() == 1 => {
+                        printed.push_str(notation.symbol);
+                        steps.extend(parenthesised(&sub_ints[0], true));
+                    },
+                    _ => {
+                        printed.push_str(&format!("{}{}", printer.print_operator(op, sub_ints), printer.left_parenthesis()));
+                        steps.push(InfixPrintingStep::Text(printer.right_parenthesis().to_owned()));
+                        for (operand_index,sub_int) in sub_ints.iter().enumerate().rev() {
+                            steps.push(InfixPrintingStep::Print(sub_int));
+                            if operand_index > 0 {
+                                steps.push(InfixPrintingStep::Text(format!("{} ", printer.operand_separator())));
+                            }
+                        }
+                    }
+                }
+            },
+            InteractionInternalRepresentation::Annotated(annotation, annotated) => {
+                // the annotation applies to the primary expression which follows it
+                if let (Some((opening,closing)),Some(printed_annotation)) = (printer.syntax().annotation_delimiters(),printer.print_annotation(annotation)) {
+                    printed.push_str(&format!("{}{}{} ", opening, printed_annotation, closing));
+                }
+                // an annotated binary operation is parenthesised whether or not its annotation is printed
+                let requires_parentheses = get_infix_binary_notation(annotated, notations).is_some();
+                steps.extend(parenthesised(annotated, requires_parentheses));
+            }
+        }
+    }
+    printed
+}
+
+
+
+/** 
+ * Whether or not the *operand_index*-th operand of an application of the binary operator of the given *notation* 
+ * must be put within parentheses when printed in the infix syntax.
+ * **/
+fn infix_operand_requires_parentheses<CioII>
+    (
+        notation : &OperatorNotation<CioII::InteractionOperatorType>,
+        operand_index : usize,
+        num_operands : usize,
+        operand : &InteractionInternalRepresentation<CioII>,
+        notations : &[OperatorNotation<CioII::InteractionOperatorType>]
+    ) -> bool
+where 
+    CioII : AnnotatedCommonIoInteractionInterface
+{
+    // only operands which are themselves printed in infix form may require parentheses
+    let Some(sub_notation) = get_infix_binary_notation(operand, notations) else {
+        return false;
+    };
+    let sub_op = &sub_notation.operator;
+    if sub_notation.precedence != notation.precedence {
+        return sub_notation.precedence < notation.precedence;
+    }
+    // without parentheses, it would be flattened with its parent
+    if *sub_op == notation.operator && sub_op.is_associative() {
+        return true;
+    }
+    match (notation.associativity,sub_notation.associativity) {
+        (InfixAssociativity::Left,InfixAssociativity::Left) => {
+            operand_index != 0
+        },
+        (InfixAssociativity::Right,InfixAssociativity::Right) => {
+            operand_index != num_operands - 1
+        },
+        _ => {
+            true
+        }
+    }
+}
+
+
+
+/** 
+ * Returns the notation of the binary operator at the root of the interaction if it is printed in infix form.
+ * **/
+fn get_infix_binary_notation<'a,CioII>
+    (
+        int : &InteractionInternalRepresentation<CioII>,
+        notations : &'a [OperatorNotation<CioII::InteractionOperatorType>]
+    ) -> Option<&'a OperatorNotation<CioII::InteractionOperatorType>>
+where 
+    CioII : AnnotatedCommonIoInteractionInterface
+{
+    let InteractionInternalRepresentation::Operator(op, sub_ints) = int else {
+        return None;
+    };
+    notations.iter()
+        .find(|x| x.operator == *op)
+        .filter(|_| op.arity() == 2 && (sub_ints.len() == 2 || op.is_associative()))
+}